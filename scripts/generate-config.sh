@@ -101,8 +101,8 @@ extract_template_variables() {
         exit 1
     fi
 
-    # Extraire tous les noms de variables uniques
-    jq -r '.[].replacements[].name | select(. != null)' "$template_config_file" 2>/dev/null | sort -u || {
+    # Extraire tous les noms de variables uniques (format liste ou objet avec "files")
+    jq -r '(if type == "array" then . else .files end) | .[].replacements[].name | select(. != null)' "$template_config_file" 2>/dev/null | sort -u || {
         log_error "Erreur lors de l'analyse du fichier JSON. Vérifiez que jq est installé et que le JSON est valide." >&2
         exit 1
     }
@@ -1,17 +1,60 @@
 use clap::Parser;
 use std::path::PathBuf;
 
+use crate::config::{SigningFormat, Visibility};
+use crate::github::{CleanupMode, OnExists};
+use crate::utils::logging::LogFormat;
+use crate::utils::notify::NotifyMode;
+use crate::utils::summary::OutputFormat;
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
-    /// Enable debug output
+    /// Enable debug output (same as -v)
     #[arg(short, long)]
     pub debug: bool,
 
+    /// More output: -v for debug messages, -vv for trace messages of the dependencies too
+    #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+
+    /// Only print warnings, errors and the requested output
+    #[arg(short, long, conflicts_with = "debug")]
+    pub quiet: bool,
+
+    /// Log format: text, or json for one JSON object per record on stderr
+    #[arg(long, value_enum, default_value_t)]
+    pub log_format: LogFormat,
+
+    /// Output: text, or json to print a summary of the run (repository, branches, workflows,
+    /// variables, step durations) on stdout once done, status messages going to stderr
+    #[arg(long, value_enum, default_value_t)]
+    pub output: OutputFormat,
+
+    /// Do not report the pipeline phases (spinner on a terminal, a line per phase otherwise)
+    /// and their duration
+    #[arg(long)]
+    pub no_progress: bool,
+
     /// Path to the configuration file (YAML or JSON)
     #[arg(short = 'f', long)]
     pub config: Option<PathBuf>,
 
+    /// Set a template variable, overriding the environment and the configuration file
+    /// (repeatable)
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable)]
+    pub vars: Vec<(String, String)>,
+
+    /// Read template variables from this file (`NAME=value` lines), in addition to
+    /// generator.env and the `GEN_` keys of .env
+    #[arg(long, value_name = "FILE")]
+    pub env_file: Option<PathBuf>,
+
+    /// Answers file (YAML or JSON) providing every template variable: nothing is prompted for,
+    /// and the run fails listing the variables the template needs that the file does not provide
+    #[arg(long, value_name = "FILE")]
+    pub answers: Option<PathBuf>,
+
     /// Template category
     #[arg(short = 'c', long)]
     pub category: Option<String>,
@@ -24,7 +67,241 @@ pub struct Args {
     #[arg(long)]
     pub remote: bool,
 
+    /// Generate the package into this directory of a checked-out monorepo, without GitHub
+    #[arg(long, conflicts_with = "remote")]
+    pub into: Option<PathBuf>,
+
+    /// Generate the package into a pnpm monorepo on GitHub (`owner/repo`, or the path of a
+    /// local checkout) and open a pull request adding it, instead of creating a repository
+    #[arg(long, value_name = "REPO", conflicts_with_all = ["remote", "into"])]
+    pub into_repo: Option<String>,
+
+    /// Initialize a local git repository with a first commit, without any GitHub interaction
+    #[arg(long, conflicts_with_all = ["remote", "into", "into_repo"])]
+    pub git_init: bool,
+
     /// GitHub token for remote workflow
     #[arg(long)]
     pub token: Option<String>,
+
+    /// GitHub App ID, to authenticate as an app installation instead of with a token
+    #[arg(long)]
+    pub app_id: Option<String>,
+
+    /// Path to the GitHub App private key (PEM)
+    #[arg(long)]
+    pub app_private_key: Option<PathBuf>,
+
+    /// GitHub App installation ID (looked up from the organization when omitted)
+    #[arg(long)]
+    pub app_installation_id: Option<u64>,
+
+    /// Named account from the credential store to use for GitHub operations
+    #[arg(long)]
+    pub account: Option<String>,
+
+    /// Repository visibility, overriding the configuration file and the template default
+    #[arg(long, value_enum)]
+    pub visibility: Option<Visibility>,
+
+    /// Repository topic, added to those of the template and configuration file (repeatable)
+    #[arg(long = "topic")]
+    pub topics: Vec<String>,
+
+    /// Organization policy file (defaults to org_policy.yaml in the templates repository)
+    #[arg(long)]
+    pub policy: Option<PathBuf>,
+
+    /// Delete the created GitHub repository (and with `all`, the local directory) if a later step fails
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "remote")]
+    pub cleanup_on_failure: Option<CleanupMode>,
+
+    /// What to do when the repository already exists, e.g. when re-running the generator
+    #[arg(long, value_enum, default_value_t = OnExists::Fail)]
+    pub on_exists: OnExists,
+
+    /// Maximum attempts for each GitHub API call (transient errors and rate limits)
+    #[arg(long, default_value_t = 3)]
+    pub retry_attempts: u32,
+
+    /// Initial backoff between GitHub API retries, in milliseconds (doubled on each retry)
+    #[arg(long, default_value_t = 1000)]
+    pub retry_backoff_ms: u64,
+
+    /// Message of the initial commit (default: "first commit")
+    #[arg(long)]
+    pub commit_message: Option<String>,
+
+    /// Author of the initial commit, as "Name <email>"
+    #[arg(long, value_name = "NAME <EMAIL>", value_parser = parse_author)]
+    pub commit_author: Option<(String, String)>,
+
+    /// Sign the generated commits with gpg or ssh
+    #[arg(long, value_enum)]
+    pub sign_commits: Option<SigningFormat>,
+
+    /// GPG key ID or SSH private key path used to sign the generated commits
+    #[arg(long)]
+    pub signing_key: Option<String>,
+
+    /// User-Agent sent to the GitHub API
+    #[arg(long)]
+    pub user_agent: Option<String>,
+
+    /// GitHub REST API version to pin (X-GitHub-Api-Version header, default: 2022-11-28)
+    #[arg(long)]
+    pub github_api_version: Option<String>,
+
+    /// Timeout for establishing HTTP connections, in seconds (default: 10)
+    #[arg(long, value_name = "SECONDS")]
+    pub connect_timeout: Option<u64>,
+
+    /// Timeout for each HTTP request, in seconds (default: 60)
+    #[arg(long, value_name = "SECONDS")]
+    pub http_timeout: Option<u64>,
+
+    /// Proxy for every HTTP request, e.g. http://proxy.corp:3128 (default: HTTPS_PROXY/HTTP_PROXY)
+    #[arg(long, value_name = "URL")]
+    pub proxy: Option<String>,
+
+    /// PEM file of extra certificate authorities to trust, e.g. a corporate proxy's
+    #[arg(long, value_name = "FILE")]
+    pub ca_certificates: Option<PathBuf>,
+
+    /// Wait for triggered deployment workflows to finish and fail if one of them fails
+    #[arg(long)]
+    pub wait_for_workflows: bool,
+
+    /// Maximum time to wait for each deployment workflow, in seconds
+    #[arg(long, default_value_t = 1800)]
+    pub workflow_timeout: u64,
+
+    /// Maximum time to wait for GitHub to expose pushed branches and workflows, in seconds
+    #[arg(long, default_value_t = 60)]
+    pub ready_timeout: u64,
+
+    /// Also dry-run the generated workflows with `act` before pushing
+    #[arg(long)]
+    pub validate_workflows: bool,
+
+    /// Templates repository to use: a git URL or GitHub `owner/repo`, optionally pinned with
+    /// `#<tag|branch|commit>`
+    #[arg(long, value_name = "SOURCE")]
+    pub template_source: Option<String>,
+
+    /// Open a pull request adding the template to the organization's template registry
+    /// (`owner/repo`, else `template_registry` from the config file) when it is not listed yet
+    #[arg(long, value_name = "REGISTRY")]
+    pub register_template: Option<Option<String>>,
+
+    /// Directory caching fetched template repositories
+    #[arg(long, value_name = "DIR")]
+    pub template_cache_dir: Option<PathBuf>,
+
+    /// Run `pnpm fetch` on the template lockfile while the project is generated, so that the
+    /// install hits a warm store
+    #[arg(long)]
+    pub prefetch: bool,
+
+    /// Do not install the dependencies: the project, and the initial push, only hold the
+    /// template files
+    #[arg(long, conflicts_with_all = ["prefetch", "offline"])]
+    pub skip_install: bool,
+
+    /// Install the dependencies from the package manager's store only, without network access
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Ring the terminal bell and/or show a desktop notification when a long step (install,
+    /// push, deployment wait) ends
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "all")]
+    pub notify: Option<NotifyMode>,
+
+    /// Log every API request and response to this file, with credentials redacted
+    #[arg(long, value_name = "FILE")]
+    pub trace_http: Option<std::path::PathBuf>,
+
+    /// Print the GitHub API calls and git operations instead of executing them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Write the ordered list of files, commands, git operations and API calls the run would
+    /// perform to this file, without creating anything on GitHub (implies --dry-run)
+    #[arg(long, value_name = "FILE", requires = "remote")]
+    pub plan: Option<PathBuf>,
+
+    /// Execute a plan saved with --plan, refusing to run if the template, variables or actions
+    /// have changed since it was produced
+    #[arg(long = "apply", value_name = "PLAN", requires = "remote", conflicts_with_all = ["plan", "dry_run"])]
+    pub apply_plan: Option<PathBuf>,
+
+    /// Generate from a template requiring another generator version, with a warning
+    #[arg(long)]
+    pub allow_incompatible_template: bool,
+
+    /// List the available templates with their description, version and variables
+    #[arg(long)]
+    pub list_templates: bool,
+
+    /// Only list the templates whose name, description or variables contain this term
+    #[arg(long, value_name = "TERM", requires = "list_templates")]
+    pub search: Option<String>,
+
+    /// Update a generated project to the current revision of its template, using the
+    /// `.generator-lock.json` recorded at generation
+    #[arg(long, value_name = "PROJECT_DIR", conflicts_with_all = ["remote", "into", "git_init"])]
+    pub update: Option<PathBuf>,
+
+    /// With `--update`, write the changes to this patch file instead of applying them
+    #[arg(long, value_name = "FILE", requires = "update")]
+    pub update_patch: Option<PathBuf>,
+
+    /// Describe a template (`category/name` or `name`) and every variable it asks for
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "list_templates")]
+    pub describe_template: Option<String>,
+
+    /// Inspect a template (`category/name` or `name`) without generating anything: its file
+    /// tree, variables, hooks, the files each replacement targets and the output size
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with_all = ["list_templates", "describe_template"]
+    )]
+    pub inspect_template: Option<String>,
+
+    /// Show the variables most often changed from their default and the choices picked, for
+    /// every template or the given one
+    #[arg(long, value_name = "TEMPLATE", num_args = 0..=1)]
+    pub template_stats: Option<Option<String>>,
+
+    /// Run package managers and hooks with an isolated HOME, caches and stores: DIR, or a
+    /// temporary directory removed afterwards
+    #[arg(long, value_name = "DIR", num_args = 0..=1)]
+    pub isolated_home: Option<Option<PathBuf>>,
+
+    /// Do not record this generation in the local template statistics
+    #[arg(long)]
+    pub no_stats: bool,
+}
+
+fn parse_variable(assignment: &str) -> Result<(String, String), String> {
+    match assignment.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected NAME=VALUE, got '{}'", assignment)),
+    }
+}
+
+fn parse_author(author: &str) -> Result<(String, String), String> {
+    let parts = author
+        .trim()
+        .strip_suffix('>')
+        .and_then(|rest| rest.split_once('<'));
+    match parts {
+        Some((name, email)) if !name.trim().is_empty() && email.contains('@') => {
+            Ok((name.trim().to_string(), email.trim().to_string()))
+        }
+        _ => Err(format!("expected \"Name <email>\", got '{}'", author)),
+    }
 }
@@ -1,8 +1,26 @@
-use inquire::{Confirm, Text};
+use inquire::{Confirm, Password, Select, Text};
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use crate::utils::validation;
+use crate::config::PromptVariable;
+
+use crate::utils::{progress, validation};
+
+/// Set when every answer is provided up front (`--answers`): prompts return `None` instead of
+/// waiting for input
+static PROMPTS_DISABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn disable_prompts() {
+    PROMPTS_DISABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn prompts_disabled() -> bool {
+    PROMPTS_DISABLED.load(Ordering::Relaxed)
+}
 
 pub fn select_template(templates: Vec<(String, String)>) -> Option<(String, String)> {
+    if prompts_disabled() {
+        return None;
+    }
     let options: Vec<String> = templates
         .iter()
         .map(|(category, name)| format!("{} ({})", name, category))
@@ -27,7 +45,12 @@ pub fn select_template(templates: Vec<(String, String)>) -> Option<(String, Stri
 }
 
 pub fn prompt_for_variable(variable_name: &str) -> Option<String> {
+    if prompts_disabled() {
+        return None;
+    }
     let prompt = format!("Enter value for {}:", variable_name);
+    // Asked while the template renders
+    let _paused = progress::pause();
 
     match variable_name {
         "project_name" => Text::new(&prompt)
@@ -42,8 +65,58 @@ pub fn prompt_for_variable(variable_name: &str) -> Option<String> {
     }
 }
 
+/// Ask a variable declared in the template prompts, with its message, default and choices
+pub fn prompt_for_declared_variable(variable: &PromptVariable) -> Option<String> {
+    if prompts_disabled() {
+        return None;
+    }
+    let prompt = variable
+        .message
+        .clone()
+        .unwrap_or_else(|| format!("Enter value for {}:", variable.name));
+    let _paused = progress::pause();
+
+    if !variable.choices.is_empty() {
+        let start = variable
+            .default
+            .as_ref()
+            .and_then(|default| variable.choices.iter().position(|c| c == default))
+            .unwrap_or(0);
+        return Select::new(&prompt, variable.choices.clone())
+            .with_starting_cursor(start)
+            .prompt()
+            .ok();
+    }
+
+    if variable.secret {
+        return Password::new(&prompt).without_confirmation().prompt().ok();
+    }
+
+    let mut text = Text::new(&prompt);
+    if let Some(default) = &variable.default {
+        text = text.with_default(default);
+    }
+    if variable.required
+        || variable.pattern.is_some()
+        || variable.min_length.is_some()
+        || variable.max_length.is_some()
+    {
+        text = text.with_validator(validation::declared_variable_validator(variable));
+    }
+    match variable.name.as_str() {
+        "project_name" => text.with_validator(validation::validate_project_name),
+        "name" => text.with_validator(validation::validate_package_name),
+        _ => text,
+    }
+    .prompt()
+    .ok()
+}
+
 pub fn prompt_for_repo_name(project_name: &str) -> Option<String> {
-    println!("Project name: {}", project_name);
+    log::info!("Project name: {}", project_name);
+    if prompts_disabled() {
+        return Some(project_name.to_string());
+    }
 
     let use_project_name =
         Confirm::new("Do you want to use the project name as the repository name?")
@@ -60,3 +133,19 @@ pub fn prompt_for_repo_name(project_name: &str) -> Option<String> {
             .ok()
     }
 }
+
+pub fn prompt_for_secret(name: &str, description: Option<&str>) -> Option<String> {
+    if prompts_disabled() {
+        return None;
+    }
+    let prompt = match description {
+        Some(description) => format!("Enter value for secret {} ({}):", name, description),
+        None => format!("Enter value for secret {}:", name),
+    };
+
+    Password::new(&prompt)
+        .without_confirmation()
+        .prompt()
+        .ok()
+        .filter(|value| !value.is_empty())
+}
@@ -1,15 +1,19 @@
 use std::collections::HashMap;
-use std::io::{Error, ErrorKind, Result};
+use std::io::{Error, ErrorKind, Result, Write};
 use std::path::Path;
+use std::sync::Arc;
 
-mod functions;
+pub mod functions;
 
 use crate::args::Args;
-use crate::config::file_config;
+use crate::config::{credentials, file_config, CommitSettings, PromptPage, SecretDeclaration};
 use crate::config::{CREATION_PATH, PACKAGE_ROOT_PATH};
-use crate::generate::project_generator;
+use crate::generate::project_generator::{self, GenerateOptions};
+use crate::generate::variables;
+use crate::github::auth::{Auth, GitHubApp};
 use crate::template::TemplateManager;
-use crate::utils::{context, strings};
+use crate::utils::error::GeneratorError;
+use crate::utils::{context, strings, summary};
 
 pub use functions::prompt_for_repo_name;
 
@@ -26,8 +30,18 @@ pub fn get_template_info(
             )
         })?;
 
-        // Set variables from config
-        context::set_variables(config.to_variables());
+        // Set variables from config, under those from the environment and the command line
+        let mut variables = config.to_variables();
+        variables.extend(context::get_variables());
+        context::set_variables(variables);
+        context::set_computed(
+            config
+                .computed
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+        );
 
         // Get template info from config
         config.get_template_info().ok_or_else(|| {
@@ -38,11 +52,16 @@ pub fn get_template_info(
         })
     } else if let (Some(cat), Some(tmpl)) = (&args.category, &args.template) {
         Ok((cat.clone(), tmpl.clone()))
+    } else if functions::prompts_disabled() {
+        Err(Error::new(
+            ErrorKind::InvalidInput,
+            "--category and --template (or --config) are required with --answers",
+        ))
     } else {
         // List available templates
-        let templates = template_manager.list_templates().map_err(|e| {
-            Error::other(format!("Failed to list templates: {}", e))
-        })?;
+        let templates = template_manager
+            .list_templates()
+            .map_err(|e| Error::other(format!("Failed to list templates: {}", e)))?;
 
         // Select template
         functions::select_template(templates)
@@ -50,34 +69,200 @@ pub fn get_template_info(
     }
 }
 
-pub fn interact(template_path: &Path) -> Result<()> {
+/// Resolve GitHub credentials for remote mode, along with the organization of the selected account.
+/// A GitHub App (--app-id or GITHUB_APP_ID) takes precedence over tokens. Token precedence is
+/// --token, --account, GITHUB_TOKEN, then the default account of the credential store.
+pub fn resolve_auth(args: &Args) -> Result<(Arc<Auth>, Option<String>)> {
+    let app_id = args
+        .app_id
+        .clone()
+        .or_else(|| std::env::var("GITHUB_APP_ID").ok());
+    if let Some(app_id) = app_id {
+        let key_path = args
+            .app_private_key
+            .clone()
+            .or_else(|| std::env::var("GITHUB_APP_PRIVATE_KEY_PATH").ok().map(Into::into))
+            .ok_or_else(|| {
+                GeneratorError::Config(
+                    "GitHub App authentication requires --app-private-key or GITHUB_APP_PRIVATE_KEY_PATH"
+                        .to_string(),
+                )
+            })?;
+        let installation_id = match args.app_installation_id {
+            Some(id) => Some(id),
+            None => std::env::var("GITHUB_APP_INSTALLATION_ID")
+                .ok()
+                .map(|id| id.parse::<u64>())
+                .transpose()
+                .map_err(|e| {
+                    GeneratorError::Config(format!("Invalid GITHUB_APP_INSTALLATION_ID: {}", e))
+                })?,
+        };
+
+        log::info!("Using GitHub App: {}", app_id);
+        let app = GitHubApp::from_key_file(&app_id, installation_id, &key_path)
+            .map_err(|e| GeneratorError::Config(e.to_string()))?;
+        return Ok((Arc::new(Auth::App(app)), None));
+    }
+
+    let credential_store = credentials::load()?;
+    let env_token = std::env::var("GITHUB_TOKEN").ok();
+    let account = if args.account.is_some() || (args.token.is_none() && env_token.is_none()) {
+        credential_store.get_account(args.account.as_deref())?
+    } else {
+        None
+    };
+    if let Some((account_name, _)) = account {
+        log::info!("Using account: {}", account_name);
+    }
+
+    // (a dry-run never talks to GitHub, so the token is optional there)
+    let account_token = match (&args.token, account) {
+        (None, Some((name, account))) => Some(account.resolve_token(name)?),
+        _ => None,
+    };
+    let token = args
+        .token
+        .clone()
+        .or(account_token)
+        .or(env_token)
+        .or_else(|| (args.dry_run || args.plan.is_some()).then(String::new))
+        .ok_or_else(|| {
+            GeneratorError::Config(
+                "GitHub token is required for remote mode. Set GITHUB_TOKEN env var, use --token or --account"
+                    .to_string(),
+            )
+        })?;
+
+    let organization = account.and_then(|(_, a)| a.organization.clone());
+    Ok((Arc::new(Auth::Token(token)), organization))
+}
+
+/// Values for the secrets declared by the template, from the environment variable of the same
+/// name (prefixed with the environment name for environment secrets, e.g. `PRODUCTION_API_KEY`)
+/// or prompted for. A dry-run never uploads them, so nothing is prompted for there.
+pub fn resolve_secrets(
+    declarations: &[SecretDeclaration],
+    environment: Option<&str>,
+    dry_run: bool,
+) -> Result<Vec<(String, String)>> {
+    let mut secrets = Vec::new();
+    for declaration in declarations {
+        let env_name = match environment {
+            Some(environment) => format!(
+                "{}_{}",
+                environment.to_uppercase().replace('-', "_"),
+                declaration.name
+            ),
+            None => declaration.name.clone(),
+        };
+        let description = match (environment, &declaration.description) {
+            (Some(environment), Some(description)) => {
+                Some(format!("{}, {}", environment, description))
+            }
+            (Some(environment), None) => Some(environment.to_string()),
+            (None, description) => description.clone(),
+        };
+
+        let value = match std::env::var(&env_name) {
+            Ok(value) if !value.is_empty() => value,
+            _ if dry_run => String::new(),
+            _ => functions::prompt_for_secret(&declaration.name, description.as_deref())
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("Secret {} is required by the template", env_name),
+                    )
+                })?,
+        };
+        log::debug!("Resolved secret {}", env_name);
+        secrets.push((declaration.name.clone(), value));
+    }
+    Ok(secrets)
+}
+
+/// Ask the declared prompts page by page. Pages and questions whose `when` condition is
+/// false given the previous answers are skipped.
+fn prompt_pages(pages: &[PromptPage], variables: &mut HashMap<String, String>) -> Result<()> {
+    let mut out = summary::status_output();
+    for (index, page) in pages.iter().enumerate() {
+        if !variables::condition_met(page.when.as_deref(), variables)? {
+            log::debug!("Skipping prompt page '{}'", page.title);
+            continue;
+        }
+
+        let _ = writeln!(out, "\n📋 {} ({}/{})", page.title, index + 1, pages.len());
+        if let Some(description) = &page.description {
+            let _ = writeln!(out, "   {}", description);
+        }
+        for variable in &page.variables {
+            if variables.contains_key(&variable.name)
+                || !variables::condition_met(variable.when.as_deref(), variables)?
+            {
+                continue;
+            }
+            let value = functions::prompt_for_declared_variable(variable).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("An error occurred while entering {}", variable.name),
+                )
+            })?;
+            variables.insert(variable.name.clone(), value);
+        }
+    }
+    Ok(())
+}
+
+/// Prompt for the template variables and generate the project, into `into` (a monorepo
+/// directory) when given, with a local git repository committed with the `git_init` settings
+/// when set
+pub fn interact(
+    template_path: &Path,
+    into: Option<&Path>,
+    git_init: Option<&CommitSettings>,
+    options: &GenerateOptions,
+) -> Result<()> {
+    // Variables from the environment and the command line are not asked again
+    let mut variables = context::get_variables();
+
     // Get project name first
-    let project_name = functions::prompt_for_variable("project_name").ok_or_else(|| {
-        Error::new(
-            ErrorKind::InvalidInput,
-            "An error occurred while entering project name",
-        )
-    })?;
+    let project_name = match variables.get("project_name") {
+        Some(project_name) => project_name.clone(),
+        None => functions::prompt_for_variable("project_name").ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "An error occurred while entering project name",
+            )
+        })?,
+    };
 
     // Get package name
-    let package_name = functions::prompt_for_variable("name").ok_or_else(|| {
-        Error::new(
-            ErrorKind::InvalidInput,
-            "An error occurred while entering package name",
-        )
-    })?;
+    if !variables.contains_key("name") {
+        let package_name = functions::prompt_for_variable("name").ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "An error occurred while entering package name",
+            )
+        })?;
+        variables.insert("name".to_string(), package_name);
+    }
+    variables.insert("project_name".to_string(), project_name.clone());
 
-    // Initialize variables with both names
-    let mut variables = HashMap::from([
-        ("project_name".to_string(), project_name.to_string()),
-        ("name".to_string(), package_name.to_string()),
-    ]);
+    // Declared prompts first, page by page, then the remaining template variables
+    let pages = strings::read_template_manifest(template_path)
+        .map(|manifest| manifest.prompts)
+        .unwrap_or_default();
+    prompt_pages(&pages, &mut variables)?;
 
-    // Try to get additional variables from template config
     match strings::extract_unique_keys(template_path) {
         Ok(unique_keys) => {
+            let declared: Vec<&str> = pages
+                .iter()
+                .flat_map(|page| &page.variables)
+                .map(|variable| variable.name.as_str())
+                .collect();
             for key in &unique_keys {
-                if key != "project_name" && key != "name" {
+                if !variables.contains_key(key) && !declared.contains(&key.as_str()) {
                     let value = functions::prompt_for_variable(key).ok_or_else(|| {
                         Error::new(
                             ErrorKind::InvalidInput,
@@ -89,35 +274,49 @@ pub fn interact(template_path: &Path) -> Result<()> {
             }
         }
         Err(err) => {
-            context::debug_print(&format!("Note: No template configuration found: {}", err));
-            println!("Note: No template configuration found. Using basic template generation.");
+            log::debug!("Note: No template configuration found: {}", err);
+            log::info!("Note: No template configuration found. Using basic template generation.");
         }
     }
 
+    options
+        .naming
+        .apply_to_variables(&mut variables, options.team.as_deref())?;
+    let project_name = variables["project_name"].clone();
     context::set_variables(variables);
 
+    if let Some(into) = into {
+        return crate::generate::handle_into_mode(template_path, &project_name, into, options);
+    }
+
     let project_path = Path::new(&PACKAGE_ROOT_PATH)
         .join(CREATION_PATH)
         .join(&project_name);
 
-    println!(
+    log::info!(
         "Generating project '{}' with template '{}'",
         project_name,
         template_path.file_name().unwrap().to_string_lossy()
     );
 
-    project_generator::generate_project(template_path, &project_path).map_err(|e| {
-        Error::other(
-            format!("An error occurred while generating the project: {}", e),
-        )
+    project_generator::generate_project(template_path, &project_path, options).map_err(|e| {
+        Error::other(format!(
+            "An error occurred while generating the project: {}",
+            e
+        ))
     })?;
 
-    project_generator::install_dependencies(&project_path).map_err(|e| {
-        Error::other(
-            format!("An error occurred while installing dependencies: {}", e),
-        )
+    project_generator::install_dependencies(&project_path, options).map_err(|e| {
+        Error::other(format!(
+            "An error occurred while installing dependencies: {}",
+            e
+        ))
     })?;
 
-    println!("Project generated successfully");
+    if let Some(commit) = git_init {
+        project_generator::init_git_repository(&project_path, commit)?;
+    }
+
+    log::info!("Project generated successfully");
     Ok(())
 }
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+/// Read an answers file: a YAML or JSON mapping of variable names to values. Lists are joined
+/// with commas, like the additional variables of a config file.
+pub fn load(path: &Path) -> Result<HashMap<String, String>> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        Error::new(
+            e.kind(),
+            format!("Failed to read answers file {}: {}", path.display(), e),
+        )
+    })?;
+    // YAML is a superset of JSON
+    let answers: HashMap<String, serde_json::Value> =
+        serde_yaml::from_str(&content).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid answers file {}: {}", path.display(), e),
+            )
+        })?;
+
+    let mut variables = HashMap::new();
+    for (name, value) in answers {
+        let value = match value {
+            serde_json::Value::String(s) => s,
+            serde_json::Value::Array(items) => items
+                .iter()
+                .map(|item| match item {
+                    serde_json::Value::String(s) => s.clone(),
+                    _ => item.to_string(),
+                })
+                .collect::<Vec<String>>()
+                .join(","),
+            serde_json::Value::Null => String::new(),
+            serde_json::Value::Object(_) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Invalid answers file {}: {} must be a value, not a mapping",
+                        path.display(),
+                        name
+                    ),
+                ))
+            }
+            value => value.to_string(),
+        };
+        log::debug!("Variable {} from {}", name, path.display());
+        variables.insert(name, value);
+    }
+    Ok(variables)
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::PathBuf;
+
+/// Environment variable overriding the credential store location
+pub const CREDENTIALS_PATH_ENV: &str = "PROJECT_GENERATOR_CREDENTIALS";
+
+/// A named GitHub credential, e.g. a work org, a personal account or a sandbox org
+#[derive(Debug, serde::Deserialize)]
+pub struct Account {
+    /// Token stored in plain text in the credential store
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Name of an environment variable holding the token
+    #[serde(default)]
+    pub token_env: Option<String>,
+    /// Organization in which repositories are created with this account
+    #[serde(default)]
+    pub organization: Option<String>,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct CredentialStore {
+    #[serde(default)]
+    pub default: Option<String>,
+    #[serde(default)]
+    pub accounts: HashMap<String, Account>,
+}
+
+impl Account {
+    pub fn resolve_token(&self, account_name: &str) -> Result<String> {
+        if let Some(token) = &self.token {
+            return Ok(token.clone());
+        }
+
+        if let Some(env_name) = &self.token_env {
+            return std::env::var(env_name).map_err(|_| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "Account '{}' reads its token from ${}, which is not set",
+                        account_name, env_name
+                    ),
+                )
+            });
+        }
+
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Account '{}' must define either 'token' or 'token_env'",
+                account_name
+            ),
+        ))
+    }
+}
+
+impl CredentialStore {
+    /// Look up an account by name, or the default account when `name` is None
+    pub fn get_account(&self, name: Option<&str>) -> Result<Option<(&str, &Account)>> {
+        let Some(name) = name.or(self.default.as_deref()) else {
+            return Ok(None);
+        };
+
+        self.accounts
+            .get_key_value(name)
+            .map(|(name, account)| Some((name.as_str(), account)))
+            .ok_or_else(|| {
+                let mut known: Vec<&str> = self.accounts.keys().map(|k| k.as_str()).collect();
+                known.sort();
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "Unknown account '{}' (configured accounts: {})",
+                        name,
+                        known.join(", ")
+                    ),
+                )
+            })
+    }
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(CREDENTIALS_PATH_ENV) {
+        return Some(PathBuf::from(path));
+    }
+
+    let config_dir = std::env::var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| std::env::var("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .ok()?;
+
+    Some(
+        config_dir
+            .join("project-generator")
+            .join("credentials.yaml"),
+    )
+}
+
+/// Load the credential store, returning an empty store when none exists
+pub fn load() -> Result<CredentialStore> {
+    let Some(path) = default_path().filter(|p| p.exists()) else {
+        log::debug!("No credential store found");
+        return Ok(CredentialStore::default());
+    };
+
+    log::debug!("Reading credential store: {}", path.display());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(metadata) = fs::metadata(&path) {
+            if metadata.permissions().mode() & 0o077 != 0 {
+                log::warn!(
+                    "Credential store '{}' is readable by other users, consider `chmod 600`",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Failed to read credential store: {}", e),
+        )
+    })?;
+
+    serde_yaml::from_str(&content).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Failed to parse credential store '{}': {}",
+                path.display(),
+                e
+            ),
+        )
+    })
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+/// Environment variables holding template variables, e.g. `GEN_PROJECT_NAME` for `project_name`
+pub const ENV_PREFIX: &str = "GEN_";
+
+/// Read from the working directory. Every key of `generator.env` is a variable, while `.env`
+/// usually belongs to the project and only its `GEN_` keys are read.
+pub const GENERATOR_ENV_FILE: &str = "generator.env";
+pub const DOTENV_FILE: &str = ".env";
+
+/// Variables from the environment, by increasing precedence: `GEN_` keys of `.env`,
+/// `generator.env`, the `--env-file` file, then `GEN_` environment variables
+pub fn load(env_file: Option<&Path>) -> Result<HashMap<String, String>> {
+    let mut variables = HashMap::new();
+
+    for (path, prefixed_only) in [
+        (Path::new(DOTENV_FILE), true),
+        (Path::new(GENERATOR_ENV_FILE), false),
+    ] {
+        if path.is_file() {
+            variables.extend(read_file(path, prefixed_only)?);
+        }
+    }
+    if let Some(path) = env_file {
+        variables.extend(read_file(path, false)?);
+    }
+
+    for (key, value) in std::env::vars() {
+        if let Some(name) = variable_name(&key, true) {
+            log::debug!("Variable {} from environment variable {}", name, key);
+            variables.insert(name, value);
+        }
+    }
+    Ok(variables)
+}
+
+/// `GEN_PROJECT_NAME` is `project_name`. Unprefixed keys are kept as is unless only prefixed
+/// keys are variables.
+fn variable_name(key: &str, prefixed_only: bool) -> Option<String> {
+    match key.strip_prefix(ENV_PREFIX) {
+        Some(name) if !name.is_empty() => Some(name.to_lowercase()),
+        Some(_) => None,
+        None if prefixed_only => None,
+        None => Some(key.to_string()),
+    }
+}
+
+/// Read `KEY=value` lines, skipping blank lines and `#` comments. Keys may be preceded by
+/// `export` and values may be quoted.
+fn read_file(path: &Path, prefixed_only: bool) -> Result<HashMap<String, String>> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        Error::new(
+            e.kind(),
+            format!("Failed to read {}: {}", path.display(), e),
+        )
+    })?;
+
+    let mut variables = HashMap::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line.split_once('=').ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "{}:{}: expected KEY=value, got '{}'",
+                    path.display(),
+                    index + 1,
+                    line
+                ),
+            )
+        })?;
+        let Some(name) = variable_name(key.trim(), prefixed_only) else {
+            continue;
+        };
+        log::debug!("Variable {} from {}", name, path.display());
+        variables.insert(name, unquote(value.trim()));
+    }
+    Ok(variables)
+}
+
+fn unquote(value: &str) -> String {
+    if let Some(rest) = value.strip_prefix('\'') {
+        return rest.split('\'').next().unwrap_or_default().to_string();
+    }
+    if let Some(rest) = value.strip_prefix('"') {
+        // Up to the first unescaped quote, with `\n` and `\"` escapes
+        let mut unquoted = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => unquoted.push('\n'),
+                    Some(escaped) => unquoted.push(escaped),
+                    None => unquoted.push('\\'),
+                },
+                c => unquoted.push(c),
+            }
+        }
+        return unquoted;
+    }
+    // Unquoted values end at an inline comment
+    value
+        .split_once(" #")
+        .map(|(value, _)| value.trim_end())
+        .unwrap_or(value)
+        .to_string()
+}
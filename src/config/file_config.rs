@@ -1,8 +1,10 @@
-use crate::utils::context;
+use crate::config::{
+    CommitSettings, GitAuth, HostnameService, MergeQueue, SigningFormat, Visibility,
+};
 use std::collections::HashMap;
 use std::fs;
 use std::io::{Error, ErrorKind, Result};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(serde::Deserialize)]
 pub struct FileConfig {
@@ -16,10 +18,75 @@ pub struct FileConfig {
     pub template_name: Option<String>,
     #[serde(default)]
     pub template_branch: Option<String>,
+    /// Templates repository to fetch instead of the default one, e.g. `org/templates#v1.2.0`
+    #[serde(default)]
+    pub template_source: Option<String>,
+    /// Organization template registry (`owner/repo`) used by `--register-template`
+    #[serde(default)]
+    pub template_registry: Option<String>,
+    /// Variables derived from the others, evaluated in order once every variable is known
+    #[serde(default)]
+    pub computed: Option<indexmap::IndexMap<String, String>>,
     #[serde(default)]
     pub github_tag: Option<String>,
     #[serde(default)]
+    pub topics: Option<Vec<String>>,
+    #[serde(default)]
+    pub visibility: Option<Visibility>,
+    #[serde(default)]
     pub create_develop_branch: Option<bool>,
+    #[serde(default)]
+    pub security_features: Option<Vec<String>>,
+    #[serde(default)]
+    pub team: Option<String>,
+    #[serde(default)]
+    pub staging_owner: Option<String>,
+    #[serde(default)]
+    pub admin_team: Option<String>,
+    /// Team slug to repository role, e.g. `frontend: push`
+    #[serde(default)]
+    pub teams: Option<indexmap::IndexMap<String, String>>,
+    /// Merge queue on `main`, replacing the template's for organizations that require one
+    #[serde(default)]
+    pub merge_queue: Option<MergeQueue>,
+    #[serde(default)]
+    pub git_auth: Option<GitAuth>,
+    #[serde(default)]
+    pub ssh_key_path: Option<String>,
+    /// Service allocating the dev and prod hostnames in remote mode
+    #[serde(default)]
+    pub hostname_service: Option<HostnameService>,
+    /// Message of the initial commit
+    #[serde(default)]
+    pub commit_message: Option<String>,
+    #[serde(default)]
+    pub commit_author_name: Option<String>,
+    #[serde(default)]
+    pub commit_author_email: Option<String>,
+    /// Sign the generated commits with `gpg` or `ssh`
+    #[serde(default)]
+    pub commit_signing: Option<SigningFormat>,
+    /// GPG key ID or SSH private key path used to sign the generated commits
+    #[serde(default)]
+    pub signing_key: Option<String>,
+    /// User-Agent sent to the GitHub API
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// GitHub REST API version pinned through the `X-GitHub-Api-Version` header
+    #[serde(default)]
+    pub github_api_version: Option<String>,
+    /// Timeout for establishing HTTP connections, in seconds
+    #[serde(default)]
+    pub connect_timeout: Option<u64>,
+    /// Timeout for each HTTP request, in seconds
+    #[serde(default)]
+    pub http_timeout: Option<u64>,
+    /// Proxy for every HTTP request
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// PEM file of extra certificate authorities to trust
+    #[serde(default)]
+    pub ca_certificates: Option<PathBuf>,
     #[serde(flatten)]
     pub additional_vars: std::collections::HashMap<String, serde_json::Value>,
 }
@@ -28,14 +95,11 @@ impl FileConfig {
     pub fn get_template_info(&self) -> Option<(String, String)> {
         match (&self.template_category, &self.template_name) {
             (Some(category), Some(name)) => {
-                context::debug_print(&format!(
-                    "Template info: category='{}', name='{}'",
-                    category, name
-                ));
+                log::debug!("Template info: category='{}', name='{}'", category, name);
                 Some((category.clone(), name.clone()))
             }
             _ => {
-                context::debug_print("Template info not found: missing category or name");
+                log::debug!("Template info not found: missing category or name");
                 None
             }
         }
@@ -45,8 +109,13 @@ impl FileConfig {
         self.template_branch.as_deref().unwrap_or("main")
     }
 
-    pub fn get_github_tag(&self) -> Option<&String> {
-        self.github_tag.as_ref()
+    /// Topics from the config, `github_tag` first
+    pub fn get_topics(&self) -> Vec<String> {
+        self.github_tag
+            .iter()
+            .chain(self.topics.iter().flatten())
+            .cloned()
+            .collect()
     }
 
     pub fn validate_github_tag(&self) -> Result<()> {
@@ -58,10 +127,114 @@ impl FileConfig {
                     tag,
                     valid_tags.join(", ")
                 );
-                context::debug_print(&format!("ERROR: {}", error_msg));
+                log::debug!("ERROR: {}", error_msg);
+                return Err(Error::new(ErrorKind::InvalidData, error_msg));
+            }
+            log::debug!("Valid github_tag found: '{}'", tag);
+        }
+        Ok(())
+    }
+
+    pub fn get_security_features(&self) -> &[String] {
+        self.security_features.as_deref().unwrap_or(&[])
+    }
+
+    pub fn validate_security_features(&self) -> Result<()> {
+        let valid_features = [
+            "advanced_security",
+            "secret_scanning",
+            "secret_scanning_push_protection",
+            "dependabot_alerts",
+            "dependabot_security_updates",
+        ];
+        for feature in self.get_security_features() {
+            if !valid_features.contains(&feature.as_str()) {
+                let error_msg = format!(
+                    "Invalid security feature '{}'. Allowed values are: {}",
+                    feature,
+                    valid_features.join(", ")
+                );
+                log::debug!("ERROR: {}", error_msg);
+                return Err(Error::new(ErrorKind::InvalidData, error_msg));
+            }
+        }
+        Ok(())
+    }
+
+    /// License declared by the template variables, if any
+    pub fn get_license(&self) -> Option<&str> {
+        ["license", "project_license"]
+            .iter()
+            .find_map(|key| self.additional_vars.get(*key))
+            .and_then(|v| v.as_str())
+    }
+
+    pub fn get_teams(&self) -> Vec<(String, String)> {
+        self.teams
+            .iter()
+            .flatten()
+            .map(|(team, role)| (team.clone(), role.clone()))
+            .collect()
+    }
+
+    pub fn validate_teams(&self) -> Result<()> {
+        let valid_roles = ["pull", "triage", "push", "maintain", "admin"];
+        for (team, role) in self.get_teams() {
+            if !valid_roles.contains(&role.as_str()) {
+                let error_msg = format!(
+                    "Invalid role '{}' for team '{}'. Allowed values are: {}",
+                    role,
+                    team,
+                    valid_roles.join(", ")
+                );
+                log::debug!("ERROR: {}", error_msg);
                 return Err(Error::new(ErrorKind::InvalidData, error_msg));
             }
-            context::debug_print(&format!("Valid github_tag found: '{}'", tag));
+        }
+        Ok(())
+    }
+
+    pub fn get_git_auth(&self) -> GitAuth {
+        self.git_auth.unwrap_or_default()
+    }
+
+    /// SSH private key path, with a leading `~/` expanded to the home directory
+    pub fn get_ssh_key_path(&self) -> Option<PathBuf> {
+        self.ssh_key_path.as_deref().map(expand_home)
+    }
+
+    /// Initial commit settings from the config file, defaulting to a "first commit" by
+    /// Project Generator
+    pub fn get_commit_settings(&self) -> CommitSettings {
+        let defaults = CommitSettings::default();
+        CommitSettings {
+            message: self.commit_message.clone().unwrap_or(defaults.message),
+            author_name: self
+                .commit_author_name
+                .clone()
+                .unwrap_or(defaults.author_name),
+            author_email: self
+                .commit_author_email
+                .clone()
+                .unwrap_or(defaults.author_email),
+            signing: defaults.signing,
+        }
+    }
+
+    pub fn validate_git_auth(&self) -> Result<()> {
+        if self.get_git_auth() == GitAuth::SshKey {
+            let key_path = self.get_ssh_key_path().ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    "ssh_key_path is required when git_auth is 'ssh-key'",
+                )
+            })?;
+            if !key_path.exists() {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("SSH key not found: {}", key_path.display()),
+                ));
+            }
         }
         Ok(())
     }
@@ -71,10 +244,10 @@ impl FileConfig {
     }
 
     pub fn to_variables(&self) -> std::collections::HashMap<String, String> {
-        context::debug_print("Converting config to variables");
-        context::debug_print(&format!("Project name: '{}'", self.project_name));
-        context::debug_print(&format!("Package name: '{}'", self.name));
-        context::debug_print(&format!("Additional variables: {:?}", self.additional_vars));
+        log::debug!("Converting config to variables");
+        log::debug!("Project name: '{}'", self.project_name);
+        log::debug!("Package name: '{}'", self.name);
+        log::debug!("Additional variables: {:?}", self.additional_vars);
 
         // Convert serde_json::Value to String for additional variables
         let mut vars: HashMap<String, String> = self
@@ -94,7 +267,7 @@ impl FileConfig {
                             })
                             .collect::<Vec<String>>()
                             .join(",")
-                    },
+                    }
                     _ => v.to_string().trim_matches('"').to_string(),
                 };
                 (k.clone(), value_str)
@@ -104,6 +277,9 @@ impl FileConfig {
         // Add required variables in specific order
         vars.insert("project_name".to_string(), self.project_name.clone());
         vars.insert("name".to_string(), self.name.clone());
+        if let Some(team) = &self.team {
+            vars.insert("team".to_string(), team.clone());
+        }
 
         // Sort variables according to template_config.json
         let mut sorted_vars = HashMap::new();
@@ -125,14 +301,14 @@ impl FileConfig {
             }
         }
 
-        context::debug_print(&format!("Final variables: {:?}", sorted_vars));
+        log::debug!("Final variables: {:?}", sorted_vars);
         sorted_vars
     }
 }
 
 pub fn from_file<P: AsRef<Path>>(path: P) -> Result<FileConfig> {
     let path_ref = path.as_ref();
-    context::debug_print(&format!("Reading config file: {}", path_ref.display()));
+    log::debug!("Reading config file: {}", path_ref.display());
 
     let content = fs::read_to_string(path_ref).map_err(|e| {
         Error::new(
@@ -141,11 +317,11 @@ pub fn from_file<P: AsRef<Path>>(path: P) -> Result<FileConfig> {
         )
     })?;
 
-    context::debug_print(&format!("Config file size: {} bytes", content.len()));
+    log::debug!("Config file size: {} bytes", content.len());
 
     // Check if file is empty
     if content.trim().is_empty() {
-        context::debug_print("ERROR: Configuration file is empty");
+        log::debug!("ERROR: Configuration file is empty");
         return Err(Error::new(
             ErrorKind::InvalidData,
             "Configuration file is empty. Please add configuration content.",
@@ -153,22 +329,30 @@ pub fn from_file<P: AsRef<Path>>(path: P) -> Result<FileConfig> {
     }
 
     // Try YAML first, then JSON
-    context::debug_print("Attempting YAML parsing");
+    log::debug!("Attempting YAML parsing");
     if let Ok(config) = serde_yaml::from_str::<FileConfig>(&content) {
-        context::debug_print("Successfully parsed YAML config");
+        log::debug!("Successfully parsed YAML config");
         Ok(config)
     } else {
-        context::debug_print("YAML parsing failed, attempting JSON parsing");
+        log::debug!("YAML parsing failed, attempting JSON parsing");
         let config: FileConfig = serde_json::from_str(&content).map_err(|e| {
             let error_msg = if content.trim().is_empty() {
                 "Configuration file is empty".to_string()
             } else {
                 format!("Failed to parse config file (neither YAML nor JSON): {}", e)
             };
-            context::debug_print(&format!("ERROR: {}", error_msg));
+            log::debug!("ERROR: {}", error_msg);
             Error::new(ErrorKind::InvalidData, error_msg)
         })?;
-        context::debug_print("Successfully parsed JSON config");
+        log::debug!("Successfully parsed JSON config");
         Ok(config)
     }
 }
+
+/// A path with a leading `~/` expanded to the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
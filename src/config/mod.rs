@@ -1,3 +1,6 @@
+pub mod answers;
+pub mod credentials;
+pub mod environment;
 pub mod file_config;
 
 pub const PACKAGE_ROOT_PATH: &str = env!("CARGO_MANIFEST_DIR");
@@ -7,12 +10,14 @@ pub const REPO_URL: &str = "https://github.com/NextNodeSolutions";
 pub const TEMPLATE_REPO_URL: &str = "/utils_project-templates.git";
 pub const TEMPLATE_BRANCH: &str = "main";
 pub const TEMPLATE_CATEGORIES: &[&str] = &["apps", "packages", "utils"];
+/// Feature layers composed into templates with `includes`, not listed as templates
+pub const TEMPLATE_LAYERS_DIR: &str = "layers";
 
 pub const TEMPLATE_CONFIG_FILE: &str = "template_config.json";
 pub const EXCLUDED_DIRS: &[&str] = &["node_modules", ".next", ".turbo", "dist", "build", "out"];
 pub const EXCLUDED_FILES: &[&str] = &[TEMPLATE_CONFIG_FILE];
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize)]
 pub struct Replacement {
     pub name: String,
     #[serde(default)]
@@ -23,6 +28,22 @@ pub struct Replacement {
     pub type_: String,
     #[serde(default)]
     pub default: Option<String>,
+    /// Ask for the variable when it has no value, instead of skipping the replacement
+    #[serde(default)]
+    pub required: bool,
+    /// Question shown when asking for the variable
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Allowed values, picked from when asking for the variable
+    #[serde(default)]
+    pub choices: Vec<String>,
+    /// Regular expression the whole value must match
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub min_length: Option<usize>,
+    #[serde(default)]
+    pub max_length: Option<usize>,
 }
 
 fn default_type() -> String {
@@ -36,3 +57,820 @@ pub struct TemplateConfig {
 }
 
 pub type TemplateJson = Vec<TemplateConfig>;
+
+/// How the initial push authenticates against GitHub
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GitAuth {
+    /// HTTPS remote with the GitHub token
+    #[default]
+    Token,
+    /// SSH remote with a key from the running ssh-agent
+    SshAgent,
+    /// SSH remote with a private key file (`ssh_key_path`)
+    SshKey,
+}
+
+/// Signature format of the generated commits
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SigningFormat {
+    /// OpenPGP signature made with `gpg`
+    Gpg,
+    /// SSH signature made with `ssh-keygen -Y sign`
+    Ssh,
+}
+
+impl SigningFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SigningFormat::Gpg => "gpg",
+            SigningFormat::Ssh => "ssh",
+        }
+    }
+}
+
+/// Message and author of the initial commit, and the signing of every commit
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitSettings {
+    pub message: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub signing: Option<crate::utils::commit::Signing>,
+}
+
+impl Default for CommitSettings {
+    fn default() -> Self {
+        Self {
+            message: "first commit".to_string(),
+            author_name: "Project Generator".to_string(),
+            author_email: "generator@nextnode.dev".to_string(),
+            signing: None,
+        }
+    }
+}
+
+/// Who can see the created repository
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    #[default]
+    Public,
+    Private,
+    /// Visible to every member of the enterprise (GitHub Enterprise organizations only)
+    Internal,
+}
+
+impl Visibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Private => "private",
+            Visibility::Internal => "internal",
+        }
+    }
+}
+
+/// Branch protection rules applied to a branch after the initial push
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct BranchProtection {
+    #[serde(default)]
+    pub required_approving_review_count: Option<u32>,
+    #[serde(default)]
+    pub dismiss_stale_reviews: bool,
+    #[serde(default)]
+    pub require_code_owner_reviews: bool,
+    #[serde(default)]
+    pub required_status_checks: Vec<String>,
+    #[serde(default)]
+    pub strict_status_checks: bool,
+    #[serde(default)]
+    pub enforce_admins: bool,
+    #[serde(default)]
+    pub allow_force_pushes: bool,
+    #[serde(default)]
+    pub allow_deletions: bool,
+}
+
+/// Merge queue required on `main`, set up as a repository ruleset
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct MergeQueue {
+    /// Checks that must pass on the queued changes, e.g. `ci / test`. The workflows reporting
+    /// them must run on `merge_group`.
+    #[serde(default)]
+    pub required_checks: Vec<String>,
+    #[serde(default)]
+    pub merge_method: MergeMethod,
+    /// Most pull requests merged together
+    #[serde(default = "default_max_entries_to_merge")]
+    pub max_entries_to_merge: u32,
+    /// Most pull requests whose checks run at the same time
+    #[serde(default = "default_max_entries_to_build")]
+    pub max_entries_to_build: u32,
+    /// Minutes to wait for more pull requests before merging a smaller group
+    #[serde(default = "default_min_entries_to_merge_wait_minutes")]
+    pub min_entries_to_merge_wait_minutes: u32,
+    /// Minutes after which a required check that has not reported fails
+    #[serde(default = "default_check_response_timeout_minutes")]
+    pub check_response_timeout_minutes: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeMethod {
+    Merge,
+    #[default]
+    Squash,
+    Rebase,
+}
+
+impl MergeMethod {
+    /// Value of the merge queue rule parameter
+    pub fn as_api_str(&self) -> &'static str {
+        match self {
+            MergeMethod::Merge => "MERGE",
+            MergeMethod::Squash => "SQUASH",
+            MergeMethod::Rebase => "REBASE",
+        }
+    }
+}
+
+fn default_max_entries_to_merge() -> u32 {
+    5
+}
+
+fn default_max_entries_to_build() -> u32 {
+    5
+}
+
+fn default_min_entries_to_merge_wait_minutes() -> u32 {
+    5
+}
+
+fn default_check_response_timeout_minutes() -> u32 {
+    60
+}
+
+/// Repository settings a template ships with, applied in remote mode
+#[derive(Default, serde::Deserialize)]
+pub struct TemplateRepositorySettings {
+    /// Default visibility of repositories generated from the template
+    #[serde(default)]
+    pub visibility: Option<Visibility>,
+    /// Topics added to repositories generated from the template, rendered with the variables
+    #[serde(default)]
+    pub topics: Vec<String>,
+    /// Repository description rendered with the variables, e.g.
+    /// `Marketing site for {{client}} built with Astro ({{year}})`
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Repository homepage URL rendered with the variables
+    #[serde(default)]
+    pub homepage: Option<String>,
+    /// Branches created from `main` after the initial push, e.g. `develop`
+    #[serde(default)]
+    pub branches: Vec<String>,
+    /// Protection rules keyed by branch name (e.g. `main`, `develop`)
+    #[serde(default)]
+    pub branch_protection: indexmap::IndexMap<String, BranchProtection>,
+    #[serde(default)]
+    pub merge_queue: Option<MergeQueue>,
+    /// Actions secrets the generated workflows need, e.g. `DEPLOY_TOKEN`
+    #[serde(default)]
+    pub secrets: Vec<SecretDeclaration>,
+    /// Repositories added as git submodules before the initial commit
+    #[serde(default)]
+    pub submodules: Vec<SubmoduleDeclaration>,
+    /// Deployment environments keyed by name (e.g. `development`, `production`)
+    #[serde(default)]
+    pub environments: indexmap::IndexMap<String, EnvironmentSettings>,
+    /// Workflows dispatched once the repository is set up. When omitted, `deploy-dev.yml` on
+    /// `develop` and `deploy-prod.yml` on `main` are dispatched if the project ships both.
+    #[serde(default)]
+    pub deployments: Option<Vec<Deployment>>,
+}
+
+/// A deployment workflow dispatched on a branch of the created repository
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Deployment {
+    /// Workflow file name in `.github/workflows`, e.g. `deploy-prod.yml`
+    pub workflow: String,
+    pub branch: String,
+    /// Workflow dispatched on the same branch when the deployment fails, with
+    /// `--wait-for-workflows`
+    #[serde(default)]
+    pub rollback_workflow: Option<String>,
+    /// Cancel the queued and in-progress runs of the deployment workflow on its branch when
+    /// the deployment fails
+    #[serde(default)]
+    pub cancel_on_failure: bool,
+    /// Inputs sent with the dispatch, e.g. `"environment": "production"`; `{{variable}}`
+    /// placeholders are replaced
+    #[serde(default)]
+    pub inputs: indexmap::IndexMap<String, String>,
+}
+
+impl Deployment {
+    fn new(workflow: &str, branch: &str) -> Self {
+        Deployment {
+            workflow: workflow.to_string(),
+            branch: branch.to_string(),
+            rollback_workflow: None,
+            cancel_on_failure: false,
+            inputs: indexmap::IndexMap::new(),
+        }
+    }
+
+    /// Deployments of templates that do not declare any: the dev and prod workflows when the
+    /// project ships both, otherwise a single `deploy.yml` on `main`
+    pub fn detect(project_path: &std::path::Path) -> Vec<Deployment> {
+        let workflows = project_path.join(".github/workflows");
+        if workflows.join("deploy-dev.yml").exists() && workflows.join("deploy-prod.yml").exists() {
+            vec![
+                Deployment::new("deploy-dev.yml", "develop"),
+                Deployment::new("deploy-prod.yml", "main"),
+            ]
+        } else if workflows.join("deploy.yml").exists() {
+            vec![Deployment::new("deploy.yml", "main")]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Protection rules and secrets of a deployment environment
+#[derive(Clone, Default, serde::Deserialize)]
+pub struct EnvironmentSettings {
+    /// Users whose approval is required before deploying
+    #[serde(default)]
+    pub reviewers: Vec<String>,
+    /// Team slugs whose approval is required before deploying
+    #[serde(default)]
+    pub team_reviewers: Vec<String>,
+    /// Minutes to wait before a deployment proceeds
+    #[serde(default)]
+    pub wait_timer: Option<u32>,
+    /// Only allow deployments from protected branches
+    #[serde(default)]
+    pub protected_branches_only: bool,
+    #[serde(default)]
+    pub secrets: Vec<SecretDeclaration>,
+}
+
+/// A git submodule pinned to a commit, e.g. a shared design-system or infra repository
+#[derive(Clone, serde::Deserialize)]
+pub struct SubmoduleDeclaration {
+    /// Path of the submodule relative to the project root
+    pub path: String,
+    pub url: String,
+    /// Commit the submodule is pinned to (a tag or branch is resolved to its current commit)
+    pub commit: String,
+}
+
+/// A repository secret required by the template, read from the environment variable
+/// of the same name or prompted for
+#[derive(Clone, serde::Deserialize)]
+pub struct SecretDeclaration {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Provenance headers injected into generated files
+#[derive(Default, serde::Deserialize)]
+pub struct ProvenanceConfig {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Who owns a generated file, and therefore how a template upgrade treats it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileOwnership {
+    /// Owned by the template: overwritten on upgrade, never edited in the project
+    Managed,
+    /// Created by the template, then owned by the project: upgrades are 3-way merged
+    Seeded,
+    /// Owned by the project: never touched by upgrades
+    User,
+}
+
+/// Paths (or directory prefixes ending with `/`) per ownership class, anything else is `user`
+#[derive(Default, serde::Deserialize)]
+pub struct OwnershipRules {
+    #[serde(default)]
+    pub managed: Vec<String>,
+    #[serde(default)]
+    pub seeded: Vec<String>,
+    /// Merge driver per path, directory (ending with `/`) or `*.<extension>`, overriding the
+    /// driver picked from the file extension
+    #[serde(default)]
+    pub merge_drivers: indexmap::IndexMap<String, MergeDriver>,
+}
+
+/// How seeded files are 3-way merged on upgrade
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeDriver {
+    /// Line by line with `git merge-file`
+    Text,
+    /// Key by key, for files a line merge breaks or conflicts on
+    Json,
+    Yaml,
+}
+
+/// Whether a project-relative path is one of `patterns`, or inside one ending with `/`
+pub fn path_matches(relative_path: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|pattern| {
+        relative_path == pattern
+            || (pattern.ends_with('/') && relative_path.starts_with(pattern.as_str()))
+    })
+}
+
+impl OwnershipRules {
+    /// The declared merge driver of a file, else JSON for `.json`, YAML for `.yml` and
+    /// `.yaml`, and text otherwise
+    pub fn merge_driver(&self, relative_path: &str) -> MergeDriver {
+        let extension = relative_path.rsplit_once('.').map(|(_, ext)| ext);
+        for (pattern, driver) in &self.merge_drivers {
+            let matches = match pattern.strip_prefix("*.") {
+                Some(pattern_extension) => extension == Some(pattern_extension),
+                None => path_matches(relative_path, std::slice::from_ref(pattern)),
+            };
+            if matches {
+                return *driver;
+            }
+        }
+        match extension {
+            Some("json") => MergeDriver::Json,
+            Some("yml" | "yaml") => MergeDriver::Yaml,
+            _ => MergeDriver::Text,
+        }
+    }
+
+    /// Classify a path relative to the project root
+    pub fn classify(&self, relative_path: &str) -> FileOwnership {
+        if path_matches(relative_path, &self.managed) {
+            FileOwnership::Managed
+        } else if path_matches(relative_path, &self.seeded) {
+            FileOwnership::Seeded
+        } else {
+            FileOwnership::User
+        }
+    }
+}
+
+/// Optimization applied to a set of template images
+#[derive(serde::Deserialize)]
+pub struct ImageRule {
+    /// Paths (or directory prefixes ending with `/`) the rule applies to
+    pub paths: Vec<String>,
+    /// Downscale wider images to this width, keeping the aspect ratio
+    #[serde(default)]
+    pub max_width: Option<u32>,
+    /// Compression quality (1-100)
+    #[serde(default)]
+    pub quality: Option<u8>,
+    /// Formats generated next to the original, e.g. `webp`, `avif`
+    #[serde(default)]
+    pub formats: Vec<String>,
+    /// Delete the original once converted to `formats`
+    #[serde(default)]
+    pub replace_original: bool,
+}
+
+/// A frontmatter field of a content collection
+#[derive(serde::Deserialize)]
+pub struct ContentField {
+    /// Column or key read from the source, defaults to the field name
+    #[serde(default)]
+    pub source: Option<String>,
+    /// `string` (default), `number`, `boolean`, `list` or `date`
+    #[serde(rename = "type", default = "default_content_field_type")]
+    pub type_: String,
+    #[serde(default)]
+    pub required: bool,
+}
+
+fn default_content_field_type() -> String {
+    "string".to_string()
+}
+
+/// A content collection seeded from an external source at generation time
+#[derive(serde::Deserialize)]
+pub struct ContentCollection {
+    /// Variable holding the source: a CSV file (e.g. a Notion database export),
+    /// a JSON file or an http(s) URL returning JSON
+    pub source_variable: String,
+    /// Directory receiving one file per entry, relative to the project root
+    pub directory: String,
+    /// Field whose value names each file
+    pub slug_field: String,
+    /// Field written as the file body, all other fields go into the frontmatter
+    #[serde(default)]
+    pub body_field: Option<String>,
+    #[serde(default = "default_content_extension")]
+    pub extension: String,
+    /// Dot-separated path to the entries array in a JSON response, e.g. `data.items`
+    #[serde(default)]
+    pub items_path: Option<String>,
+    /// Environment variable holding a bearer token for the API
+    #[serde(default)]
+    pub token_env: Option<String>,
+    pub fields: indexmap::IndexMap<String, ContentField>,
+}
+
+fn default_content_extension() -> String {
+    "md".to_string()
+}
+
+/// Internal service allocating a unique hostname per environment of a generated site
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct HostnameService {
+    /// Endpoint receiving a POST with the organization, repository, project and environments
+    pub url: String,
+    /// Environment variable holding a bearer token for the service
+    #[serde(default)]
+    pub token_env: Option<String>,
+    #[serde(default = "default_hostname_environments")]
+    pub environments: Vec<String>,
+}
+
+fn default_hostname_environments() -> Vec<String> {
+    vec!["dev".to_string(), "prod".to_string()]
+}
+
+/// How to build and serve the generated site for the quality baseline
+#[derive(serde::Deserialize)]
+pub struct QualityCheck {
+    #[serde(default = "default_build_command")]
+    pub build_command: String,
+    /// Long-running command serving the built site, e.g. `pnpm preview --port 4173`
+    pub serve_command: String,
+    /// URL audited once the server answers
+    pub url: String,
+    /// Lighthouse categories to score
+    #[serde(default = "default_quality_categories")]
+    pub categories: Vec<String>,
+}
+
+fn default_build_command() -> String {
+    "pnpm build".to_string()
+}
+
+fn default_quality_categories() -> Vec<String> {
+    ["performance", "accessibility", "best-practices", "seo"]
+        .iter()
+        .map(|c| c.to_string())
+        .collect()
+}
+
+/// How a Python project is installed and tested
+#[derive(serde::Deserialize)]
+pub struct PythonConfig {
+    /// `uv`, `poetry` or `pip`, detected from the template's lockfile when not declared
+    #[serde(default)]
+    pub backend: Option<crate::generate::python::PythonBackend>,
+    /// Install into the project's `.venv`. Without it the dependencies are only locked.
+    #[serde(default = "default_true")]
+    pub venv: bool,
+    #[serde(default = "default_python_version")]
+    pub python_version: String,
+    /// Run by the generated CI workflow after the install, e.g. `pytest`
+    #[serde(default)]
+    pub test_command: Option<String>,
+}
+
+impl Default for PythonConfig {
+    fn default() -> Self {
+        Self {
+            backend: None,
+            venv: true,
+            python_version: default_python_version(),
+            test_command: None,
+        }
+    }
+}
+
+fn default_python_version() -> String {
+    "3.12".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Release pipeline: a release tag is built, then deployed once its environment approves
+#[derive(serde::Deserialize)]
+pub struct ReleaseFlow {
+    /// Tags starting a release
+    #[serde(default = "default_release_tag_pattern")]
+    pub tag_pattern: String,
+    #[serde(default = "default_build_command")]
+    pub build_command: String,
+    /// Environment gating the deployment
+    #[serde(default = "default_release_environment")]
+    pub environment: String,
+    /// Workflow dispatched on the tag once the deployment is approved
+    #[serde(default = "default_release_deploy_workflow")]
+    pub deploy_workflow: String,
+    /// Approval rules, unless the environment is declared in `repository.environments`
+    #[serde(default)]
+    pub reviewers: Vec<String>,
+    #[serde(default)]
+    pub team_reviewers: Vec<String>,
+    #[serde(default)]
+    pub wait_timer: Option<u32>,
+}
+
+fn default_release_tag_pattern() -> String {
+    "v*".to_string()
+}
+
+fn default_release_environment() -> String {
+    "production".to_string()
+}
+
+fn default_release_deploy_workflow() -> String {
+    "deploy-prod.yml".to_string()
+}
+
+/// A CODEOWNERS line; `{{variable}}` placeholders are replaced from the template variables
+#[derive(serde::Deserialize)]
+pub struct CodeownersRule {
+    /// Path pattern, e.g. `*` or `/infra/`
+    pub pattern: String,
+    /// Owners, e.g. `@{{organization}}/{{team}}`
+    pub owners: Vec<String>,
+}
+
+/// Local script replaying the `run` steps of the generated CI workflows
+#[derive(serde::Deserialize)]
+pub struct CiScript {
+    #[serde(default = "default_ci_script_path")]
+    pub path: String,
+    /// Workflow files to mirror, defaults to those triggered by push or pull_request
+    #[serde(default)]
+    pub workflows: Vec<String>,
+}
+
+fn default_ci_script_path() -> String {
+    "scripts/ci-local.sh".to_string()
+}
+
+/// Workflows generated as thin callers of the organization's reusable workflows, when the
+/// `reusable_workflows` variable is set
+#[derive(serde::Deserialize)]
+pub struct ReusableWorkflows {
+    /// Repository holding the reusable workflows
+    #[serde(default = "default_reusable_workflows_repository")]
+    pub repository: String,
+    /// Tag or branch the callers pin
+    #[serde(rename = "ref", default = "default_reusable_workflows_ref")]
+    pub ref_: String,
+    /// Callers keyed by their file name in `.github/workflows`, e.g. `ci.yml`
+    pub workflows: indexmap::IndexMap<String, ReusableWorkflowCall>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ReusableWorkflowCall {
+    /// Reusable workflow called, defaults to the caller's file name
+    #[serde(default)]
+    pub workflow: Option<String>,
+    /// Triggers, defaults to those of the template's workflow
+    #[serde(default)]
+    pub on: Option<serde_yaml::Value>,
+    /// Inputs of the reusable workflow; `{{variable}}` placeholders are replaced
+    #[serde(default)]
+    pub inputs: indexmap::IndexMap<String, String>,
+    /// Pass the repository secrets along (`secrets: inherit`)
+    #[serde(default = "default_true")]
+    pub inherit_secrets: bool,
+}
+
+fn default_reusable_workflows_repository() -> String {
+    "NextNodeSolutions/workflows".to_string()
+}
+
+fn default_reusable_workflows_ref() -> String {
+    "v1".to_string()
+}
+
+/// How a package generated inside a pnpm workspace is wired into it
+#[derive(Default, serde::Deserialize)]
+pub struct WorkspaceConfig {
+    /// Turbo tasks registered as `<package>#<task>` in the workspace's turbo.json
+    #[serde(default)]
+    pub turbo_tasks: indexmap::IndexMap<String, serde_json::Value>,
+}
+
+/// Object form of template_config.json: file replacements plus repository settings
+#[derive(Default, serde::Deserialize)]
+pub struct TemplateManifest {
+    /// One-line summary shown by `--list-templates`
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Generator versions the template works with, e.g. `>=0.2, <0.4`
+    #[serde(default)]
+    pub generator_version: Option<String>,
+    /// Base template, composed before the template's own files
+    #[serde(default)]
+    pub extends: Option<String>,
+    /// Feature layers composed on top of the base, in order
+    #[serde(default)]
+    pub includes: Vec<String>,
+    #[serde(default)]
+    pub files: TemplateJson,
+    #[serde(default)]
+    pub repository: TemplateRepositorySettings,
+    #[serde(default)]
+    pub provenance: ProvenanceConfig,
+    #[serde(default)]
+    pub ownership: OwnershipRules,
+    /// Image optimization rules, the first matching rule applies to each image
+    #[serde(default)]
+    pub images: Vec<ImageRule>,
+    /// Content collections keyed by name (e.g. `blog`, `products`)
+    #[serde(default)]
+    pub content: indexmap::IndexMap<String, ContentCollection>,
+    #[serde(default)]
+    pub quality: Option<QualityCheck>,
+    /// Rules rendered into .github/CODEOWNERS
+    #[serde(default)]
+    pub codeowners: Vec<CodeownersRule>,
+    #[serde(default)]
+    pub ci_script: Option<CiScript>,
+    #[serde(default)]
+    pub reusable_workflows: Option<ReusableWorkflows>,
+    #[serde(default)]
+    pub release: Option<ReleaseFlow>,
+    #[serde(default)]
+    pub workspace: WorkspaceConfig,
+    #[serde(default)]
+    pub substitution: Option<SubstitutionConfig>,
+    /// Interactive prompts, asked page by page before the remaining variables
+    #[serde(default)]
+    pub prompts: Vec<PromptPage>,
+    /// Variables derived from the others, e.g. `"project_slug": "{{slugify project_name}}"`
+    #[serde(default)]
+    pub computed: indexmap::IndexMap<String, String>,
+    #[serde(default)]
+    pub i18n: Option<I18nConfig>,
+    #[serde(default)]
+    pub hooks: Hooks,
+    /// `pnpm`, `npm`, `yarn` or `bun`, detected from the template lockfile when not declared
+    #[serde(default)]
+    pub package_manager: Option<crate::generate::package_manager::PackageManager>,
+    /// `node`, `rust` or `python`
+    #[serde(default)]
+    pub project_kind: crate::generate::project_kind::ProjectKind,
+    /// Install backend and CI of `python` projects
+    #[serde(default)]
+    pub python: PythonConfig,
+}
+
+/// Shell commands run at each stage of the generation, with the variables exported as `GEN_`
+/// environment variables
+#[derive(Clone, Default, serde::Deserialize)]
+pub struct Hooks {
+    /// Run in the empty project directory, before the template is copied
+    #[serde(default)]
+    pub pre_generate: Vec<String>,
+    /// Run in the generated project, before dependencies are installed
+    #[serde(default)]
+    pub post_generate: Vec<String>,
+    /// Run in the generated project before the initial push (remote mode)
+    #[serde(default)]
+    pub pre_push: Vec<String>,
+    /// Run in the generated project once it is pushed (remote mode)
+    #[serde(default)]
+    pub post_push: Vec<String>,
+}
+
+/// Per-locale scaffolding generated from the selected locales when i18n is enabled
+#[derive(serde::Deserialize)]
+pub struct I18nConfig {
+    /// Condition enabling the scaffolding, written like an `{{#if}}` argument
+    #[serde(default = "default_i18n_when")]
+    pub when: String,
+    /// Variable holding the comma-separated locales, e.g. `en,fr,de`
+    #[serde(default = "default_locales_variable")]
+    pub locales_variable: String,
+    /// Variable holding the default locale, the first locale when unset
+    #[serde(default = "default_locale_variable")]
+    pub default_locale_variable: String,
+    /// Variable holding the site URL used for absolute hreflang links
+    #[serde(default = "default_site_url_variable")]
+    pub site_url_variable: String,
+    /// Directory of content stubs copied once per locale, removed from the project afterwards
+    #[serde(default)]
+    pub stubs: Option<String>,
+    /// Directory receiving the stubs of each locale under `<content_dir>/<locale>`
+    #[serde(default = "default_i18n_content_dir")]
+    pub content_dir: String,
+    /// JSON file receiving the locales, routes, hreflang links and sitemap configuration
+    #[serde(default = "default_i18n_output")]
+    pub output: String,
+}
+
+fn default_i18n_when() -> String {
+    "i18n".to_string()
+}
+
+fn default_locales_variable() -> String {
+    "locales".to_string()
+}
+
+fn default_locale_variable() -> String {
+    "default_locale".to_string()
+}
+
+fn default_site_url_variable() -> String {
+    "site_url".to_string()
+}
+
+fn default_i18n_content_dir() -> String {
+    "src/content".to_string()
+}
+
+fn default_i18n_output() -> String {
+    "src/i18n/locales.json".to_string()
+}
+
+/// `{{variable}}` placeholders replaced in every text file of the generated project
+#[derive(serde::Deserialize)]
+pub struct SubstitutionConfig {
+    /// Globs of the files to process, relative to the project root
+    #[serde(default = "default_substitution_include")]
+    pub include: Vec<String>,
+    /// Globs of the files left untouched, checked after `include`
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+fn default_substitution_include() -> Vec<String> {
+    vec!["**".to_string()]
+}
+
+/// A page of related interactive prompts (e.g. project info, deployment, features)
+#[derive(serde::Deserialize)]
+pub struct PromptPage {
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Condition written like an `{{#if}}` argument, e.g. `(ne cms "none")`
+    #[serde(default)]
+    pub when: Option<String>,
+    pub variables: Vec<PromptVariable>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct PromptVariable {
+    pub name: String,
+    /// Question shown instead of "Enter value for <name>:"
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub default: Option<String>,
+    /// Values to pick from instead of free text
+    #[serde(default)]
+    pub choices: Vec<String>,
+    /// Hide the input (tokens, passwords)
+    #[serde(default)]
+    pub secret: bool,
+    /// Condition on the previous answers for asking this question. When false, the variable
+    /// is omitted from the generation context, even if a value was provided.
+    #[serde(default)]
+    pub when: Option<String>,
+    /// Refuse an empty value when the variable is active
+    #[serde(default)]
+    pub required: bool,
+    /// Regular expression the whole value must match when the variable is active
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub min_length: Option<usize>,
+    #[serde(default)]
+    pub max_length: Option<usize>,
+}
+
+/// template_config.json is either the legacy list of file configurations or a manifest object
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+pub enum TemplateConfigFile {
+    Legacy(TemplateJson),
+    Manifest(Box<TemplateManifest>),
+}
+
+impl From<TemplateConfigFile> for TemplateManifest {
+    fn from(file: TemplateConfigFile) -> Self {
+        match file {
+            TemplateConfigFile::Legacy(files) => TemplateManifest {
+                files,
+                ..Default::default()
+            },
+            TemplateConfigFile::Manifest(manifest) => *manifest,
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use serde_yaml::Value;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+use crate::config::CiScript;
+use crate::generate::workflows::{trigger_events, triggers, WORKFLOWS_DIR};
+
+/// Workflows run on every push or pull request, i.e. the ones developers want to reproduce
+fn is_ci_workflow(workflow: &Value) -> bool {
+    triggers(workflow).is_some_and(|triggers| {
+        trigger_events(triggers)
+            .iter()
+            .any(|event| *event == "push" || *event == "pull_request")
+    })
+}
+
+fn selected_workflows(project_path: &Path, script: &CiScript) -> Result<Vec<(String, Value)>> {
+    let dir = project_path.join(WORKFLOWS_DIR);
+    let mut files: Vec<String> = if script.workflows.is_empty() {
+        match fs::read_dir(&dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| name.ends_with(".yml") || name.ends_with(".yaml"))
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    } else {
+        script.workflows.clone()
+    };
+    files.sort();
+
+    let mut workflows = Vec::new();
+    for file in files {
+        let content = fs::read_to_string(dir.join(&file)).map_err(|e| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("Failed to read workflow {}: {}", file, e),
+            )
+        })?;
+        let workflow: Value = serde_yaml::from_str(&content).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Failed to parse workflow {}: {}", file, e),
+            )
+        })?;
+        if script.workflows.is_empty() && !is_ci_workflow(&workflow) {
+            log::debug!("Skipping non-CI workflow: {}", file);
+            continue;
+        }
+        workflows.push((file, workflow));
+    }
+    Ok(workflows)
+}
+
+fn render_job(script: &mut String, name: &str, job: &Value) {
+    script.push_str(&format!("\nsection \"{}\"\n", name));
+
+    let steps = job.get("steps").and_then(Value::as_sequence);
+    for step in steps.into_iter().flatten() {
+        let label = step.get("name").and_then(Value::as_str);
+        if let Some(action) = step.get("uses").and_then(Value::as_str) {
+            script.push_str(&format!("# skipped action: {}\n", label.unwrap_or(action)));
+            continue;
+        }
+        let Some(run) = step.get("run").and_then(Value::as_str) else {
+            continue;
+        };
+
+        if let Some(label) = label {
+            script.push_str(&format!("# {}\n", label));
+        }
+        let working_directory = step.get("working-directory").and_then(Value::as_str);
+        // GitHub expressions only exist on the runner, keep them visible but inactive
+        let needs_runner = run.contains("${{");
+        let mut command = run.trim_end().to_string();
+        if let Some(dir) = working_directory {
+            command = format!("(cd {} && {})", dir, command);
+        }
+        if needs_runner {
+            script.push_str("# needs GitHub Actions expressions, run manually:\n");
+            for line in command.lines() {
+                script.push_str(&format!("# {}\n", line));
+            }
+        } else {
+            script.push_str(&command);
+            script.push('\n');
+        }
+    }
+}
+
+/// Write a shell script replaying the CI workflows' `run` steps, job by job
+pub fn write_ci_script(project_path: &Path, script: &CiScript) -> Result<()> {
+    let workflows = selected_workflows(project_path, script)?;
+    if workflows.is_empty() {
+        log::info!("ℹ️  No CI workflows found, skipping {}", script.path);
+        return Ok(());
+    }
+
+    let mut content = String::from(
+        "#!/usr/bin/env sh\n\
+         # Reproduces the CI workflows locally. Regenerated from .github/workflows by the template.\n\
+         set -e\n\
+         cd \"$(dirname \"$0\")/..\"\n\
+         \n\
+         section() {\n  printf '\\n==> %s\\n' \"$1\"\n}\n",
+    );
+    for (file, workflow) in &workflows {
+        content.push_str(&format!("\n# Workflow: {}\n", file));
+        let jobs = workflow.get("jobs").and_then(Value::as_mapping);
+        for (name, job) in jobs.into_iter().flatten() {
+            let name = name.as_str().unwrap_or("job");
+            render_job(&mut content, &format!("{} / {}", file, name), job);
+        }
+    }
+
+    let path = project_path.join(&script.path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, content)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    log::info!(
+        "✅ Generated {} from {} workflows",
+        script.path,
+        workflows.len()
+    );
+    Ok(())
+}
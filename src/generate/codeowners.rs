@@ -0,0 +1,85 @@
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+use crate::config::CodeownersRule;
+use crate::generate::render;
+use crate::github::extract_organization_from_repo_url;
+use crate::utils::context;
+
+pub const CODEOWNERS_PATH: &str = ".github/CODEOWNERS";
+
+/// Value of a `{{variable}}` placeholder, `organization` defaulting to the templates organization
+fn variable(name: &str) -> Option<String> {
+    context::get_variable(name).or_else(|| match name {
+        "organization" => extract_organization_from_repo_url().ok(),
+        _ => None,
+    })
+}
+
+fn render(input: &str) -> Result<String> {
+    let replaced = render::replace_placeholders(input, variable);
+    if let Some(name) = replaced.missing.first() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("CODEOWNERS uses undefined variable '{}'", name),
+        ));
+    }
+    if let Some((placeholder, filter)) = replaced.unknown_filters.first() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "CODEOWNERS uses unknown filter '{}' in {}",
+                filter, placeholder
+            ),
+        ));
+    }
+    Ok(replaced.text)
+}
+
+pub fn write_codeowners(project_path: &Path, rules: &[CodeownersRule]) -> Result<()> {
+    let mut content = String::from("# Generated from the template configuration\n");
+    for rule in rules {
+        let owners = rule
+            .owners
+            .iter()
+            .map(|owner| render(owner))
+            .collect::<Result<Vec<String>>>()?;
+        content.push_str(&format!(
+            "{} {}\n",
+            render(&rule.pattern)?,
+            owners.join(" ")
+        ));
+    }
+
+    let path = project_path.join(CODEOWNERS_PATH);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, content)?;
+    log::info!(
+        "✅ Generated {} with {} rules",
+        CODEOWNERS_PATH,
+        rules.len()
+    );
+    Ok(())
+}
+
+/// Teams (`org/slug`) referenced as owners in the project's CODEOWNERS file
+pub fn referenced_teams(project_path: &Path) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(project_path.join(CODEOWNERS_PATH)) else {
+        return Vec::new();
+    };
+
+    let mut teams: Vec<String> = content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .flat_map(|line| line.split_whitespace().skip(1))
+        .filter_map(|owner| owner.strip_prefix('@'))
+        .filter(|owner| owner.contains('/'))
+        .map(|owner| owner.to_string())
+        .collect();
+    teams.sort();
+    teams.dedup();
+    teams
+}
@@ -0,0 +1,461 @@
+//! In-place edits of YAML and TOML files, preserving key order, formatting and comments
+//! of the rest of the file. YAML values are rewritten on their own lines, TOML documents are
+//! edited with `toml_edit`.
+
+use serde_json::Value;
+use toml_edit::{DocumentMut, Item};
+
+/// Split a value from its trailing comment, ignoring `#` inside quoted strings
+fn split_comment(value: &str) -> (&str, &str) {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in value.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') if i == 0 || value[..i].ends_with(char::is_whitespace) => {
+                let start = value[..i].trim_end().len();
+                return (&value[..start], &value[start..]);
+            }
+            _ => {}
+        }
+        escaped = false;
+    }
+    (value.trim_end(), "")
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn is_content(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+fn unquote(key: &str) -> &str {
+    let key = key.trim();
+    key.strip_prefix('"')
+        .and_then(|k| k.strip_suffix('"'))
+        .or_else(|| key.strip_prefix('\'').and_then(|k| k.strip_suffix('\'')))
+        .unwrap_or(key)
+}
+
+/// Rebuild the content from its lines, keeping the original trailing newline
+fn join_lines(lines: &[String], original: &str) -> String {
+    let mut content = lines.join("\n");
+    if original.ends_with('\n') {
+        content.push('\n');
+    }
+    content
+}
+
+/// Key and inline value of a mapping line (`key: value`), `None` for other lines
+fn yaml_entry(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('-') || trimmed.starts_with('#') {
+        return None;
+    }
+    let mut quote = None;
+    for (i, c) in trimmed.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') if i == 0 => quote = Some(c),
+            (None, ':') => {
+                let rest = &trimmed[i + 1..];
+                if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+                    return Some((unquote(&trimmed[..i]), rest));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Index after the last line belonging to the block of the key on line `index`
+fn yaml_block_end(lines: &[String], index: usize) -> usize {
+    let indent = indent_of(&lines[index]);
+    let mut end = index + 1;
+    for (i, line) in lines.iter().enumerate().skip(index + 1) {
+        if !is_content(line) {
+            continue;
+        }
+        // Sequences may sit at their parent key's indentation
+        if indent_of(line) < indent
+            || (indent_of(line) == indent && !line.trim_start().starts_with('-'))
+        {
+            break;
+        }
+        end = i + 1;
+    }
+    end
+}
+
+/// Words YAML 1.1 parsers (e.g. older docker-compose) read as booleans
+const YAML_BOOLEAN_WORDS: &[&str] = &["y", "n", "yes", "no", "on", "off"];
+
+/// Whether a string can be written unquoted. Strings not starting with a letter are
+/// quoted, which also keeps values like `8080:80` from being read as sexagesimal numbers.
+fn yaml_plain_safe(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_alphabetic() || c == '/' || c == '.')
+        && !YAML_BOOLEAN_WORDS.contains(&s.to_lowercase().as_str())
+        && s.trim() == s
+        && !s.contains(['\n', '#'])
+        && serde_yaml::from_str::<serde_yaml::Value>(s).ok()
+            == Some(serde_yaml::Value::String(s.to_string()))
+}
+
+fn yaml_value(value: &Value) -> String {
+    match value {
+        Value::String(s) if yaml_plain_safe(s) => s.clone(),
+        Value::String(s) => Value::String(s.clone()).to_string(),
+        Value::Array(items) => format!(
+            "[{}]",
+            items.iter().map(yaml_value).collect::<Vec<_>>().join(", ")
+        ),
+        Value::Object(object) => format!(
+            "{{{}}}",
+            object
+                .iter()
+                .map(|(k, v)| format!(
+                    "{}: {}",
+                    yaml_value(&Value::String(k.clone())),
+                    yaml_value(v)
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        other => other.to_string(),
+    }
+}
+
+fn yaml_key(key: &str) -> String {
+    yaml_value(&Value::String(key.to_string()))
+}
+
+/// Set the value at a key path in a YAML document, adding missing keys
+pub fn set_yaml_value(content: &str, path: &[&str], value: &Value) -> Result<String, String> {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let mut start = 0;
+    let mut end = lines.len();
+    let mut parent_indent: Option<usize> = None;
+
+    for (depth, segment) in path.iter().enumerate() {
+        let level = lines[start..end]
+            .iter()
+            .find(|line| is_content(line))
+            .map(|line| indent_of(line))
+            .filter(|indent| parent_indent.is_none_or(|parent| *indent > parent));
+
+        let found = level.and_then(|level| {
+            (start..end).find(|&i| {
+                indent_of(&lines[i]) == level
+                    && yaml_entry(&lines[i]).is_some_and(|(key, _)| key == *segment)
+            })
+        });
+
+        let Some(index) = found else {
+            // Add the missing keys at the end of the parent block
+            let indent = level.unwrap_or_else(|| parent_indent.map_or(0, |parent| parent + 2));
+            let position = (start..end)
+                .rev()
+                .find(|&i| is_content(&lines[i]))
+                .map_or(start, |i| i + 1);
+            let remaining = &path[depth..];
+            let added = remaining.iter().enumerate().map(|(offset, key)| {
+                let indent = " ".repeat(indent + 2 * offset);
+                if offset + 1 == remaining.len() {
+                    format!("{}{}: {}", indent, yaml_key(key), yaml_value(value))
+                } else {
+                    format!("{}{}:", indent, yaml_key(key))
+                }
+            });
+            lines.splice(position..position, added.collect::<Vec<_>>());
+            return verify_yaml(join_lines(&lines, content), path, value);
+        };
+
+        let block_end = yaml_block_end(&lines, index);
+        let (_, rest) = yaml_entry(&lines[index]).unwrap_or_default();
+        let (inline, comment) = split_comment(rest.trim_start());
+        let inline = inline.to_string();
+        let comment = comment.to_string();
+
+        if depth + 1 == path.len() {
+            let key_end = lines[index].len() - rest.len();
+            let prefix = lines[index][..key_end].to_string();
+            lines[index] = format!("{} {}{}", prefix, yaml_value(value), comment);
+            lines.drain(index + 1..block_end);
+            return verify_yaml(join_lines(&lines, content), path, value);
+        }
+
+        if !inline.is_empty() {
+            return Err(format!("'{}' is not a mapping", path[..=depth].join(".")));
+        }
+        start = index + 1;
+        end = block_end;
+        parent_indent = Some(indent_of(&lines[index]));
+    }
+    Ok(content.to_string())
+}
+
+/// Parse the edited document back, so that an edit never produces invalid YAML
+fn verify_yaml(content: String, path: &[&str], value: &Value) -> Result<String, String> {
+    let document: serde_yaml::Value =
+        serde_yaml::from_str(&content).map_err(|e| format!("edit produced invalid YAML: {}", e))?;
+    let expected = serde_yaml::to_value(value).map_err(|e| e.to_string())?;
+    let actual = path.iter().try_fold(&document, |node, key| node.get(*key));
+    if actual != Some(&expected) {
+        return Err(format!("failed to set '{}'", path.join(".")));
+    }
+    Ok(content)
+}
+
+/// How keys missing from a TOML document are added below an existing one
+#[derive(Clone, Copy)]
+enum TomlContainer {
+    /// `[table]` headers, for keys missing at the root of the document
+    Header,
+    /// Dotted keys inside an existing `[table]`
+    Dotted,
+    /// Keys of an inline table
+    Inline,
+}
+
+fn toml_value(value: &Value) -> Result<toml_edit::Value, String> {
+    Ok(match value {
+        Value::Null => return Err("TOML has no null value".to_string()),
+        Value::Bool(boolean) => (*boolean).into(),
+        Value::Number(number) => match (number.as_i64(), number.as_f64()) {
+            (Some(integer), _) => integer.into(),
+            (None, Some(float)) if number.is_f64() => float.into(),
+            _ => return Err(format!("{} does not fit in a TOML integer", number)),
+        },
+        Value::String(string) => string.as_str().into(),
+        Value::Array(items) => toml_edit::Value::Array(
+            items
+                .iter()
+                .map(toml_value)
+                .collect::<Result<toml_edit::Array, _>>()?,
+        ),
+        Value::Object(object) => toml_edit::Value::InlineTable(
+            object
+                .iter()
+                .map(|(key, value)| Ok((key.as_str(), toml_value(value)?)))
+                .collect::<Result<toml_edit::InlineTable, String>>()?,
+        ),
+    })
+}
+
+/// The item added for a missing key: `value` nested below the `keys` that are missing too
+fn toml_nested(keys: &[&str], value: toml_edit::Value, container: TomlContainer) -> Item {
+    let Some((key, rest)) = keys.split_first() else {
+        return Item::Value(value);
+    };
+    match container {
+        TomlContainer::Inline => {
+            let mut table = toml_edit::InlineTable::new();
+            if let Item::Value(nested) = toml_nested(rest, value, container) {
+                table.insert(*key, nested);
+            }
+            Item::Value(toml_edit::Value::InlineTable(table))
+        }
+        TomlContainer::Header | TomlContainer::Dotted => {
+            let mut table = toml_edit::Table::new();
+            match container {
+                TomlContainer::Dotted => table.set_dotted(true),
+                _ => table.set_implicit(true),
+            }
+            table.insert(key, toml_nested(rest, value, container));
+            Item::Table(table)
+        }
+    }
+}
+
+/// Set the value at a key path in a TOML document, adding missing keys: as dotted keys in
+/// the deepest existing table, or in a new `[table]` at the end of the document
+pub fn set_toml_value(content: &str, path: &[&str], value: &Value) -> Result<String, String> {
+    let value = toml_value(value)?;
+    let mut document: DocumentMut = content
+        .parse()
+        .map_err(|e| format!("invalid TOML: {}", e))?;
+
+    let mut item = document.as_item_mut();
+    for (depth, key) in path.iter().enumerate() {
+        let container = if item.is_inline_table() {
+            TomlContainer::Inline
+        } else if depth == 0 {
+            TomlContainer::Header
+        } else {
+            TomlContainer::Dotted
+        };
+        let table = item
+            .as_table_like_mut()
+            .ok_or_else(|| format!("'{}' is not a table", path[..depth].join(".")))?;
+        if !table.contains_key(key) {
+            table.insert(key, toml_nested(&path[depth + 1..], value, container));
+            // Inline tables are respaced, the new key would follow the padding of the last one
+            if let TomlContainer::Inline = container {
+                table.fmt();
+            }
+            return Ok(document.to_string());
+        }
+
+        let existing = table.get_mut(key).expect("checked above");
+        if depth + 1 == path.len() {
+            // The new value keeps the spacing and trailing comment of the old one
+            let mut value = value;
+            match existing.as_value() {
+                Some(old) => *value.decor_mut() = old.decor().clone(),
+                None => return Err(format!("'{}' is a table", path.join("."))),
+            }
+            *existing = Item::Value(value);
+            return Ok(document.to_string());
+        }
+        item = existing;
+    }
+    Ok(content.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const CARGO_TOML: &str = r#"# Package manifest
+[package]
+name = "demo" # renamed by the generator
+description = """
+A multi-line
+description = "not a key"
+"""
+version = "0.1.0"
+
+[dependencies]
+serde = { version = "1", features = ["derive"] }
+
+[[bin]]
+name = "demo"
+path = "src/main.rs"
+"#;
+
+    #[test]
+    fn sets_toml_values_keeping_comments() {
+        let edited = set_toml_value(CARGO_TOML, &["package", "name"], &json!("app")).unwrap();
+        assert_eq!(
+            edited,
+            CARGO_TOML.replace(r#"name = "demo" # renamed"#, r#"name = "app" # renamed"#)
+        );
+
+        let edited =
+            set_toml_value(CARGO_TOML, &["package", "description"], &json!("Short")).unwrap();
+        assert!(edited.contains("description = \"Short\"\nversion = \"0.1.0\"\n"));
+        assert!(!edited.contains("multi-line"));
+    }
+
+    #[test]
+    fn adds_missing_toml_keys() {
+        let edited =
+            set_toml_value(CARGO_TOML, &["package", "metadata", "docs"], &json!(true)).unwrap();
+        assert!(edited.contains("version = \"0.1.0\"\nmetadata.docs = true\n"));
+
+        let edited = set_toml_value(
+            CARGO_TOML,
+            &["dependencies", "serde", "optional"],
+            &json!(true),
+        )
+        .unwrap();
+        assert!(
+            edited.contains(r#"serde = { version = "1", features = ["derive"], optional = true }"#)
+        );
+
+        let edited =
+            set_toml_value(CARGO_TOML, &["workspace", "members"], &json!(["a", "b"])).unwrap();
+        assert!(
+            edited.ends_with("path = \"src/main.rs\"\n\n[workspace]\nmembers = [\"a\", \"b\"]\n")
+        );
+
+        let edited = set_toml_value("[tool]\n", &["edition"], &json!("2021")).unwrap();
+        assert_eq!(edited, "edition = \"2021\"\n[tool]\n");
+    }
+
+    #[test]
+    fn rejects_toml_paths_through_values_and_arrays_of_tables() {
+        assert!(set_toml_value(CARGO_TOML, &["package", "name", "x"], &json!(1)).is_err());
+        assert!(set_toml_value(CARGO_TOML, &["bin", "name"], &json!("app")).is_err());
+        assert!(set_toml_value(CARGO_TOML, &["package"], &json!("app")).is_err());
+        assert!(set_toml_value(CARGO_TOML, &["package", "x"], &json!(null)).is_err());
+    }
+
+    const COMPOSE_YAML: &str = "services:
+  web:
+    image: node:20 # pinned
+    ports:
+    - \"3000:3000\"
+    environment:
+      NODE_ENV: development
+  db:
+    image: postgres
+# trailing comment
+";
+
+    #[test]
+    fn sets_yaml_values_at_their_indentation() {
+        let edited = set_yaml_value(
+            COMPOSE_YAML,
+            &["services", "db", "image"],
+            &json!("postgres:16"),
+        )
+        .unwrap();
+        assert_eq!(
+            edited,
+            COMPOSE_YAML.replace("image: postgres\n", "image: postgres:16\n")
+        );
+
+        let edited = set_yaml_value(
+            COMPOSE_YAML,
+            &["services", "web", "image"],
+            &json!("node:22"),
+        )
+        .unwrap();
+        assert!(edited.contains("    image: node:22 # pinned\n"));
+
+        // A block value is replaced with its nested lines
+        let edited = set_yaml_value(
+            COMPOSE_YAML,
+            &["services", "web", "ports"],
+            &json!(["8080:80"]),
+        )
+        .unwrap();
+        assert!(edited.contains("    ports: [\"8080:80\"]\n    environment:\n"));
+    }
+
+    #[test]
+    fn adds_missing_yaml_keys_in_their_block() {
+        let edited = set_yaml_value(
+            COMPOSE_YAML,
+            &["services", "web", "environment", "PORT"],
+            &json!(3000),
+        )
+        .unwrap();
+        assert!(edited.contains("      NODE_ENV: development\n      PORT: 3000\n  db:\n"));
+
+        let edited = set_yaml_value(
+            COMPOSE_YAML,
+            &["volumes", "data", "driver"],
+            &json!("local"),
+        )
+        .unwrap();
+        assert!(edited.ends_with(
+            "    image: postgres\nvolumes:\n  data:\n    driver: local\n# trailing comment\n"
+        ));
+
+        assert!(
+            set_yaml_value(COMPOSE_YAML, &["services", "web", "image", "x"], &json!(1)).is_err()
+        );
+    }
+}
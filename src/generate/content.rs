@@ -0,0 +1,279 @@
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+use crate::config::{ContentCollection, ContentField};
+use crate::github::headers::DEFAULT_USER_AGENT;
+use crate::github::{http, trace};
+use crate::utils::context;
+
+/// Seed every collection whose source variable is set
+pub fn seed_collections(
+    project_path: &Path,
+    collections: &IndexMap<String, ContentCollection>,
+) -> Result<()> {
+    for (name, collection) in collections {
+        let Some(source) = context::get_variable(&collection.source_variable) else {
+            log::debug!(
+                "No {} set, skipping content collection '{}'",
+                collection.source_variable,
+                name
+            );
+            continue;
+        };
+
+        let entries = load_entries(&source, collection)?;
+        let directory = project_path.join(&collection.directory);
+        fs::create_dir_all(&directory)?;
+
+        for (index, entry) in entries.iter().enumerate() {
+            let (slug, content) = render_entry(entry, collection).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Content '{}', entry {}: {}", name, index + 1, e),
+                )
+            })?;
+            fs::write(
+                directory.join(format!("{}.{}", slug, collection.extension)),
+                content,
+            )?;
+        }
+
+        log::info!(
+            "📝 Seeded {} entries into content collection '{}'",
+            entries.len(),
+            name
+        );
+    }
+    Ok(())
+}
+
+fn load_entries(source: &str, collection: &ContentCollection) -> Result<Vec<Value>> {
+    log::debug!("Loading content from {}", source);
+
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let body = fetch(source, collection.token_env.as_deref())?;
+        return json_entries(&body, collection.items_path.as_deref());
+    }
+
+    let content = fs::read_to_string(source).map_err(|e| {
+        Error::new(
+            ErrorKind::NotFound,
+            format!("Failed to read content source '{}': {}", source, e),
+        )
+    })?;
+    if source.to_lowercase().ends_with(".csv") {
+        Ok(csv_entries(&content))
+    } else {
+        json_entries(&content, collection.items_path.as_deref())
+    }
+}
+
+/// Generation is synchronous, block on the request from within the runtime
+fn fetch(url: &str, token_env: Option<&str>) -> Result<String> {
+    let token = match token_env {
+        Some(name) => Some(std::env::var(name).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Content API token ${} is not set", name),
+            )
+        })?),
+        None => None,
+    };
+
+    tokio::task::block_in_place(|| {
+        tokio::runtime::Handle::current().block_on(async {
+            let mut request = http::client()
+                .get(url)
+                .header(reqwest::header::USER_AGENT, DEFAULT_USER_AGENT);
+            if let Some(token) = token {
+                request = request.bearer_auth(token);
+            }
+
+            let response = trace::send(request).await.map_err(Error::other)?;
+            if !response.status().is_success() {
+                return Err(Error::other(format!(
+                    "Content API returned {} for {}",
+                    response.status(),
+                    url
+                )));
+            }
+            response.text().await.map_err(Error::other)
+        })
+    })
+}
+
+fn json_entries(content: &str, items_path: Option<&str>) -> Result<Vec<Value>> {
+    let mut value: Value = serde_json::from_str(content)?;
+    if let Some(path) = items_path {
+        for key in path.split('.') {
+            value = value.get_mut(key).map(Value::take).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("No '{}' in content source", path),
+                )
+            })?;
+        }
+    }
+
+    match value {
+        Value::Array(entries) => Ok(entries),
+        _ => Err(Error::new(
+            ErrorKind::InvalidData,
+            "Content source must be an array of entries",
+        )),
+    }
+}
+
+/// Parse CSV with a header row (RFC 4180 quoting) into one JSON object per row
+fn csv_entries(content: &str) -> Vec<Value> {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', _) => in_quotes = !in_quotes,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    let mut rows = rows.into_iter();
+    let Some(header) = rows.next() else {
+        return Vec::new();
+    };
+    rows.filter(|row| row.iter().any(|cell| !cell.is_empty()))
+        .map(|row| {
+            Value::Object(
+                header
+                    .iter()
+                    .map(|column| column.trim().to_string())
+                    .zip(row.into_iter().map(Value::String))
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+fn convert(value: &Value, field: &ContentField) -> std::result::Result<Value, String> {
+    let text = match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Null => return Ok(Value::Null),
+        other => other.to_string(),
+    };
+
+    match field.type_.as_str() {
+        "number" => match value {
+            Value::Number(_) => Ok(value.clone()),
+            _ => text
+                .parse::<i64>()
+                .map(serde_json::Number::from)
+                .ok()
+                .or_else(|| {
+                    text.parse::<f64>()
+                        .ok()
+                        .and_then(serde_json::Number::from_f64)
+                })
+                .map(Value::Number)
+                .ok_or_else(|| format!("'{}' is not a number", text)),
+        },
+        "boolean" => match value {
+            Value::Bool(_) => Ok(value.clone()),
+            _ => Ok(Value::Bool(matches!(
+                text.to_lowercase().as_str(),
+                "true" | "yes" | "1" | "on"
+            ))),
+        },
+        "list" => match value {
+            Value::Array(_) => Ok(value.clone()),
+            _ => Ok(Value::Array(
+                text.split(',')
+                    .map(|item| item.trim())
+                    .filter(|item| !item.is_empty())
+                    .map(|item| Value::String(item.to_string()))
+                    .collect(),
+            )),
+        },
+        _ => Ok(Value::String(text)),
+    }
+}
+
+fn slugify(input: &str) -> String {
+    let mut slug = String::new();
+    for c in input.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// File name and content (YAML frontmatter, then the body) of one entry
+fn render_entry(
+    entry: &Value,
+    collection: &ContentCollection,
+) -> std::result::Result<(String, String), String> {
+    let lookup = |name: &str| {
+        let key = collection
+            .fields
+            .get(name)
+            .and_then(|field| field.source.as_deref())
+            .unwrap_or(name);
+        entry.get(key).filter(|value| match value {
+            Value::String(s) => !s.trim().is_empty(),
+            value => !value.is_null(),
+        })
+    };
+
+    let slug = lookup(&collection.slug_field)
+        .map(|value| slugify(value.as_str().unwrap_or(&value.to_string())))
+        .filter(|slug| !slug.is_empty())
+        .ok_or_else(|| format!("missing slug field '{}'", collection.slug_field))?;
+
+    let mut frontmatter = IndexMap::new();
+    for (name, field) in &collection.fields {
+        if collection.body_field.as_deref() == Some(name.as_str()) {
+            continue;
+        }
+        match lookup(name) {
+            Some(value) => {
+                frontmatter.insert(name.clone(), convert(value, field)?);
+            }
+            None if field.required => return Err(format!("missing required field '{}'", name)),
+            None => {}
+        }
+    }
+
+    let body = collection
+        .body_field
+        .as_deref()
+        .and_then(lookup)
+        .map(|value| value.as_str().map_or(value.to_string(), str::to_string))
+        .unwrap_or_default();
+
+    let frontmatter = serde_yaml::to_string(&frontmatter).map_err(|e| e.to_string())?;
+    Ok((
+        slug,
+        format!("---\n{}---\n\n{}\n", frontmatter, body.trim()),
+    ))
+}
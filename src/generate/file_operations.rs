@@ -5,15 +5,15 @@ use serde_json::{self, Value};
 use std::path::Path;
 use std::{fs, io};
 
-use super::functions;
+use super::{config_files, functions, jsonc, render};
 use crate::utils::context;
 
 pub fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
-    context::debug_print(&format!(
+    log::debug!(
         "Copying directory from '{}' to '{}'",
         src.display(),
         dst.display()
-    ));
+    );
     fs::create_dir_all(dst)?;
     for entry in fs::read_dir(src)? {
         let entry = entry?;
@@ -21,21 +21,21 @@ pub fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
         let file_name_str = file_name.to_string_lossy();
 
         if EXCLUDED_DIRS.contains(&file_name_str.as_ref()) {
-            context::debug_print(&format!("Skipping excluded directory: {}", file_name_str));
+            log::debug!("Skipping excluded directory: {}", file_name_str);
             continue;
         }
 
         if EXCLUDED_FILES.contains(&file_name_str.as_ref()) {
-            context::debug_print(&format!("Skipping excluded file: {}", file_name_str));
+            log::debug!("Skipping excluded file: {}", file_name_str);
             continue;
         }
 
         let file_type = entry.file_type()?;
         if file_type.is_dir() {
-            context::debug_print(&format!("Copying subdirectory: {}", file_name_str));
+            log::debug!("Copying subdirectory: {}", file_name_str);
             copy_dir_all(&entry.path(), &dst.join(entry.file_name()))?;
         } else {
-            context::debug_print(&format!("Copying file: {}", file_name_str));
+            log::debug!("Copying file: {}", file_name_str);
             fs::copy(entry.path(), dst.join(entry.file_name()))?;
         }
     }
@@ -43,26 +43,86 @@ pub fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
 }
 
 pub fn replace_in_file(file_path: &Path, replacements: &[Replacement]) -> io::Result<()> {
-    context::debug_print(&format!("Processing file: {}", file_path.display()));
-    context::debug_print(&format!(
-        "Found {} replacements to apply",
-        replacements.len()
-    ));
+    log::debug!("Processing file: {}", file_path.display());
+    log::debug!("Found {} replacements to apply", replacements.len());
 
     let content = fs::read_to_string(file_path)?;
 
-    if file_path.extension().and_then(|s| s.to_str()) == Some("json") {
-        context::debug_print("Detected JSON file, using JSON replacement logic");
-        replace_in_json_file(file_path, &content, replacements)
-    } else {
-        context::debug_print("Using text replacement logic");
-        replace_in_text_file(file_path, &content, replacements)
+    match file_path.extension().and_then(|s| s.to_str()) {
+        Some("jsonc" | "json5") => {
+            log::debug!("Detected JSONC file, editing keys in place");
+            replace_in_jsonc_file(file_path, &content, replacements)
+        }
+        // tsconfig.json, devcontainer.json and VS Code settings allow comments
+        Some("json") if jsonc::is_jsonc(&content) => {
+            log::debug!("Detected JSON file with comments, editing keys in place");
+            replace_in_jsonc_file(file_path, &content, replacements)
+        }
+        Some("json") => {
+            log::debug!("Detected JSON file, using JSON replacement logic");
+            replace_in_json_file(file_path, &content, replacements)
+        }
+        Some(extension @ ("yml" | "yaml" | "toml")) => {
+            log::debug!(
+                "Detected {} file, using key replacement logic",
+                extension.to_uppercase()
+            );
+            replace_in_config_file(file_path, &content, replacements)
+        }
+        _ => {
+            log::debug!("Using text replacement logic");
+            let new_content = apply_text_replacements(&content, replacements)?;
+            fs::write(file_path, new_content)
+        }
+    }
+}
+
+/// YAML and TOML files: replacements with an `attribute` set that key path in place,
+/// the others are applied as text replacements
+fn replace_in_config_file(
+    file_path: &Path,
+    content: &str,
+    replacements: &[Replacement],
+) -> io::Result<()> {
+    let (keyed, text): (Vec<&Replacement>, Vec<&Replacement>) = replacements
+        .iter()
+        .partition(|replacement| replacement.attribute.is_some());
+    let text: Vec<Replacement> = text.into_iter().cloned().collect();
+    let mut new_content = apply_text_replacements(content, &text)?;
+
+    let is_toml = file_path.extension().and_then(|s| s.to_str()) == Some("toml");
+    for replacement in keyed {
+        let key = functions::get_json_key(replacement);
+        let Some(value) = context::get_variable(&replacement.name) else {
+            log::debug!(
+                "Warning: Variable '{}' not found for key '{}'",
+                replacement.name,
+                key
+            );
+            continue;
+        };
+        let json_value = functions::convert_replacement_value(&value, replacement)?;
+        let path: Vec<&str> = key.split('.').collect();
+        let result = if is_toml {
+            config_files::set_toml_value(&new_content, &path, &json_value)
+        } else {
+            config_files::set_yaml_value(&new_content, &path, &json_value)
+        };
+        new_content = result.map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Failed to set '{}' in {}: {}", key, file_path.display(), e),
+            )
+        })?;
+        log::debug!("Set key '{}' to {}", key, json_value);
     }
+
+    fs::write(file_path, new_content)
 }
 
 fn write_json_to_file(file_path: &Path, ordered_map: IndexMap<String, Value>) -> io::Result<()> {
-    context::debug_print(&format!("Writing JSON file: {}", file_path.display()));
-    context::debug_print(&format!("JSON contains {} keys", ordered_map.len()));
+    log::debug!("Writing JSON file: {}", file_path.display());
+    log::debug!("JSON contains {} keys", ordered_map.len());
 
     let json_str = serde_json::to_string_pretty(&ordered_map)?;
     fs::write(file_path, json_str)
@@ -73,24 +133,48 @@ fn replace_in_json_file(
     content: &str,
     replacements: &[Replacement],
 ) -> io::Result<()> {
-    context::debug_print("Parsing JSON content");
+    log::debug!("Parsing JSON content");
     let template_json: IndexMap<String, Value> = serde_json::from_str(content)?;
-    context::debug_print(&format!(
-        "Template JSON contains {} keys",
-        template_json.len()
-    ));
+    log::debug!("Template JSON contains {} keys", template_json.len());
 
-    let mut ordered_map = functions::create_ordered_map(&template_json, replacements);
-    functions::update_existing_values(&mut ordered_map, replacements);
+    let mut ordered_map = functions::create_ordered_map(&template_json, replacements)?;
+    functions::update_existing_values(&mut ordered_map, replacements)?;
     write_json_to_file(file_path, ordered_map)
 }
 
-fn replace_in_text_file(
+/// JSONC and JSON5 files: each replacement sets its key in place, keeping comments and
+/// trailing commas
+fn replace_in_jsonc_file(
     file_path: &Path,
     content: &str,
     replacements: &[Replacement],
 ) -> io::Result<()> {
-    context::debug_print("Applying text replacements");
+    let mut new_content = content.to_string();
+    for replacement in replacements {
+        let key = functions::get_json_key(replacement);
+        let Some(value) = context::get_variable(&replacement.name) else {
+            log::debug!(
+                "Warning: Variable '{}' not found for key '{}'",
+                replacement.name,
+                key
+            );
+            continue;
+        };
+        let json_value = functions::convert_replacement_value(&value, replacement)?;
+        new_content = jsonc::set_value(&new_content, &key, &json_value).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Failed to set '{}' in {}: {}", key, file_path.display(), e),
+            )
+        })?;
+        log::debug!("Set key '{}' to {}", key, json_value);
+    }
+
+    fs::write(file_path, new_content)
+}
+
+fn apply_text_replacements(content: &str, replacements: &[Replacement]) -> io::Result<String> {
+    log::debug!("Applying text replacements");
     let mut new_content = content.to_string();
 
     for replacement in replacements {
@@ -100,48 +184,55 @@ fn replace_in_text_file(
 
         if let Some(value) = value {
             // For non-JSON files, use raw string values to avoid JSON quotes
-            let formatted_value = match replacement.type_.as_str() {
-                "array" => {
-                    let json_value = functions::convert_value_to_json(&value, &replacement.type_);
-                    serde_json::to_string(&json_value).unwrap_or(value)
-                }
-                _ => value, // Use raw string value for non-JSON files
+            let formatted_value = match functions::convert_replacement_value(&value, replacement)? {
+                Value::String(s) => s,
+                json_value => json_value.to_string(),
             };
 
             let old_content = new_content.clone();
 
-            // Determine replacement pattern
+            // Determine replacement pattern: the key, else `{{name}}` with optional filters
             let pattern = if let Some(key) = &replacement.key {
+                new_content = new_content.replace(key, &formatted_value);
                 key.clone()
             } else {
+                let replaced = render::replace_placeholders(&new_content, |name| {
+                    (name == replacement.name).then(|| formatted_value.clone())
+                });
+                for (placeholder, filter) in &replaced.unknown_filters {
+                    log::warn!("Unknown filter '{}' in {}", filter, placeholder);
+                }
+                new_content = replaced.text;
                 format!("{{{{{}}}}}", replacement.name)
             };
 
-            new_content = new_content.replace(&pattern, &formatted_value);
-
             if old_content != new_content {
                 let source = if crate::utils::context::get_variable(&replacement.name).is_some() {
                     "variable"
                 } else {
                     "default"
                 };
-                context::debug_print(&format!(
+                log::debug!(
                     "Applied replacement for '{}' (pattern: '{}') with {} value '{}'",
-                    replacement.name, pattern, source, formatted_value
-                ));
+                    replacement.name,
+                    pattern,
+                    source,
+                    formatted_value
+                );
             } else {
-                context::debug_print(&format!(
+                log::debug!(
                     "No matches found for replacement '{}' (pattern: '{}')",
-                    replacement.name, pattern
-                ));
+                    replacement.name,
+                    pattern
+                );
             }
         } else {
-            context::debug_print(&format!(
+            log::debug!(
                 "Warning: Variable '{}' not found and no default value provided",
                 replacement.name
-            ));
+            );
         }
     }
 
-    fs::write(file_path, new_content)
+    Ok(new_content)
 }
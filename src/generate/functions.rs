@@ -1,34 +1,106 @@
 use indexmap::IndexMap;
-use serde_json::Value;
+use serde_json::{Map, Value};
+use std::io::{Error, ErrorKind, Result};
 
 use crate::config::Replacement;
 use crate::utils::context;
 
-pub fn convert_value_to_json(value: &str, type_: &str) -> Value {
-    context::debug_print(&format!("Converting value '{}' to type '{}'", value, type_));
-
-    match type_ {
-        "array" => {
-            let array_values: Vec<Value> = value
-                .split(',')
-                .map(|s| Value::String(s.trim().to_string()))
-                .collect();
-            context::debug_print(&format!("Converted to array: {:?}", array_values));
-            Value::Array(array_values)
-        }
-        _ => {
-            context::debug_print(&format!("Converted to string: '{}'", value));
-            Value::String(value.to_string())
-        }
+/// Convert a variable to the JSON type declared by its replacement: `string` (default),
+/// `number`, `boolean`, `object` (inline JSON), `array` or a typed array like `array<number>`
+pub fn convert_value_to_json(value: &str, type_: &str) -> std::result::Result<Value, String> {
+    log::debug!("Converting value '{}' to type '{}'", value, type_);
+
+    let json_value = match type_ {
+        "number" => parse_number(value)?,
+        "boolean" => parse_boolean(value)?,
+        "object" => match serde_json::from_str(value) {
+            Ok(Value::Object(object)) => Value::Object(object),
+            _ => return Err(format!("'{}' is not a JSON object", value)),
+        },
+        "array" => convert_array(value, "string")?,
+        _ => match type_
+            .strip_prefix("array<")
+            .and_then(|rest| rest.strip_suffix('>'))
+        {
+            Some(item_type) => convert_array(value, item_type.trim())?,
+            None => Value::String(value.to_string()),
+        },
+    };
+    log::debug!("Converted to {}: {}", type_, json_value);
+    Ok(json_value)
+}
+
+/// Arrays accept inline JSON (`[1, 2]`) or comma-separated values
+fn convert_array(value: &str, item_type: &str) -> std::result::Result<Value, String> {
+    if !matches!(item_type, "string" | "number" | "boolean" | "object") {
+        return Err(format!("unsupported array item type '{}'", item_type));
+    }
+
+    let items: Vec<String> = match serde_json::from_str::<Value>(value.trim()) {
+        Ok(Value::Array(items)) if value.trim().starts_with('[') => items
+            .into_iter()
+            .map(|item| match item {
+                Value::String(s) => s,
+                other => other.to_string(),
+            })
+            .collect(),
+        _ => value
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+    };
+
+    items
+        .iter()
+        .map(|item| convert_value_to_json(item, item_type))
+        .collect::<std::result::Result<Vec<Value>, String>>()
+        .map(Value::Array)
+}
+
+fn parse_number(value: &str) -> std::result::Result<Value, String> {
+    let value = value.trim();
+    value
+        .parse::<i64>()
+        .map(serde_json::Number::from)
+        .ok()
+        .or_else(|| {
+            value
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+        })
+        .map(Value::Number)
+        .ok_or_else(|| format!("'{}' is not a number", value))
+}
+
+fn parse_boolean(value: &str) -> std::result::Result<Value, String> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "1" | "on" => Ok(Value::Bool(true)),
+        "false" | "no" | "0" | "off" => Ok(Value::Bool(false)),
+        _ => Err(format!("'{}' is not a boolean", value)),
     }
 }
 
+/// Convert the variable of a replacement, naming it in the error
+pub fn convert_replacement_value(value: &str, replacement: &Replacement) -> Result<Value> {
+    convert_value_to_json(value, &replacement.type_).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Invalid value for '{}' (type {}): {}",
+                replacement.name, replacement.type_, e
+            ),
+        )
+    })
+}
+
 pub fn create_ordered_map(
     template_json: &IndexMap<String, Value>,
     replacements: &[Replacement],
-) -> IndexMap<String, Value> {
+) -> Result<IndexMap<String, Value>> {
     let mut ordered_map = IndexMap::new();
-    context::debug_print(&format!("Processing {} replacements", replacements.len()));
+    log::debug!("Processing {} replacements", replacements.len());
 
     // First, insert all existing keys in their original order
     for (key, value) in template_json.iter() {
@@ -37,64 +109,75 @@ pub fn create_ordered_map(
 
     // Then, insert new keys after the "name" key if it exists
     if let Some(name_pos) = ordered_map.get_index_of("name") {
-        context::debug_print(&format!(
+        log::debug!(
             "Found 'name' key at position {}, inserting new keys after it",
             name_pos
-        ));
+        );
 
         // Insert new keys
         for replacement in replacements {
             let json_key = get_json_key(replacement);
-            if !template_json.contains_key(&json_key) {
+            if lookup_path(template_json, &json_key).is_none() {
                 if let Some(value) = context::get_variable(&replacement.name) {
-                    let json_value = convert_value_to_json(&value, &replacement.type_);
-                    ordered_map.insert(json_key.clone(), json_value);
-                    context::debug_print(&format!(
-                        "Added new key '{}' with value from variable '{}'",
-                        json_key, replacement.name
-                    ));
+                    let json_value = convert_replacement_value(&value, replacement)?;
+                    if insert_path(&mut ordered_map, &json_key, json_value) {
+                        log::debug!(
+                            "Added new key '{}' with value from variable '{}'",
+                            json_key,
+                            replacement.name
+                        );
+                    } else {
+                        log::debug!(
+                            "Warning: Cannot add key '{}', a parent value is not an object",
+                            json_key
+                        );
+                    }
                 } else {
-                    context::debug_print(&format!(
+                    log::debug!(
                         "Warning: Variable '{}' not found for key '{}'",
-                        replacement.name, json_key
-                    ));
+                        replacement.name,
+                        json_key
+                    );
                 }
             } else {
-                context::debug_print(&format!(
-                    "Key '{}' already exists in template, skipping",
-                    json_key
-                ));
+                log::debug!("Key '{}' already exists in template, skipping", json_key);
             }
         }
     } else {
-        context::debug_print(
-            "Warning: No 'name' key found in template, new keys will be added at the end",
-        );
+        log::debug!("Warning: No 'name' key found in template, new keys will be added at the end");
         // Insert new keys at the end
         for replacement in replacements {
             let json_key = get_json_key(replacement);
-            if !template_json.contains_key(&json_key) {
+            if lookup_path(template_json, &json_key).is_none() {
                 if let Some(value) = context::get_variable(&replacement.name) {
-                    let json_value = convert_value_to_json(&value, &replacement.type_);
-                    ordered_map.insert(json_key.clone(), json_value);
-                    context::debug_print(&format!(
-                        "Added new key '{}' with value from variable '{}'",
-                        json_key, replacement.name
-                    ));
+                    let json_value = convert_replacement_value(&value, replacement)?;
+                    if insert_path(&mut ordered_map, &json_key, json_value) {
+                        log::debug!(
+                            "Added new key '{}' with value from variable '{}'",
+                            json_key,
+                            replacement.name
+                        );
+                    } else {
+                        log::debug!(
+                            "Warning: Cannot add key '{}', a parent value is not an object",
+                            json_key
+                        );
+                    }
                 } else {
-                    context::debug_print(&format!(
+                    log::debug!(
                         "Warning: Variable '{}' not found for key '{}'",
-                        replacement.name, json_key
-                    ));
+                        replacement.name,
+                        json_key
+                    );
                 }
             }
         }
     }
 
-    ordered_map
+    Ok(ordered_map)
 }
 
-fn get_json_key(replacement: &Replacement) -> String {
+pub fn get_json_key(replacement: &Replacement) -> String {
     // Priority: attribute > key > fallback to replacement name
     if let Some(attribute) = &replacement.attribute {
         attribute.clone()
@@ -105,33 +188,93 @@ fn get_json_key(replacement: &Replacement) -> String {
     }
 }
 
+/// Resolve a key in the template JSON. Dot-separated keys (`scripts.build`) reach nested
+/// values, unless the template has a top-level key with that exact name.
+fn lookup_path<'a>(map: &'a IndexMap<String, Value>, key: &str) -> Option<&'a Value> {
+    if let Some(value) = map.get(key) {
+        return Some(value);
+    }
+    let mut segments = key.split('.');
+    let mut value = map.get(segments.next()?)?;
+    for segment in segments {
+        value = value.get(segment)?;
+    }
+    Some(value)
+}
+
+fn lookup_path_mut<'a>(map: &'a mut IndexMap<String, Value>, key: &str) -> Option<&'a mut Value> {
+    if map.contains_key(key) {
+        return map.get_mut(key);
+    }
+    let mut segments = key.split('.');
+    let mut value = map.get_mut(segments.next()?)?;
+    for segment in segments {
+        value = value.get_mut(segment)?;
+    }
+    Some(value)
+}
+
+/// Insert a value at a dot-separated key, creating missing parent objects. Returns false
+/// when a parent exists but is not an object.
+fn insert_path(map: &mut IndexMap<String, Value>, key: &str, value: Value) -> bool {
+    let Some((first, rest)) = key.split_once('.') else {
+        map.insert(key.to_string(), value);
+        return true;
+    };
+
+    let mut parent = map
+        .entry(first.to_string())
+        .or_insert_with(|| Value::Object(Map::new()));
+    let mut segments: Vec<&str> = rest.split('.').collect();
+    let last = segments.pop().unwrap_or(rest);
+    for segment in segments {
+        let Some(object) = parent.as_object_mut() else {
+            return false;
+        };
+        parent = object
+            .entry(segment)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    match parent.as_object_mut() {
+        Some(object) => {
+            object.insert(last.to_string(), value);
+            true
+        }
+        None => false,
+    }
+}
+
 pub fn update_existing_values(
     ordered_map: &mut IndexMap<String, Value>,
     replacements: &[Replacement],
-) {
-    context::debug_print("Updating existing values in template");
+) -> Result<()> {
+    log::debug!("Updating existing values in template");
 
     for replacement in replacements {
         if let Some(value) = context::get_variable(&replacement.name) {
             let json_key = get_json_key(replacement);
-            if let Some(existing_value) = ordered_map.get_mut(&json_key) {
-                let json_value = convert_value_to_json(&value, &replacement.type_);
-                context::debug_print(&format!(
+            if let Some(existing_value) = lookup_path_mut(ordered_map, &json_key) {
+                let json_value = convert_replacement_value(&value, replacement)?;
+                log::debug!(
                     "Updated key '{}' from '{}' to '{}'",
-                    json_key, existing_value, json_value
-                ));
+                    json_key,
+                    existing_value,
+                    json_value
+                );
                 *existing_value = json_value;
             } else {
-                context::debug_print(&format!(
+                log::debug!(
                     "Warning: Key '{}' not found in template for replacement",
                     json_key
-                ));
+                );
             }
         } else {
-            context::debug_print(&format!(
+            log::debug!(
                 "Warning: Variable '{}' not found for replacement of key '{}'",
-                replacement.name, get_json_key(replacement)
-            ));
+                replacement.name,
+                get_json_key(replacement)
+            );
         }
     }
+    Ok(())
 }
@@ -0,0 +1,73 @@
+use std::io::{Error, Result};
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::environment::ENV_PREFIX;
+use crate::generate::sandbox;
+use crate::plan::{self, ActionKind};
+use crate::utils::{context, progress, summary};
+
+/// Run the commands of a hook stage (e.g. `post_generate`) in order with `sh -c`, in
+/// `working_dir`, with every variable exported as `GEN_<NAME>` and `home` as HOME when the
+/// HOME is isolated. The first failing command fails the run. In a dry run, the commands are
+/// only printed.
+pub fn run(
+    stage: &str,
+    commands: &[String],
+    working_dir: &Path,
+    dry_run: bool,
+    home: Option<&Path>,
+) -> Result<()> {
+    for command in commands {
+        plan::record(
+            ActionKind::Command,
+            &format!("run {} hook: {}", stage, command),
+        );
+        if dry_run {
+            log::info!("🔍 [dry-run] Would run {} hook: {}", stage, command);
+            continue;
+        }
+
+        log::info!("🪝 Running {} hook: {}", stage, command);
+        let _paused = progress::pause();
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .current_dir(working_dir)
+            .envs(sandbox::environment(home))
+            .stdout(summary::child_stdout())
+            .envs(environment())
+            .status()
+            .map_err(|e| {
+                Error::other(format!("Failed to run {} hook '{}': {}", stage, command, e))
+            })?;
+        if !status.success() {
+            return Err(Error::other(format!(
+                "{} hook '{}' failed with {}",
+                stage, command, status
+            )));
+        }
+        log::debug!("{} hook '{}' succeeded", stage, command);
+    }
+    Ok(())
+}
+
+/// `GEN_PROJECT_NAME` for `project_name`, the names read back by `config::environment`
+fn environment() -> Vec<(String, String)> {
+    context::get_variables()
+        .into_iter()
+        .map(|(name, value)| {
+            let name: String = name
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() {
+                        c.to_ascii_uppercase()
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            (format!("{}{}", ENV_PREFIX, name), value)
+        })
+        .collect()
+}
@@ -0,0 +1,109 @@
+use serde_json::json;
+use std::io::{Error, ErrorKind, Result};
+
+use crate::config::HostnameService;
+use crate::github::headers::DEFAULT_USER_AGENT;
+use crate::github::{http, trace};
+
+/// Ask the allocation service for a unique hostname per environment of the repository,
+/// returned as (environment, hostname) pairs in the configured order
+pub async fn allocate(
+    service: &HostnameService,
+    organization: &str,
+    repository: &str,
+    project_name: &str,
+) -> Result<Vec<(String, String)>> {
+    let body = json!({
+        "organization": organization,
+        "repository": repository,
+        "project": project_name,
+        "environments": service.environments,
+    });
+    log::debug!("Requesting hostnames from {}: {}", service.url, body);
+
+    let mut request = http::client()
+        .post(&service.url)
+        .header(reqwest::header::USER_AGENT, DEFAULT_USER_AGENT)
+        .json(&body);
+    if let Some(name) = &service.token_env {
+        let token = std::env::var(name).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Hostname service token ${} is not set", name),
+            )
+        })?;
+        request = request.bearer_auth(token);
+    }
+
+    let response = trace::send(request)
+        .await
+        .map_err(|e| Error::other(format!("Failed to reach the hostname service: {}", e)))?;
+    let status = response.status();
+    let text = response.text().await.map_err(Error::other)?;
+    if !status.is_success() {
+        return Err(Error::other(format!(
+            "Hostname service returned {}: {}",
+            status,
+            text.trim()
+        )));
+    }
+
+    let response: serde_json::Value = serde_json::from_str(&text).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid hostname service response: {}", e),
+        )
+    })?;
+    let mut hostnames = Vec::new();
+    for environment in &service.environments {
+        let hostname = response["hostnames"][environment]
+            .as_str()
+            .map(|hostname| hostname.trim().to_lowercase())
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The hostname service allocated no {} hostname", environment),
+                )
+            })?;
+        if !is_hostname(&hostname) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "The hostname service allocated an invalid {} hostname: '{}'",
+                    environment, hostname
+                ),
+            ));
+        }
+        hostnames.push((environment.clone(), hostname));
+    }
+    Ok(hostnames)
+}
+
+/// Template variable receiving the hostname of an environment, e.g. `prod_hostname`
+pub fn variable_name(environment: &str) -> String {
+    format!("{}_hostname", environment.replace('-', "_"))
+}
+
+/// Whether `name` is a variable holding an allocated hostname
+pub fn is_variable_name(name: &str) -> bool {
+    name.ends_with("_hostname")
+}
+
+/// Actions variable read by the deployment workflows, e.g. `PROD_HOSTNAME`
+pub fn actions_variable_name(environment: &str) -> String {
+    variable_name(environment).to_uppercase()
+}
+
+fn is_hostname(hostname: &str) -> bool {
+    hostname.len() <= 253
+        && hostname.contains('.')
+        && hostname.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+}
@@ -0,0 +1,186 @@
+use regex::Regex;
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+use crate::config::I18nConfig;
+use crate::generate::{render, substitution};
+use crate::utils::context;
+
+/// Generate the content stubs, routes and hreflang/sitemap configuration of every selected
+/// locale when the i18n condition holds. The stubs directory never ships as is.
+pub fn scaffold_locales(project_path: &Path, config: &I18nConfig) -> Result<()> {
+    let variables = context::get_variables();
+    let enabled = render::evaluate_condition(&config.when, &variables).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid i18n condition '{}': {}", config.when, e),
+        )
+    })?;
+    let stubs_dir = config.stubs.as_ref().map(|stubs| project_path.join(stubs));
+
+    if !enabled {
+        log::debug!("i18n is disabled, skipping locale scaffolding");
+        if let Some(stubs_dir) = stubs_dir.filter(|dir| dir.exists()) {
+            fs::remove_dir_all(stubs_dir)?;
+        }
+        return Ok(());
+    }
+
+    let locales = parse_locales(
+        variables
+            .get(&config.locales_variable)
+            .map(String::as_str)
+            .unwrap_or_default(),
+    )?;
+    if locales.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "i18n is enabled but no locales are set in {}",
+                config.locales_variable
+            ),
+        ));
+    }
+
+    let default_locale = match variables.get(&config.default_locale_variable) {
+        Some(locale) if !locale.is_empty() => locale.clone(),
+        _ => locales[0].clone(),
+    };
+    if !locales.contains(&default_locale) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Default locale '{}' is not one of the locales {}",
+                default_locale,
+                locales.join(", ")
+            ),
+        ));
+    }
+    context::set_variable(&config.default_locale_variable, &default_locale);
+
+    if let Some(stubs_dir) = stubs_dir {
+        if !stubs_dir.is_dir() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("i18n stubs directory {} not found", stubs_dir.display()),
+            ));
+        }
+        for locale in &locales {
+            let target = project_path.join(&config.content_dir).join(segment(locale));
+            copy_stubs(&stubs_dir, &target, locale)?;
+        }
+        fs::remove_dir_all(&stubs_dir)?;
+    }
+
+    let site_url = variables
+        .get(&config.site_url_variable)
+        .map(|url| url.trim_end_matches('/').to_string())
+        .unwrap_or_default();
+    let output = project_path.join(&config.output);
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let document = locale_config(&locales, &default_locale, &site_url);
+    fs::write(&output, serde_json::to_string_pretty(&document)? + "\n")?;
+
+    log::info!(
+        "🌐 Scaffolded {} locales ({}), default {}",
+        locales.len(),
+        locales.join(", "),
+        default_locale
+    );
+    Ok(())
+}
+
+/// Comma-separated locales like `en,fr-CA`, without duplicates
+fn parse_locales(value: &str) -> Result<Vec<String>> {
+    let tag = Regex::new(r"^[A-Za-z]{2,3}(-[A-Za-z0-9]{2,8})*$").unwrap();
+    let mut locales: Vec<String> = Vec::new();
+    for locale in value.split(',').map(str::trim).filter(|l| !l.is_empty()) {
+        if !tag.is_match(locale) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Invalid locale '{}', expected a tag like en or fr-CA",
+                    locale
+                ),
+            ));
+        }
+        if !locales.iter().any(|l| l.eq_ignore_ascii_case(locale)) {
+            locales.push(locale.to_string());
+        }
+    }
+    Ok(locales)
+}
+
+/// URL and directory segment of a locale, e.g. `fr-ca` for `fr-CA`
+fn segment(locale: &str) -> String {
+    locale.to_lowercase()
+}
+
+/// The default locale is served at the root, the others under their segment
+fn route(locale: &str, default_locale: &str) -> String {
+    if locale == default_locale {
+        "/".to_string()
+    } else {
+        format!("/{}/", segment(locale))
+    }
+}
+
+fn locale_config(locales: &[String], default_locale: &str, site_url: &str) -> Value {
+    let mut routes = Map::new();
+    let mut sitemap_locales = Map::new();
+    let mut hreflang = Vec::new();
+    for locale in locales {
+        let route = route(locale, default_locale);
+        routes.insert(locale.clone(), Value::String(route.clone()));
+        sitemap_locales.insert(segment(locale), Value::String(locale.clone()));
+        hreflang.push(json!({ "hreflang": locale, "href": format!("{}{}", site_url, route) }));
+    }
+    hreflang.push(json!({ "hreflang": "x-default", "href": format!("{}/", site_url) }));
+
+    json!({
+        "defaultLocale": default_locale,
+        "locales": locales,
+        "routes": routes,
+        "hreflang": hreflang,
+        // Shape of the @astrojs/sitemap `i18n` option
+        "sitemap": {
+            "defaultLocale": segment(default_locale),
+            "locales": sitemap_locales,
+        },
+    })
+}
+
+/// Copy the stubs for one locale, replacing `{{locale}}` in text files. Files already present
+/// in the target (hand-written translations) are kept.
+fn copy_stubs(source: &Path, target: &Path, locale: &str) -> Result<()> {
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let destination = target.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_stubs(&entry.path(), &destination, locale)?;
+            continue;
+        }
+        if destination.exists() {
+            log::debug!("Keeping existing {}", destination.display());
+            continue;
+        }
+        let bytes = fs::read(entry.path())?;
+        match substitution::as_text(&bytes) {
+            Some(content) => fs::write(
+                &destination,
+                render::replace_placeholders(content, |name| {
+                    (name == "locale").then(|| locale.to_string())
+                })
+                .text
+                .as_bytes(),
+            )?,
+            None => fs::write(&destination, &bytes)?,
+        }
+    }
+    Ok(())
+}
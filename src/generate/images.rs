@@ -0,0 +1,120 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::{path_matches, ImageRule, EXCLUDED_DIRS};
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "tif", "tiff"];
+
+/// ImageMagick 7 ships `magick`, older versions only `convert`
+fn find_imagemagick() -> Option<&'static str> {
+    ["magick", "convert"].into_iter().find(|tool| {
+        Command::new(tool)
+            .arg("-version")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    })
+}
+
+/// Resize, compress and convert the copied template images matching `rules`.
+/// Skipped with a warning when ImageMagick is not installed.
+pub fn optimize_images(project_path: &Path, rules: &[ImageRule]) -> io::Result<()> {
+    let Some(tool) = find_imagemagick() else {
+        log::info!("⚠️  ImageMagick not found, skipping image optimization");
+        return Ok(());
+    };
+    log::debug!("Using ImageMagick command: {}", tool);
+
+    optimize_in_dir(tool, project_path, project_path, rules)
+}
+
+fn optimize_in_dir(tool: &str, root: &Path, dir: &Path, rules: &[ImageRule]) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let file_name_str = file_name.to_string_lossy();
+
+        if entry.file_type()?.is_dir() {
+            if file_name_str != ".git" && !EXCLUDED_DIRS.contains(&file_name_str.as_ref()) {
+                optimize_in_dir(tool, root, &path, rules)?;
+            }
+            continue;
+        }
+
+        let is_image = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+        if !is_image {
+            continue;
+        }
+
+        let relative_path = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let Some(rule) = rules
+            .iter()
+            .find(|rule| path_matches(&relative_path, &rule.paths))
+        else {
+            continue;
+        };
+
+        if let Err(e) = optimize_image(tool, &path, rule) {
+            log::warn!("Failed to optimize {}: {}", relative_path, e);
+        } else {
+            log::debug!("Optimized image: {}", relative_path);
+        }
+    }
+    Ok(())
+}
+
+fn optimize_image(tool: &str, path: &Path, rule: &ImageRule) -> io::Result<()> {
+    let mut options = vec!["-strip".to_string()];
+    if let Some(max_width) = rule.max_width {
+        // `>` only shrinks images larger than the given width
+        options.push("-resize".to_string());
+        options.push(format!("{}x>", max_width));
+    }
+    if let Some(quality) = rule.quality {
+        options.push("-quality".to_string());
+        options.push(quality.to_string());
+    }
+
+    // Converted copies are rendered from the untouched original, which is compressed last
+    let mut outputs: Vec<_> = rule
+        .formats
+        .iter()
+        .map(|format| path.with_extension(format))
+        .filter(|output| output != path)
+        .collect();
+    let replace_original = rule.replace_original && !outputs.is_empty();
+    if !replace_original {
+        outputs.push(path.to_path_buf());
+    }
+
+    for output in &outputs {
+        let status = Command::new(tool)
+            .arg(path)
+            .args(&options)
+            .arg(output)
+            .status()?;
+        if !status.success() {
+            return Err(io::Error::other(format!(
+                "{} exited with status {} writing {}",
+                tool,
+                status,
+                output.display()
+            )));
+        }
+    }
+
+    if replace_original {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
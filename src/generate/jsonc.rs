@@ -0,0 +1,509 @@
+//! In-place edits of JSONC and JSON5 files (tsconfig.json, devcontainer.json, VS Code
+//! settings). Only the edited value is rewritten, so comments, trailing commas and the
+//! formatting of the rest of the file are preserved.
+
+use serde_json::{Map, Value};
+
+struct Node {
+    start: usize,
+    end: usize,
+    kind: Kind,
+}
+
+enum Kind {
+    Object(Vec<Member>),
+    Array(Vec<Node>),
+    Scalar,
+}
+
+struct Member {
+    key: String,
+    key_start: usize,
+    value: Node,
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn error(&self, message: &str) -> String {
+        let line = self.src[..self.pos].matches('\n').count() + 1;
+        format!("{} at line {}", message, line)
+    }
+
+    /// Skip whitespace and `//` or `/* */` comments
+    fn skip_trivia(&mut self) -> Result<(), String> {
+        loop {
+            let rest = &self.src[self.pos..];
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if let Some(comment) = trimmed.strip_prefix("/*") {
+                let end = comment
+                    .find("*/")
+                    .ok_or_else(|| self.error("unterminated comment"))?;
+                self.pos += end + 4;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Node, String> {
+        self.skip_trivia()?;
+        let start = self.pos;
+        let kind = match self.peek() {
+            Some('{') => Kind::Object(self.members()?),
+            Some('[') => Kind::Array(self.items()?),
+            Some('"' | '\'') => {
+                self.string()?;
+                Kind::Scalar
+            }
+            Some(_) => {
+                self.literal()?;
+                Kind::Scalar
+            }
+            None => return Err(self.error("unexpected end of file")),
+        };
+        Ok(Node {
+            start,
+            end: self.pos,
+            kind,
+        })
+    }
+
+    fn members(&mut self) -> Result<Vec<Member>, String> {
+        self.pos += 1;
+        let mut members = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if self.peek() == Some('}') {
+                self.pos += 1;
+                return Ok(members);
+            }
+            let key_start = self.pos;
+            let key = match self.peek() {
+                Some('"' | '\'') => self.string()?,
+                _ => self.literal()?.to_string(),
+            };
+            self.skip_trivia()?;
+            if self.peek() != Some(':') {
+                return Err(self.error(&format!("expected ':' after key '{}'", key)));
+            }
+            self.pos += 1;
+            let value = self.value()?;
+            members.push(Member {
+                key,
+                key_start,
+                value,
+            });
+            if !self.separator('}')? {
+                return Err(self.error("expected ',' or '}'"));
+            }
+        }
+    }
+
+    fn items(&mut self) -> Result<Vec<Node>, String> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                return Ok(items);
+            }
+            items.push(self.value()?);
+            if !self.separator(']')? {
+                return Err(self.error("expected ',' or ']'"));
+            }
+        }
+    }
+
+    /// Consume the comma after a member or item. Trailing commas are allowed, the closing
+    /// bracket is left for the caller.
+    fn separator(&mut self, close: char) -> Result<bool, String> {
+        self.skip_trivia()?;
+        match self.peek() {
+            Some(',') => {
+                self.pos += 1;
+                Ok(true)
+            }
+            Some(c) => Ok(c == close),
+            None => Ok(false),
+        }
+    }
+
+    /// A quoted string, returned unescaped
+    fn string(&mut self) -> Result<String, String> {
+        let quote = self.peek().unwrap_or('"');
+        let start = self.pos;
+        let mut escaped = false;
+        for (i, c) in self.src[start + 1..].char_indices() {
+            match c {
+                '\\' if !escaped => escaped = true,
+                c if c == quote && !escaped => {
+                    self.pos = start + 1 + i + 1;
+                    return unescape(&self.src[start..self.pos])
+                        .ok_or_else(|| self.error("invalid string"));
+                }
+                _ => escaped = false,
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    /// Numbers, `true`, `false`, `null` and JSON5 unquoted keys
+    fn literal(&mut self) -> Result<&str, String> {
+        let rest = &self.src[self.pos..];
+        let length = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, ',' | ':' | ']' | '}' | '/'))
+            .unwrap_or(rest.len());
+        if length == 0 {
+            return Err(self.error("unexpected character"));
+        }
+        self.pos += length;
+        Ok(&rest[..length])
+    }
+}
+
+fn unescape(quoted: &str) -> Option<String> {
+    if quoted.starts_with('"') {
+        return serde_json::from_str(quoted).ok();
+    }
+    let inner = &quoted[1..quoted.len() - 1];
+    serde_json::from_str(&format!(
+        "\"{}\"",
+        inner.replace("\\'", "'").replace('"', "\\\"")
+    ))
+    .ok()
+}
+
+fn parse(content: &str) -> Result<Node, String> {
+    let mut parser = Parser {
+        src: content,
+        pos: 0,
+    };
+    let root = parser.value()?;
+    parser.skip_trivia()?;
+    if parser.pos < content.len() {
+        return Err(parser.error("unexpected content after the document"));
+    }
+    Ok(root)
+}
+
+/// Value of a parsed node, for checking edits
+fn to_value(content: &str, node: &Node) -> Result<Value, String> {
+    let text = &content[node.start..node.end];
+    match &node.kind {
+        Kind::Object(members) => members
+            .iter()
+            .map(|member| Ok((member.key.clone(), to_value(content, &member.value)?)))
+            .collect::<Result<Map<_, _>, String>>()
+            .map(Value::Object),
+        Kind::Array(items) => items
+            .iter()
+            .map(|item| to_value(content, item))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        Kind::Scalar if text.starts_with('\'') => unescape(text)
+            .map(Value::String)
+            .ok_or_else(|| format!("invalid string {}", text)),
+        Kind::Scalar => {
+            serde_json::from_str(text).map_err(|_| format!("unsupported value {}", text))
+        }
+    }
+}
+
+/// A value on a single line: `[1, 2]`, `{ "a": 1 }`
+fn inline(value: &Value) -> String {
+    match value {
+        Value::Array(items) => format!(
+            "[{}]",
+            items.iter().map(inline).collect::<Vec<_>>().join(", ")
+        ),
+        Value::Object(object) if object.is_empty() => "{}".to_string(),
+        Value::Object(object) => format!(
+            "{{ {} }}",
+            object
+                .iter()
+                .map(|(key, value)| format!("{}: {}", Value::String(key.clone()), inline(value)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        scalar => scalar.to_string(),
+    }
+}
+
+fn line_start(content: &str, position: usize) -> usize {
+    content[..position].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// Indentation of the line at `position`, if only whitespace precedes it on that line
+fn indentation_at(content: &str, position: usize) -> Option<&str> {
+    let before = &content[line_start(content, position)..position];
+    before.trim().is_empty().then_some(before)
+}
+
+/// Indentation step of the document, two spaces when nothing is indented
+fn indent_unit(content: &str) -> &str {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .find(|indent| !indent.is_empty())
+        .unwrap_or("  ")
+}
+
+/// Text between `from` and the end of its line when it only holds whitespace and a `//`
+/// comment, so that an added member goes after the comment of the previous one
+fn end_of_line_after(content: &str, from: usize) -> Option<usize> {
+    let line_end = content[from..]
+        .find('\n')
+        .map_or(content.len(), |i| from + i);
+    let rest = content[from..line_end].trim();
+    (rest.is_empty() || rest.starts_with("//")).then_some(line_end)
+}
+
+/// Add `key: value` as the last member of `object`
+fn add_member(
+    content: &str,
+    object: &Node,
+    members: &[Member],
+    key: &str,
+    value: &Value,
+) -> String {
+    let member = format!("{}: {}", Value::String(key.to_string()), inline(value));
+    let Some(last) = members.last() else {
+        let rest_of_line = content[object.end..].trim_start_matches(',');
+        if end_of_line_after(rest_of_line, 0).is_none() {
+            return format!(
+                "{}{{ {} }}{}",
+                &content[..object.start],
+                member,
+                &content[object.end..]
+            );
+        }
+        // `{}` ending its line becomes a block indented one step deeper than that line
+        let outer = &content[line_start(content, object.start)..object.start];
+        let outer = &outer[..outer.len() - outer.trim_start().len()];
+        return format!(
+            "{}{{\n{}{}{}\n{}}}{}",
+            &content[..object.start],
+            outer,
+            indent_unit(content),
+            member,
+            outer,
+            &content[object.end..]
+        );
+    };
+
+    // Where the last member ends, after its comma when there is a trailing one
+    let mut parser = Parser {
+        src: content,
+        pos: last.value.end,
+    };
+    let _ = parser.skip_trivia();
+    let trailing_comma = parser.peek() == Some(',');
+    let after_last = if trailing_comma {
+        parser.pos + 1
+    } else {
+        last.value.end
+    };
+
+    match (
+        indentation_at(content, last.key_start),
+        end_of_line_after(content, after_last),
+    ) {
+        // One member per line: a new line with the same indentation and comma style
+        (Some(indent), Some(line_end)) => {
+            let comma = if trailing_comma { "," } else { "" };
+            let mut edited = content.to_string();
+            edited.insert_str(line_end, &format!("\n{}{}{}", indent, member, comma));
+            if !trailing_comma {
+                edited.insert(last.value.end, ',');
+            }
+            edited
+        }
+        // Members on a single line
+        _ if trailing_comma => format!(
+            "{} {},{}",
+            &content[..after_last],
+            member,
+            &content[after_last..]
+        ),
+        _ => format!(
+            "{}, {}{}",
+            &content[..last.value.end],
+            member,
+            &content[last.value.end..]
+        ),
+    }
+}
+
+/// Set the value at a key path in a JSONC or JSON5 document, adding missing keys at the end
+/// of their object. A top-level key with the exact dotted name takes precedence over the
+/// nested path, as in JSON replacements.
+pub fn set_value(content: &str, key: &str, value: &Value) -> Result<String, String> {
+    let root = parse(content)?;
+    let Kind::Object(top_level) = &root.kind else {
+        return Err("the document is not an object".to_string());
+    };
+    let path: Vec<&str> = if top_level.iter().any(|member| member.key == key) {
+        vec![key]
+    } else {
+        key.split('.').collect()
+    };
+
+    let mut node = &root;
+    for (depth, segment) in path.iter().enumerate() {
+        let Kind::Object(members) = &node.kind else {
+            return Err(format!("'{}' is not an object", path[..depth].join(".")));
+        };
+        let Some(member) = members.iter().find(|member| member.key == *segment) else {
+            // The missing keys are added as nested objects
+            let added = path[depth + 1..]
+                .iter()
+                .rev()
+                .fold(value.clone(), |value, key| {
+                    Value::Object(Map::from_iter([(key.to_string(), value)]))
+                });
+            let edited = add_member(content, node, members, segment, &added);
+            return verify(edited, &path, value);
+        };
+        node = &member.value;
+    }
+
+    let edited = format!(
+        "{}{}{}",
+        &content[..node.start],
+        inline(value),
+        &content[node.end..]
+    );
+    verify(edited, &path, value)
+}
+
+/// Parse the edited document back, so that an edit never produces an invalid file
+fn verify(content: String, path: &[&str], expected: &Value) -> Result<String, String> {
+    let root = parse(&content).map_err(|e| format!("edit produced an invalid document: {}", e))?;
+    let node = path.iter().try_fold(&root, |node, key| match &node.kind {
+        Kind::Object(members) => members
+            .iter()
+            .find(|member| member.key == *key)
+            .map(|member| &member.value),
+        _ => None,
+    });
+    match node.map(|node| to_value(&content, node)) {
+        Some(Ok(actual)) if actual == *expected => Ok(content),
+        _ => Err(format!("failed to set '{}'", path.join("."))),
+    }
+}
+
+/// Whether a document needs the JSONC reader: it has comments or trailing commas, or uses
+/// JSON5 syntax, but parses with it
+pub fn is_jsonc(content: &str) -> bool {
+    serde_json::from_str::<Value>(content).is_err() && parse(content).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn keeps_comments_and_trailing_commas() {
+        let content = r#"{
+  // Compiler options
+  "compilerOptions": {
+    /* ES target */
+    "target": "es2020", // kept
+    "strict": false,
+  },
+}
+"#;
+        assert!(is_jsonc(content));
+        assert_eq!(
+            set_value(content, "compilerOptions.strict", &json!(true)).unwrap(),
+            r#"{
+  // Compiler options
+  "compilerOptions": {
+    /* ES target */
+    "target": "es2020", // kept
+    "strict": true,
+  },
+}
+"#
+        );
+    }
+
+    #[test]
+    fn strings_holding_comment_markers_are_not_comments() {
+        let content =
+            "{\n  \"url\": \"https://example.com/*\", // site\n  \"name\": 'a // b',\n}\n";
+        assert!(is_jsonc(content));
+        assert_eq!(
+            set_value(content, "name", &json!("demo")).unwrap(),
+            "{\n  \"url\": \"https://example.com/*\", // site\n  \"name\": \"demo\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn adds_nested_keys_after_the_last_member() {
+        let content = "{\n  \"a\": 1 // one\n}\n";
+        assert_eq!(
+            set_value(content, "b.c.d", &json!("x")).unwrap(),
+            "{\n  \"a\": 1, // one\n  \"b\": { \"c\": { \"d\": \"x\" } }\n}\n"
+        );
+
+        let content = "{\n  \"a\": {\n    \"b\": 1,\n  },\n}\n";
+        assert_eq!(
+            set_value(content, "a.c", &json!([1, 2])).unwrap(),
+            "{\n  \"a\": {\n    \"b\": 1,\n    \"c\": [1, 2],\n  },\n}\n"
+        );
+
+        assert_eq!(
+            set_value("{ a: 1, /* c */ }", "b", &json!(null)).unwrap(),
+            "{ a: 1, \"b\": null, /* c */ }"
+        );
+    }
+
+    #[test]
+    fn adds_keys_to_empty_objects() {
+        assert_eq!(
+            set_value("{}", "name", &json!("demo")).unwrap(),
+            "{\n  \"name\": \"demo\"\n}"
+        );
+        // An empty object followed by more members stays on its line
+        assert_eq!(
+            set_value("{ \"a\": {}, \"b\": 1 }", "a.x", &json!(true)).unwrap(),
+            "{ \"a\": { \"x\": true }, \"b\": 1 }"
+        );
+        assert_eq!(
+            set_value("{\n  \"settings\": {}\n}\n", "settings.tabSize", &json!(4)).unwrap(),
+            "{\n  \"settings\": {\n    \"tabSize\": 4\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn dotted_top_level_keys_take_precedence() {
+        let content = "{\n  \"editor.tabSize\": 2,\n}\n";
+        assert_eq!(
+            set_value(content, "editor.tabSize", &json!(4)).unwrap(),
+            "{\n  \"editor.tabSize\": 4,\n}\n"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        assert!(set_value("{ \"a\": 1 /* open", "a", &json!(2)).is_err());
+        assert!(set_value("[1, 2]", "a", &json!(2)).is_err());
+        assert!(set_value("{ \"a\": 1 }", "a.b", &json!(2)).is_err());
+        assert!(!is_jsonc("{ \"a\": 1 }"));
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+use crate::config::TemplateManifest;
+use crate::generate::hostnames;
+use crate::utils::{context, summary};
+
+/// Written at the root of every generated project
+pub const LOCKFILE: &str = ".generator-lock.json";
+
+/// Content of `.generator-lock.json`
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct GeneratorLock {
+    pub generator: GeneratorRecord,
+    pub template: TemplateRecord,
+    pub variables: BTreeMap<String, String>,
+    /// Variables recorded without their value: the ones declared as secret, and the ones the
+    /// template does not declare, such as tokens from the environment or the config
+    #[serde(default)]
+    pub secret_variables: Vec<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct GeneratorRecord {
+    pub name: String,
+    pub version: String,
+}
+
+/// The template a project was generated from: `category/name`, version, and the templates
+/// repository and commit it was read from
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct TemplateRecord {
+    pub category: String,
+    pub name: String,
+    pub version: Option<String>,
+    pub source: Option<String>,
+    pub commit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub includes: Vec<String>,
+}
+
+/// Variables set by the generator itself rather than declared by templates
+const GENERATOR_VARIABLES: &[&str] = &["project_name", "name", "organization"];
+
+/// Non-secret variables the template declares: its prompts, replacements, computed variables
+/// and i18n variables, plus the computed variables of the config and the ones the generator
+/// sets
+fn declared_variables(manifest: &TemplateManifest) -> Vec<String> {
+    let mut declared: Vec<String> = GENERATOR_VARIABLES.iter().map(|v| v.to_string()).collect();
+    declared.extend(
+        manifest
+            .prompts
+            .iter()
+            .flat_map(|page| &page.variables)
+            .filter(|variable| !variable.secret)
+            .map(|variable| variable.name.clone()),
+    );
+    declared.extend(
+        manifest
+            .files
+            .iter()
+            .flat_map(|file| &file.replacements)
+            .map(|replacement| replacement.name.clone()),
+    );
+    declared.extend(manifest.computed.keys().cloned());
+    declared.extend(context::get_computed().into_iter().map(|(name, _)| name));
+    if let Some(i18n) = &manifest.i18n {
+        declared.extend([
+            i18n.locales_variable.clone(),
+            i18n.default_locale_variable.clone(),
+            i18n.site_url_variable.clone(),
+        ]);
+    }
+    declared
+}
+
+/// Record the template revision and the resolved variables in the generated project, so that
+/// it can be audited or regenerated from the same template later. The values of secret
+/// variables and of variables the template does not declare (tokens from `GEN_*`, env files
+/// or the config) are left out, only their names are listed.
+pub fn write(template_path: &Path, project_path: &Path, manifest: &TemplateManifest) -> Result<()> {
+    let declared = declared_variables(manifest);
+    let (variables, omitted): (BTreeMap<String, String>, BTreeMap<String, String>) =
+        context::get_variables()
+            .into_iter()
+            .partition(|(name, _)| declared.contains(name) || hostnames::is_variable_name(name));
+
+    let lock = GeneratorLock {
+        generator: GeneratorRecord {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        },
+        template: template_record(template_path, manifest),
+        variables,
+        secret_variables: omitted.into_keys().collect(),
+    };
+    summary::record_variables(&lock.variables);
+
+    fs::write(
+        project_path.join(LOCKFILE),
+        serde_json::to_string_pretty(&lock)? + "\n",
+    )?;
+    log::info!(
+        "🔒 Recorded template revision and variables in {}",
+        LOCKFILE
+    );
+    Ok(())
+}
+
+/// Read the lockfile of a generated project
+pub fn read(project_path: &Path) -> Result<GeneratorLock> {
+    let path = project_path.join(LOCKFILE);
+    let content = fs::read_to_string(&path).map_err(|e| {
+        Error::new(
+            e.kind(),
+            format!("Failed to read {}: {}", path.display(), e),
+        )
+    })?;
+    serde_json::from_str(&content).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid {}: {}", path.display(), e),
+        )
+    })
+}
+
+pub fn template_record(template_path: &Path, manifest: &TemplateManifest) -> TemplateRecord {
+    let component = |path: Option<&Path>| {
+        path.and_then(|p| p.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    let repository = git2::Repository::discover(template_path).ok();
+    let commit = repository.as_ref().and_then(|repo| {
+        let commit = repo.head().ok()?.peel_to_commit().ok()?;
+        Some(commit.id().to_string())
+    });
+    let source = repository.as_ref().and_then(|repo| {
+        let remote = repo.find_remote("origin").ok()?;
+        remote.url().map(str::to_string)
+    });
+
+    TemplateRecord {
+        category: component(template_path.parent()),
+        name: component(Some(template_path)),
+        version: manifest.version.clone(),
+        source,
+        commit,
+        extends: manifest.extends.clone(),
+        includes: manifest.includes.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undeclared_and_secret_variables_are_recorded_without_value() {
+        let work_dir =
+            std::env::temp_dir().join(format!("project-generator-lockfile-{}", std::process::id()));
+        fs::create_dir_all(&work_dir).unwrap();
+        let manifest: TemplateManifest = serde_json::from_str(
+            r#"{"files": [], "prompts": [{"title": "Site", "variables": [
+                {"name": "cms"}, {"name": "cms_token", "secret": true}
+            ]}]}"#,
+        )
+        .unwrap();
+        context::set_variables(
+            [
+                ("project_name", "demo"),
+                ("cms", "strapi"),
+                ("cms_token", "declared-secret"),
+                ("prod_hostname", "demo.example.com"),
+                ("npm_token", "from-the-environment"),
+            ]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        );
+
+        write(&work_dir, &work_dir, &manifest).unwrap();
+        let content = fs::read_to_string(work_dir.join(LOCKFILE)).unwrap();
+        let lock = read(&work_dir).unwrap();
+        fs::remove_dir_all(&work_dir).unwrap();
+
+        assert_eq!(
+            lock.variables.keys().collect::<Vec<_>>(),
+            ["cms", "prod_hostname", "project_name"]
+        );
+        assert_eq!(lock.secret_variables, ["cms_token", "npm_token"]);
+        assert!(!content.contains("declared-secret"));
+        assert!(!content.contains("from-the-environment"));
+    }
+}
@@ -0,0 +1,109 @@
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+
+use crate::config::MergeDriver;
+
+/// A value changed differently in the project and in the template
+struct Conflict;
+
+/// 3-way merge of a JSON or YAML document key by key: a key changed on one side only takes
+/// that side's value, the project's order of keys is kept and keys added by the template are
+/// appended. Lists are merged as a whole. `None` when a value was changed differently on
+/// both sides, or when a version does not parse.
+pub fn merge_structured(
+    driver: MergeDriver,
+    current: &[u8],
+    base: &[u8],
+    updated: &[u8],
+) -> Option<Vec<u8>> {
+    let base_value = parse(driver, base)?;
+    let current_value = parse(driver, current)?;
+    let updated_value = parse(driver, updated)?;
+    let merged = merge_values(
+        Some(&base_value),
+        Some(&current_value),
+        Some(&updated_value),
+    )
+    .ok()??;
+    serialize(driver, &merged, current)
+}
+
+/// Whether the content is a valid document for the driver
+pub fn parses(driver: MergeDriver, content: &[u8]) -> bool {
+    driver == MergeDriver::Text || parse(driver, content).is_some()
+}
+
+fn parse(driver: MergeDriver, content: &[u8]) -> Option<Value> {
+    let content = std::str::from_utf8(content).ok()?;
+    match driver {
+        // Read into YAML values, whose mappings keep the order of the keys
+        MergeDriver::Json => serde_json::from_str(content).ok(),
+        MergeDriver::Yaml => serde_yaml::from_str(content).ok(),
+        MergeDriver::Text => None,
+    }
+}
+
+fn merge_values(
+    base: Option<&Value>,
+    current: Option<&Value>,
+    updated: Option<&Value>,
+) -> Result<Option<Value>, Conflict> {
+    if current == updated || updated == base {
+        return Ok(current.cloned());
+    }
+    if current == base {
+        return Ok(updated.cloned());
+    }
+
+    let (Some(Value::Mapping(current)), Some(Value::Mapping(updated))) = (current, updated) else {
+        return Err(Conflict);
+    };
+    let empty = Mapping::new();
+    let base = match base {
+        Some(Value::Mapping(base)) => base,
+        _ => &empty,
+    };
+
+    let mut merged = Mapping::new();
+    for (key, value) in current {
+        if let Some(value) = merge_values(base.get(key), Some(value), updated.get(key))? {
+            merged.insert(key.clone(), value);
+        }
+    }
+    for (key, value) in updated {
+        if current.contains_key(key) {
+            continue;
+        }
+        if let Some(value) = merge_values(base.get(key), None, Some(value))? {
+            merged.insert(key.clone(), value);
+        }
+    }
+    Ok(Some(Value::Mapping(merged)))
+}
+
+/// Write the merged document, indented like the project's version for JSON
+fn serialize(driver: MergeDriver, value: &Value, current: &[u8]) -> Option<Vec<u8>> {
+    let current = String::from_utf8_lossy(current);
+    let mut output = match driver {
+        MergeDriver::Json => {
+            let indent = current
+                .lines()
+                .skip(1)
+                .find(|line| !line.trim().is_empty())
+                .map(|line| &line[..line.len() - line.trim_start().len()])
+                .filter(|indent| !indent.is_empty())
+                .unwrap_or("  ");
+            let mut output = Vec::new();
+            let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+            let mut serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+            value.serialize(&mut serializer).ok()?;
+            output
+        }
+        MergeDriver::Yaml => serde_yaml::to_string(value).ok()?.into_bytes(),
+        MergeDriver::Text => return None,
+    };
+    if current.ends_with('\n') && !output.ends_with(b"\n") {
+        output.push(b'\n');
+    }
+    Some(output)
+}
@@ -1,42 +1,82 @@
 use std::io::{self, Error, ErrorKind, Result, Write};
 use std::path::{Path, PathBuf};
 
+use crate::config::CommitSettings;
+use crate::utils::error::GeneratorError;
+use package_manager::PackageManager;
+use project_generator::GenerateOptions;
+
+pub mod ci_script;
+pub mod codeowners;
+pub mod config_files;
+pub mod content;
 pub mod file_operations;
 pub mod functions;
+pub mod hooks;
+pub mod hostnames;
+pub mod i18n;
+pub mod images;
+pub mod jsonc;
+pub mod lockfile;
+pub mod merge;
+pub mod package_manager;
+pub mod pages;
+pub mod prefetch;
 pub mod project_generator;
+pub mod project_kind;
+pub mod provenance;
+pub mod python;
+pub mod quality;
+pub mod release;
+pub mod render;
+pub mod reusable_workflows;
+pub mod sandbox;
+pub mod stats;
+pub mod substitution;
+pub mod update;
+pub mod variables;
+pub mod workflows;
+pub mod workspace;
 
-pub fn handle_interactive_mode(template_path: &Path) -> Result<()> {
-    match crate::cli::interact(template_path) {
-        Ok(_) => {
-            println!("Project generated successfully");
-            Ok(())
-        }
-        Err(e) => Err(Error::other(e.to_string())),
-    }
+pub fn handle_interactive_mode(
+    template_path: &Path,
+    into: Option<&Path>,
+    git_init: Option<&CommitSettings>,
+    options: &GenerateOptions,
+) -> Result<()> {
+    crate::cli::interact(template_path, into, git_init, options)?;
+    log::info!("Project generated successfully");
+    Ok(())
 }
 
-pub fn handle_config_mode(template_path: &Path, project_name: &str) -> Result<()> {
+pub fn handle_config_mode(
+    template_path: &Path,
+    project_name: &str,
+    git_init: Option<&CommitSettings>,
+    options: &GenerateOptions,
+) -> Result<()> {
     let default_project_path = std::path::Path::new(&crate::config::PACKAGE_ROOT_PATH)
         .join(crate::config::CREATION_PATH)
         .join(project_name);
 
     // Display the default path and ask for confirmation
-    println!(
+    log::info!(
         "Project will be created in: {}",
         default_project_path.display()
     );
-    print!("Is this path correct? (Y/n): ");
-    io::stdout().flush().unwrap();
+    // Without prompts, the default path is used
+    let use_default = crate::cli::functions::prompts_disabled() || {
+        print!("Is this path correct? (Y/n): ");
+        io::stdout().flush().unwrap();
 
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).map_err(|e| {
-        Error::other(
-            format!("Failed to read user input: {}", e),
-        )
-    })?;
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .map_err(|e| Error::other(format!("Failed to read user input: {}", e)))?;
 
-    let input = input.trim().to_lowercase();
-    let use_default = input.is_empty() || input == "y" || input == "yes";
+        let input = input.trim().to_lowercase();
+        input.is_empty() || input == "y" || input == "yes"
+    };
 
     let project_path = if use_default {
         default_project_path
@@ -46,11 +86,9 @@ pub fn handle_config_mode(template_path: &Path, project_name: &str) -> Result<()
         io::stdout().flush().unwrap();
 
         let mut custom_path = String::new();
-        io::stdin().read_line(&mut custom_path).map_err(|e| {
-            Error::other(
-                format!("Failed to read custom path: {}", e),
-            )
-        })?;
+        io::stdin()
+            .read_line(&mut custom_path)
+            .map_err(|e| Error::other(format!("Failed to read custom path: {}", e)))?;
 
         let custom_path = custom_path.trim();
         if custom_path.is_empty() {
@@ -60,7 +98,12 @@ pub fn handle_config_mode(template_path: &Path, project_name: &str) -> Result<()
         PathBuf::from(custom_path).join(project_name)
     };
 
-    handle_config_mode_with_path(template_path, project_name, &project_path, true)
+    handle_config_mode_with_path(template_path, project_name, &project_path, true, options)?;
+
+    if let Some(commit) = git_init {
+        project_generator::init_git_repository(&project_path, commit)?;
+    }
+    Ok(())
 }
 
 pub fn handle_config_mode_with_path(
@@ -68,27 +111,72 @@ pub fn handle_config_mode_with_path(
     project_name: &str,
     project_path: &Path,
     install_deps: bool,
+    options: &GenerateOptions,
 ) -> Result<()> {
-    println!(
+    log::info!(
         "Generating project '{}' with template '{}'",
         project_name,
         template_path.display()
     );
 
-    project_generator::generate_project(template_path, project_path).map_err(|e| {
-        Error::other(
-            format!("An error occurred while generating the project: {}", e),
-        )
+    project_generator::generate_project(template_path, project_path, options).map_err(|e| {
+        GeneratorError::from(e).context("An error occurred while generating the project")
     })?;
 
     if install_deps {
-        project_generator::install_dependencies(project_path).map_err(|e| {
-            Error::other(
-                format!("An error occurred while installing dependencies: {}", e),
-            )
+        project_generator::install_dependencies(project_path, options).map_err(|e| {
+            GeneratorError::from(e).context("An error occurred while installing dependencies")
         })?;
+        quality::record_baseline(template_path, project_path, &options.install)?;
     }
 
-    println!("Project generated successfully");
+    log::info!("Project generated successfully");
+    Ok(())
+}
+
+/// Generate a package inside a checked-out monorepo, without creating any GitHub repository
+pub fn handle_into_mode(
+    template_path: &Path,
+    project_name: &str,
+    into: &Path,
+    options: &GenerateOptions,
+) -> Result<()> {
+    let root = workspace::enclosing_root(into).ok_or_else(|| {
+        Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "'{}' is not inside a pnpm workspace (no {} found)",
+                into.display(),
+                workspace::WORKSPACE_FILE
+            ),
+        )
+    })?;
+
+    let project_path = into.join(project_name);
+    if project_path.exists() {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("'{}' already exists", project_path.display()),
+        ));
+    }
+
+    log::info!(
+        "Generating package '{}' into workspace '{}'",
+        project_name,
+        root.display()
+    );
+    project_generator::generate_project(template_path, &project_path, options).map_err(|e| {
+        GeneratorError::from(e).context("An error occurred while generating the package")
+    })?;
+
+    // Installing from the root links the new package with the rest of the workspace
+    project_generator::install_dependencies_with(PackageManager::Pnpm, &root, options).map_err(
+        |e| GeneratorError::from(e).context("An error occurred while installing dependencies"),
+    )?;
+
+    log::info!(
+        "Package generated successfully in {}",
+        project_path.display()
+    );
     Ok(())
 }
@@ -0,0 +1,200 @@
+use std::fs;
+use std::io::{Error, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use crate::generate::sandbox;
+use crate::utils::{progress, summary};
+
+/// Package manager installing the dependencies of the generated project
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageManager {
+    #[default]
+    Pnpm,
+    Npm,
+    Yarn,
+    Bun,
+}
+
+const ALL: [PackageManager; 4] = [
+    PackageManager::Pnpm,
+    PackageManager::Yarn,
+    PackageManager::Bun,
+    PackageManager::Npm,
+];
+
+/// How the dependencies of the generated project are installed
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    /// Leave the dependencies uninstalled, set by `--skip-install`
+    pub skip: bool,
+    /// Install without network access, set by `--offline`
+    pub offline: bool,
+    /// HOME of the package managers and hooks, set by `--isolated-home`
+    pub home: Option<PathBuf>,
+}
+
+impl InstallOptions {
+    /// Variables of the commands run for the project, see [`sandbox::environment`]
+    pub fn environment(&self) -> Vec<(&'static str, PathBuf)> {
+        sandbox::environment(self.home.as_deref())
+    }
+}
+
+/// Package manager of the last generated project, used for its installation
+static SELECTED: Mutex<Option<PackageManager>> = Mutex::new(None);
+
+impl PackageManager {
+    pub fn command(self) -> &'static str {
+        match self {
+            PackageManager::Pnpm => "pnpm",
+            PackageManager::Npm => "npm",
+            PackageManager::Yarn => "yarn",
+            PackageManager::Bun => "bun",
+        }
+    }
+
+    /// Lockfiles the package manager writes, the current format first
+    pub fn lockfiles(self) -> &'static [&'static str] {
+        match self {
+            PackageManager::Pnpm => &["pnpm-lock.yaml"],
+            PackageManager::Npm => &["package-lock.json", "npm-shrinkwrap.json"],
+            PackageManager::Yarn => &["yarn.lock"],
+            PackageManager::Bun => &["bun.lock", "bun.lockb"],
+        }
+    }
+
+    /// Arguments installing the dependencies. A frozen install fails instead of updating an
+    /// outdated lockfile, as CI should.
+    pub fn install_args(self, frozen: bool) -> &'static [&'static str] {
+        match (self, frozen) {
+            (PackageManager::Npm, true) => &["ci"],
+            (PackageManager::Npm, false) => &["install"],
+            (_, true) => &["install", "--frozen-lockfile"],
+            (_, false) => &["install"],
+        }
+    }
+
+    /// Flag installing from the local store only. Bun has none, it installs from its cache
+    /// when it can.
+    pub fn offline_flag(self) -> Option<&'static str> {
+        match self {
+            PackageManager::Pnpm | PackageManager::Npm | PackageManager::Yarn => Some("--offline"),
+            PackageManager::Bun => None,
+        }
+    }
+
+    /// e.g. `pnpm install`
+    pub fn install_command(self, frozen: bool) -> String {
+        format!("{} {}", self.command(), self.install_args(frozen).join(" "))
+    }
+
+    /// The command [`install`] runs, e.g. `pnpm install --offline`
+    pub fn local_install_command(self, offline: bool) -> String {
+        match self.offline_flag().filter(|_| offline) {
+            Some(flag) => format!("{} {}", self.install_command(false), flag),
+            None => self.install_command(false),
+        }
+    }
+
+    /// The package manager whose lockfile `dir` holds
+    pub fn detect(dir: &Path) -> Option<PackageManager> {
+        ALL.into_iter().find(|manager| {
+            manager
+                .lockfiles()
+                .iter()
+                .any(|lockfile| dir.join(lockfile).is_file())
+        })
+    }
+
+    /// The package manager of a generated project: the one selected for it during generation,
+    /// else the one of its lockfile, else pnpm
+    pub fn for_project(project_path: &Path) -> PackageManager {
+        SELECTED
+            .lock()
+            .ok()
+            .and_then(|selected| *selected)
+            .or_else(|| PackageManager::detect(project_path))
+            .unwrap_or_default()
+    }
+}
+
+/// Select the package manager of the generated project, declared by the template or detected
+/// from its lockfile, and remove the lockfiles of the other package managers so that the
+/// project and its CI agree on one
+pub fn prepare(project_path: &Path, declared: Option<PackageManager>) -> Result<PackageManager> {
+    let manager = declared
+        .or_else(|| PackageManager::detect(project_path))
+        .unwrap_or_default();
+    log::debug!("Using package manager {}", manager.command());
+
+    for other in ALL.into_iter().filter(|other| *other != manager) {
+        for lockfile in other.lockfiles() {
+            let path = project_path.join(lockfile);
+            if path.is_file() {
+                fs::remove_file(&path).map_err(|e| {
+                    Error::new(
+                        e.kind(),
+                        format!("Failed to remove {}: {}", path.display(), e),
+                    )
+                })?;
+                log::info!(
+                    "🧹 Removed {}, the project uses {}",
+                    lockfile,
+                    manager.command()
+                );
+            }
+        }
+    }
+
+    if let Ok(mut selected) = SELECTED.lock() {
+        *selected = Some(manager);
+    }
+    Ok(manager)
+}
+
+/// Install the dependencies of `dir` with `manager`, from the local store with `--offline`
+pub fn install(manager: PackageManager, dir: &Path, options: &InstallOptions) -> Result<()> {
+    let mut command = Command::new(manager.command());
+    command
+        .args(manager.install_args(false))
+        .envs(options.environment())
+        .stdout(summary::child_stdout());
+    if options.offline {
+        match manager.offline_flag() {
+            Some(flag) => {
+                command.arg(flag);
+            }
+            None => log::warn!(
+                "{} has no offline mode, installing from its cache when possible",
+                manager.command()
+            ),
+        }
+    }
+    let _paused = progress::pause();
+    let status = command.current_dir(dir).status().map_err(|e| {
+        Error::new(
+            e.kind(),
+            format!(
+                "Failed to run {}: {}",
+                manager.local_install_command(options.offline),
+                e
+            ),
+        )
+    })?;
+    if !status.success() {
+        log::debug!(
+            "ERROR: {} failed with status: {}",
+            manager.local_install_command(options.offline),
+            status
+        );
+        return Err(Error::other(format!(
+            "{} failed with status: {}",
+            manager.local_install_command(options.offline),
+            status
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use std::fs;
+use std::path::Path;
+
+/// Where static site generators expect the `CNAME` file giving a GitHub Pages site its custom
+/// domain: the published root, or the directory copied into it at build time
+pub const CNAME_PATHS: &[&str] = &["CNAME", "public/CNAME", "static/CNAME", "docs/CNAME"];
+
+/// Custom domains the project's GitHub Pages site will claim, from its `CNAME` files
+pub fn custom_domains(project_path: &Path) -> Vec<String> {
+    let mut domains: Vec<String> = CNAME_PATHS
+        .iter()
+        .filter_map(|path| fs::read_to_string(project_path.join(path)).ok())
+        .filter_map(|content| {
+            content
+                .lines()
+                .map(|line| line.trim().trim_end_matches('.').to_lowercase())
+                .find(|line| !line.is_empty())
+        })
+        .collect();
+    domains.sort();
+    domains.dedup();
+    domains
+}
@@ -0,0 +1,68 @@
+use std::io::Result;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+use crate::generate::package_manager::InstallOptions;
+
+/// Lockfile `pnpm fetch` reads the packages to download from
+const LOCKFILE: &str = "pnpm-lock.yaml";
+
+/// `pnpm fetch` output with its start time
+type Fetch = JoinHandle<Result<(Output, Instant)>>;
+
+/// The running `pnpm fetch`, awaited before installing the dependencies
+static RUNNING: Mutex<Option<Fetch>> = Mutex::new(None);
+
+/// Start `pnpm fetch` on the template lockfile in the background, to warm the pnpm store
+/// while the project is generated. Without a lockfile there is nothing to prefetch.
+pub fn start(template_path: &Path, options: &InstallOptions) {
+    if !template_path.join(LOCKFILE).exists() {
+        log::debug!("No {} in the template, skipping prefetch", LOCKFILE);
+        return;
+    }
+
+    log::info!("📦 Warming the pnpm store in the background");
+    let template_path = template_path.to_path_buf();
+    let environment = options.environment();
+    let handle = thread::spawn(move || {
+        let started = Instant::now();
+        let output = Command::new("pnpm")
+            .arg("fetch")
+            .current_dir(&template_path)
+            .envs(environment)
+            .stdin(Stdio::null())
+            .output()?;
+        Ok((output, started))
+    });
+    if let Ok(mut running) = RUNNING.lock() {
+        *running = Some(handle);
+    }
+}
+
+/// Wait for the prefetch started by [`start`], if any. A failed prefetch only means a colder
+/// cache, so it is reported without failing the generation.
+pub fn wait() {
+    let Some(handle) = RUNNING.lock().ok().and_then(|mut running| running.take()) else {
+        return;
+    };
+
+    log::debug!("Waiting for pnpm fetch to finish");
+    match handle.join() {
+        Ok(Ok((output, started))) if output.status.success() => {
+            log::debug!(
+                "pnpm fetch finished in {} ms",
+                started.elapsed().as_millis()
+            );
+        }
+        Ok(Ok((output, _))) => log::warn!(
+            "Pnpm fetch failed ({}), installing with a cold cache: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Ok(Err(e)) => log::warn!("Could not run pnpm fetch: {}", e),
+        Err(_) => log::warn!("Pnpm fetch panicked"),
+    }
+}
@@ -1,98 +1,426 @@
 use std::fs;
 use std::io::{Error, ErrorKind};
 use std::path::Path;
-use std::process::Command;
+use std::time::Instant;
 
-use crate::config::TemplateJson;
-use crate::generate::file_operations;
+use crate::config::{CommitSettings, TemplateJson};
+use crate::generate::package_manager::{InstallOptions, PackageManager};
+use crate::generate::project_kind::{self, ProjectKind};
+use crate::generate::{
+    ci_script, codeowners, content, file_operations, hooks, i18n, images, lockfile,
+    package_manager, prefetch, provenance, python, release, render, reusable_workflows, stats,
+    substitution, variables, workflows, workspace,
+};
+use crate::policy::naming::NamingRules;
+use crate::utils::commit;
 use crate::utils::context;
+use crate::utils::error::GeneratorError;
+use crate::utils::logging;
+use crate::utils::notify::{self, NotifyMode};
 use crate::utils::strings;
 
-pub fn generate_project(template_path: &Path, project_path: &Path) -> std::io::Result<()> {
-    context::debug_print("Starting project generation");
-    context::debug_print(&format!("Template path: {}", template_path.display()));
-    context::debug_print(&format!("Project path: {}", project_path.display()));
+/// How a generation runs, set from the command line and the config file
+#[derive(Debug, Clone)]
+pub struct GenerateOptions {
+    /// Run the template's pre_generate and post_generate hooks
+    pub run_hooks: bool,
+    /// Warm the pnpm store while the project is rendered, set by `--prefetch`
+    pub prefetch: bool,
+    /// Register the project in the enclosing workspace
+    pub register_workspace: bool,
+    /// Count the generation in the template usage statistics, unset by `--no-stats`
+    pub record_stats: bool,
+    pub install: InstallOptions,
+    /// Notify the end of a long installation, set by `--notify`
+    pub notify: Option<NotifyMode>,
+    /// Org naming conventions, applied to the names prompted for
+    pub naming: NamingRules,
+    /// Team of the project, selecting its prefix in the naming conventions
+    pub team: Option<String>,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        GenerateOptions {
+            run_hooks: true,
+            prefetch: false,
+            register_workspace: true,
+            record_stats: true,
+            install: InstallOptions::default(),
+            notify: None,
+            naming: NamingRules::default(),
+            team: None,
+        }
+    }
+}
+
+impl GenerateOptions {
+    /// Scratch renders, e.g. the base and updated renders of an update, only write the files
+    pub fn render_only() -> Self {
+        GenerateOptions {
+            run_hooks: false,
+            prefetch: false,
+            register_workspace: false,
+            record_stats: false,
+            install: InstallOptions {
+                skip: true,
+                ..Default::default()
+            },
+            notify: None,
+            naming: NamingRules::default(),
+            team: None,
+        }
+    }
+}
+
+pub fn generate_project(
+    template_path: &Path,
+    project_path: &Path,
+    options: &GenerateOptions,
+) -> std::io::Result<()> {
+    let _stage = logging::stage("render");
+    log::debug!("Starting project generation");
+    log::debug!("Template path: {}", template_path.display());
+    log::debug!("Project path: {}", project_path.display());
 
     if !template_path.exists() {
-        context::debug_print(&format!(
-            "ERROR: Template not found at {}",
-            template_path.display()
-        ));
-        return Err(Error::new(ErrorKind::NotFound, "Template not found"));
+        log::debug!("ERROR: Template not found at {}", template_path.display());
+        return Err(GeneratorError::Template("Template not found".to_string()).into());
+    }
+
+    // Every variable is checked before anything is written
+    log::debug!("Reading template configuration");
+    let manifest = strings::read_template_manifest(template_path)?;
+    let config = &manifest.files;
+    log::debug!("Found {} template configurations", config.len());
+
+    log::debug!("Resolving conditional variables");
+    let active = variables::resolve_conditional_variables(&manifest.prompts)?;
+
+    log::debug!("Computing derived variables");
+    variables::resolve_computed_variables(&manifest.computed)?;
+
+    log::debug!("Asking for missing required variables");
+    variables::prompt_for_missing_replacements(config)?;
+
+    log::debug!("Validating variables");
+    variables::validate_variables(&manifest, &active)?;
+
+    // `pnpm fetch` only warms the store of pnpm projects that are installed
+    if options.prefetch
+        && !options.install.skip
+        && manifest.project_kind == ProjectKind::Node
+        && matches!(manifest.package_manager, None | Some(PackageManager::Pnpm))
+    {
+        prefetch::start(template_path, &options.install);
     }
 
-    context::debug_print("Creating project directory");
+    log::debug!("Creating project directory");
     fs::create_dir_all(project_path)?;
+    if options.run_hooks {
+        hooks::run(
+            "pre_generate",
+            &manifest.hooks.pre_generate,
+            project_path,
+            false,
+            options.install.home.as_deref(),
+        )?;
+    }
 
-    context::debug_print("Copying template files");
+    log::debug!("Copying template files");
     file_operations::copy_dir_all(template_path, project_path)?;
 
-    println!(
+    log::info!(
         "Project '{}' copied from template '{}' successfully",
         project_path.file_name().unwrap().to_string_lossy(),
         template_path.file_name().unwrap().to_string_lossy()
     );
 
-    context::debug_print("Reading template configuration");
-    let config = strings::read_template_config(template_path)?;
-    context::debug_print(&format!("Found {} template configurations", config.len()));
+    project_kind::prepare(project_path, &manifest)?;
+    if manifest.project_kind == ProjectKind::Node {
+        log::debug!("Selecting the package manager");
+        package_manager::prepare(project_path, manifest.package_manager)?;
+    }
+
+    log::debug!("Rendering templated file names");
+    render::render_file_names(project_path)?;
+
+    log::debug!("Rendering template files");
+    render::render_templates(project_path)?;
+
+    if !manifest.images.is_empty() {
+        log::debug!("Optimizing template images");
+        images::optimize_images(project_path, &manifest.images)?;
+    }
+
+    log::debug!("Applying template configuration");
+    apply_template_config(project_path, config)?;
+
+    if let Some(i18n_config) = &manifest.i18n {
+        log::debug!("Scaffolding locales");
+        i18n::scaffold_locales(project_path, i18n_config)?;
+    }
+
+    if let Some(substitution_config) = &manifest.substitution {
+        log::debug!("Substituting variables in text files");
+        substitution::substitute_variables(project_path, substitution_config)?;
+    }
+
+    if reusable_workflows::is_requested() {
+        match &manifest.reusable_workflows {
+            Some(config) => {
+                log::debug!("Generating reusable workflow callers");
+                reusable_workflows::write_callers(project_path, config)?;
+            }
+            None => log::warn!(
+                "Reusable_workflows is enabled but the template does not declare reusable workflows, its workflows are kept"
+            ),
+        }
+    }
+
+    if release::is_requested() {
+        let flow = manifest.release.as_ref().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "release_flow is enabled but the template does not declare a release flow",
+            )
+        })?;
+        log::debug!("Generating release workflow");
+        release::write_release_workflow(project_path, flow)?;
+    }
+
+    log::debug!("Checking workflows");
+    workflows::check_schema(project_path)?;
+
+    if let Some(script) = &manifest.ci_script {
+        log::debug!("Generating local CI script");
+        ci_script::write_ci_script(project_path, script)?;
+    }
+
+    if !manifest.codeowners.is_empty() {
+        log::debug!("Rendering CODEOWNERS");
+        codeowners::write_codeowners(project_path, &manifest.codeowners)?;
+    }
+
+    if manifest.provenance.enabled {
+        log::debug!("Injecting provenance headers");
+        provenance::inject_headers(template_path, project_path, &manifest.ownership)?;
+    }
+
+    // Seeded after provenance headers, which would break the entries' frontmatter
+    if !manifest.content.is_empty() {
+        log::debug!("Seeding content collections");
+        content::seed_collections(project_path, &manifest.content)?;
+    }
+
+    // Packages generated inside a monorepo are registered in its workspace
+    if let Some(root) = workspace::find_root(project_path).filter(|_| options.register_workspace) {
+        log::debug!("Found workspace root: {}", root.display());
+        workspace::register_package(&root, project_path, &manifest.workspace)?;
+    }
 
-    context::debug_print("Applying template configuration");
-    apply_template_config(project_path, &config)?;
+    if options.run_hooks {
+        hooks::run(
+            "post_generate",
+            &manifest.hooks.post_generate,
+            project_path,
+            false,
+            options.install.home.as_deref(),
+        )?;
+    }
 
-    context::debug_print("Project generation completed successfully");
+    lockfile::write(template_path, project_path, &manifest)?;
+    if options.record_stats {
+        stats::record(template_path, &manifest);
+    }
+
+    log::debug!("Project generation completed successfully");
     Ok(())
 }
 
 fn apply_template_config(project_path: &Path, config: &TemplateJson) -> std::io::Result<()> {
-    context::debug_print(&format!(
-        "Applying {} template configurations",
-        config.len()
-    ));
+    log::debug!("Applying {} template configurations", config.len());
 
+    let variables = context::get_variables();
     for (i, file) in config.iter().enumerate() {
-        context::debug_print(&format!(
+        log::debug!(
             "Processing configuration {}: {} files to replace",
             i + 1,
             file.files_to_replace.len()
-        ));
+        );
 
         for file_to_replace in &file.files_to_replace {
+            // Listed with their template name, e.g. `src/{{module_name}}/index.ts`
+            let file_to_replace =
+                &render::render_path(file_to_replace, &variables).map_err(|e| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Invalid file to replace '{}': {}", file_to_replace, e),
+                    )
+                })?;
             let file_path = project_path.join(file_to_replace);
-            context::debug_print(&format!("Processing file: {}", file_path.display()));
+            let _task = context::debug_task(file_to_replace.as_str());
+            log::debug!("Processing file: {}", file_path.display());
 
             if let Err(e) = file_operations::replace_in_file(&file_path, &file.replacements) {
-                context::debug_print(&format!("ERROR updating file {}: {}", file_to_replace, e));
-                println!("Error updating file {}: {}", file_to_replace, e);
+                log::debug!("ERROR updating file {}: {}", file_to_replace, e);
+                log::info!("Error updating file {}: {}", file_to_replace, e);
             } else {
-                context::debug_print(&format!("Successfully updated file: {}", file_to_replace));
+                log::debug!("Successfully updated file: {}", file_to_replace);
             }
         }
     }
     Ok(())
 }
 
-pub fn install_dependencies(project_path: &Path) -> std::io::Result<()> {
-    context::debug_print(&format!(
-        "Installing dependencies in: {}",
-        project_path.display()
-    ));
+pub fn install_dependencies(project_path: &Path, options: &GenerateOptions) -> std::io::Result<()> {
+    let _stage = logging::stage("install");
+    let kind = ProjectKind::for_project(project_path);
+    if kind == ProjectKind::Node {
+        return install_dependencies_with(
+            PackageManager::for_project(project_path),
+            project_path,
+            options,
+        );
+    }
+    if options.install.skip {
+        log::info!("⏭️  Skipping dependency installation (--skip-install)");
+        return Ok(());
+    }
 
-    let status = Command::new("pnpm")
-        .arg("install")
-        .current_dir(project_path)
-        .status()?;
+    log::debug!(
+        "Installing {:?} project in: {}",
+        kind,
+        project_path.display()
+    );
+    let started = Instant::now();
+    let result = match kind {
+        ProjectKind::Python => python::install(project_path, &options.install),
+        _ => project_kind::install_crate(project_path, &options.install),
+    };
+    notify::finished(
+        options.notify,
+        "Dependency installation",
+        started,
+        result.is_ok(),
+    );
+    result
+}
 
-    if !status.success() {
-        context::debug_print(&format!(
-            "ERROR: pnpm install failed with status: {}",
-            status
-        ));
-        return Err(std::io::Error::other(
-            "Failed to install dependencies",
-        ));
+/// Install the dependencies of `project_path` with the given package manager
+pub fn install_dependencies_with(
+    manager: PackageManager,
+    project_path: &Path,
+    options: &GenerateOptions,
+) -> std::io::Result<()> {
+    if options.install.skip {
+        log::info!("⏭️  Skipping dependency installation (--skip-install)");
+        return Ok(());
     }
 
-    context::debug_print("Dependencies installed successfully");
+    log::debug!(
+        "Installing dependencies in: {} with {}",
+        project_path.display(),
+        manager.command()
+    );
+
+    prefetch::wait();
+
+    let started = Instant::now();
+    let result = package_manager::install(manager, project_path, &options.install);
+    notify::finished(
+        options.notify,
+        "Dependency installation",
+        started,
+        result.is_ok(),
+    );
+    result?;
+
+    log::debug!("Dependencies installed successfully");
+    Ok(())
+}
+
+/// Initialize a git repository holding every generated file in a first commit on `main`,
+/// for projects pushed manually later
+pub fn init_git_repository(project_path: &Path, commit: &CommitSettings) -> std::io::Result<()> {
+    let git_error =
+        |e: git2::Error| Error::other(format!("Failed to initialize git repository: {}", e));
+
+    let mut options = git2::RepositoryInitOptions::new();
+    options.initial_head("main");
+    let repo = git2::Repository::init_opts(project_path, &options).map_err(git_error)?;
+
+    let mut index = repo.index().map_err(git_error)?;
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .map_err(git_error)?;
+    index.write().map_err(git_error)?;
+    let tree_id = index.write_tree().map_err(git_error)?;
+    let tree = repo.find_tree(tree_id).map_err(git_error)?;
+
+    // Without a configured author, commit as the user when their git identity is configured
+    let default = CommitSettings::default();
+    let signature = match repo.signature() {
+        Ok(signature)
+            if commit.author_name == default.author_name
+                && commit.author_email == default.author_email =>
+        {
+            signature
+        }
+        _ => git2::Signature::now(&commit.author_name, &commit.author_email).map_err(git_error)?,
+    };
+    commit::create(
+        &repo,
+        &signature,
+        &commit.message,
+        &tree,
+        &[],
+        commit.signing.as_ref(),
+    )
+    .map_err(git_error)?;
+
+    log::info!("📁 Initialized git repository with a first commit on main");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_only_generation_skips_hooks() {
+        let work_dir = std::env::temp_dir().join(format!(
+            "project-generator-render-only-{}",
+            std::process::id()
+        ));
+        let template_path = work_dir.join("template");
+        fs::create_dir_all(&template_path).unwrap();
+        fs::write(
+            template_path.join("template_config.json"),
+            r#"{"files": [], "hooks": {"pre_generate": ["touch pre"], "post_generate": ["touch post"]}}"#,
+        )
+        .unwrap();
+        fs::write(template_path.join("README.md.hbs"), "# {{project_name}}\n").unwrap();
+        context::set_variables([("project_name".to_string(), "demo".to_string())].into());
+
+        let rendered = work_dir.join("rendered");
+        generate_project(&template_path, &rendered, &GenerateOptions::render_only()).unwrap();
+        assert_eq!(
+            fs::read_to_string(rendered.join("README.md")).unwrap(),
+            "# demo\n"
+        );
+        assert!(!rendered.join("pre").exists());
+        assert!(!rendered.join("post").exists());
+
+        let generated = work_dir.join("generated");
+        let options = GenerateOptions {
+            record_stats: false,
+            ..Default::default()
+        };
+        generate_project(&template_path, &generated, &options).unwrap();
+        assert!(generated.join("pre").exists());
+        assert!(generated.join("post").exists());
+
+        fs::remove_dir_all(&work_dir).unwrap();
+    }
+}
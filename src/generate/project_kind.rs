@@ -0,0 +1,210 @@
+use std::fs;
+use std::io::{Error, Result};
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+
+use crate::config::TemplateManifest;
+use crate::generate::package_manager::{InstallOptions, PackageManager};
+use crate::generate::python;
+use crate::utils::{context, progress, summary};
+
+/// What the template generates, deciding how the project is scaffolded and installed
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectKind {
+    /// Installed with the template's package manager
+    #[default]
+    Node,
+    /// A crate, built and formatted with cargo
+    Rust,
+    /// A pyproject.toml project, installed with uv, poetry or pip
+    Python,
+}
+
+/// Kind of the last generated project, used for its installation
+static SELECTED: Mutex<Option<ProjectKind>> = Mutex::new(None);
+
+impl ProjectKind {
+    /// Topic added to the repository
+    pub fn topic(self) -> Option<&'static str> {
+        match self {
+            ProjectKind::Node => None,
+            ProjectKind::Rust => Some("rust"),
+            ProjectKind::Python => Some("python"),
+        }
+    }
+
+    /// The kind of a generated project: the one selected for it during generation, else a
+    /// crate or a Python project when it has a Cargo.toml or a pyproject.toml and no
+    /// package.json
+    pub fn for_project(project_path: &Path) -> ProjectKind {
+        SELECTED
+            .lock()
+            .ok()
+            .and_then(|selected| *selected)
+            .unwrap_or_else(|| {
+                let has = |file: &str| project_path.join(file).is_file();
+                if has("package.json") {
+                    ProjectKind::Node
+                } else if has("Cargo.toml") {
+                    ProjectKind::Rust
+                } else if has("pyproject.toml") {
+                    ProjectKind::Python
+                } else {
+                    ProjectKind::Node
+                }
+            })
+    }
+
+    /// Commands installing the project, as listed in plans
+    pub fn install_commands(self, project_path: &Path, offline: bool) -> Vec<String> {
+        match self {
+            ProjectKind::Node => {
+                vec![PackageManager::for_project(project_path).local_install_command(offline)]
+            }
+            ProjectKind::Rust => vec![cargo_build_command(offline), "cargo fmt".to_string()],
+            ProjectKind::Python => python::install_commands(project_path, offline),
+        }
+    }
+}
+
+/// Select the kind of the generated project. Crates get what `cargo init` would create and
+/// the template does not ship: a Cargo.toml named after the package, a `src/main.rs` and a
+/// `.gitignore` ignoring `target`. Python projects are scaffolded by [`python::prepare`].
+pub fn prepare(project_path: &Path, manifest: &TemplateManifest) -> Result<()> {
+    if let Ok(mut selected) = SELECTED.lock() {
+        *selected = Some(manifest.project_kind);
+    }
+    match manifest.project_kind {
+        ProjectKind::Node => Ok(()),
+        ProjectKind::Rust => scaffold_crate(project_path),
+        ProjectKind::Python => python::prepare(project_path, &manifest.python),
+    }
+}
+
+fn scaffold_crate(project_path: &Path) -> Result<()> {
+    let manifest = project_path.join("Cargo.toml");
+    if !manifest.exists() {
+        let name = context::get_variable("name")
+            .or_else(|| context::get_variable("project_name"))
+            .unwrap_or_else(|| "app".to_string());
+        let name = crate_name(&name);
+        fs::write(
+            &manifest,
+            format!(
+                "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n",
+                name
+            ),
+        )?;
+        log::info!("🦀 Created Cargo.toml for crate {}", name);
+    }
+
+    let src = project_path.join("src");
+    if !src.join("main.rs").exists() && !src.join("lib.rs").exists() {
+        fs::create_dir_all(&src)?;
+        fs::write(
+            src.join("main.rs"),
+            "fn main() {\n    log::info!(\"Hello, world!\");\n}\n",
+        )?;
+        log::debug!("Created src/main.rs");
+    }
+
+    ensure_ignored(project_path, &["/target"])
+}
+
+/// Append the patterns missing from the project's `.gitignore`, a pattern with or without
+/// its leading or trailing `/` counting as present
+pub fn ensure_ignored(project_path: &Path, patterns: &[&str]) -> Result<()> {
+    let gitignore = project_path.join(".gitignore");
+    let mut ignored = fs::read_to_string(&gitignore).unwrap_or_default();
+    let bare = |pattern: &str| pattern.trim().trim_matches('/').to_string();
+    let present: Vec<String> = ignored.lines().map(bare).collect();
+    let missing: Vec<&&str> = patterns
+        .iter()
+        .filter(|pattern| !present.contains(&bare(pattern)))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    if !ignored.is_empty() && !ignored.ends_with('\n') {
+        ignored.push('\n');
+    }
+    for pattern in missing {
+        ignored.push_str(pattern);
+        ignored.push('\n');
+        log::debug!("Ignored {} in .gitignore", pattern);
+    }
+    fs::write(&gitignore, ignored)
+}
+
+/// Package names may hold letters, digits, `-` and `_`
+fn crate_name(name: &str) -> String {
+    let name: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    name.trim_matches('-').to_string()
+}
+
+fn cargo_build_command(offline: bool) -> String {
+    if offline {
+        "cargo build --offline".to_string()
+    } else {
+        "cargo build".to_string()
+    }
+}
+
+/// Build the crate, which fetches its dependencies and writes Cargo.lock, then format it.
+/// Formatting is best effort: rustfmt may not be installed.
+pub fn install_crate(project_path: &Path, options: &InstallOptions) -> Result<()> {
+    let mut build = Command::new("cargo");
+    build
+        .arg("build")
+        .current_dir(project_path)
+        .envs(options.environment())
+        .stdout(summary::child_stdout());
+    if options.offline {
+        build.arg("--offline");
+    }
+    let _paused = progress::pause();
+    let status = build.status().map_err(|e| {
+        Error::new(
+            e.kind(),
+            format!(
+                "Failed to run {}: {}",
+                cargo_build_command(options.offline),
+                e
+            ),
+        )
+    })?;
+    if !status.success() {
+        return Err(Error::other(format!(
+            "{} failed with status: {}",
+            cargo_build_command(options.offline),
+            status
+        )));
+    }
+
+    let _paused = progress::pause();
+    match Command::new("cargo")
+        .arg("fmt")
+        .current_dir(project_path)
+        .envs(options.environment())
+        .stdout(summary::child_stdout())
+        .status()
+    {
+        Ok(status) if status.success() => log::debug!("Formatted the crate"),
+        Ok(status) => log::warn!("Cargo fmt failed with status: {}", status),
+        Err(e) => log::warn!("Failed to run cargo fmt: {}", e),
+    }
+    Ok(())
+}
@@ -0,0 +1,207 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::config::{FileOwnership, OwnershipRules, EXCLUDED_DIRS};
+
+/// Marker identifying a provenance header, so it is never injected twice
+pub const PROVENANCE_MARKER: &str = "@generated-from-template";
+
+/// Marker flagging files owned by the template rather than the project
+pub const MANAGED_MARKER: &str = "DO NOT EDIT";
+
+/// Line comment delimiters for a file, `None` when the format has no comments (e.g. JSON)
+fn comment_style(path: &Path) -> Option<(&'static str, &'static str)> {
+    let file_name = path.file_name()?.to_str()?;
+    if file_name == "Dockerfile" || file_name == "Makefile" || file_name.starts_with(".env") {
+        return Some(("# ", ""));
+    }
+
+    match path.extension()?.to_str()? {
+        "js" | "mjs" | "cjs" | "jsx" | "ts" | "mts" | "cts" | "tsx" | "rs" | "go" | "java"
+        | "kt" | "swift" | "c" | "h" | "cpp" | "scss" | "less" => Some(("// ", "")),
+        "yml" | "yaml" | "toml" | "sh" | "py" | "rb" | "ini" | "conf" => Some(("# ", "")),
+        "html" | "md" | "mdx" | "vue" | "svelte" | "xml" => Some(("<!-- ", " -->")),
+        "css" => Some(("/* ", " */")),
+        _ => None,
+    }
+}
+
+/// Byte offset of the line after `prefix`, the end of the content when it is the last line
+fn after_line(content: &str, prefix: usize) -> usize {
+    content[prefix..]
+        .find('\n')
+        .map_or(content.len(), |i| prefix + i + 1)
+}
+
+/// Where the header goes: after a shebang, an XML declaration or doctype, or Markdown
+/// front matter, all of which must stay at the top of the file
+fn header_offset(path: &Path, content: &str) -> usize {
+    let mut offset = 0;
+    if content.starts_with("#!") {
+        offset = after_line(content, 0);
+    }
+
+    let is_markdown = matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("md" | "mdx")
+    );
+    if is_markdown && (content.starts_with("---\n") || content.starts_with("---\r\n")) {
+        let mut line_start = after_line(content, 0);
+        while line_start < content.len() {
+            let line_end = after_line(content, line_start);
+            if content[line_start..line_end].trim_end() == "---" {
+                return line_end;
+            }
+            line_start = line_end;
+        }
+        // Never closed, so not front matter
+        return offset;
+    }
+
+    let rest = &content[offset..];
+    if rest.starts_with("<?xml") {
+        if let Some(end) = rest.find("?>") {
+            offset = after_line(content, offset + end + 2);
+        }
+    }
+    let rest = content[offset..].trim_start();
+    if rest.len() >= 9 && rest[..9].eq_ignore_ascii_case("<!doctype") {
+        let start = content.len() - rest.len();
+        if let Some(end) = rest.find('>') {
+            offset = after_line(content, start + end + 1);
+        }
+    }
+    offset
+}
+
+/// Template identity recorded in headers: `category/name@revision`
+fn template_identity(template_path: &Path) -> String {
+    let component = |path: Option<&Path>| {
+        path.and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    let name = format!(
+        "{}/{}",
+        component(template_path.parent()),
+        component(Some(template_path))
+    );
+
+    let revision = git2::Repository::discover(template_path)
+        .ok()
+        .and_then(|repo| {
+            let commit = repo.head().ok()?.peel_to_commit().ok()?;
+            Some(commit.id().to_string()[..7].to_string())
+        });
+
+    match revision {
+        Some(revision) => format!("{}@{}", name, revision),
+        None => name,
+    }
+}
+
+/// Prepend a provenance header to every generated file that supports comments
+pub fn inject_headers(
+    template_path: &Path,
+    project_path: &Path,
+    ownership: &OwnershipRules,
+) -> io::Result<()> {
+    let identity = template_identity(template_path);
+    log::debug!("Injecting provenance headers for {}", identity);
+    inject_in_dir(project_path, project_path, &identity, ownership)
+}
+
+fn inject_in_dir(
+    root: &Path,
+    dir: &Path,
+    identity: &str,
+    ownership: &OwnershipRules,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let file_name_str = file_name.to_string_lossy();
+
+        if entry.file_type()?.is_dir() {
+            if file_name_str != ".git" && !EXCLUDED_DIRS.contains(&file_name_str.as_ref()) {
+                inject_in_dir(root, &path, identity, ownership)?;
+            }
+            continue;
+        }
+
+        let Some((open, close)) = comment_style(&path) else {
+            continue;
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if content.contains(PROVENANCE_MARKER) {
+            continue;
+        }
+
+        let relative_path = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let mut header = format!("{}{} {}{}\n", open, PROVENANCE_MARKER, identity, close);
+        if ownership.classify(&relative_path) == FileOwnership::Managed {
+            header.push_str(&format!(
+                "{}{}: managed by the template, changes will be overwritten{}\n",
+                open, MANAGED_MARKER, close
+            ));
+        }
+
+        let offset = header_offset(&path, &content);
+        let (before, after) = content.split_at(offset);
+        let separator = if before.is_empty() || before.ends_with('\n') {
+            ""
+        } else {
+            "\n"
+        };
+        let new_content = format!("{}{}{}{}", before, separator, header, after);
+
+        log::debug!("Added provenance header to {}", relative_path);
+        fs::write(&path, new_content)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_header(file: &str, content: &str) -> String {
+        let (before, after) = content.split_at(header_offset(Path::new(file), content));
+        format!("{}HEADER\n{}", before, after)
+    }
+
+    #[test]
+    fn header_goes_after_leading_declarations() {
+        assert_eq!(with_header("a.ts", "let a;\n"), "HEADER\nlet a;\n");
+        assert_eq!(
+            with_header("run.sh", "#!/bin/sh\necho\n"),
+            "#!/bin/sh\nHEADER\necho\n"
+        );
+        assert_eq!(
+            with_header("a.xml", "<?xml version=\"1.0\"?>\n<a/>\n"),
+            "<?xml version=\"1.0\"?>\nHEADER\n<a/>\n"
+        );
+        assert_eq!(
+            with_header("a.xml", "<?xml version=\"1.0\"?>\n<!DOCTYPE a>\n<a/>\n"),
+            "<?xml version=\"1.0\"?>\n<!DOCTYPE a>\nHEADER\n<a/>\n"
+        );
+        assert_eq!(
+            with_header("index.html", "<!doctype html>\n<html></html>\n"),
+            "<!doctype html>\nHEADER\n<html></html>\n"
+        );
+        assert_eq!(
+            with_header("post.md", "---\ntitle: a\n---\n# A\n"),
+            "---\ntitle: a\n---\nHEADER\n# A\n"
+        );
+        // A YAML document start is not front matter
+        assert_eq!(with_header("a.yml", "---\na: 1\n"), "HEADER\n---\na: 1\n");
+    }
+}
@@ -0,0 +1,280 @@
+use std::fs;
+use std::io::{Error, Result};
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+
+use crate::config::PythonConfig;
+use crate::generate::package_manager::InstallOptions;
+use crate::generate::project_kind;
+use crate::utils::{context, progress, summary};
+
+/// Workflow generated when the template does not ship one
+const CI_WORKFLOW: &str = ".github/workflows/ci.yml";
+
+/// Tool installing the dependencies of a Python project
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PythonBackend {
+    Uv,
+    Poetry,
+    Pip,
+}
+
+/// Backend of the last generated Python project and whether it installs into `.venv`
+static SELECTED: Mutex<Option<(PythonBackend, bool)>> = Mutex::new(None);
+
+impl PythonBackend {
+    /// The backend whose lockfile or configuration `dir` holds
+    pub fn detect(dir: &Path) -> Option<PythonBackend> {
+        if dir.join("uv.lock").is_file() {
+            return Some(PythonBackend::Uv);
+        }
+        let pyproject = fs::read_to_string(dir.join("pyproject.toml")).unwrap_or_default();
+        if dir.join("poetry.lock").is_file() || pyproject.contains("[tool.poetry") {
+            return Some(PythonBackend::Poetry);
+        }
+        if dir.join("requirements.txt").is_file() {
+            return Some(PythonBackend::Pip);
+        }
+        None
+    }
+
+    /// Commands installing the project, or only locking its dependencies without a venv
+    fn commands(self, project_path: &Path, venv: bool, offline: bool) -> Vec<Vec<String>> {
+        let command = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect();
+        let mut commands: Vec<Vec<String>> = match (self, venv) {
+            (PythonBackend::Uv, true) => vec![command(&["uv", "sync"])],
+            (PythonBackend::Uv, false) => vec![command(&["uv", "lock"])],
+            (PythonBackend::Poetry, true) => vec![command(&["poetry", "install"])],
+            (PythonBackend::Poetry, false) => vec![command(&["poetry", "lock"])],
+            (PythonBackend::Pip, true) => {
+                let mut install = command(&[".venv/bin/python", "-m", "pip", "install"]);
+                install.extend(pip_target(project_path).iter().map(|arg| arg.to_string()));
+                vec![command(&["python3", "-m", "venv", ".venv"]), install]
+            }
+            (PythonBackend::Pip, false) => Vec::new(),
+        };
+        if offline {
+            let flag = match self {
+                PythonBackend::Uv => Some("--offline"),
+                PythonBackend::Pip => Some("--no-index"),
+                PythonBackend::Poetry => None,
+            };
+            if let (Some(flag), Some(last)) = (flag, commands.last_mut()) {
+                last.push(flag.to_string());
+            }
+        }
+        commands
+    }
+
+    /// Steps of the CI workflow installing the project, then running `test_command`
+    fn ci_steps(self, project_path: &Path, test_command: Option<&str>) -> String {
+        let (setup, install, run) = match self {
+            PythonBackend::Uv => (
+                "      - uses: astral-sh/setup-uv@v5\n",
+                "uv sync".to_string(),
+                "uv run ",
+            ),
+            PythonBackend::Poetry => (
+                "      - run: pipx install poetry\n",
+                "poetry install".to_string(),
+                "poetry run ",
+            ),
+            PythonBackend::Pip => (
+                "",
+                format!(
+                    "python -m pip install {}",
+                    pip_target(project_path).join(" ")
+                ),
+                "",
+            ),
+        };
+        let mut steps = format!("{}      - run: {}\n", setup, install);
+        if let Some(test_command) = test_command {
+            steps.push_str(&format!("      - run: {}{}\n", run, test_command));
+        }
+        steps
+    }
+}
+
+/// The project itself when it has a pyproject.toml, else its requirements
+fn pip_target(project_path: &Path) -> Vec<&'static str> {
+    if project_path.join("pyproject.toml").is_file() {
+        vec!["-e", "."]
+    } else {
+        vec!["-r", "requirements.txt"]
+    }
+}
+
+/// Select the backend of the generated project, declared by the template or detected, uv
+/// otherwise, and add what the template does not ship: a pyproject.toml named after the
+/// package with its module under `src`, the Python entries of `.gitignore` and a CI workflow
+pub fn prepare(project_path: &Path, config: &PythonConfig) -> Result<()> {
+    let backend = config
+        .backend
+        .or_else(|| PythonBackend::detect(project_path))
+        .unwrap_or(PythonBackend::Uv);
+    log::debug!("Using Python backend {:?}", backend);
+    if let Ok(mut selected) = SELECTED.lock() {
+        *selected = Some((backend, config.venv));
+    }
+
+    if !project_path.join("pyproject.toml").exists()
+        && !project_path.join("requirements.txt").exists()
+    {
+        scaffold_pyproject(project_path, &config.python_version)?;
+    }
+
+    project_kind::ensure_ignored(
+        project_path,
+        &[
+            "__pycache__/",
+            "*.py[cod]",
+            ".venv/",
+            "dist/",
+            "*.egg-info/",
+            ".pytest_cache/",
+        ],
+    )?;
+
+    let workflow = project_path.join(CI_WORKFLOW);
+    if !workflow.exists() {
+        write_ci_workflow(project_path, backend, config)?;
+    }
+    Ok(())
+}
+
+fn scaffold_pyproject(project_path: &Path, python_version: &str) -> Result<()> {
+    let name = context::get_variable("name")
+        .or_else(|| context::get_variable("project_name"))
+        .unwrap_or_else(|| "app".to_string());
+    let name = package_name(&name);
+    fs::write(
+        project_path.join("pyproject.toml"),
+        format!(
+            r#"[project]
+name = "{name}"
+version = "0.1.0"
+requires-python = ">={python_version}"
+dependencies = []
+
+[build-system]
+requires = ["hatchling"]
+build-backend = "hatchling.build"
+"#
+        ),
+    )?;
+
+    let module = project_path.join("src").join(name.replace('-', "_"));
+    if !module.exists() {
+        fs::create_dir_all(&module)?;
+        fs::write(module.join("__init__.py"), "")?;
+    }
+    log::info!("🐍 Created pyproject.toml for package {}", name);
+    Ok(())
+}
+
+/// Names hold letters, digits, `-` and `_`, and start with a letter
+fn package_name(name: &str) -> String {
+    let name: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    name.trim_matches(|c: char| c == '-' || c.is_ascii_digit())
+        .to_string()
+}
+
+fn write_ci_workflow(
+    project_path: &Path,
+    backend: PythonBackend,
+    config: &PythonConfig,
+) -> Result<()> {
+    let workflow = format!(
+        r#"name: CI
+
+on:
+  push:
+    branches: [main]
+  pull_request:
+
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - uses: actions/setup-python@v5
+        with:
+          python-version: "{python_version}"
+{steps}"#,
+        python_version = config.python_version,
+        steps = backend.ci_steps(project_path, config.test_command.as_deref()),
+    );
+
+    let path = project_path.join(CI_WORKFLOW);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, workflow)?;
+    log::debug!("Generated {}", CI_WORKFLOW);
+    Ok(())
+}
+
+fn selected(project_path: &Path) -> (PythonBackend, bool) {
+    SELECTED
+        .lock()
+        .ok()
+        .and_then(|selected| *selected)
+        .unwrap_or_else(|| {
+            (
+                PythonBackend::detect(project_path).unwrap_or(PythonBackend::Uv),
+                true,
+            )
+        })
+}
+
+/// Commands installing the project, as listed in plans
+pub fn install_commands(project_path: &Path, offline: bool) -> Vec<String> {
+    let (backend, venv) = selected(project_path);
+    backend
+        .commands(project_path, venv, offline)
+        .iter()
+        .map(|command| command.join(" "))
+        .collect()
+}
+
+/// Install the project with its backend, into `.venv` unless the template disabled it
+pub fn install(project_path: &Path, options: &InstallOptions) -> Result<()> {
+    let (backend, venv) = selected(project_path);
+    if options.offline && backend == PythonBackend::Poetry {
+        log::warn!("Poetry has no offline mode, installing from its cache when possible");
+    }
+
+    for command in backend.commands(project_path, venv, options.offline) {
+        let display = command.join(" ");
+        let _paused = progress::pause();
+        let status = Command::new(&command[0])
+            .args(&command[1..])
+            .current_dir(project_path)
+            .envs(options.environment())
+            .stdout(summary::child_stdout())
+            // Poetry keeps its virtualenvs outside the project unless told otherwise
+            .env("POETRY_VIRTUALENVS_IN_PROJECT", "true")
+            .status()
+            .map_err(|e| Error::new(e.kind(), format!("Failed to run {}: {}", display, e)))?;
+        if !status.success() {
+            return Err(Error::other(format!(
+                "{} failed with status: {}",
+                display, status
+            )));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,188 @@
+use indexmap::IndexMap;
+use std::fs;
+use std::io::{Error, Result};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::process::{Child, Command};
+use std::time::{Duration, Instant};
+
+use crate::config::QualityCheck;
+use crate::generate::package_manager::InstallOptions;
+use crate::utils::{context, progress, strings, summary};
+
+/// Where baseline scores are written in the generated project
+pub const BASELINE_PATH: &str = ".quality/baseline.json";
+
+const SERVER_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Build and serve the generated site, then record Lighthouse scores in the project.
+/// Runs only when the `quality_baseline` variable is enabled and the template declares
+/// how to serve the site. Failures are reported without stopping the generation.
+pub fn record_baseline(
+    template_path: &Path,
+    project_path: &Path,
+    options: &InstallOptions,
+) -> Result<()> {
+    if !is_enabled() {
+        return Ok(());
+    }
+    if options.skip {
+        log::warn!("Dependencies were not installed (--skip-install), skipping quality baseline");
+        return Ok(());
+    }
+
+    let Some(check) = strings::read_template_manifest(template_path)?.quality else {
+        log::info!("⚠️  Template does not declare a quality check, skipping baseline");
+        return Ok(());
+    };
+
+    log::info!("🔎 Recording quality baseline...");
+    match run_check(&check, project_path, options) {
+        Ok(scores) => {
+            for (category, score) in &scores {
+                log::info!("   {}: {}", category, score);
+            }
+            log::info!("✅ Quality baseline written to {}", BASELINE_PATH);
+        }
+        Err(e) => log::warn!("Failed to record quality baseline: {}", e),
+    }
+    Ok(())
+}
+
+/// Whether the `quality_baseline` variable asks for a baseline
+pub fn is_enabled() -> bool {
+    context::get_variable("quality_baseline")
+        .is_some_and(|v| matches!(v.to_lowercase().as_str(), "true" | "1" | "yes" | "on"))
+}
+
+fn shell(command: &str, project_path: &Path, options: &InstallOptions) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(command)
+        .current_dir(project_path)
+        .envs(options.environment())
+        .stdout(summary::child_stdout());
+    cmd
+}
+
+fn run_check(
+    check: &QualityCheck,
+    project_path: &Path,
+    options: &InstallOptions,
+) -> Result<IndexMap<String, u64>> {
+    log::debug!("Building site: {}", check.build_command);
+    let _paused = progress::pause();
+    let status = shell(&check.build_command, project_path, options).status()?;
+    if !status.success() {
+        return Err(Error::other(format!(
+            "Build failed with status: {}",
+            status
+        )));
+    }
+
+    log::debug!("Starting server: {}", check.serve_command);
+    let mut command = shell(&check.serve_command, project_path, options);
+    #[cfg(unix)]
+    {
+        // Own process group, so package manager wrappers and their servers stop together
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    let mut server = command.spawn()?;
+
+    let result = wait_for_server(&check.url).and_then(|_| audit(check, project_path, options));
+    stop(&mut server);
+    result
+}
+
+fn wait_for_server(url: &str) -> Result<()> {
+    let parsed = reqwest::Url::parse(url).map_err(Error::other)?;
+    let address = format!(
+        "{}:{}",
+        parsed.host_str().unwrap_or("localhost"),
+        parsed.port_or_known_default().unwrap_or(80)
+    );
+
+    let deadline = Instant::now() + SERVER_TIMEOUT;
+    while Instant::now() < deadline {
+        let reachable = address.to_socket_addrs().map(|addresses| {
+            addresses
+                .into_iter()
+                .any(|addr| TcpStream::connect_timeout(&addr, Duration::from_secs(1)).is_ok())
+        });
+        if let Ok(true) = reachable {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+    Err(Error::other(format!(
+        "Server at {} did not start within {}s",
+        url,
+        SERVER_TIMEOUT.as_secs()
+    )))
+}
+
+fn stop(server: &mut Child) {
+    #[cfg(unix)]
+    {
+        let _ = Command::new("kill")
+            .arg("-TERM")
+            .arg(format!("-{}", server.id()))
+            .status();
+    }
+    let _ = server.kill();
+    let _ = server.wait();
+}
+
+fn audit(
+    check: &QualityCheck,
+    project_path: &Path,
+    options: &InstallOptions,
+) -> Result<IndexMap<String, u64>> {
+    let report_path = std::env::temp_dir().join(format!("lighthouse-{}.json", std::process::id()));
+    let _paused = progress::pause();
+    let status = Command::new("npx")
+        .args(["--yes", "lighthouse"])
+        .arg(&check.url)
+        .arg("--output=json")
+        .arg(format!("--output-path={}", report_path.display()))
+        .arg(format!("--only-categories={}", check.categories.join(",")))
+        .arg("--chrome-flags=--headless")
+        .arg("--quiet")
+        .current_dir(project_path)
+        .envs(options.environment())
+        .stdout(summary::child_stdout())
+        .status()?;
+    if !status.success() {
+        return Err(Error::other(format!(
+            "Lighthouse failed with status: {}",
+            status
+        )));
+    }
+
+    let report: serde_json::Value = serde_json::from_str(&fs::read_to_string(&report_path)?)?;
+    let _ = fs::remove_file(&report_path);
+
+    let scores: IndexMap<String, u64> = check
+        .categories
+        .iter()
+        .filter_map(|category| {
+            let score = report["categories"][category]["score"].as_f64()?;
+            Some((category.clone(), (score * 100.0).round() as u64))
+        })
+        .collect();
+
+    let baseline = serde_json::json!({
+        "url": check.url,
+        "fetch_time": report["fetchTime"],
+        "lighthouse_version": report["lighthouseVersion"],
+        "scores": scores,
+    });
+    let baseline_path = project_path.join(BASELINE_PATH);
+    if let Some(parent) = baseline_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&baseline_path, serde_json::to_string_pretty(&baseline)?)?;
+
+    Ok(scores)
+}
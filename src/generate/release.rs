@@ -0,0 +1,88 @@
+use std::fs;
+use std::io::Result;
+use std::path::Path;
+
+use crate::config::ReleaseFlow;
+use crate::generate::package_manager::PackageManager;
+use crate::utils::context;
+
+pub const RELEASE_WORKFLOW: &str = ".github/workflows/release.yml";
+
+/// Whether the gated release flow was requested with the `release_flow` variable. Production
+/// is then deployed from approved release tags instead of right after the first push.
+pub fn is_requested() -> bool {
+    context::get_variable("release_flow")
+        .is_some_and(|v| matches!(v.to_lowercase().as_str(), "true" | "1" | "yes" | "on"))
+}
+
+/// Write the release workflow: build the tag, then wait for the environment's approval
+/// before dispatching the deployment workflow on it
+pub fn write_release_workflow(project_path: &Path, flow: &ReleaseFlow) -> Result<()> {
+    let manager = PackageManager::for_project(project_path);
+    let workflow = format!(
+        r#"name: Release
+
+on:
+  push:
+    tags: ["{tag_pattern}"]
+
+permissions:
+  contents: read
+  actions: write
+
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+{setup}      - run: {install_command}
+      - run: {build_command}
+
+  deploy:
+    needs: build
+    runs-on: ubuntu-latest
+    environment: {environment}
+    steps:
+      - name: Deploy ${{{{ github.ref_name }}}}
+        run: gh workflow run {deploy_workflow} --repo "$GITHUB_REPOSITORY" --ref "$GITHUB_REF_NAME"
+        env:
+          GH_TOKEN: ${{{{ github.token }}}}
+"#,
+        tag_pattern = flow.tag_pattern,
+        setup = setup_steps(manager),
+        install_command = manager.install_command(true),
+        build_command = flow.build_command,
+        environment = flow.environment,
+        deploy_workflow = flow.deploy_workflow,
+    );
+
+    let path = project_path.join(RELEASE_WORKFLOW);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, workflow)?;
+    log::info!(
+        "🏷️  Generated release workflow: {} tags deploy to {} once approved",
+        flow.tag_pattern,
+        flow.environment
+    );
+    Ok(())
+}
+
+/// Steps installing the package manager and Node with its cache
+fn setup_steps(manager: PackageManager) -> String {
+    let tool = match manager {
+        PackageManager::Pnpm => "      - uses: pnpm/action-setup@v4\n",
+        PackageManager::Bun => "      - uses: oven-sh/setup-bun@v2\n",
+        PackageManager::Npm | PackageManager::Yarn => "",
+    };
+    // setup-node has no cache for bun
+    let cache = match manager {
+        PackageManager::Bun => String::new(),
+        _ => format!("\n          cache: {}", manager.command()),
+    };
+    format!(
+        "{}      - uses: actions/setup-node@v4\n        with:\n          node-version: 20{}\n",
+        tool, cache
+    )
+}
@@ -0,0 +1,983 @@
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::config::EXCLUDED_DIRS;
+use crate::utils::context;
+
+/// Files rendered by the template engine, written without this extension
+pub const TEMPLATE_EXTENSION: &str = "hbs";
+
+/// Directory of the template holding partials (`{{> name}}` reads `_partials/name.hbs`)
+pub const PARTIALS_DIR: &str = "_partials";
+
+/// The generator's own markup, borrowed from Handlebars but not compatible with it: `{{var}}`
+/// (never HTML-escaped), helpers (`{{upper name}}`) and filters (`{{name | upper}}`), `{{#if}}`,
+/// `{{#unless}}`, `{{#each}}` over comma-separated values, `{{else}}`, partials and
+/// `{{! comments }}`
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Value(Expr),
+    If {
+        condition: Expr,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Each {
+        list: Expr,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Partial(String),
+}
+
+#[derive(Debug)]
+enum Expr {
+    Path(String),
+    Literal(String),
+    Helper(String, Vec<Expr>),
+}
+
+enum Tag {
+    Value(String),
+    Open(String, String),
+    Else,
+    Close(String),
+    Partial(String),
+    Comment,
+}
+
+/// Render every `.hbs` file of the project in place, then drop the partials directory
+pub fn render_templates(project_path: &Path) -> Result<()> {
+    let partials_dir = project_path.join(PARTIALS_DIR);
+    let mut partials = HashMap::new();
+    if partials_dir.is_dir() {
+        for entry in fs::read_dir(&partials_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) == Some(TEMPLATE_EXTENSION) {
+                let name = path.file_stem().unwrap_or_default().to_string_lossy();
+                partials.insert(name.to_string(), fs::read_to_string(&path)?);
+            }
+        }
+        fs::remove_dir_all(&partials_dir)?;
+    }
+
+    let variables = context::get_variables();
+    let rendered = render_dir(project_path, &variables, &partials)?;
+    if rendered > 0 {
+        log::info!("🧩 Rendered {} template files", rendered);
+    }
+    Ok(())
+}
+
+fn render_dir(
+    dir: &Path,
+    variables: &HashMap<String, String>,
+    partials: &HashMap<String, String>,
+) -> Result<usize> {
+    let mut rendered = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+
+        if entry.file_type()?.is_dir() {
+            if file_name != ".git" && !EXCLUDED_DIRS.contains(&file_name.as_str()) {
+                rendered += render_dir(&path, variables, partials)?;
+            }
+            continue;
+        }
+        if path.extension().and_then(|e| e.to_str()) != Some(TEMPLATE_EXTENSION) {
+            continue;
+        }
+
+        let _task = context::debug_task(file_name.as_str());
+        log::debug!("Rendering template: {}", path.display());
+        let content = fs::read_to_string(&path)?;
+        let output = render(&content, variables, partials).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Failed to render {}: {}", file_name, e),
+            )
+        })?;
+        fs::write(path.with_extension(""), output)?;
+        fs::remove_file(&path)?;
+        rendered += 1;
+    }
+    Ok(rendered)
+}
+
+/// Evaluate a condition written like an `{{#if}}` argument, e.g. `i18n` or `(ne cms "none")`
+pub fn evaluate_condition(
+    condition: &str,
+    variables: &HashMap<String, String>,
+) -> std::result::Result<bool, String> {
+    let condition = condition.trim();
+    let condition = condition
+        .strip_prefix('(')
+        .and_then(|inner| inner.strip_suffix(')'))
+        .unwrap_or(condition);
+    let scope = Scope {
+        variables,
+        partials: &HashMap::new(),
+        items: Vec::new(),
+        depth: 0,
+    };
+    Ok(is_truthy(&scope.eval(&parse_expr(condition)?, true)?))
+}
+
+/// Render variables in file and directory names, e.g. `src/{{module_name}}/index.ts` or
+/// `{{kebab_case project_name}}.config.ts`. A name may render to a relative path.
+pub fn render_file_names(project_path: &Path) -> Result<()> {
+    let variables = context::get_variables();
+    let renamed = rename_entries(project_path, &variables)?;
+    if renamed > 0 {
+        log::info!("🧩 Renamed {} templated paths", renamed);
+    }
+    Ok(())
+}
+
+fn rename_entries(dir: &Path, variables: &HashMap<String, String>) -> Result<usize> {
+    let mut renamed = 0;
+    let entries: Vec<_> = fs::read_dir(dir)?.collect::<Result<_>>()?;
+    for entry in entries {
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+
+        // Children first, their paths change once the directory is renamed
+        if entry.file_type()?.is_dir() {
+            if file_name == ".git" || EXCLUDED_DIRS.contains(&file_name.as_str()) {
+                continue;
+            }
+            renamed += rename_entries(&path, variables)?;
+        }
+        if !file_name.contains("{{") {
+            continue;
+        }
+
+        let new_name = render_path(&file_name, variables).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Failed to render file name '{}': {}", file_name, e),
+            )
+        })?;
+        let target = dir.join(&new_name);
+        if target.exists() {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("'{}' renders to existing path '{}'", file_name, new_name),
+            ));
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        log::debug!("Renaming {} to {}", file_name, new_name);
+        fs::rename(&path, &target)?;
+        renamed += 1;
+    }
+    Ok(renamed)
+}
+
+/// Render a templated relative path, rejecting empty results and `..` components
+pub fn render_path(
+    path: &str,
+    variables: &HashMap<String, String>,
+) -> std::result::Result<String, String> {
+    let rendered = render(path, variables, &HashMap::new())?;
+    let rendered = rendered.trim().trim_matches('/');
+    if rendered.is_empty() {
+        return Err("renders to an empty name".to_string());
+    }
+    if rendered
+        .split('/')
+        .any(|part| part.is_empty() || part == "..")
+    {
+        return Err(format!("'{}' is not a valid relative path", rendered));
+    }
+    Ok(rendered.to_string())
+}
+
+/// Render a template string against the given variables
+pub fn render(
+    template: &str,
+    variables: &HashMap<String, String>,
+    partials: &HashMap<String, String>,
+) -> std::result::Result<String, String> {
+    let nodes = parse(template)?;
+    let mut output = String::new();
+    let mut scope = Scope {
+        variables,
+        partials,
+        items: Vec::new(),
+        depth: 0,
+    };
+    scope.render_nodes(&nodes, &mut output)?;
+    Ok(output)
+}
+
+/// Variables referenced by a template, to prompt for them in interactive mode
+pub fn referenced_variables(template: &str) -> Vec<String> {
+    fn collect(nodes: &[Node], names: &mut Vec<String>) {
+        for node in nodes {
+            match node {
+                Node::Value(expr) => collect_expr(expr, names),
+                Node::If {
+                    condition,
+                    then,
+                    otherwise,
+                    ..
+                } => {
+                    collect_expr(condition, names);
+                    collect(then, names);
+                    collect(otherwise, names);
+                }
+                Node::Each {
+                    list,
+                    body,
+                    otherwise,
+                } => {
+                    collect_expr(list, names);
+                    collect(body, names);
+                    collect(otherwise, names);
+                }
+                Node::Text(_) | Node::Partial(_) => {}
+            }
+        }
+    }
+    fn collect_expr(expr: &Expr, names: &mut Vec<String>) {
+        match expr {
+            Expr::Path(name)
+                if name != "this" && !name.starts_with('@') && !names.contains(name) =>
+            {
+                names.push(name.clone());
+            }
+            Expr::Helper(_, args) => args.iter().for_each(|arg| collect_expr(arg, names)),
+            _ => {}
+        }
+    }
+
+    let mut names = Vec::new();
+    if let Ok(nodes) = parse(template) {
+        collect(&nodes, &mut names);
+    }
+    names
+}
+
+/// Variables referenced by the `.hbs` files of a template, partials included
+pub fn template_variables(template_path: &Path) -> Vec<String> {
+    fn walk(dir: &Path, names: &mut Vec<String>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            for name in referenced_variables(&file_name) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            if path.is_dir() {
+                walk(&path, names);
+            } else if path.extension().and_then(|e| e.to_str()) == Some(TEMPLATE_EXTENSION) {
+                let content = fs::read_to_string(&path).unwrap_or_default();
+                for name in referenced_variables(&content) {
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut names = Vec::new();
+    walk(template_path, &mut names);
+    names
+}
+
+/// Split the template into text and tags, dropping the lines that only hold a block tag.
+/// `\{{` is a literal `{{`, and GitHub expressions (`${{ github.ref }}`) are kept as text.
+fn tokenize(template: &str) -> std::result::Result<Vec<(String, Option<Tag>)>, String> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut rest = template;
+    let mut at_line_start = true;
+
+    while let Some(start) = rest.find("{{") {
+        if rest[..start].ends_with('\\') {
+            text.push_str(&rest[..start - 1]);
+            text.push_str("{{");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        if rest[..start].ends_with('$') {
+            let end = rest[start..]
+                .find("}}")
+                .map(|i| start + i + 2)
+                .ok_or_else(|| "unclosed '${{'".to_string())?;
+            text.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        let (open_len, close) = if rest[start..].starts_with("{{!--") {
+            (2, "--}}")
+        } else if rest[start..].starts_with("{{{") {
+            (3, "}}}")
+        } else {
+            (2, "}}")
+        };
+        let end = rest[start + open_len..]
+            .find(close)
+            .map(|i| start + open_len + i)
+            .ok_or_else(|| "unclosed '{{'".to_string())?;
+
+        text.push_str(&rest[..start]);
+        let tag = parse_tag(rest[start + open_len..end].trim(), open_len == 3)?;
+        rest = &rest[end + close.len()..];
+
+        // A block tag alone on its line leaves no blank line behind
+        let line_start = text.rfind('\n').map(|i| i + 1);
+        let blank_before = text[line_start.unwrap_or(0)..].trim().is_empty()
+            && (line_start.is_some() || at_line_start);
+        let line_end = rest.find('\n');
+        let blank_after = line_end.map_or(rest, |i| &rest[..i]).trim().is_empty();
+        at_line_start = false;
+        if !matches!(tag, Tag::Value(_)) && blank_before && blank_after {
+            text.truncate(line_start.unwrap_or(0));
+            rest = line_end.map_or("", |i| &rest[i + 1..]);
+            at_line_start = true;
+        }
+        tokens.push((std::mem::take(&mut text), Some(tag)));
+    }
+    text.push_str(rest);
+    tokens.push((text, None));
+    Ok(tokens)
+}
+
+fn parse_tag(inner: &str, raw: bool) -> std::result::Result<Tag, String> {
+    if raw {
+        return Ok(Tag::Value(inner.to_string()));
+    }
+    if inner.starts_with('!') {
+        return Ok(Tag::Comment);
+    }
+    if inner == "else" {
+        return Ok(Tag::Else);
+    }
+    if let Some(name) = inner.strip_prefix('>') {
+        return Ok(Tag::Partial(name.trim().to_string()));
+    }
+    if let Some(block) = inner.strip_prefix('#') {
+        let (name, args) = block.split_once(char::is_whitespace).unwrap_or((block, ""));
+        return Ok(Tag::Open(name.to_string(), args.trim().to_string()));
+    }
+    if let Some(name) = inner.strip_prefix('/') {
+        return Ok(Tag::Close(name.trim().to_string()));
+    }
+    Ok(Tag::Value(inner.to_string()))
+}
+
+fn parse(template: &str) -> std::result::Result<Vec<Node>, String> {
+    let tokens = tokenize(template)?;
+    let mut tokens = tokens.into_iter();
+    let (nodes, end) = parse_block(&mut tokens)?;
+    match end {
+        None => Ok(nodes),
+        Some(EndTag::Else) => Err("'{{else}}' outside of a block".to_string()),
+        Some(EndTag::Close(name)) => Err(format!("unexpected '{{{{/{}}}}}'", name)),
+    }
+}
+
+enum EndTag {
+    Else,
+    Close(String),
+}
+
+/// Parse nodes until `{{else}}`, a closing tag or the end of the template
+fn parse_block(
+    tokens: &mut impl Iterator<Item = (String, Option<Tag>)>,
+) -> std::result::Result<(Vec<Node>, Option<EndTag>), String> {
+    let mut nodes = Vec::new();
+    while let Some((text, tag)) = tokens.next() {
+        if !text.is_empty() {
+            nodes.push(Node::Text(text));
+        }
+        match tag {
+            None | Some(Tag::Comment) => {}
+            Some(Tag::Value(expr)) => nodes.push(Node::Value(parse_expr(&expr)?)),
+            Some(Tag::Partial(name)) => nodes.push(Node::Partial(name)),
+            Some(Tag::Else) => return Ok((nodes, Some(EndTag::Else))),
+            Some(Tag::Close(name)) => return Ok((nodes, Some(EndTag::Close(name)))),
+            Some(Tag::Open(name, args)) => {
+                let expr = parse_expr(&args)?;
+                let (body, end) = parse_block(tokens)?;
+                let otherwise = match end {
+                    Some(EndTag::Else) => match parse_block(tokens)? {
+                        (otherwise, Some(EndTag::Close(close))) if close == name => otherwise,
+                        _ => return Err(format!("'{{{{#{}}}}}' is not closed", name)),
+                    },
+                    Some(EndTag::Close(close)) if close == name => Vec::new(),
+                    _ => return Err(format!("'{{{{#{}}}}}' is not closed", name)),
+                };
+                nodes.push(match name.as_str() {
+                    "if" | "unless" => Node::If {
+                        condition: expr,
+                        negate: name == "unless",
+                        then: body,
+                        otherwise,
+                    },
+                    "each" => Node::Each {
+                        list: expr,
+                        body,
+                        otherwise,
+                    },
+                    _ => return Err(format!("unknown block helper '{}'", name)),
+                });
+            }
+        }
+    }
+    Ok((nodes, None))
+}
+
+/// `name`, `"literal"`, `helper arg...` or a `(helper arg...)` subexpression, optionally piped
+/// through filters: `name | kebab_case | upper`. The piped value is the first argument of each
+/// filter.
+fn parse_expr(input: &str) -> std::result::Result<Expr, String> {
+    let words = split_words(input)?;
+    let mut segments = words.split(|word| word == "|");
+    let mut expr = parse_call(input, segments.next().unwrap_or_default())?;
+    for filter in segments {
+        expr = match parse_call(input, filter)? {
+            Expr::Path(name) => Expr::Helper(name, vec![expr]),
+            Expr::Helper(name, mut args) => {
+                args.insert(0, expr);
+                Expr::Helper(name, args)
+            }
+            Expr::Literal(_) => return Err(format!("'{}' pipes into a literal", input)),
+        };
+    }
+    Ok(expr)
+}
+
+fn parse_call(input: &str, words: &[String]) -> std::result::Result<Expr, String> {
+    let mut args = words
+        .iter()
+        .map(|word| parse_word(word))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    match args.len() {
+        0 => Err("empty expression".to_string()),
+        1 => Ok(args.remove(0)),
+        _ => match args.remove(0) {
+            Expr::Path(helper) => Ok(Expr::Helper(helper, args)),
+            _ => Err(format!("'{}' does not start with a helper name", input)),
+        },
+    }
+}
+
+fn parse_word(word: &str) -> std::result::Result<Expr, String> {
+    if let Some(inner) = word.strip_prefix('(').and_then(|w| w.strip_suffix(')')) {
+        return parse_expr(inner);
+    }
+    for quote in ['"', '\''] {
+        if let Some(literal) = word.strip_prefix(quote).and_then(|w| w.strip_suffix(quote)) {
+            return Ok(Expr::Literal(literal.to_string()));
+        }
+    }
+    Ok(Expr::Path(word.to_string()))
+}
+
+/// Split on whitespace, keeping quoted strings and parenthesized subexpressions whole. A `|`
+/// outside of them is a word of its own.
+fn split_words(input: &str) -> std::result::Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut depth = 0;
+    let mut quote = None;
+
+    for c in input.chars() {
+        match (c, quote) {
+            (c, Some(q)) if c == q => {
+                quote = None;
+                word.push(c);
+            }
+            (_, Some(_)) => word.push(c),
+            ('"' | '\'', None) => {
+                quote = Some(c);
+                word.push(c);
+            }
+            ('(', None) => {
+                depth += 1;
+                word.push(c);
+            }
+            (')', None) => {
+                depth -= 1;
+                word.push(c);
+            }
+            (c, None) if c.is_whitespace() && depth == 0 => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            ('|', None) if depth == 0 => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+                words.push("|".to_string());
+            }
+            _ => word.push(c),
+        }
+    }
+    if quote.is_some() || depth != 0 {
+        return Err(format!("unbalanced quotes or parentheses in '{}'", input));
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+struct Scope<'a> {
+    variables: &'a HashMap<String, String>,
+    partials: &'a HashMap<String, String>,
+    /// Current `{{#each}}` items: (value, index, count)
+    items: Vec<(String, usize, usize)>,
+    depth: usize,
+}
+
+impl Scope<'_> {
+    fn render_nodes(
+        &mut self,
+        nodes: &[Node],
+        output: &mut String,
+    ) -> std::result::Result<(), String> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => output.push_str(text),
+                Node::Value(expr) => output.push_str(&self.eval(expr, false)?),
+                Node::If {
+                    condition,
+                    negate,
+                    then,
+                    otherwise,
+                } => {
+                    let branch = if is_truthy(&self.eval(condition, true)?) != *negate {
+                        then
+                    } else {
+                        otherwise
+                    };
+                    self.render_nodes(branch, output)?;
+                }
+                Node::Each {
+                    list,
+                    body,
+                    otherwise,
+                } => {
+                    let items = list_items(&self.eval(list, true)?);
+                    if items.is_empty() {
+                        self.render_nodes(otherwise, output)?;
+                    }
+                    for (index, item) in items.iter().enumerate() {
+                        self.items.push((item.clone(), index, items.len()));
+                        let result = self.render_nodes(body, output);
+                        self.items.pop();
+                        result?;
+                    }
+                }
+                Node::Partial(name) => {
+                    let partial = self
+                        .partials
+                        .get(name)
+                        .ok_or_else(|| format!("unknown partial '{}'", name))?;
+                    if self.depth >= 16 {
+                        return Err(format!("partial '{}' includes itself", name));
+                    }
+                    let nodes = parse(partial).map_err(|e| format!("partial '{}': {}", name, e))?;
+                    self.depth += 1;
+                    let result = self.render_nodes(&nodes, output);
+                    self.depth -= 1;
+                    result?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn lookup(&self, name: &str, lenient: bool) -> std::result::Result<String, String> {
+        let item = self.items.last();
+        Ok(match (name, item) {
+            ("this", Some((value, _, _))) => value.clone(),
+            ("@index", Some((_, index, _))) => index.to_string(),
+            ("@first", Some((_, index, _))) => (*index == 0).to_string(),
+            ("@last", Some((_, index, count))) => (index + 1 == *count).to_string(),
+            _ => match self.variables.get(name) {
+                Some(value) => value.clone(),
+                None if lenient => String::new(),
+                None => return Err(format!("undefined variable '{}'", name)),
+            },
+        })
+    }
+
+    /// Evaluate an expression. Undefined variables are an error, unless `lenient`: conditions,
+    /// `{{#each}}` lists and the first argument of `default` read them as empty.
+    fn eval(&self, expr: &Expr, lenient: bool) -> std::result::Result<String, String> {
+        let (name, args) = match expr {
+            Expr::Path(name) => return self.lookup(name, lenient),
+            Expr::Literal(value) => return Ok(value.clone()),
+            Expr::Helper(name, args) => (name, args),
+        };
+        let args = args
+            .iter()
+            .enumerate()
+            .map(|(index, arg)| self.eval(arg, lenient || (name == "default" && index == 0)))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let arg = |index: usize| args.get(index).map(String::as_str).unwrap_or_default();
+        if let Some(value) = transform(name, arg(0)) {
+            return Ok(value);
+        }
+
+        Ok(match name.as_str() {
+            "default" => {
+                if is_truthy(arg(0)) {
+                    arg(0).to_string()
+                } else {
+                    arg(1).to_string()
+                }
+            }
+            "eq" => (arg(0) == arg(1)).to_string(),
+            "ne" => (arg(0) != arg(1)).to_string(),
+            "not" => (!is_truthy(arg(0))).to_string(),
+            "and" => args.iter().all(|a| is_truthy(a)).to_string(),
+            "or" => args.iter().any(|a| is_truthy(a)).to_string(),
+            "join" => list_items(arg(0)).join(arg(1)),
+            _ => return Err(format!("unknown helper '{}'", name)),
+        })
+    }
+}
+
+/// Text with its placeholders replaced by [`replace_placeholders`]
+#[derive(Debug, Default)]
+pub struct Replaced {
+    pub text: String,
+    /// Variables of the placeholders kept because they have no value
+    pub missing: Vec<String>,
+    /// Placeholders kept because they pipe into an unknown filter, with that filter
+    pub unknown_filters: Vec<(String, String)>,
+}
+
+/// Replace the `{{name}}` placeholders of text outside of `.hbs` files, optionally piped
+/// through filters (`{{ name | kebab_case }}`), with the value `lookup` gives for `name`.
+/// Placeholders without a value or with an unknown filter are kept and reported, as are GitHub
+/// expressions (`${{ ... }}`).
+pub fn replace_placeholders(
+    input: &str,
+    mut lookup: impl FnMut(&str) -> Option<String>,
+) -> Replaced {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    let placeholder = PLACEHOLDER.get_or_init(|| {
+        Regex::new(r"(\$?)\{\{\s*([A-Za-z_][A-Za-z0-9_]*)((?:\s*\|\s*[a-z_]+)*)\s*\}\}").unwrap()
+    });
+
+    let mut replaced = Replaced::default();
+    let text = placeholder.replace_all(input, |captures: &Captures| {
+        if !captures[1].is_empty() {
+            return captures[0].to_string();
+        }
+        let Some(value) = lookup(&captures[2]) else {
+            replaced.missing.push(captures[2].to_string());
+            return captures[0].to_string();
+        };
+        apply_filters(&value, &captures[3]).unwrap_or_else(|filter| {
+            replaced
+                .unknown_filters
+                .push((captures[0].to_string(), filter));
+            captures[0].to_string()
+        })
+    });
+    replaced.text = text.into_owned();
+    replaced
+}
+
+/// Single-value helpers, usable as filters anywhere a variable is substituted, e.g.
+/// `{{project_name | screaming_snake_case}}`
+pub fn transform(helper: &str, value: &str) -> Option<String> {
+    Some(match helper {
+        "upper" => value.to_uppercase(),
+        "lower" => value.to_lowercase(),
+        "slugify" => slugify(value),
+        "kebab_case" => join_words(value, "-", false),
+        "snake_case" => join_words(value, "_", false),
+        "screaming_snake_case" => join_words(value, "_", false).to_uppercase(),
+        "pascal_case" => join_words(value, "", true),
+        "camel_case" => {
+            let pascal = join_words(value, "", true);
+            let mut chars = pascal.chars();
+            chars
+                .next()
+                .map(|c| c.to_lowercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+        _ => return None,
+    })
+}
+
+/// Apply a chain of filters captured after a placeholder name, e.g. ` | kebab_case | upper`.
+/// Returns the unknown filter on failure.
+fn apply_filters(value: &str, chain: &str) -> std::result::Result<String, String> {
+    chain
+        .split('|')
+        .map(str::trim)
+        .filter(|filter| !filter.is_empty())
+        .try_fold(value.to_string(), |value, filter| {
+            transform(filter, &value).ok_or_else(|| filter.to_string())
+        })
+}
+
+/// Variables are strings: empty, `false`, `0`, `no` and `off` are false
+fn is_truthy(value: &str) -> bool {
+    !matches!(
+        value.trim().to_lowercase().as_str(),
+        "" | "false" | "0" | "no" | "off"
+    )
+}
+
+/// Array variables are stored comma-separated
+fn list_items(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Lowercase with runs of anything but letters and digits turned into single hyphens,
+/// e.g. `My Project!` becomes `my-project`
+fn slugify(input: &str) -> String {
+    input
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-")
+}
+
+/// Split on non-alphanumerics and case changes, then join with `separator`
+fn join_words(input: &str, separator: &str, capitalize: bool) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in input.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lower {
+            words.push(std::mem::take(&mut current));
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+        .iter()
+        .map(|word| {
+            if !capitalize {
+                return word.clone();
+            }
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn render_with(template: &str, pairs: &[(&str, &str)]) -> std::result::Result<String, String> {
+        render(template, &variables(pairs), &HashMap::new())
+    }
+
+    #[test]
+    fn renders_values_helpers_and_filters() {
+        let pairs = [("project_name", "my-app"), ("org", "Next Node")];
+        assert_eq!(render_with("{{project_name}}", &pairs).unwrap(), "my-app");
+        assert_eq!(render_with("{{{project_name}}}", &pairs).unwrap(), "my-app");
+        assert_eq!(
+            render_with("{{pascal_case project_name}}", &pairs).unwrap(),
+            "MyApp"
+        );
+        assert_eq!(
+            render_with("{{ project_name | snake_case | upper }}", &pairs).unwrap(),
+            "MY_APP"
+        );
+        assert_eq!(render_with("{{slugify org}}", &pairs).unwrap(), "next-node");
+        assert_eq!(
+            render_with("{{camel_case (join \"a b,c\" \" \")}}", &pairs).unwrap(),
+            "aBC"
+        );
+    }
+
+    #[test]
+    fn undefined_variables_are_errors_outside_of_conditions() {
+        assert_eq!(
+            render_with("Hello {{missing}}", &[]).unwrap_err(),
+            "undefined variable 'missing'"
+        );
+        assert!(render_with("{{upper missing}}", &[]).is_err());
+        assert_eq!(
+            render_with("{{#if missing}}yes{{else}}no{{/if}}", &[]).unwrap(),
+            "no"
+        );
+        assert_eq!(
+            render_with("{{#each missing}}x{{else}}none{{/each}}", &[]).unwrap(),
+            "none"
+        );
+        assert_eq!(
+            render_with("{{default missing \"fallback\"}}", &[]).unwrap(),
+            "fallback"
+        );
+        assert!(render_with("{{this}}", &[]).is_err());
+    }
+
+    #[test]
+    fn github_expressions_and_escaped_braces_are_kept() {
+        let pairs = [("name", "app")];
+        assert_eq!(
+            render_with("ref: ${{ github.ref }} for {{name}}", &pairs).unwrap(),
+            "ref: ${{ github.ref }} for app"
+        );
+        assert_eq!(
+            render_with("\\{{name}} is {{name}}", &pairs).unwrap(),
+            "{{name}} is app"
+        );
+        assert!(render_with("${{ github.ref", &pairs).is_err());
+    }
+
+    #[test]
+    fn renders_blocks_without_blank_lines() {
+        let pairs = [("cms", "none"), ("features", "a, b,c")];
+        let template = "start\n{{#if (ne cms \"none\")}}\ncms\n{{else}}\nno cms\n{{/if}}\nend\n";
+        assert_eq!(
+            render_with(template, &pairs).unwrap(),
+            "start\nno cms\nend\n"
+        );
+        let template =
+            "{{#each features}}\n- {{@index}} {{this}}{{#if @last}}.{{/if}}\n{{/each}}\n";
+        assert_eq!(
+            render_with(template, &pairs).unwrap(),
+            "- 0 a\n- 1 b\n- 2 c.\n"
+        );
+        assert_eq!(
+            render_with("{{#unless cms}}x{{/unless}}{{! comment }}", &pairs).unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn reports_malformed_templates() {
+        assert!(render_with("{{#if a}}open", &[]).is_err());
+        assert!(render_with("{{/if}}", &[]).is_err());
+        assert!(render_with("{{else}}", &[]).is_err());
+        assert!(render_with("{{name", &[]).is_err());
+        assert!(render_with("{{unknown_helper name}}", &[("name", "x")]).is_err());
+    }
+
+    #[test]
+    fn renders_partials() {
+        let partials = variables(&[("footer", "by {{author}}"), ("loop", "{{> loop}}")]);
+        let pairs = variables(&[("author", "me")]);
+        assert_eq!(render("{{> footer}}", &pairs, &partials).unwrap(), "by me");
+        assert!(render("{{> loop}}", &pairs, &partials).is_err());
+        assert!(render("{{> missing}}", &pairs, &partials).is_err());
+    }
+
+    #[test]
+    fn evaluates_conditions() {
+        let pairs = variables(&[("cms", "strapi"), ("i18n", "off")]);
+        assert!(evaluate_condition("(ne cms \"none\")", &pairs).unwrap());
+        assert!(!evaluate_condition("i18n", &pairs).unwrap());
+        assert!(!evaluate_condition("missing", &pairs).unwrap());
+        assert!(evaluate_condition("(or i18n (eq cms \"strapi\"))", &pairs).unwrap());
+    }
+
+    #[test]
+    fn renders_paths() {
+        let pairs = variables(&[("module", "auth/login"), ("empty", "")]);
+        assert_eq!(
+            render_path("src/{{module}}/index.ts", &pairs).unwrap(),
+            "src/auth/login/index.ts"
+        );
+        assert!(render_path("{{empty}}", &pairs).is_err());
+        assert!(render_path("../{{module}}", &pairs).is_err());
+    }
+
+    #[test]
+    fn transforms_cases() {
+        assert_eq!(
+            transform("kebab_case", "myHTTPApp v2").unwrap(),
+            "my-httpapp-v2"
+        );
+        assert_eq!(
+            transform("screaming_snake_case", "my-app").unwrap(),
+            "MY_APP"
+        );
+        assert_eq!(transform("camel_case", "My app").unwrap(), "myApp");
+        assert_eq!(transform("nope", "x"), None);
+        assert_eq!(
+            apply_filters("my-app", " | pascal_case | upper").unwrap(),
+            "MYAPP"
+        );
+        assert_eq!(apply_filters("x", " | nope").unwrap_err(), "nope");
+    }
+
+    #[test]
+    fn replaces_placeholders_outside_of_templates() {
+        let pairs = variables(&[("project_name", "my-app")]);
+        let replaced = replace_placeholders(
+            "{{project_name}} {{ project_name | screaming_snake_case }} ${{ github.ref }} \
+             {{missing}} {{project_name | nope}}",
+            |name| pairs.get(name).cloned(),
+        );
+        assert_eq!(
+            replaced.text,
+            "my-app MY_APP ${{ github.ref }} {{missing}} {{project_name | nope}}"
+        );
+        assert_eq!(replaced.missing, vec!["missing"]);
+        assert_eq!(
+            replaced.unknown_filters,
+            vec![("{{project_name | nope}}".to_string(), "nope".to_string())]
+        );
+    }
+
+    #[test]
+    fn lists_referenced_variables() {
+        assert_eq!(
+            referenced_variables("{{a}} {{#if (eq b \"x\")}}{{upper c}}{{/if}} {{a}} {{this}}"),
+            vec!["a", "b", "c"]
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+use crate::config::{ReusableWorkflowCall, ReusableWorkflows};
+use crate::generate::render;
+use crate::generate::workflows::{self, WORKFLOWS_DIR};
+use crate::utils::context;
+
+/// Top-level keys of the template's workflow kept by its caller, before and after `on`
+const KEPT_KEYS: [&[&str]; 2] = [&["name", "run-name"], &["permissions", "concurrency"]];
+
+/// Whether reusable workflow callers were requested with the `reusable_workflows` variable
+pub fn is_requested() -> bool {
+    context::get_variable("reusable_workflows")
+        .is_some_and(|v| matches!(v.to_lowercase().as_str(), "true" | "1" | "yes" | "on"))
+}
+
+/// Replace `{{variable}}` placeholders, with their filters, leaving GitHub `${{ }}` expressions
+/// alone
+fn render(input: &str, file: &str) -> Result<String> {
+    let replaced = render::replace_placeholders(input, context::get_variable);
+    if let Some(name) = replaced.missing.first() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Inputs of {} use undefined variable '{}'", file, name),
+        ));
+    }
+    if let Some((placeholder, filter)) = replaced.unknown_filters.first() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Inputs of {} use unknown filter '{}' in {}",
+                file, filter, placeholder
+            ),
+        ));
+    }
+    Ok(replaced.text)
+}
+
+/// Booleans and integers are passed as such, reusable workflows reject them as strings.
+/// Decimals stay strings: `18.10` is a version, not `18.1`.
+fn input_value(value: String) -> Value {
+    if let Ok(flag) = value.parse::<bool>() {
+        Value::Bool(flag)
+    } else if let Ok(number) = value.parse::<i64>() {
+        Value::Number(number.into())
+    } else {
+        Value::String(value)
+    }
+}
+
+/// Workflow of the template the caller replaces, if it ships one
+fn template_workflow(path: &Path, file: &str) -> Result<Option<Value>> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Ok(None);
+    };
+    serde_yaml::from_str(&content).map(Some).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Failed to parse workflow {}: {}", file, e),
+        )
+    })
+}
+
+fn caller(
+    file: &str,
+    call: &ReusableWorkflowCall,
+    config: &ReusableWorkflows,
+    template: Option<&Value>,
+) -> Result<(String, Value)> {
+    let mut workflow = Mapping::new();
+    let keep = |workflow: &mut Mapping, keys: &[&str]| {
+        for key in keys {
+            if let Some(value) = template.and_then(|t| t.get(*key)) {
+                workflow.insert(Value::from(*key), value.clone());
+            }
+        }
+    };
+    keep(&mut workflow, KEPT_KEYS[0]);
+
+    let triggers = call
+        .on
+        .clone()
+        .or_else(|| template.and_then(workflows::triggers).cloned());
+    let Some(triggers) = triggers else {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Reusable workflow caller {} needs `on` triggers, the template has no such workflow",
+                file
+            ),
+        ));
+    };
+    workflow.insert(Value::from("on"), triggers);
+    keep(&mut workflow, KEPT_KEYS[1]);
+
+    let called = call.workflow.as_deref().unwrap_or(file);
+    let uses = format!(
+        "{}/{}/{}@{}",
+        config.repository, WORKFLOWS_DIR, called, config.ref_
+    );
+    let mut job = Mapping::new();
+    job.insert(Value::from("uses"), Value::from(uses.clone()));
+    if !call.inputs.is_empty() {
+        let mut inputs = Mapping::new();
+        for (name, value) in &call.inputs {
+            inputs.insert(
+                Value::from(name.as_str()),
+                input_value(render(value, file)?),
+            );
+        }
+        job.insert(Value::from("with"), Value::Mapping(inputs));
+    }
+    if call.inherit_secrets {
+        job.insert(Value::from("secrets"), Value::from("inherit"));
+    }
+
+    let job_id = Path::new(file)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().replace('.', "-"))
+        .unwrap_or_else(|| "call".to_string());
+    let mut jobs = Mapping::new();
+    jobs.insert(Value::from(job_id), Value::Mapping(job));
+    workflow.insert(Value::from("jobs"), Value::Mapping(jobs));
+    Ok((uses, Value::Mapping(workflow)))
+}
+
+/// Write the configured workflows as callers of the organization's reusable workflows,
+/// replacing the copies shipped by the template
+pub fn write_callers(project_path: &Path, config: &ReusableWorkflows) -> Result<()> {
+    let dir = project_path.join(WORKFLOWS_DIR);
+    fs::create_dir_all(&dir)?;
+    for (file, call) in &config.workflows {
+        let path = dir.join(file);
+        let template = template_workflow(&path, file)?;
+        let (uses, workflow) = caller(file, call, config, template.as_ref())?;
+        let yaml = serde_yaml::to_string(&workflow).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Failed to write workflow {}: {}", file, e),
+            )
+        })?;
+        fs::write(
+            &path,
+            format!("# Calls {}, CI logic is maintained there\n{}", uses, yaml),
+        )?;
+        log::debug!("{} now calls {}", file, uses);
+    }
+    log::info!(
+        "♻️  Generated {} workflows calling {}@{}",
+        config.workflows.len(),
+        config.repository,
+        config.ref_
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn types_booleans_and_integers_only() {
+        assert_eq!(input_value("true".to_string()), Value::Bool(true));
+        assert_eq!(input_value("20".to_string()), Value::from(20));
+        assert_eq!(input_value("18.10".to_string()), Value::from("18.10"));
+        assert_eq!(input_value("1.20".to_string()), Value::from("1.20"));
+        assert_eq!(input_value("pnpm".to_string()), Value::from("pnpm"));
+    }
+}
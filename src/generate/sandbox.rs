@@ -0,0 +1,96 @@
+use std::fs;
+use std::io::Result;
+use std::path::{Path, PathBuf};
+
+/// HOME given to package managers and hooks, set by `--isolated-home`. A temporary one is
+/// removed once generation is over.
+pub struct Sandbox {
+    home: PathBuf,
+    temporary: bool,
+}
+
+impl Sandbox {
+    pub fn home(&self) -> &Path {
+        &self.home
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        if self.temporary {
+            if let Err(e) = fs::remove_dir_all(&self.home) {
+                log::debug!("Failed to remove {}: {}", self.home.display(), e);
+            }
+        }
+    }
+}
+
+/// Isolated HOME for package managers and hooks: `home`, or a temporary directory removed
+/// when the returned guard is dropped
+pub fn enable(home: Option<&Path>) -> Result<Sandbox> {
+    let (home, temporary) = match home {
+        Some(home) => (home.to_path_buf(), false),
+        None => (
+            std::env::temp_dir().join(format!("project-generator-home-{}", std::process::id())),
+            true,
+        ),
+    };
+    fs::create_dir_all(&home)?;
+    let home = home.canonicalize()?;
+    log::info!("🏠 Installing with an isolated HOME: {}", home.display());
+    Ok(Sandbox { home, temporary })
+}
+
+/// Variables pointing HOME, the XDG directories and the package manager caches and stores
+/// into the isolated `home`, empty without one. Rust toolchains stay those of the agent,
+/// only the cargo registry is isolated.
+pub fn environment(home: Option<&Path>) -> Vec<(&'static str, PathBuf)> {
+    let Some(home) = home else {
+        return Vec::new();
+    };
+    let rustup_home = std::env::var_os("RUSTUP_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".rustup")));
+
+    let mut environment = vec![
+        ("HOME", home.to_path_buf()),
+        ("XDG_CONFIG_HOME", home.join(".config")),
+        ("XDG_CACHE_HOME", home.join(".cache")),
+        ("XDG_DATA_HOME", home.join(".local/share")),
+        ("XDG_STATE_HOME", home.join(".local/state")),
+        ("npm_config_cache", home.join(".npm")),
+        ("npm_config_store_dir", home.join(".pnpm-store")),
+        ("YARN_CACHE_FOLDER", home.join(".cache/yarn")),
+        ("BUN_INSTALL_CACHE_DIR", home.join(".bun/install/cache")),
+        ("CARGO_HOME", home.join(".cargo")),
+    ];
+    if let Some(rustup_home) = rustup_home {
+        environment.push(("RUSTUP_HOME", rustup_home));
+    }
+    environment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn environment_points_home_and_caches_into_the_isolated_home() {
+        assert!(environment(None).is_empty());
+
+        let home = Path::new("/tmp/isolated");
+        let environment = environment(Some(home));
+        let variable = |name: &str| {
+            environment
+                .iter()
+                .find(|(variable, _)| *variable == name)
+                .map(|(_, value)| value.clone())
+        };
+        assert_eq!(variable("HOME"), Some(home.to_path_buf()));
+        assert_eq!(
+            variable("npm_config_store_dir"),
+            Some(home.join(".pnpm-store"))
+        );
+        assert_eq!(variable("CARGO_HOME"), Some(home.join(".cargo")));
+    }
+}
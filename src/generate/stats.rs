@@ -0,0 +1,204 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Error, ErrorKind, Result, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::TemplateManifest;
+use crate::utils::{context, summary};
+
+/// Overrides the location of the statistics file
+const STATS_PATH_ENV: &str = "PROJECT_GENERATOR_STATS";
+
+/// What the generations of a template chose, recorded without any free-text value
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct TemplateStats {
+    pub generations: u64,
+    /// Generations per variable given another value than its default
+    #[serde(default)]
+    pub overridden: BTreeMap<String, u64>,
+    /// Generations per value of the variables picked from choices
+    #[serde(default)]
+    pub features: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+/// Statistics per `category/name`
+type Stats = BTreeMap<String, TemplateStats>;
+
+/// `$PROJECT_GENERATOR_STATS`, else `$XDG_DATA_HOME/project-generator/template-stats.json`,
+/// else under `~/.local/share`
+pub fn default_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(STATS_PATH_ENV) {
+        return Some(PathBuf::from(path));
+    }
+
+    let data_dir = std::env::var("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| {
+            std::env::var("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+        })
+        .ok()?;
+
+    Some(
+        data_dir
+            .join("project-generator")
+            .join("template-stats.json"),
+    )
+}
+
+/// Count the generation of the template with the current variables: the variables given
+/// another value than their default, and the choices picked. Failing to record only prints a
+/// warning. `--no-stats`, dry runs and updates do not call it.
+pub fn record(template_path: &Path, manifest: &TemplateManifest) {
+    let Some(path) = default_path() else {
+        log::debug!("No location for template statistics, set HOME");
+        return;
+    };
+    if let Err(e) = update(&path, &template_key(template_path), manifest) {
+        log::warn!("Failed to record template statistics: {}", e);
+    }
+}
+
+fn update(path: &Path, key: &str, manifest: &TemplateManifest) -> Result<()> {
+    let mut stats = load(path)?;
+    let entry = stats.entry(key.to_string()).or_default();
+    entry.generations += 1;
+
+    let prompts = manifest
+        .prompts
+        .iter()
+        .flat_map(|page| &page.variables)
+        .filter(|variable| !variable.secret)
+        .map(|variable| {
+            (
+                &variable.name,
+                variable.default.as_ref(),
+                variable.choices.as_slice(),
+            )
+        });
+    let replacements = manifest
+        .files
+        .iter()
+        .flat_map(|file| &file.replacements)
+        .map(|replacement| {
+            (
+                &replacement.name,
+                replacement.default.as_ref(),
+                &[] as &[String],
+            )
+        });
+
+    let mut seen = Vec::new();
+    for (name, default, choices) in prompts.chain(replacements) {
+        if seen.contains(&name) {
+            continue;
+        }
+        seen.push(name);
+        let Some(value) = context::get_variable(name).filter(|value| !value.is_empty()) else {
+            continue;
+        };
+        if default.is_some_and(|default| *default != value) {
+            *entry.overridden.entry(name.clone()).or_default() += 1;
+        }
+        if choices.contains(&value) {
+            *entry
+                .features
+                .entry(name.clone())
+                .or_default()
+                .entry(value)
+                .or_default() += 1;
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&stats)? + "\n")?;
+    log::debug!("Recorded the generation of {} in {}", key, path.display());
+    Ok(())
+}
+
+fn load(path: &Path) -> Result<Stats> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid {}: {}", path.display(), e),
+            )
+        }),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Stats::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// `category/name`, like the lockfile's template record
+fn template_key(template_path: &Path) -> String {
+    let component = |path: Option<&Path>| {
+        path.and_then(|p| p.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    format!(
+        "{}/{}",
+        component(template_path.parent()),
+        component(Some(template_path))
+    )
+}
+
+/// Print the recorded statistics of every template, or of the template given as
+/// `category/name` or `name`, most overridden variables first
+pub fn print(reference: Option<&str>) -> Result<()> {
+    let mut out = summary::status_output();
+    let path = default_path().ok_or_else(|| {
+        Error::new(
+            ErrorKind::NotFound,
+            "No location for template statistics, set HOME or PROJECT_GENERATOR_STATS",
+        )
+    })?;
+    let stats = load(&path)?;
+    let selected: Vec<(&String, &TemplateStats)> = stats
+        .iter()
+        .filter(|(key, _)| {
+            reference.is_none_or(|reference| {
+                *key == reference || key.rsplit('/').next() == Some(reference)
+            })
+        })
+        .collect();
+
+    if selected.is_empty() {
+        let _ = writeln!(
+            out,
+            "No template statistics recorded in {}{}",
+            path.display(),
+            reference.map(|r| format!(" for {}", r)).unwrap_or_default()
+        );
+        return Ok(());
+    }
+
+    for (key, template) in selected {
+        let _ = writeln!(out, "📊 {}: {} generation(s)", key, template.generations);
+        let percent = |count: u64| count * 100 / template.generations.max(1);
+
+        let mut overridden: Vec<(&String, &u64)> = template.overridden.iter().collect();
+        overridden.sort_by(|a, b| b.1.cmp(a.1));
+        if !overridden.is_empty() {
+            let _ = writeln!(out, "   Overridden defaults:");
+            for (name, count) in overridden {
+                let _ = writeln!(out, "     {:<24} {} ({}%)", name, count, percent(*count));
+            }
+        }
+
+        if !template.features.is_empty() {
+            let _ = writeln!(out, "   Selected choices:");
+            for (name, values) in &template.features {
+                let mut values: Vec<(&String, &u64)> = values.iter().collect();
+                values.sort_by(|a, b| b.1.cmp(a.1));
+                let values: Vec<String> = values
+                    .into_iter()
+                    .map(|(value, count)| format!("{} {} ({}%)", value, count, percent(*count)))
+                    .collect();
+                let _ = writeln!(out, "     {:<24} {}", name, values.join(", "));
+            }
+        }
+    }
+    Ok(())
+}
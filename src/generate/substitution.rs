@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Result;
+use std::path::Path;
+
+use crate::config::{SubstitutionConfig, EXCLUDED_DIRS};
+use crate::generate::render;
+use crate::utils::{context, glob};
+
+/// Bytes inspected to tell binary files from text files
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Replace `{{variable}}` placeholders of the known variables in every text file matched by
+/// the configuration, applying their filters (`{{ variable | pascal_case }}`). Unknown
+/// placeholders and GitHub expressions (`${{ ... }}`) are kept.
+pub fn substitute_variables(project_path: &Path, config: &SubstitutionConfig) -> Result<()> {
+    let variables = context::get_variables();
+    let mut changed = 0;
+    substitute_in_dir(project_path, project_path, config, &variables, &mut changed)?;
+    if changed > 0 {
+        log::info!("🔤 Substituted variables in {} files", changed);
+    }
+    Ok(())
+}
+
+fn substitute_in_dir(
+    root: &Path,
+    dir: &Path,
+    config: &SubstitutionConfig,
+    variables: &HashMap<String, String>,
+    changed: &mut usize,
+) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let file_name_str = file_name.to_string_lossy();
+
+        if entry.file_type()?.is_dir() {
+            if file_name_str != ".git" && !EXCLUDED_DIRS.contains(&file_name_str.as_ref()) {
+                substitute_in_dir(root, &path, config, variables, changed)?;
+            }
+            continue;
+        }
+
+        let relative_path = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let included = config
+            .include
+            .iter()
+            .any(|pattern| glob::matches(pattern, &relative_path));
+        let excluded = config
+            .exclude
+            .iter()
+            .any(|pattern| glob::matches(pattern, &relative_path));
+        if !included || excluded {
+            continue;
+        }
+
+        let bytes = fs::read(&path)?;
+        let Some(content) = as_text(&bytes) else {
+            log::debug!("Skipping binary file {}", relative_path);
+            continue;
+        };
+
+        let replaced = render::replace_placeholders(content, |name| variables.get(name).cloned());
+        for (placeholder, filter) in &replaced.unknown_filters {
+            log::warn!(
+                "Unknown filter '{}' in {} of {}",
+                filter,
+                placeholder,
+                relative_path
+            );
+        }
+        if replaced.text != content {
+            log::debug!("Substituted variables in {}", relative_path);
+            fs::write(&path, replaced.text.as_bytes())?;
+            *changed += 1;
+        }
+    }
+    Ok(())
+}
+
+/// The file content when it is UTF-8 text without NUL bytes in its first bytes
+pub fn as_text(bytes: &[u8]) -> Option<&str> {
+    if bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+        return None;
+    }
+    std::str::from_utf8(bytes).ok()
+}
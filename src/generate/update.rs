@@ -0,0 +1,649 @@
+use git2::build::CheckoutBuilder;
+use inquire::Select;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{Error, ErrorKind, IsTerminal, Result, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cli::functions::prompts_disabled;
+use crate::config::{FileOwnership, OwnershipRules, EXCLUDED_DIRS};
+use crate::generate::lockfile::{self, GeneratorLock, LOCKFILE};
+use crate::generate::merge;
+use crate::generate::project_generator::{self, GenerateOptions};
+use crate::template::{compatibility, compose, TemplateManager};
+use crate::utils::{context, strings, summary};
+
+/// One file changed by the update
+struct Change {
+    relative_path: String,
+    current: Option<Vec<u8>>,
+    updated: Option<Vec<u8>>,
+    conflicts: bool,
+    /// Template version of a conflicting file, offered when resolving it
+    template: Option<Vec<u8>>,
+}
+
+/// Re-render the current revision of the template a project was generated from and merge it
+/// into the project, following the template ownership rules: managed files are replaced,
+/// seeded files are 3-way merged against the revision recorded in the lockfile, and user
+/// files are left alone. With `patch`, the changes are written there as a unified diff
+/// instead of being applied. `allow_incompatible` is `--allow-incompatible-template`.
+pub fn update_project(
+    template_manager: &TemplateManager,
+    project_path: &Path,
+    patch: Option<&Path>,
+    allow_incompatible: bool,
+) -> Result<()> {
+    let lock = lockfile::read(project_path)?;
+    let variables = locked_variables(&lock)?;
+    let category = &lock.template.category;
+    let name = &lock.template.name;
+
+    let work_dir = std::env::temp_dir().join(format!(
+        "project-update-{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+    ));
+    fs::create_dir_all(&work_dir)?;
+    let result = (|| {
+        let template_path = template_manager.resolve_template_path(category, name)?;
+        compatibility::check(
+            &template_path,
+            &format!("{}/{}", category, name),
+            allow_incompatible,
+        )?;
+        let current_commit = git2::Repository::discover(&template_path)
+            .ok()
+            .and_then(|repo| Some(repo.head().ok()?.peel_to_commit().ok()?.id().to_string()));
+        if current_commit.is_some() && current_commit == lock.template.commit {
+            log::info!("✅ Project is up to date with {}/{}", category, name);
+            return Ok(());
+        }
+
+        log::info!(
+            "🔄 Updating from {}/{} {} to {}",
+            category,
+            name,
+            short(lock.template.commit.as_deref()),
+            short(current_commit.as_deref())
+        );
+        let updated_dir = work_dir.join("updated");
+        render(&template_path, &updated_dir, &variables)?;
+
+        let base_dir = match &lock.template.commit {
+            Some(commit) => {
+                let base_template = checkout_revision(
+                    template_manager.root_path(),
+                    &work_dir.join("templates"),
+                    commit,
+                    category,
+                    name,
+                )?;
+                let base_dir = work_dir.join("base");
+                render(&base_template, &base_dir, &variables)?;
+                Some(base_dir)
+            }
+            None => {
+                log::warn!(
+                    "No template commit in {}, seeded files cannot be merged cleanly",
+                    LOCKFILE
+                );
+                None
+            }
+        };
+
+        let ownership = strings::read_template_manifest(&template_path)?.ownership;
+        let mut changes =
+            collect_changes(project_path, base_dir.as_deref(), &updated_dir, &ownership)?;
+        if patch.is_none() && std::io::stdin().is_terminal() && !prompts_disabled() {
+            resolve_conflicts(&mut changes)?;
+        }
+        let conflicts = changes.iter().filter(|change| change.conflicts).count();
+
+        match patch {
+            Some(patch_path) => {
+                fs::write(patch_path, unified_diff(&changes)?)?;
+                log::info!(
+                    "📝 Wrote {} changed files to {}",
+                    changes.len(),
+                    patch_path.display()
+                );
+            }
+            None => {
+                apply(project_path, &changes)?;
+                log::info!("✅ Updated {} files", changes.len());
+            }
+        }
+        if conflicts > 0 {
+            log::info!(
+                "⚠️  {} files have conflicts, look for <<<<<<< markers:",
+                conflicts
+            );
+            for change in changes.iter().filter(|change| change.conflicts) {
+                log::info!("   - {}", change.relative_path);
+            }
+        }
+        Ok(())
+    })();
+
+    if let Err(e) = fs::remove_dir_all(&work_dir) {
+        log::debug!("Failed to remove {}: {}", work_dir.display(), e);
+    }
+    result
+}
+
+/// The variables of the lockfile, with the ones recorded without their value read from the
+/// environment (`NAME` in upper case) or prompted for
+fn locked_variables(lock: &GeneratorLock) -> Result<HashMap<String, String>> {
+    let mut variables: HashMap<String, String> = lock.variables.clone().into_iter().collect();
+    for name in &lock.secret_variables {
+        let value = match std::env::var(name.to_uppercase()) {
+            Ok(value) if !value.is_empty() => value,
+            _ => crate::cli::functions::prompt_for_secret(name, None).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Secret variable {} is required", name),
+                )
+            })?,
+        };
+        variables.insert(name.clone(), value);
+    }
+    Ok(variables)
+}
+
+fn short(commit: Option<&str>) -> &str {
+    commit.map(|c| &c[..c.len().min(7)]).unwrap_or("unknown")
+}
+
+/// Generate the template into `target` with the locked variables. Only the files are
+/// rendered: hooks, installs and workspace registration would act on the real system, and
+/// regenerating is not a new use of the template.
+fn render(template_path: &Path, target: &Path, variables: &HashMap<String, String>) -> Result<()> {
+    log::debug!(
+        "Rendering {} into {}",
+        template_path.display(),
+        target.display()
+    );
+    context::set_variables(variables.clone());
+    project_generator::generate_project(template_path, target, &GenerateOptions::render_only())
+}
+
+/// Check the templates repository out at `commit` in `target`, returning the template path.
+/// Every ref of the checkout is fetched, since the commit may only be reachable from a tag or
+/// a remote-tracking branch of a cached repository. Provenance headers need the result to be
+/// a repository at that commit.
+fn checkout_revision(
+    root: &Path,
+    target: &Path,
+    commit: &str,
+    category: &str,
+    name: &str,
+) -> Result<PathBuf> {
+    let git_error = |e: git2::Error| {
+        Error::other(format!(
+            "Failed to check out template revision {}: {}",
+            short(Some(commit)),
+            e
+        ))
+    };
+    let repo = git2::Repository::init(target).map_err(git_error)?;
+    repo.remote_anonymous(&root.to_string_lossy())
+        .and_then(|mut remote| remote.fetch(&["+refs/*:refs/source/*"], None, None))
+        .map_err(git_error)?;
+    let revision = repo.revparse_single(commit).map_err(git_error)?;
+    repo.checkout_tree(&revision, Some(CheckoutBuilder::new().force()))
+        .map_err(git_error)?;
+    repo.set_head_detached(revision.id()).map_err(git_error)?;
+    compose::compose(target, &target.join(category).join(name))
+}
+
+/// Files of the base and updated renders, relative to their root
+fn rendered_files(dir: &Path, root: &Path, files: &mut BTreeSet<String>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let file_name_str = file_name.to_string_lossy();
+        if entry.file_type()?.is_dir() {
+            if file_name_str != ".git" && !EXCLUDED_DIRS.contains(&file_name_str.as_ref()) {
+                rendered_files(&path, root, files)?;
+            }
+            continue;
+        }
+        files.insert(
+            path.strip_prefix(root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/"),
+        );
+    }
+    Ok(())
+}
+
+fn collect_changes(
+    project_path: &Path,
+    base_dir: Option<&Path>,
+    updated_dir: &Path,
+    ownership: &OwnershipRules,
+) -> Result<Vec<Change>> {
+    let mut files = BTreeSet::new();
+    rendered_files(updated_dir, updated_dir, &mut files)?;
+    if let Some(base_dir) = base_dir {
+        rendered_files(base_dir, base_dir, &mut files)?;
+    }
+
+    let read = |path: PathBuf| fs::read(path).ok();
+    let mut changes = Vec::new();
+    for relative_path in files {
+        let driver = ownership.merge_driver(&relative_path);
+        // The lockfile always follows the template
+        let ownership = if relative_path == LOCKFILE {
+            FileOwnership::Managed
+        } else {
+            ownership.classify(&relative_path)
+        };
+        if ownership == FileOwnership::User {
+            continue;
+        }
+
+        let current = read(project_path.join(&relative_path));
+        let base = base_dir.and_then(|dir| read(dir.join(&relative_path)));
+        let updated = read(updated_dir.join(&relative_path));
+        if (base_dir.is_some() && base == updated) || current == updated {
+            continue;
+        }
+
+        let template = updated.clone();
+        let (updated, conflicts) = match ownership {
+            FileOwnership::Managed => (updated, false),
+            _ if current == base => (updated, false),
+            // Deleted in the project, or removed from the template but edited in the project
+            _ if current.is_none() || updated.is_none() => {
+                log::warn!(
+                    "Keeping {}, changed both in the project and in the template",
+                    relative_path
+                );
+                continue;
+            }
+            // git merge-file cannot merge binary files, the project keeps its version
+            _ if [&current, &base, &updated]
+                .iter()
+                .any(|content| content.as_deref().is_some_and(is_binary)) =>
+            {
+                log::warn!(
+                    "Conflict in {}: binary file changed both in the project and in the template, keeping the project version",
+                    relative_path
+                );
+                continue;
+            }
+            _ => {
+                let current = current.as_deref().unwrap_or_default();
+                let base = base.as_deref().unwrap_or_default();
+                let updated = updated.as_deref().unwrap_or_default();
+                let (merged, conflicts) = merge_file(&relative_path, current, base, updated)?;
+                // Structured files merge key by key when the line merge conflicts or breaks them
+                let structured = if conflicts || !merge::parses(driver, &merged) {
+                    merge::merge_structured(driver, current, base, updated)
+                } else {
+                    None
+                };
+                match structured {
+                    Some(merged) => {
+                        log::info!("🔀 Merged {} key by key", relative_path);
+                        (Some(merged), false)
+                    }
+                    None => (Some(merged), conflicts),
+                }
+            }
+        };
+        if updated != current {
+            changes.push(Change {
+                relative_path,
+                current,
+                updated,
+                conflicts,
+                template: if conflicts { template } else { None },
+            });
+        }
+    }
+    Ok(changes)
+}
+
+/// Whether content is binary, the way git tells: a NUL byte in its first 8000 bytes
+fn is_binary(content: &[u8]) -> bool {
+    content.iter().take(8000).any(|byte| *byte == 0)
+}
+
+/// 3-way merge with `git merge-file`, returning the merged content and whether it has
+/// conflict markers
+fn merge_file(
+    relative_path: &str,
+    current: &[u8],
+    base: &[u8],
+    updated: &[u8],
+) -> Result<(Vec<u8>, bool)> {
+    let dir = std::env::temp_dir().join(format!(
+        "project-merge-{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+    ));
+    fs::create_dir_all(&dir)?;
+    let paths = [dir.join("current"), dir.join("base"), dir.join("updated")];
+    for (path, content) in paths.iter().zip([current, base, updated]) {
+        fs::write(path, content)?;
+    }
+
+    let output = Command::new("git")
+        .args([
+            "merge-file",
+            "-p",
+            "-L",
+            "project",
+            "-L",
+            "previous template",
+            "-L",
+            "template",
+        ])
+        .args(&paths)
+        .output();
+    fs::remove_dir_all(&dir)?;
+    let output = output.map_err(|e| {
+        Error::other(format!(
+            "Failed to run git merge-file for {}: {}",
+            relative_path, e
+        ))
+    })?;
+
+    // The exit code is the number of conflicts, negative on errors
+    match output.status.code() {
+        Some(0) => Ok((output.stdout, false)),
+        Some(code) if code > 0 => Ok((output.stdout, true)),
+        _ => Err(Error::other(format!(
+            "git merge-file failed for {}: {}",
+            relative_path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))),
+    }
+}
+
+/// Ask how to resolve each conflicting file: keep the conflict markers, keep the project
+/// version, take the template version, or edit the merged file until no marker is left.
+/// Files whose resolution leaves them unchanged are dropped from the changes.
+fn resolve_conflicts(changes: &mut Vec<Change>) -> Result<()> {
+    const KEEP_MARKERS: &str = "Keep the conflict markers, resolve later";
+    const OURS: &str = "Keep the project version";
+    const THEIRS: &str = "Take the template version";
+    const EDIT: &str = "Edit the merged file";
+    const SHOW: &str = "Show the conflicts";
+
+    let total = changes.iter().filter(|change| change.conflicts).count();
+    for (index, change) in changes
+        .iter_mut()
+        .filter(|change| change.conflicts)
+        .enumerate()
+    {
+        loop {
+            let message = format!(
+                "Conflict in {} ({}/{}):",
+                change.relative_path,
+                index + 1,
+                total
+            );
+            let choice = Select::new(&message, vec![SHOW, EDIT, OURS, THEIRS, KEEP_MARKERS])
+                .prompt()
+                .map_err(|e| Error::other(format!("Conflict resolution cancelled: {}", e)))?;
+            match choice {
+                SHOW => print_conflicts(change.updated.as_deref().unwrap_or_default()),
+                EDIT => {
+                    let edited = edit_in_editor(
+                        &change.relative_path,
+                        change.updated.as_deref().unwrap_or_default(),
+                    )?;
+                    let resolved = !has_conflict_markers(&edited);
+                    change.updated = Some(edited);
+                    if resolved {
+                        change.conflicts = false;
+                        break;
+                    }
+                    log::warn!("{} still has conflict markers", change.relative_path);
+                }
+                OURS => {
+                    change.updated = change.current.clone();
+                    change.conflicts = false;
+                    break;
+                }
+                THEIRS => {
+                    change.updated = change.template.clone();
+                    change.conflicts = false;
+                    break;
+                }
+                _ => break,
+            }
+        }
+    }
+    changes.retain(|change| change.updated != change.current);
+    Ok(())
+}
+
+fn has_conflict_markers(content: &[u8]) -> bool {
+    String::from_utf8_lossy(content)
+        .lines()
+        .any(|line| line.starts_with("<<<<<<<") || line.starts_with(">>>>>>>"))
+}
+
+/// Print the conflicting regions of a merged file with their line numbers
+fn print_conflicts(content: &[u8]) {
+    let mut out = summary::status_output();
+    let content = String::from_utf8_lossy(content);
+    let mut in_conflict = false;
+    for (number, line) in content.lines().enumerate() {
+        if line.starts_with("<<<<<<<") {
+            in_conflict = true;
+        }
+        if in_conflict {
+            let _ = writeln!(out, "{:>5} | {}", number + 1, line);
+        }
+        if line.starts_with(">>>>>>>") {
+            in_conflict = false;
+            let _ = writeln!(out);
+        }
+    }
+}
+
+/// Open the content in `$VISUAL`, `$EDITOR` or `vi`, returning it once the editor exits
+fn edit_in_editor(relative_path: &str, content: &[u8]) -> Result<Vec<u8>> {
+    let file_name = Path::new(relative_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "merged".to_string());
+    let path = std::env::temp_dir().join(format!(
+        "project-resolve-{}-{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+        file_name
+    ));
+    fs::write(&path, content)?;
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // The editor may come with arguments, e.g. `code --wait`
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(&path)
+        .status();
+    let edited = fs::read(&path);
+    fs::remove_file(&path)?;
+
+    let status =
+        status.map_err(|e| Error::other(format!("Failed to run editor {}: {}", editor, e)))?;
+    if !status.success() {
+        return Err(Error::other(format!(
+            "Editor {} exited with status: {}",
+            editor, status
+        )));
+    }
+    edited
+}
+
+fn apply(project_path: &Path, changes: &[Change]) -> Result<()> {
+    for change in changes {
+        let path = project_path.join(&change.relative_path);
+        match &change.updated {
+            Some(content) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&path, content)?;
+                log::debug!("Updated {}", change.relative_path);
+            }
+            None => {
+                fs::remove_file(&path)?;
+                log::debug!("Removed {}", change.relative_path);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Patch set turning the project into the updated one, applicable with `git apply`
+fn unified_diff(changes: &[Change]) -> Result<Vec<u8>> {
+    let mut diff = Vec::new();
+    for change in changes {
+        let path = Path::new(&change.relative_path);
+        let mut patch = git2::Patch::from_buffers(
+            change.current.as_deref().unwrap_or_default(),
+            change.current.as_ref().map(|_| path),
+            change.updated.as_deref().unwrap_or_default(),
+            change.updated.as_ref().map(|_| path),
+            None,
+        )
+        .map_err(|e| Error::other(format!("Failed to diff {}: {}", change.relative_path, e)))?;
+        let buffer = patch
+            .to_buf()
+            .map_err(|e| Error::other(format!("Failed to diff {}: {}", change.relative_path, e)))?;
+        // Mark additions and deletions the way `git apply` expects them
+        let mut text = String::from_utf8_lossy(&buffer).to_string();
+        if change.current.is_none() {
+            text = text
+                .replacen("\nindex ", "\nnew file mode 100644\nindex ", 1)
+                .replacen(
+                    &format!("--- a/{}", change.relative_path),
+                    "--- /dev/null",
+                    1,
+                );
+        } else if change.updated.is_none() {
+            text = text
+                .replacen("\nindex ", "\ndeleted file mode 100644\nindex ", 1)
+                .replacen(
+                    &format!("+++ b/{}", change.relative_path),
+                    "+++ /dev/null",
+                    1,
+                );
+        }
+        diff.extend_from_slice(text.as_bytes());
+    }
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Project, base and updated renders holding `file` with the given contents
+    fn merge_case(name: &str, file: &str, current: &[u8], base: &[u8], updated: &[u8]) -> PathBuf {
+        let work_dir = std::env::temp_dir().join(format!(
+            "project-generator-update-{}-{}",
+            name,
+            std::process::id()
+        ));
+        for (dir, content) in [("project", current), ("base", base), ("updated", updated)] {
+            fs::create_dir_all(work_dir.join(dir)).unwrap();
+            fs::write(work_dir.join(dir).join(file), content).unwrap();
+        }
+        work_dir
+    }
+
+    fn changes(work_dir: &Path, file: &str) -> Vec<Change> {
+        let ownership = OwnershipRules {
+            seeded: vec![file.to_string()],
+            ..Default::default()
+        };
+        let changes = collect_changes(
+            &work_dir.join("project"),
+            Some(&work_dir.join("base")),
+            &work_dir.join("updated"),
+            &ownership,
+        )
+        .unwrap();
+        fs::remove_dir_all(work_dir).unwrap();
+        changes
+    }
+
+    #[test]
+    fn merges_project_and_template_changes() {
+        let work_dir = merge_case(
+            "clean",
+            "notes.txt",
+            b"one\ntwo (project)\nthree\nfour\nfive\n",
+            b"one\ntwo\nthree\nfour\nfive\n",
+            b"one\ntwo\nthree\nfour\nfive (template)\n",
+        );
+        let changes = changes(&work_dir, "notes.txt");
+        assert_eq!(changes.len(), 1);
+        assert!(!changes[0].conflicts);
+        assert_eq!(
+            changes[0].updated.as_deref().unwrap(),
+            b"one\ntwo (project)\nthree\nfour\nfive (template)\n"
+        );
+
+        let diff = String::from_utf8(unified_diff(&changes).unwrap()).unwrap();
+        assert!(diff.contains("--- a/notes.txt\n+++ b/notes.txt\n"));
+        assert!(diff.contains("\n-five\n+five (template)\n"));
+    }
+
+    #[test]
+    fn conflicting_changes_keep_markers() {
+        let work_dir = merge_case(
+            "conflict",
+            "notes.txt",
+            b"one\ntwo (project)\nthree\n",
+            b"one\ntwo\nthree\n",
+            b"one\ntwo (template)\nthree\n",
+        );
+        let changes = changes(&work_dir, "notes.txt");
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].conflicts);
+        let merged = changes[0].updated.as_deref().unwrap();
+        assert!(has_conflict_markers(merged));
+        assert_eq!(
+            changes[0].template.as_deref().unwrap(),
+            b"one\ntwo (template)\nthree\n"
+        );
+
+        let diff = String::from_utf8(unified_diff(&changes).unwrap()).unwrap();
+        assert!(diff.contains("+<<<<<<< project\n"));
+    }
+
+    #[test]
+    fn binary_files_changed_on_both_sides_are_kept() {
+        let work_dir = merge_case(
+            "binary",
+            "logo.png",
+            b"\x89PNG\0project",
+            b"\x89PNG\0base",
+            b"\x89PNG\0template",
+        );
+        assert!(changes(&work_dir, "logo.png").is_empty());
+    }
+}
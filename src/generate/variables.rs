@@ -0,0 +1,301 @@
+use indexmap::IndexMap;
+use regex::Regex;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, IsTerminal, Result};
+use std::path::Path;
+
+use crate::cli::functions::{prompt_for_declared_variable, prompts_disabled};
+use crate::config::{PromptPage, PromptVariable, Replacement, TemplateJson, TemplateManifest};
+use crate::generate::render;
+use crate::utils::{context, strings};
+
+/// Whether a `when` condition holds for the given variables (no condition always holds)
+pub fn condition_met(when: Option<&str>, variables: &HashMap<String, String>) -> Result<bool> {
+    match when {
+        Some(condition) => render::evaluate_condition(condition, variables).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid variable condition '{}': {}", condition, e),
+            )
+        }),
+        None => Ok(true),
+    }
+}
+
+/// Drop the declared variables whose page or own `when` condition is false from the context,
+/// in declaration order so that variables depending on a dropped one are dropped too.
+/// Returns the variables that remain active.
+pub fn resolve_conditional_variables(pages: &[PromptPage]) -> Result<Vec<&PromptVariable>> {
+    let mut variables = context::get_variables();
+    let mut active = Vec::new();
+
+    for page in pages {
+        let page_enabled = condition_met(page.when.as_deref(), &variables)?;
+        for variable in &page.variables {
+            if page_enabled && condition_met(variable.when.as_deref(), &variables)? {
+                active.push(variable);
+            } else if variables.remove(&variable.name).is_some() {
+                log::debug!(
+                    "Omitting variable {}: its condition is not met",
+                    variable.name
+                );
+            }
+        }
+    }
+
+    context::set_variables(variables);
+    Ok(active)
+}
+
+/// Evaluate the computed variables of the template, then those of the config (which replace
+/// the template's definition of the same variable), and store them in the context. They are
+/// evaluated in order so that each can use the previous ones. A variable that already has a
+/// value keeps it.
+pub fn resolve_computed_variables(template: &IndexMap<String, String>) -> Result<()> {
+    let mut definitions = template.clone();
+    definitions.extend(context::get_computed());
+
+    let mut variables = context::get_variables();
+    for (name, expression) in definitions {
+        if variables.get(&name).is_some_and(|value| !value.is_empty()) {
+            log::debug!("Keeping provided value of computed variable {}", name);
+            continue;
+        }
+        let value = render::render(&expression, &variables, &HashMap::new()).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid computed variable {} '{}': {}", name, expression, e),
+            )
+        })?;
+        log::debug!("Computed {} = '{}'", name, value);
+        variables.insert(name, value);
+    }
+
+    context::set_variables(variables);
+    Ok(())
+}
+
+/// Ask for the required replacement variables that have no value, each once, with the
+/// default, choices and pattern of the replacement. Without a terminal to ask on, they are
+/// left to the validation.
+pub fn prompt_for_missing_replacements(config: &TemplateJson) -> Result<()> {
+    let mut asked: Vec<&str> = Vec::new();
+    for replacement in config.iter().flat_map(|file| &file.replacements) {
+        if !replacement.required
+            || asked.contains(&replacement.name.as_str())
+            || context::get_variable(&replacement.name).is_some_and(|value| !value.is_empty())
+        {
+            continue;
+        }
+        asked.push(&replacement.name);
+
+        // Reported by the validation instead
+        if !std::io::stdin().is_terminal() || prompts_disabled() {
+            continue;
+        }
+        let value = prompt_for_declared_variable(&as_prompt(replacement)).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Variable {} is required", replacement.name),
+            )
+        })?;
+        context::set_variable(&replacement.name, &value);
+    }
+    Ok(())
+}
+
+/// With `--answers`, check that every variable the template needs is provided up front: the
+/// project and package names, the active declared variables and the replacement variables
+/// without a default, unless they are computed. Declared defaults are applied, and every
+/// missing variable is reported at once instead of being prompted for.
+pub fn check_answers(template_path: &Path) -> Result<()> {
+    let manifest = match strings::read_template_manifest(template_path) {
+        Ok(manifest) => manifest,
+        Err(e) if e.kind() == ErrorKind::NotFound => TemplateManifest::default(),
+        Err(e) => return Err(e),
+    };
+    let active = resolve_conditional_variables(&manifest.prompts)?;
+    let omitted: Vec<&str> = manifest
+        .prompts
+        .iter()
+        .flat_map(|page| &page.variables)
+        .map(|variable| variable.name.as_str())
+        .filter(|name| !active.iter().any(|variable| variable.name == *name))
+        .collect();
+
+    // (name, description) of every needed variable
+    let mut needed: Vec<(&str, Option<&str>)> = vec![
+        ("project_name", Some("project name")),
+        ("name", Some("package name")),
+    ];
+    for variable in &active {
+        if let Some(default) = &variable.default {
+            if context::get_variable(&variable.name).is_none() {
+                context::set_variable(&variable.name, default);
+            }
+        }
+        needed.push((&variable.name, variable.message.as_deref()));
+    }
+    for replacement in manifest.files.iter().flat_map(|file| &file.replacements) {
+        if replacement.default.is_none() && !omitted.contains(&replacement.name.as_str()) {
+            needed.push((&replacement.name, replacement.message.as_deref()));
+        }
+    }
+
+    let mut missing: Vec<String> = Vec::new();
+    for (index, (name, description)) in needed.iter().enumerate() {
+        if context::get_variable(name).is_some_and(|value| !value.is_empty())
+            || manifest.computed.contains_key(*name)
+            || needed[..index].iter().any(|(previous, _)| previous == name)
+        {
+            continue;
+        }
+        missing.push(match description {
+            Some(description) => format!("{} ({})", name, description),
+            None => name.to_string(),
+        });
+    }
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+    Err(Error::new(
+        ErrorKind::InvalidInput,
+        format!(
+            "The answers file does not provide every variable the template needs:\n  - {}",
+            missing.join("\n  - ")
+        ),
+    ))
+}
+
+/// The question asked for a replacement variable
+fn as_prompt(replacement: &Replacement) -> PromptVariable {
+    PromptVariable {
+        name: replacement.name.clone(),
+        message: replacement.message.clone(),
+        default: replacement.default.clone(),
+        choices: replacement.choices.clone(),
+        secret: false,
+        when: None,
+        required: true,
+        pattern: replacement.pattern.clone(),
+        min_length: replacement.min_length,
+        max_length: replacement.max_length,
+    }
+}
+
+/// Validation rules of a declared variable or a replacement
+pub struct Rules<'a> {
+    pub required: bool,
+    pub pattern: Option<&'a str>,
+    /// Allowed values, any value when empty
+    pub choices: &'a [String],
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+}
+
+impl<'a> From<&'a PromptVariable> for Rules<'a> {
+    fn from(variable: &'a PromptVariable) -> Self {
+        Rules {
+            required: variable.required,
+            pattern: variable.pattern.as_deref(),
+            choices: &variable.choices,
+            min_length: variable.min_length,
+            max_length: variable.max_length,
+        }
+    }
+}
+
+impl<'a> From<&'a Replacement> for Rules<'a> {
+    fn from(replacement: &'a Replacement) -> Self {
+        Rules {
+            required: replacement.required,
+            pattern: replacement.pattern.as_deref(),
+            choices: &replacement.choices,
+            min_length: replacement.min_length,
+            max_length: replacement.max_length,
+        }
+    }
+}
+
+/// Every rule the value breaks, e.g. `must match '[a-z]+'`. An empty value only breaks
+/// `required`.
+pub fn check(rules: &Rules, value: Option<&str>) -> Vec<String> {
+    let value = match value {
+        Some(value) if !value.is_empty() => value,
+        _ if rules.required => return vec!["is required".to_string()],
+        _ => return Vec::new(),
+    };
+
+    let mut problems = Vec::new();
+    if let Some(pattern) = rules.pattern {
+        match matches_pattern(pattern, value) {
+            Ok(true) => {}
+            Ok(false) => problems.push(format!("must match '{}'", pattern)),
+            Err(e) => problems.push(format!("cannot be checked: {}", e)),
+        }
+    }
+    if !rules.choices.is_empty() && !rules.choices.iter().any(|choice| choice == value) {
+        problems.push(format!("must be one of {}", rules.choices.join(", ")));
+    }
+    let length = value.chars().count();
+    if let Some(min_length) = rules.min_length.filter(|min| length < *min) {
+        problems.push(format!("must be at least {} characters long", min_length));
+    }
+    if let Some(max_length) = rules.max_length.filter(|max| length > *max) {
+        problems.push(format!("must be at most {} characters long", max_length));
+    }
+    problems
+}
+
+/// Check the active declared variables and the replacement variables against their rules,
+/// reporting every invalid or missing value at once. Replacements fall back to their default.
+pub fn validate_variables(manifest: &TemplateManifest, active: &[&PromptVariable]) -> Result<()> {
+    let variables = context::get_variables();
+    let omitted: Vec<&str> = manifest
+        .prompts
+        .iter()
+        .flat_map(|page| &page.variables)
+        .map(|variable| variable.name.as_str())
+        .filter(|name| !active.iter().any(|variable| variable.name == *name))
+        .collect();
+
+    let mut report: Vec<String> = Vec::new();
+    let mut add = |name: &str, problems: Vec<String>| {
+        for problem in problems {
+            let line = format!("{} {}", name, problem);
+            if !report.contains(&line) {
+                report.push(line);
+            }
+        }
+    };
+    for variable in active {
+        let value = variables.get(&variable.name).map(String::as_str);
+        add(&variable.name, check(&Rules::from(*variable), value));
+    }
+    for replacement in manifest.files.iter().flat_map(|file| &file.replacements) {
+        if omitted.contains(&replacement.name.as_str()) {
+            continue;
+        }
+        let value = variables
+            .get(&replacement.name)
+            .or(replacement.default.as_ref())
+            .map(String::as_str);
+        add(&replacement.name, check(&Rules::from(replacement), value));
+    }
+
+    if report.is_empty() {
+        return Ok(());
+    }
+    Err(Error::new(
+        ErrorKind::InvalidInput,
+        format!("Invalid template variables:\n  - {}", report.join("\n  - ")),
+    ))
+}
+
+/// Whether the whole value matches the regular expression `pattern`
+pub fn matches_pattern(pattern: &str, value: &str) -> std::result::Result<bool, String> {
+    let regex = Regex::new(&format!("^(?:{})$", pattern))
+        .map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?;
+    Ok(regex.is_match(value))
+}
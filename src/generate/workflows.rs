@@ -0,0 +1,557 @@
+use regex::Regex;
+use serde_yaml::Value;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use std::process::Command;
+
+pub const WORKFLOWS_DIR: &str = ".github/workflows";
+
+/// Secrets GitHub provides to every workflow
+const BUILTIN_SECRETS: &[&str] = &["GITHUB_TOKEN"];
+
+const TOP_LEVEL_KEYS: &[&str] = &[
+    "name",
+    "run-name",
+    "on",
+    "permissions",
+    "env",
+    "defaults",
+    "concurrency",
+    "jobs",
+];
+
+const JOB_KEYS: &[&str] = &[
+    "name",
+    "needs",
+    "permissions",
+    "if",
+    "runs-on",
+    "environment",
+    "concurrency",
+    "outputs",
+    "env",
+    "defaults",
+    "steps",
+    "timeout-minutes",
+    "strategy",
+    "continue-on-error",
+    "container",
+    "services",
+    "uses",
+    "with",
+    "secrets",
+];
+
+const STEP_KEYS: &[&str] = &[
+    "id",
+    "if",
+    "name",
+    "uses",
+    "run",
+    "working-directory",
+    "shell",
+    "with",
+    "env",
+    "continue-on-error",
+    "timeout-minutes",
+];
+
+const EVENTS: &[&str] = &[
+    "branch_protection_rule",
+    "check_run",
+    "check_suite",
+    "create",
+    "delete",
+    "deployment",
+    "deployment_status",
+    "discussion",
+    "discussion_comment",
+    "fork",
+    "gollum",
+    "issue_comment",
+    "issues",
+    "label",
+    "merge_group",
+    "milestone",
+    "page_build",
+    "project",
+    "project_card",
+    "project_column",
+    "public",
+    "pull_request",
+    "pull_request_review",
+    "pull_request_review_comment",
+    "pull_request_target",
+    "push",
+    "registry_package",
+    "release",
+    "repository_dispatch",
+    "schedule",
+    "status",
+    "watch",
+    "workflow_call",
+    "workflow_dispatch",
+    "workflow_run",
+];
+
+/// Location of a schema error in a workflow, resolved to a line for the report
+#[derive(Clone)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+struct SchemaError {
+    path: Vec<Segment>,
+    message: String,
+}
+
+/// The `on` triggers of a workflow. serde_yaml reads the bare `on` key as a string,
+/// YAML 1.1 parsers as `true`, so both are looked up.
+pub(crate) fn triggers(workflow: &Value) -> Option<&Value> {
+    workflow
+        .get("on")
+        .or_else(|| workflow.get(Value::Bool(true)))
+}
+
+/// Event names of `on` triggers, whether written as a string, a list or a mapping
+pub(crate) fn trigger_events(triggers: &Value) -> Vec<&str> {
+    match triggers {
+        Value::String(event) => vec![event.as_str()],
+        Value::Sequence(events) => events.iter().filter_map(Value::as_str).collect(),
+        Value::Mapping(events) => events.keys().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn workflow_files(project_path: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(project_path.join(WORKFLOWS_DIR)) else {
+        return Vec::new();
+    };
+    let mut files: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.ends_with(".yml") || name.ends_with(".yaml"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Check the generated workflows against the GitHub workflow schema (YAML syntax, known
+/// keys and events, jobs and steps), failing with `file:line` errors
+pub fn check_schema(project_path: &Path) -> Result<()> {
+    let files = workflow_files(project_path);
+    if files.is_empty() {
+        return Ok(());
+    }
+    log::debug!("Checking {} workflows", files.len());
+
+    let mut errors = Vec::new();
+    for file in &files {
+        let content = fs::read_to_string(project_path.join(WORKFLOWS_DIR).join(file))?;
+        match serde_yaml::from_str::<Value>(&content) {
+            Ok(workflow) => {
+                for error in schema_errors(&workflow) {
+                    errors.push(format!(
+                        "{}/{}:{}: {}",
+                        WORKFLOWS_DIR,
+                        file,
+                        line_of(&content, &error.path),
+                        error.message
+                    ));
+                }
+            }
+            Err(e) => {
+                let line = e.location().map_or(1, |location| location.line());
+                errors.push(format!(
+                    "{}/{}:{}: invalid YAML: {}",
+                    WORKFLOWS_DIR, file, line, e
+                ));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid workflows:\n  {}", errors.join("\n  ")),
+        ));
+    }
+    Ok(())
+}
+
+/// Before anything is pushed, warn about secrets referenced by the workflows but not
+/// declared by the template (they may be organization secrets). With `use_act`, also
+/// dry-run the workflows with `act`.
+pub fn validate(project_path: &Path, declared_secrets: &[String], use_act: bool) -> Result<()> {
+    let files = workflow_files(project_path);
+    if files.is_empty() {
+        return Ok(());
+    }
+
+    log::info!("🔍 Validating {} workflows...", files.len());
+    let mut undeclared = Vec::new();
+
+    for file in &files {
+        let content = fs::read_to_string(project_path.join(WORKFLOWS_DIR).join(file))?;
+        for name in secret_names(&content) {
+            let known = declared_secrets.contains(&name);
+            let reference = format!("{} ({})", name, file);
+            if !known && !undeclared.contains(&reference) {
+                undeclared.push(reference);
+            }
+        }
+    }
+
+    for reference in &undeclared {
+        log::warn!(
+            "Workflow secret {} is not declared by the template, it must exist at the organization level",
+            reference
+        );
+    }
+
+    if use_act {
+        run_act(project_path).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    }
+    log::info!("✅ Workflows are valid");
+    Ok(())
+}
+
+/// Secrets referenced as `secrets.NAME` or `secrets['NAME']`, except those GitHub provides
+fn secret_names(content: &str) -> Vec<String> {
+    let secret_reference =
+        Regex::new(r#"secrets(?:\.([A-Za-z_][A-Za-z0-9_]*)|\[\s*'([A-Za-z_][A-Za-z0-9_]*)'\s*\])"#)
+            .unwrap();
+    let mut names: Vec<String> = Vec::new();
+    for captures in secret_reference.captures_iter(content) {
+        let Some(name) = captures.get(1).or_else(|| captures.get(2)) else {
+            continue;
+        };
+        let name = name.as_str();
+        if !BUILTIN_SECRETS.contains(&name) && !names.iter().any(|known| known == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Secrets a workflow needs to run, each with the deployment environment of the job using it
+/// (whose secrets are available to that job as well)
+pub fn required_secrets(
+    project_path: &Path,
+    workflow: &str,
+) -> Result<Vec<(String, Option<String>)>> {
+    let path = project_path.join(WORKFLOWS_DIR).join(workflow);
+    let content = fs::read_to_string(&path)?;
+    let parsed: Value = serde_yaml::from_str(&content).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid workflow {}: {}", path.display(), e),
+        )
+    })?;
+
+    let mut required = Vec::new();
+    let mut add = |name: String, environment: Option<String>| {
+        let secret = (name, environment);
+        if !required.contains(&secret) {
+            required.push(secret);
+        }
+    };
+    // Top-level references (e.g. workflow `env`) apply to every job
+    let mut scoped = parsed.clone();
+    if let Some(mapping) = scoped.as_mapping_mut() {
+        mapping.remove("jobs");
+    }
+    for name in secret_names(&serde_yaml::to_string(&scoped).unwrap_or_default()) {
+        add(name, None);
+    }
+    if let Some(jobs) = parsed.get("jobs").and_then(Value::as_mapping) {
+        for job in jobs.values() {
+            // `environment: production` or `environment: { name: production, url: ... }`
+            let environment = job
+                .get("environment")
+                .and_then(|environment| environment.get("name").or(Some(environment)))
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            for name in secret_names(&serde_yaml::to_string(job).unwrap_or_default()) {
+                add(name, environment.clone());
+            }
+        }
+    }
+    Ok(required)
+}
+
+/// Workflows of the project triggered by `event`, e.g. `merge_group`
+pub fn triggered_by(project_path: &Path, event: &str) -> Vec<String> {
+    workflow_files(project_path)
+        .into_iter()
+        .filter(|file| {
+            let Ok(content) = fs::read_to_string(project_path.join(WORKFLOWS_DIR).join(file))
+            else {
+                return false;
+            };
+            let Ok(workflow) = serde_yaml::from_str::<Value>(&content) else {
+                return false;
+            };
+            triggers(&workflow).is_some_and(|triggers| trigger_events(triggers).contains(&event))
+        })
+        .collect()
+}
+
+/// Inputs a workflow declares for `workflow_dispatch`, `None` when it cannot be read
+pub fn dispatch_inputs(project_path: &Path, workflow: &str) -> Option<Vec<String>> {
+    let content = fs::read_to_string(project_path.join(WORKFLOWS_DIR).join(workflow)).ok()?;
+    let parsed: Value = serde_yaml::from_str(&content).ok()?;
+    let inputs = triggers(&parsed)
+        .and_then(|triggers| triggers.get("workflow_dispatch"))
+        .and_then(|dispatch| dispatch.get("inputs"))
+        .and_then(Value::as_mapping)
+        .map(|inputs| {
+            inputs
+                .keys()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(inputs)
+}
+
+fn key(name: &str) -> Segment {
+    Segment::Key(name.to_string())
+}
+
+fn unknown_keys(
+    value: &Value,
+    allowed: &[&str],
+    what: &str,
+    path: &[Segment],
+    errors: &mut Vec<SchemaError>,
+) {
+    let Some(mapping) = value.as_mapping() else {
+        return;
+    };
+    for name in mapping.keys().filter_map(Value::as_str) {
+        if !allowed.contains(&name) {
+            let mut key_path = path.to_vec();
+            key_path.push(key(name));
+            errors.push(SchemaError {
+                path: key_path,
+                message: format!("unknown {} key '{}'", what, name),
+            });
+        }
+    }
+}
+
+fn schema_errors(workflow: &Value) -> Vec<SchemaError> {
+    let mut errors = Vec::new();
+    let error = |path: Vec<Segment>, message: String| SchemaError { path, message };
+
+    if !workflow.is_mapping() {
+        errors.push(error(
+            Vec::new(),
+            "a workflow must be a mapping".to_string(),
+        ));
+        return errors;
+    }
+    unknown_keys(workflow, TOP_LEVEL_KEYS, "workflow", &[], &mut errors);
+
+    match triggers(workflow) {
+        None => errors.push(error(Vec::new(), "missing 'on' triggers".to_string())),
+        Some(triggers) => {
+            let events = trigger_events(triggers);
+            if events.is_empty() {
+                errors.push(error(vec![key("on")], "no trigger events".to_string()));
+            }
+            for event in events.into_iter().filter(|event| !EVENTS.contains(event)) {
+                errors.push(error(
+                    vec![key("on"), key(event)],
+                    format!("unknown event '{}'", event),
+                ));
+            }
+        }
+    }
+
+    let Some(jobs) = workflow.get("jobs").and_then(Value::as_mapping) else {
+        errors.push(error(Vec::new(), "missing 'jobs'".to_string()));
+        return errors;
+    };
+    if jobs.is_empty() {
+        errors.push(error(vec![key("jobs")], "no jobs".to_string()));
+    }
+    for (name, job) in jobs {
+        let name = name.as_str().unwrap_or("?");
+        let job_path = vec![key("jobs"), key(name)];
+        if !job.is_mapping() {
+            errors.push(error(job_path, format!("job '{}' must be a mapping", name)));
+            continue;
+        }
+        unknown_keys(job, JOB_KEYS, "job", &job_path, &mut errors);
+
+        if let Some(needs) = job.get("needs") {
+            let needs: Vec<&str> = match needs {
+                Value::String(need) => vec![need.as_str()],
+                Value::Sequence(needs) => needs.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            for need in needs.into_iter().filter(|need| !jobs.contains_key(*need)) {
+                let mut path = job_path.clone();
+                path.push(key("needs"));
+                errors.push(error(
+                    path,
+                    format!("job '{}' needs unknown job '{}'", name, need),
+                ));
+            }
+        }
+
+        // Jobs calling a reusable workflow have no runner or steps of their own
+        if job.get("uses").is_some() {
+            continue;
+        }
+        if job.get("runs-on").is_none() {
+            errors.push(error(
+                job_path.clone(),
+                format!("job '{}' has no 'runs-on'", name),
+            ));
+        }
+        let Some(steps) = job.get("steps").and_then(Value::as_sequence) else {
+            errors.push(error(
+                job_path.clone(),
+                format!("job '{}' has no 'steps'", name),
+            ));
+            continue;
+        };
+        for (index, step) in steps.iter().enumerate() {
+            let mut step_path = job_path.clone();
+            step_path.extend([key("steps"), Segment::Index(index)]);
+            unknown_keys(step, STEP_KEYS, "step", &step_path, &mut errors);
+            let message = match (step.get("run").is_some(), step.get("uses").is_some()) {
+                (false, false) => "has neither 'run' nor 'uses'",
+                (true, true) => "has both 'run' and 'uses'",
+                _ => continue,
+            };
+            errors.push(error(
+                step_path,
+                format!("job '{}', step {} {}", name, index + 1, message),
+            ));
+        }
+    }
+    errors
+}
+
+/// Line (1-based) of a path in the workflow source, following indentation. Falls back to
+/// the deepest parent found.
+fn line_of(content: &str, path: &[Segment]) -> usize {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut line = 1;
+    let mut start = 0;
+    let mut parent_indent: Option<usize> = None;
+    let mut in_item = false;
+
+    for segment in path {
+        let mut child_indent = None;
+        let mut count = 0;
+        let mut found = None;
+
+        for (i, text) in lines.iter().enumerate().skip(start) {
+            let trimmed = text.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let raw_indent = text.len() - trimmed.len();
+            let item = trimmed
+                .strip_prefix('-')
+                .filter(|rest| rest.is_empty() || rest.starts_with(' '));
+
+            match segment {
+                Segment::Key(name) => {
+                    // The first key of a sequence item shares the line of its dash
+                    let (indent, entry) = match item {
+                        Some(rest) if in_item && i == start => (raw_indent + 2, rest.trim_start()),
+                        _ => (raw_indent, trimmed),
+                    };
+                    if parent_indent.is_some_and(|parent| indent <= parent) {
+                        break;
+                    }
+                    let level = *child_indent.get_or_insert(indent);
+                    if indent == level && is_key(entry, name) {
+                        found = Some((i, indent));
+                        break;
+                    }
+                }
+                Segment::Index(index) => {
+                    // Sequence items may sit at their parent key's indentation
+                    if parent_indent.is_some_and(|parent| {
+                        raw_indent < parent || (raw_indent == parent && item.is_none())
+                    }) {
+                        break;
+                    }
+                    let level = *child_indent.get_or_insert(raw_indent);
+                    if raw_indent < level || (raw_indent == level && item.is_none()) {
+                        break;
+                    }
+                    if raw_indent == level {
+                        if count == *index {
+                            found = Some((i, raw_indent));
+                            break;
+                        }
+                        count += 1;
+                    }
+                }
+            }
+        }
+
+        let Some((i, indent)) = found else {
+            break;
+        };
+        line = i + 1;
+        parent_indent = Some(indent);
+        in_item = matches!(segment, Segment::Index(_));
+        start = if in_item { i } else { i + 1 };
+    }
+    line
+}
+
+fn is_key(entry: &str, name: &str) -> bool {
+    [
+        name.to_string(),
+        format!("\"{}\"", name),
+        format!("'{}'", name),
+    ]
+    .iter()
+    .any(|key| {
+        entry
+            .strip_prefix(key.as_str())
+            .is_some_and(|rest| rest.trim_start().starts_with(':'))
+    })
+}
+
+/// Dry-run the workflows with `act`, skipped with a warning when it is not installed
+fn run_act(project_path: &Path) -> std::result::Result<(), String> {
+    log::debug!("Running act in dry-run mode");
+    let output = match Command::new("act")
+        .args(["--dryrun", "--workflows", WORKFLOWS_DIR])
+        .current_dir(project_path)
+        .output()
+    {
+        Ok(output) => output,
+        Err(_) => {
+            log::warn!("Act is not installed, workflows were only checked statically");
+            return Ok(());
+        }
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let details = if stderr.trim().is_empty() {
+            stdout
+        } else {
+            stderr
+        };
+        return Err(format!("act dry-run failed: {}", details.trim()));
+    }
+    log::info!("✅ act dry-run succeeded");
+    Ok(())
+}
@@ -0,0 +1,256 @@
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+
+use crate::config::{WorkspaceConfig, EXCLUDED_DIRS};
+use crate::utils::glob;
+
+pub const WORKSPACE_FILE: &str = "pnpm-workspace.yaml";
+const TURBO_FILE: &str = "turbo.json";
+const DEPENDENCY_SECTIONS: &[&str] = &[
+    "dependencies",
+    "devDependencies",
+    "peerDependencies",
+    "optionalDependencies",
+];
+
+#[derive(Default, serde::Deserialize)]
+struct WorkspaceFile {
+    #[serde(default)]
+    packages: Vec<String>,
+}
+
+/// Closest directory above the project holding a pnpm-workspace.yaml
+pub fn find_root(project_path: &Path) -> Option<PathBuf> {
+    let project_path = fs::canonicalize(project_path).ok()?;
+    enclosing_root(project_path.parent()?)
+}
+
+/// `dir` itself or its closest ancestor holding a pnpm-workspace.yaml
+pub fn enclosing_root(dir: &Path) -> Option<PathBuf> {
+    let dir = fs::canonicalize(dir).ok()?;
+    dir.ancestors()
+        .find(|dir| dir.join(WORKSPACE_FILE).is_file())
+        .map(Path::to_path_buf)
+}
+
+/// Register the package in the workspace, its turbo tasks in the pipeline, and point its
+/// dependencies on other workspace packages at their local version
+pub fn register_package(root: &Path, project_path: &Path, config: &WorkspaceConfig) -> Result<()> {
+    let project_path = fs::canonicalize(project_path)?;
+    let relative_path = project_path
+        .strip_prefix(root)
+        .map_err(|_| Error::other("Project is not inside the workspace root"))?
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    let workspace_path = root.join(WORKSPACE_FILE);
+    let content = fs::read_to_string(&workspace_path)?;
+    let patterns = serde_yaml::from_str::<Option<WorkspaceFile>>(&content)
+        .map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Failed to parse {}: {}", WORKSPACE_FILE, e),
+            )
+        })?
+        .unwrap_or_default()
+        .packages;
+
+    if is_workspace_package(&relative_path, &patterns) {
+        log::debug!(
+            "{} is already covered by the workspace patterns",
+            relative_path
+        );
+    } else {
+        fs::write(&workspace_path, add_package_entry(&content, &relative_path))?;
+        log::info!("📦 Registered {} in {}", relative_path, WORKSPACE_FILE);
+    }
+
+    let mut packages = IndexMap::new();
+    collect_packages(root, root, &patterns, &mut packages)?;
+    packages.shift_remove(&relative_path);
+    let package_names: Vec<String> = packages.into_values().collect();
+
+    let package_json_path = project_path.join("package.json");
+    if !package_json_path.exists() {
+        return Ok(());
+    }
+    let mut package_json: IndexMap<String, Value> =
+        serde_json::from_str(&fs::read_to_string(&package_json_path)?)?;
+
+    let mut linked = Vec::new();
+    for section in DEPENDENCY_SECTIONS {
+        let Some(Value::Object(dependencies)) = package_json.get_mut(*section) else {
+            continue;
+        };
+        for (name, version) in dependencies.iter_mut() {
+            let is_local = version
+                .as_str()
+                .is_some_and(|v| v.starts_with("workspace:"));
+            if package_names.contains(name) && !is_local {
+                *version = Value::String("workspace:*".to_string());
+                linked.push(name.clone());
+            }
+        }
+    }
+    if !linked.is_empty() {
+        fs::write(
+            &package_json_path,
+            serde_json::to_string_pretty(&package_json)?,
+        )?;
+        log::info!("🔗 Linked workspace dependencies: {}", linked.join(", "));
+    }
+
+    if !config.turbo_tasks.is_empty() {
+        match package_json.get("name").and_then(Value::as_str) {
+            Some(name) => register_turbo_tasks(root, name, &config.turbo_tasks)?,
+            None => log::warn!("Package.json has no name, turbo tasks not registered"),
+        }
+    }
+    Ok(())
+}
+
+fn is_workspace_package(relative_path: &str, patterns: &[String]) -> bool {
+    let matches = |pattern: &str| glob::matches(pattern, relative_path);
+
+    patterns.iter().any(|p| !p.starts_with('!') && matches(p))
+        && !patterns
+            .iter()
+            .filter_map(|p| p.strip_prefix('!'))
+            .any(matches)
+}
+
+/// Append the package path to the `packages` list, keeping the rest of the file untouched
+fn add_package_entry(content: &str, relative_path: &str) -> String {
+    let entry = format!("\"{}\"", relative_path);
+    let lines: Vec<&str> = content.lines().collect();
+
+    let Some(start) = lines.iter().position(|line| line.starts_with("packages:")) else {
+        let separator = if content.is_empty() || content.ends_with('\n') {
+            ""
+        } else {
+            "\n"
+        };
+        return format!("{}{}packages:\n  - {}\n", content, separator, entry);
+    };
+
+    // Flow style (`packages: [...]`) is rewritten in block style
+    if lines[start].trim_end() != "packages:" {
+        let mut workspace: serde_yaml::Mapping = serde_yaml::from_str(content).unwrap_or_default();
+        let mut packages: Vec<String> = workspace
+            .get("packages")
+            .and_then(|p| serde_yaml::from_value(p.clone()).ok())
+            .unwrap_or_default();
+        packages.push(relative_path.to_string());
+        workspace.insert("packages".into(), packages.into());
+        return serde_yaml::to_string(&workspace).unwrap_or_else(|_| content.to_string());
+    }
+
+    let mut last_item = start;
+    let mut indent = "  ".to_string();
+    for (index, line) in lines.iter().enumerate().skip(start + 1) {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('-') {
+            last_item = index;
+            indent = line[..line.len() - trimmed.len()].to_string();
+        } else if !trimmed.is_empty() && !trimmed.starts_with('#') {
+            break;
+        }
+    }
+
+    let mut result: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    result.insert(last_item + 1, format!("{}- {}", indent, entry));
+    result.join("\n") + "\n"
+}
+
+/// Workspace packages, relative directory to package name
+fn collect_packages(
+    root: &Path,
+    dir: &Path,
+    patterns: &[String],
+    packages: &mut IndexMap<String, String>,
+) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        if !entry.file_type()?.is_dir()
+            || file_name.starts_with('.')
+            || EXCLUDED_DIRS.contains(&file_name.as_ref())
+        {
+            continue;
+        }
+
+        let path = entry.path();
+        let relative_path = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let package_json = path.join("package.json");
+        if package_json.is_file() && is_workspace_package(&relative_path, patterns) {
+            let name = fs::read_to_string(&package_json)
+                .ok()
+                .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+                .and_then(|json| json.get("name")?.as_str().map(str::to_string));
+            if let Some(name) = name {
+                packages.insert(relative_path, name);
+            }
+        }
+        collect_packages(root, &path, patterns, packages)?;
+    }
+    Ok(())
+}
+
+/// Add `<package>#<task>` entries to the root turbo.json (`tasks`, or `pipeline` before Turbo 2)
+fn register_turbo_tasks(
+    root: &Path,
+    package_name: &str,
+    tasks: &IndexMap<String, Value>,
+) -> Result<()> {
+    let turbo_path = root.join(TURBO_FILE);
+    if !turbo_path.exists() {
+        log::debug!("No turbo.json in the workspace, skipping turbo tasks");
+        return Ok(());
+    }
+
+    let mut turbo: IndexMap<String, Value> =
+        serde_json::from_str(&fs::read_to_string(&turbo_path)?).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Failed to parse {}: {}", TURBO_FILE, e),
+            )
+        })?;
+    let key = if turbo.contains_key("pipeline") {
+        "pipeline"
+    } else {
+        "tasks"
+    };
+    let Value::Object(pipeline) = turbo
+        .entry(key.to_string())
+        .or_insert_with(|| Value::Object(Default::default()))
+    else {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("'{}' in {} is not an object", key, TURBO_FILE),
+        ));
+    };
+
+    let mut added = 0;
+    for (task, definition) in tasks {
+        let name = format!("{}#{}", package_name, task);
+        if !pipeline.contains_key(&name) {
+            pipeline.insert(name, definition.clone());
+            added += 1;
+        }
+    }
+    if added == 0 {
+        return Ok(());
+    }
+
+    fs::write(&turbo_path, serde_json::to_string_pretty(&turbo)? + "\n")?;
+    log::info!("⚙️  Registered {} turbo tasks for {}", added, package_name);
+    Ok(())
+}
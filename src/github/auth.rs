@@ -0,0 +1,253 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Private};
+use openssl::sign::Signer;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use super::client::API_URL;
+use super::headers::ApiHeaders;
+use super::http;
+use super::retry::{RetryPolicy, SendWithRetry};
+use crate::utils::error::GeneratorError;
+
+/// Installation tokens are refreshed this long before their `expires_at`
+const INSTALLATION_TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+/// Lifetime GitHub documents for installation tokens, used when `expires_at` is missing
+const INSTALLATION_TOKEN_LIFETIME: Duration = Duration::from_secs(60 * 60);
+
+/// How the generator authenticates against the GitHub API
+pub enum Auth {
+    /// Personal access token
+    Token(String),
+    /// GitHub App installation, exchanging a signed JWT for short-lived tokens
+    App(GitHubApp),
+}
+
+pub struct GitHubApp {
+    app_id: String,
+    installation_id: Option<u64>,
+    private_key: PKey<Private>,
+    /// Installation tokens per owner, with the instant they must be refreshed
+    tokens: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl GitHubApp {
+    pub fn from_key_file(
+        app_id: &str,
+        installation_id: Option<u64>,
+        private_key_path: &Path,
+    ) -> Result<Self, GeneratorError> {
+        let pem = std::fs::read(private_key_path).map_err(|e| {
+            GeneratorError::Config(format!(
+                "Failed to read GitHub App private key '{}': {}",
+                private_key_path.display(),
+                e
+            ))
+        })?;
+        let private_key = PKey::private_key_from_pem(&pem).map_err(|e| {
+            GeneratorError::Config(format!("Invalid GitHub App private key: {}", e))
+        })?;
+
+        Ok(Self {
+            app_id: app_id.to_string(),
+            installation_id,
+            private_key,
+            tokens: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// RS256-signed JWT identifying the app, valid for a few minutes
+    fn create_jwt(&self) -> Result<String, GeneratorError> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let header = json!({ "alg": "RS256", "typ": "JWT" });
+        // Backdate iat to tolerate clock drift, GitHub caps exp at 10 minutes
+        let claims = json!({ "iat": now - 60, "exp": now + 9 * 60, "iss": self.app_id });
+
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+
+        let mut signer = Signer::new(MessageDigest::sha256(), &self.private_key)?;
+        signer.update(signing_input.as_bytes())?;
+        let signature = signer.sign_to_vec()?;
+
+        Ok(format!(
+            "{}.{}",
+            signing_input,
+            URL_SAFE_NO_PAD.encode(signature)
+        ))
+    }
+
+    async fn find_installation_id(
+        &self,
+        owner: &str,
+        retry: RetryPolicy,
+        api_headers: &ApiHeaders,
+    ) -> Result<u64, GeneratorError> {
+        if let Some(id) = self.installation_id {
+            return Ok(id);
+        }
+
+        // The installation may be on an organization or on a user account
+        let client = http::client();
+        for kind in ["orgs", "users"] {
+            let response = client
+                .get(format!("{}/{}/{}/installation", API_URL, kind, owner))
+                .headers(api_headers.build(&self.create_jwt()?)?)
+                .send_with_retry(retry)
+                .await
+                .map_err(|e| e.context("Failed to look up GitHub App installation"))?;
+
+            // 404: not installed on this kind of account, any other error is reported as is
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                continue;
+            }
+            if !response.status().is_success() {
+                return Err(GeneratorError::from_response(
+                    response,
+                    "GitHub API error looking up GitHub App installation",
+                )
+                .await);
+            }
+            let installation: serde_json::Value = response.json().await.map_err(|e| {
+                GeneratorError::Http(format!("Failed to parse installation response: {}", e))
+            })?;
+            return installation["id"]
+                .as_u64()
+                .ok_or_else(|| GeneratorError::Http("No id in installation response".to_string()));
+        }
+
+        Err(GeneratorError::Config(format!(
+            "GitHub App {} is not installed on '{}'",
+            self.app_id, owner
+        )))
+    }
+
+    async fn installation_token(
+        &self,
+        owner: &str,
+        retry: RetryPolicy,
+        api_headers: &ApiHeaders,
+    ) -> Result<String, GeneratorError> {
+        if let Some((token, refresh_at)) = self.tokens.lock().unwrap().get(owner) {
+            if Instant::now() < *refresh_at {
+                return Ok(token.clone());
+            }
+            log::debug!("Installation token for '{}' expired", owner);
+        }
+
+        let installation_id = self.find_installation_id(owner, retry, api_headers).await?;
+        log::debug!(
+            "Requesting installation token for installation {}",
+            installation_id
+        );
+
+        let client = http::client();
+        let response = client
+            .post(format!(
+                "{}/app/installations/{}/access_tokens",
+                API_URL, installation_id
+            ))
+            .headers(api_headers.build(&self.create_jwt()?)?)
+            .send_with_retry(retry)
+            .await
+            .map_err(|e| e.context("Failed to request installation token"))?;
+
+        if !response.status().is_success() {
+            return Err(GeneratorError::from_response(
+                response,
+                "GitHub API error requesting installation token",
+            )
+            .await);
+        }
+
+        let token_data: serde_json::Value = response.json().await.map_err(|e| {
+            GeneratorError::Http(format!(
+                "Failed to parse installation token response: {}",
+                e
+            ))
+        })?;
+        let token = token_data["token"]
+            .as_str()
+            .ok_or_else(|| {
+                GeneratorError::Http("No token in installation token response".to_string())
+            })?
+            .to_string();
+
+        let valid_for =
+            token_validity(token_data["expires_at"].as_str(), OffsetDateTime::now_utc());
+        log::debug!(
+            "Installation token for '{}' refreshed in {}s",
+            owner,
+            valid_for.as_secs()
+        );
+        self.tokens.lock().unwrap().insert(
+            owner.to_string(),
+            (token.clone(), Instant::now() + valid_for),
+        );
+        Ok(token)
+    }
+}
+
+/// How long a token expiring at `expires_at` (RFC 3339) can be used from `now`, leaving
+/// the refresh margin
+fn token_validity(expires_at: Option<&str>, now: OffsetDateTime) -> Duration {
+    let lifetime = match expires_at.map(|value| OffsetDateTime::parse(value, &Rfc3339)) {
+        Some(Ok(expires_at)) => Duration::try_from(expires_at - now).unwrap_or_default(),
+        Some(Err(e)) => {
+            log::warn!("Invalid expires_at in installation token response: {}", e);
+            INSTALLATION_TOKEN_LIFETIME
+        }
+        None => INSTALLATION_TOKEN_LIFETIME,
+    };
+    lifetime.saturating_sub(INSTALLATION_TOKEN_REFRESH_MARGIN)
+}
+
+impl Auth {
+    /// A valid token for API calls and pushes on `owner`'s repositories,
+    /// refreshing the installation token when it is about to expire
+    pub async fn token(
+        &self,
+        owner: &str,
+        retry: RetryPolicy,
+        api_headers: &ApiHeaders,
+    ) -> Result<String, GeneratorError> {
+        match self {
+            Auth::Token(token) => Ok(token.clone()),
+            Auth::App(app) => app.installation_token(owner, retry, api_headers).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_validity_follows_expires_at() {
+        let now = OffsetDateTime::parse("2024-05-01T12:00:00Z", &Rfc3339).unwrap();
+        assert_eq!(
+            token_validity(Some("2024-05-01T12:30:00Z"), now),
+            Duration::from_secs(25 * 60)
+        );
+        // Already within the margin or past: refresh on next use
+        assert_eq!(
+            token_validity(Some("2024-05-01T12:02:00Z"), now),
+            Duration::ZERO
+        );
+        assert_eq!(
+            token_validity(Some("2024-05-01T11:00:00Z"), now),
+            Duration::ZERO
+        );
+        assert_eq!(token_validity(None, now), Duration::from_secs(55 * 60));
+    }
+}
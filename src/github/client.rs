@@ -0,0 +1,217 @@
+//! Calls to the GitHub REST API: one HTTP client for the run, the identification and
+//! authorization headers, retries, pagination and the decoding of error responses.
+//!
+//! The calls are plain reqwest requests rather than octocrab ones. Every request has to go
+//! through the retry policy (SSO refusals, POSTs only retried when GitHub did not process
+//! them), the redacted `--trace-http` log and the proxy and certificates shared with git,
+//! which all hang on one `reqwest::Client`. octocrab sends through its own hyper client,
+//! bypassing them, and has no typed API for most of what the generator configures
+//! (rulesets, environments, Pages), which would stay raw JSON.
+
+use reqwest::{Method, Response};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use super::auth::Auth;
+use super::headers::ApiHeaders;
+use super::http;
+use super::retry::{RetryPolicy, SendWithRetry};
+use crate::utils::error::GeneratorError;
+
+pub const API_URL: &str = "https://api.github.com";
+
+/// Items requested per page from list endpoints, the maximum GitHub allows
+const PER_PAGE: u32 = 100;
+
+/// Client for the API calls made on behalf of `owner`, whose token authenticates them
+#[derive(Clone)]
+pub struct GitHubClient {
+    http: reqwest::Client,
+    auth: Arc<Auth>,
+    owner: String,
+    retry: RetryPolicy,
+    headers: ApiHeaders,
+}
+
+impl GitHubClient {
+    pub fn new(auth: Arc<Auth>, owner: &str) -> Self {
+        Self {
+            http: http::client(),
+            auth,
+            owner: owner.to_string(),
+            retry: RetryPolicy::default(),
+            headers: ApiHeaders::default(),
+        }
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_api_headers(mut self, headers: ApiHeaders) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    /// Token of the owner, for the API calls and the git operations on its repositories
+    pub async fn token(&self) -> Result<String, GeneratorError> {
+        self.auth
+            .token(&self.owner, self.retry, &self.headers)
+            .await
+    }
+
+    /// Full URL of an API path such as `/repos/{owner}/{repo}`. Full URLs, e.g. from a `Link`
+    /// header, are kept.
+    pub fn url(path: &str) -> String {
+        if path.starts_with('/') {
+            format!("{}{}", API_URL, path)
+        } else {
+            path.to_string()
+        }
+    }
+
+    /// Send a request with a JSON `body`, returning the response whatever its status.
+    /// Only a request that got no answer fails.
+    pub async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<Response, GeneratorError> {
+        let headers = self.headers.build(&self.token().await?)?;
+        let mut request = self.http.request(method, Self::url(path)).headers(headers);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        request.send_with_retry(self.retry).await
+    }
+
+    /// Send a request, failing with the decoded GitHub error unless it succeeds. `context`
+    /// says what was being done, e.g. `Failed to create branch develop`.
+    pub async fn execute(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&Value>,
+        context: impl Display,
+    ) -> Result<Response, GeneratorError> {
+        let response = self
+            .send(method, path, body)
+            .await
+            .map_err(|e| e.context(&context))?;
+        if !response.status().is_success() {
+            return Err(GeneratorError::from_response(response, context).await);
+        }
+        Ok(response)
+    }
+
+    /// Send a request and decode its JSON response
+    pub async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&Value>,
+        context: impl Display,
+    ) -> Result<T, GeneratorError> {
+        let response = self.execute(method, path, body, &context).await?;
+        decode(response, context).await
+    }
+
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        context: impl Display,
+    ) -> Result<T, GeneratorError> {
+        self.request(Method::GET, path, None, context).await
+    }
+
+    /// Same as [`get`](Self::get), `None` when the resource does not exist
+    pub async fn get_optional<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        context: impl Display,
+    ) -> Result<Option<T>, GeneratorError> {
+        let response = self
+            .send(Method::GET, path, None)
+            .await
+            .map_err(|e| e.context(&context))?;
+        match response.status() {
+            status if status.is_success() => decode(response, context).await.map(Some),
+            reqwest::StatusCode::NOT_FOUND => Ok(None),
+            _ => Err(GeneratorError::from_response(response, context).await),
+        }
+    }
+
+    pub async fn post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &Value,
+        context: impl Display,
+    ) -> Result<T, GeneratorError> {
+        self.request(Method::POST, path, Some(body), context).await
+    }
+
+    /// Every item of a list endpoint, following the `Link` header from page to page. `field`
+    /// names the list in responses wrapping it, such as `{"total_count": 1, "secrets": [...]}`.
+    pub async fn paginate<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        field: Option<&str>,
+        context: impl Display,
+    ) -> Result<Vec<T>, GeneratorError> {
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let mut next = Some(format!("{}{}per_page={}", path, separator, PER_PAGE));
+        let mut items = Vec::new();
+        while let Some(page) = next {
+            let response = self.execute(Method::GET, &page, None, &context).await?;
+            next = next_page(&response);
+            let mut data: Value = decode(response, &context).await?;
+            let list = match field {
+                Some(field) => data[field].take(),
+                None => data,
+            };
+            items.extend(serde_json::from_value::<Vec<T>>(list).map_err(|e| {
+                GeneratorError::Http(format!("{}: unexpected response: {}", context, e))
+            })?);
+        }
+        Ok(items)
+    }
+}
+
+/// JSON body of a successful response. An empty body (`204 No Content`) reads as `null`.
+async fn decode<T: DeserializeOwned>(
+    response: Response,
+    context: impl Display,
+) -> Result<T, GeneratorError> {
+    let text = response
+        .text()
+        .await
+        .map_err(|e| GeneratorError::from(e).context(&context))?;
+    let result = if text.trim().is_empty() {
+        serde_json::from_value(Value::Null)
+    } else {
+        serde_json::from_str(&text)
+    };
+    result.map_err(|e| GeneratorError::Http(format!("{}: unexpected response: {}", context, e)))
+}
+
+/// URL of the next page in a `Link: <...>; rel="next", <...>; rel="last"` header
+fn next_page(response: &Response) -> Option<String> {
+    let link = response.headers().get("link")?.to_str().ok()?;
+    link.split(',').find_map(|part| {
+        let (url, rel) = part.split_once(';')?;
+        rel.contains("rel=\"next\"").then(|| {
+            url.trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string()
+        })
+    })
+}
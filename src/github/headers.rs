@@ -0,0 +1,57 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, AUTHORIZATION, USER_AGENT};
+
+use crate::utils::error::GeneratorError;
+
+pub const DEFAULT_USER_AGENT: &str = "NextNode-Project-Generator/1.0";
+
+/// REST API version the generator is written against, pinned so that a new default
+/// version on GitHub's side cannot silently change responses
+pub const DEFAULT_API_VERSION: &str = "2022-11-28";
+
+const API_VERSION_HEADER: HeaderName = HeaderName::from_static("x-github-api-version");
+
+/// Identification headers sent with every GitHub API call
+#[derive(Debug, Clone)]
+pub struct ApiHeaders {
+    pub user_agent: String,
+    pub api_version: String,
+}
+
+impl Default for ApiHeaders {
+    fn default() -> Self {
+        Self {
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            api_version: DEFAULT_API_VERSION.to_string(),
+        }
+    }
+}
+
+impl ApiHeaders {
+    /// Headers for a call authenticated with `bearer` (a token or an app JWT)
+    pub fn build(&self, bearer: &str) -> Result<HeaderMap, GeneratorError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", bearer)).map_err(|_| {
+                GeneratorError::Config("Failed to create authorization header".to_string())
+            })?,
+        );
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("application/vnd.github.v3+json"),
+        );
+        headers.insert(
+            USER_AGENT,
+            HeaderValue::from_str(&self.user_agent).map_err(|_| {
+                GeneratorError::Config(format!("Invalid user agent '{}'", self.user_agent))
+            })?,
+        );
+        headers.insert(
+            API_VERSION_HEADER,
+            HeaderValue::from_str(&self.api_version).map_err(|_| {
+                GeneratorError::Config(format!("Invalid GitHub API version '{}'", self.api_version))
+            })?,
+        );
+        Ok(headers)
+    }
+}
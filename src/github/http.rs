@@ -0,0 +1,116 @@
+//! The HTTP client shared by every call of the run: connections are pooled across calls, and
+//! timeouts, proxy and trusted certificates are configured in one place. git fetches and
+//! pushes go through the same proxy and trust the same certificates.
+
+use reqwest::{Certificate, Client, Proxy};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::utils::error::GeneratorError;
+
+static CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// Proxy of the configured settings, for git operations
+static GIT_PROXY: OnceLock<Option<String>> = OnceLock::new();
+
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub struct HttpSettings {
+    pub connect_timeout: Duration,
+    /// Limit for a whole request, from connecting to reading the body
+    pub timeout: Duration,
+    /// Proxy for every request. `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY` apply otherwise.
+    pub proxy: Option<String>,
+    /// PEM file of certificate authorities trusted on top of the system ones, e.g. the one of
+    /// a TLS-inspecting corporate proxy
+    pub ca_certificates: Option<PathBuf>,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            timeout: DEFAULT_TIMEOUT,
+            proxy: None,
+            ca_certificates: None,
+        }
+    }
+}
+
+fn build(settings: &HttpSettings) -> Result<Client, GeneratorError> {
+    let mut builder = Client::builder()
+        .connect_timeout(settings.connect_timeout)
+        .timeout(settings.timeout);
+    if let Some(proxy) = &settings.proxy {
+        let proxy = Proxy::all(proxy)
+            .map_err(|e| GeneratorError::Config(format!("Invalid proxy '{}': {}", proxy, e)))?;
+        builder = builder.proxy(proxy);
+    }
+    if let Some(path) = &settings.ca_certificates {
+        let pem = std::fs::read(path).map_err(|e| {
+            GeneratorError::Config(format!(
+                "Failed to read CA certificates {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let certificates = Certificate::from_pem_bundle(&pem).map_err(|e| {
+            GeneratorError::Config(format!("Invalid CA certificates {}: {}", path.display(), e))
+        })?;
+        if certificates.is_empty() {
+            return Err(GeneratorError::Config(format!(
+                "No PEM certificate found in {}",
+                path.display()
+            )));
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    Ok(builder.build()?)
+}
+
+/// Build the shared client and apply the proxy and certificates to git, before the first
+/// request or git operation of the run
+pub fn configure(settings: &HttpSettings) -> Result<(), GeneratorError> {
+    let client = build(settings)?;
+    if CLIENT.set(client).is_err() {
+        log::debug!("HTTP client already in use, its settings are kept");
+        return Ok(());
+    }
+    let _ = GIT_PROXY.set(settings.proxy.clone());
+    if let Some(path) = &settings.ca_certificates {
+        // SAFETY: libgit2 options are global, this runs before any git operation of the run.
+        // The file is added to the system certificate locations, it does not replace them.
+        unsafe { git2::opts::set_ssl_cert_file(path) }.map_err(|e| {
+            GeneratorError::Config(format!(
+                "git cannot use CA certificates {}: {}",
+                path.display(),
+                e.message()
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+/// Proxy options for git fetches and pushes: the configured proxy, else the one of the git
+/// configuration or the environment (`https_proxy`, `http_proxy`)
+pub fn git_proxy_options() -> git2::ProxyOptions<'static> {
+    let mut options = git2::ProxyOptions::new();
+    match GIT_PROXY.get().and_then(Option::as_deref) {
+        Some(proxy) => options.url(proxy),
+        None => options.auto(),
+    };
+    options
+}
+
+/// The shared client, with the default settings unless configured. Clones share its
+/// connection pool.
+pub fn client() -> Client {
+    CLIENT
+        .get_or_init(|| build(&HttpSettings::default()).unwrap_or_default())
+        .clone()
+}
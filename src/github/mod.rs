@@ -1,84 +1,823 @@
+pub mod auth;
+pub mod client;
+pub mod headers;
+pub mod http;
+pub mod monorepo;
 pub mod repo;
+pub mod retry;
+pub mod sealed_box;
+pub mod template_registry;
+pub mod trace;
 
 use crate::config::REPO_URL;
-use std::io::{Error, ErrorKind, Result};
+use crate::generate::{hooks, pages, render};
+use crate::utils::error::GeneratorError;
+use crate::utils::{notify, summary};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
 
-pub fn extract_organization_from_repo_url() -> Result<String> {
+/// Longest repository description GitHub accepts
+pub const MAX_DESCRIPTION_LENGTH: usize = 350;
+
+pub fn extract_organization_from_repo_url() -> Result<String, GeneratorError> {
     // Extract organization from REPO_URL constant
     // REPO_URL = "https://github.com/NextNodeSolutions"
     let org_name = REPO_URL.split('/').next_back().ok_or_else(|| {
-        Error::new(
-            ErrorKind::InvalidData,
-            "Could not extract organization from REPO_URL",
-        )
+        GeneratorError::Config("Could not extract organization from REPO_URL".to_string())
     })?;
 
     Ok(org_name.to_string())
 }
 
+/// Check topics against GitHub's rules: lowercase letters, digits and hyphens, at most 50 characters
+pub fn validate_topics(topics: &[String]) -> Result<(), GeneratorError> {
+    let invalid: Vec<&str> = topics
+        .iter()
+        .map(|t| t.as_str())
+        .filter(|topic| {
+            topic.is_empty()
+                || topic.len() > 50
+                || topic.starts_with('-')
+                || !topic
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+        .collect();
+
+    if !invalid.is_empty() {
+        return Err(GeneratorError::Config(format!(
+            "Invalid topics: {} (use lowercase letters, digits and hyphens, at most 50 characters)",
+            invalid.join(", ")
+        )));
+    }
+    if topics.len() > 20 {
+        return Err(GeneratorError::Config(format!(
+            "GitHub allows at most 20 topics, got {}",
+            topics.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Render a repository metadata template such as `Marketing site for {{client}} ({{year}})`.
+/// `year` is the current year unless set as a variable.
+pub fn render_metadata(
+    field: &str,
+    template: &str,
+    variables: &HashMap<String, String>,
+) -> Result<String, GeneratorError> {
+    let mut variables = variables.clone();
+    variables
+        .entry("year".to_string())
+        .or_insert_with(|| time::OffsetDateTime::now_utc().year().to_string());
+    render::render(template, &variables, &HashMap::new())
+        .map(|rendered| rendered.trim().to_string())
+        .map_err(|e| {
+            GeneratorError::Config(format!(
+                "Invalid repository {} '{}': {}",
+                field, template, e
+            ))
+        })
+}
+
+/// A description GitHub accepts: a single line of at most 350 characters
+pub fn validate_description(description: &str) -> Result<(), GeneratorError> {
+    let length = description.chars().count();
+    if length > MAX_DESCRIPTION_LENGTH {
+        return Err(GeneratorError::Config(format!(
+            "Repository description is {} characters long, GitHub allows at most {}",
+            length, MAX_DESCRIPTION_LENGTH
+        )));
+    }
+    if description.chars().any(char::is_control) {
+        return Err(GeneratorError::Config(
+            "Repository description must be a single line".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// A homepage is an http(s) URL
+pub fn validate_homepage(homepage: &str) -> Result<(), GeneratorError> {
+    let valid = ["https://", "http://"]
+        .iter()
+        .filter_map(|scheme| homepage.strip_prefix(scheme))
+        .any(|rest| !rest.is_empty() && !rest.contains(char::is_whitespace));
+    if !valid {
+        return Err(GeneratorError::Config(format!(
+            "Repository homepage '{}' must be an http(s) URL",
+            homepage
+        )));
+    }
+    Ok(())
+}
+
+/// What to remove when a step fails after the repository has been created
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum CleanupMode {
+    /// Delete the remote GitHub repository
+    Remote,
+    /// Delete the remote GitHub repository and the local generated directory
+    All,
+}
+
+/// What to do when the repository to create already exists, e.g. when re-running
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum OnExists {
+    /// Stop with an error
+    #[default]
+    Fail,
+    /// Leave the existing repository untouched
+    Skip,
+    /// Push the generated code to the existing repository, on a branch if it has history
+    PushToExisting,
+    /// Create the repository as `<name>-2`, `<name>-3`... instead
+    AppendSuffix,
+}
+
+/// Branch receiving the generated code when pushing to a repository that has history
+pub const REGENERATE_BRANCH: &str = "project-generator/regenerate";
+
+/// How the remote run behaves, independently of the repository being created
+pub struct RemoteOptions {
+    pub dry_run: bool,
+    pub cleanup_on_failure: Option<CleanupMode>,
+    pub on_exists: OnExists,
+    /// Wait at most this long for each triggered deployment workflow, `None` to not wait
+    pub workflow_timeout: Option<std::time::Duration>,
+    /// Maximum wait for GitHub to expose pushed branches, workflows or transferred repositories
+    pub ready_timeout: std::time::Duration,
+    pub retry: retry::RetryPolicy,
+    pub api_headers: headers::ApiHeaders,
+    pub git_auth: crate::config::GitAuth,
+    pub ssh_key_path: Option<std::path::PathBuf>,
+    pub commit: crate::config::CommitSettings,
+    /// Isolated HOME of the push hooks, set by `--isolated-home`
+    pub home: Option<std::path::PathBuf>,
+    /// Notify the end of a long push or deployment wait, set by `--notify`
+    pub notify: Option<notify::NotifyMode>,
+}
+
+/// How the runs opening a pull request instead of creating a repository (monorepo packages,
+/// template registry entries) reach GitHub
+pub struct PullRequestOptions {
+    pub dry_run: bool,
+    pub retry: retry::RetryPolicy,
+    pub commit: crate::config::CommitSettings,
+}
+
+/// Repository-level settings applied when creating the GitHub repository
+pub struct RepositorySettings {
+    pub organization: String,
+    pub description: String,
+    pub homepage: Option<String>,
+    pub visibility: crate::config::Visibility,
+    pub topics: Vec<String>,
+    /// Branches created from `main` after the initial push
+    pub branches: Vec<String>,
+    pub security_features: Vec<String>,
+    pub branch_protection: indexmap::IndexMap<String, crate::config::BranchProtection>,
+    pub merge_queue: Option<crate::config::MergeQueue>,
+    /// Org or user the repository is created in before being transferred to `organization`
+    pub staging_owner: Option<String>,
+    /// Team granted admin access once the repository is in `organization`
+    pub admin_team: Option<String>,
+    /// (team slug, role) pairs granted access to the repository
+    pub teams: Vec<(String, String)>,
+    /// Actions secrets as (name, value) pairs
+    pub secrets: Vec<(String, String)>,
+    /// Actions variables as (name, value) pairs, such as the allocated hostnames
+    pub variables: Vec<(String, String)>,
+    pub submodules: Vec<crate::config::SubmoduleDeclaration>,
+    pub environments: Vec<EnvironmentSetup>,
+    /// Workflows dispatched once the branches and environments are set up
+    pub deployments: Vec<crate::config::Deployment>,
+    /// Secrets read by the deployment workflows, checked before they are dispatched
+    pub required_secrets: Vec<RequiredSecret>,
+    /// Template hooks, of which `pre_push` and `post_push` run here
+    pub hooks: crate::config::Hooks,
+}
+
+/// A secret a deployment workflow reads
+pub struct RequiredSecret {
+    pub name: String,
+    /// Environment of the job reading it, whose secrets it may come from
+    pub environment: Option<String>,
+    pub workflow: String,
+}
+
+/// A deployment environment to create, with its resolved secrets
+pub struct EnvironmentSetup {
+    pub name: String,
+    pub settings: crate::config::EnvironmentSettings,
+    /// Environment secrets as (name, value) pairs
+    pub secrets: Vec<(String, String)>,
+}
+
 pub async fn create_github_repository_with_code(
-    token: &str,
+    auth: Arc<auth::Auth>,
     repo_name: &str,
     project_path: &std::path::Path,
-    description: &str,
-    github_tag: Option<&str>,
-    create_develop_branch: bool,
-) -> Result<()> {
-    let github_repo = repo::GitHubRepo::new(token);
-
-    // Create the repository (with topic if provided)
-    let repo_url = github_repo
-        .create_repository(repo_name, description, false, github_tag)
-        .await
-        .map_err(|e| {
-            Error::other(
-                format!("Failed to create GitHub repository: {}", e),
+    settings: &RepositorySettings,
+    options: &RemoteOptions,
+) -> Result<(), GeneratorError> {
+    let github_repo = repo::GitHubRepo::new(auth.clone(), &settings.organization, options.dry_run)
+        .with_retry_policy(options.retry)
+        .with_api_headers(options.api_headers.clone())
+        .with_git_auth(options.git_auth, options.ssh_key_path.clone())
+        .with_commit_settings(options.commit.clone())
+        .with_ready_timeout(options.ready_timeout);
+
+    if github_repo.is_dry_run() {
+        log::info!("🔍 Dry-run mode: no GitHub resources will be created or modified");
+    }
+
+    // Fail before creating anything if CODEOWNERS points at teams that do not exist
+    let codeowner_teams = crate::generate::codeowners::referenced_teams(project_path);
+    if !codeowner_teams.is_empty() {
+        let missing = github_repo
+            .find_missing_teams(&codeowner_teams)
+            .await
+            .map_err(|e| e.context("Failed to validate CODEOWNERS teams"))?;
+        if !missing.is_empty() {
+            return Err(GeneratorError::Config(format!(
+                "CODEOWNERS references unknown teams: {}",
+                missing.join(", ")
+            )));
+        }
+    }
+
+    // A custom domain serves a single Pages site: the deploy would only fail once everything
+    // is created
+    let domains = pages::custom_domains(project_path);
+    if !domains.is_empty() {
+        let in_use = github_repo
+            .find_pages_domains_in_use(&domains, repo_name)
+            .await
+            .map_err(|e| e.context("Failed to check GitHub Pages custom domains"))?;
+        if !in_use.is_empty() {
+            let conflicts: Vec<String> = in_use
+                .iter()
+                .map(|(domain, repository)| {
+                    format!(
+                        "{} is the custom domain of the Pages site of {}",
+                        domain, repository
+                    )
+                })
+                .collect();
+            return Err(GeneratorError::Config(format!(
+                "{}. Remove it from that repository (Settings > Pages > Custom domain) or change the CNAME file of the project, then run again",
+                conflicts.join("; ")
+            )));
+        }
+    }
+
+    // Repositories may be created in a staging owner and transferred once populated
+    let staging_repo = match &settings.staging_owner {
+        Some(owner) => {
+            let owner_is_user = github_repo
+                .is_user_account(owner)
+                .await
+                .map_err(|e| e.context("Failed to look up staging owner"))?;
+            log::info!("Creating repository in staging owner: {}", owner);
+            Some(
+                repo::GitHubRepo::new(auth.clone(), owner, options.dry_run)
+                    .with_retry_policy(options.retry)
+                    .with_api_headers(options.api_headers.clone())
+                    .with_git_auth(options.git_auth, options.ssh_key_path.clone())
+                    .with_commit_settings(options.commit.clone())
+                    .with_ready_timeout(options.ready_timeout)
+                    .with_user_owner(owner_is_user),
             )
-        })?;
+        }
+        None => None,
+    };
+    let creating_repo = staging_repo.as_ref().unwrap_or(&github_repo);
 
-    println!("Created GitHub repository: {}", repo_url);
+    // A previous run may have left the repository behind
+    let mut repo_name = repo_name.to_string();
+    if repository_exists(&github_repo, staging_repo.as_ref(), &repo_name).await? {
+        let full_name = format!("{}/{}", settings.organization, repo_name);
+        match options.on_exists {
+            OnExists::Fail => {
+                return Err(GeneratorError::Config(format!(
+                        "Repository {} already exists, use --on-exists to skip it, push to it or pick another name",
+                        full_name
+                    )));
+            }
+            OnExists::Skip => {
+                log::info!("⏭️  Repository {} already exists, skipping", full_name);
+                return Ok(());
+            }
+            OnExists::PushToExisting => {
+                return push_to_existing_repository(
+                    &github_repo,
+                    &repo_name,
+                    project_path,
+                    settings,
+                    options,
+                )
+                .await;
+            }
+            OnExists::AppendSuffix => {
+                let base_name = repo_name.clone();
+                let mut suffix = 2;
+                loop {
+                    repo_name = format!("{}-{}", base_name, suffix);
+                    if !repository_exists(&github_repo, staging_repo.as_ref(), &repo_name).await? {
+                        break;
+                    }
+                    suffix += 1;
+                }
+                log::info!(
+                    "Repository {} already exists, creating {} instead",
+                    full_name,
+                    repo_name
+                );
+            }
+        }
+    }
+    let repo_name = repo_name.as_str();
 
-    // Initialize git and push the generated code (includes pnpm install results)
-    github_repo
-        .initialize_git_and_push(
-            project_path,
-            &repo_url,
-            "Project Generator",
-            "generator@nextnode.dev",
+    // Create the repository (with topics if provided)
+    let repo_url = creating_repo
+        .create_repository(
+            repo_name,
+            &settings.description,
+            settings.homepage.as_deref(),
+            settings.visibility,
+            &settings.topics,
         )
-        .map_err(|e| {
-            Error::other(
-                format!("Failed to initialize and push to GitHub: {}", e),
-            )
-        })?;
+        .await
+        .map_err(|e| e.context("Failed to create GitHub repository"))?;
+
+    log::info!("Created GitHub repository: {}", repo_url);
+    summary::record_repository(&repo_url);
+
+    // From here on a failure would leave an orphan repository behind
+    if let Err(e) = populate_repository(
+        creating_repo,
+        repo_name,
+        project_path,
+        settings,
+        options,
+        &repo_url,
+    )
+    .await
+    {
+        if let Some(mode) = options.cleanup_on_failure {
+            rollback_repository(creating_repo, repo_name, project_path, mode).await;
+        }
+        return Err(e);
+    }
+    // The repository is kept when a post-push hook fails
+    hooks::run(
+        "post_push",
+        &settings.hooks.post_push,
+        project_path,
+        github_repo.is_dry_run(),
+        options.home.as_deref(),
+    )?;
+
+    if let Some(staging_repo) = &staging_repo {
+        log::info!("🚚 Transferring repository to {}...", settings.organization);
+        staging_repo
+            .transfer_repository(repo_name, &settings.organization)
+            .await
+            .map_err(|e| {
+                e.context(format!(
+                    "Failed to transfer repository, it is still available at {}",
+                    repo_url
+                ))
+            })?;
+        github_repo
+            .wait_for_repository(repo_name, options.ready_timeout)
+            .await
+            .map_err(|e| e.context("Repository transfer did not complete"))?;
+        let transferred_url = format!("https://github.com/{}/{}", settings.organization, repo_name);
+        log::info!("✅ Repository transferred to {}", transferred_url);
+        summary::record_repository(&transferred_url);
+    }
 
-    println!("Successfully pushed generated code to GitHub repository!");
+    configure_repository(&github_repo, repo_name, settings, options).await
+}
+
+/// Secrets, access, branches, environments and deployments of a populated repository
+async fn configure_repository(
+    github_repo: &repo::GitHubRepo,
+    repo_name: &str,
+    settings: &RepositorySettings,
+    options: &RemoteOptions,
+) -> Result<(), GeneratorError> {
+    if !settings.secrets.is_empty() {
+        log::info!("🔐 Setting repository secrets...");
+        match github_repo
+            .set_repository_secrets(repo_name, &settings.secrets)
+            .await
+        {
+            Ok(_) => log::info!("✅ Repository secrets set"),
+            Err(e) => log::warn!("Failed to set repository secrets: {}", e),
+        }
+    }
+
+    if !settings.variables.is_empty() {
+        log::info!("📋 Setting repository variables...");
+        match github_repo
+            .set_repository_variables(repo_name, &settings.variables)
+            .await
+        {
+            Ok(_) => log::info!("✅ Repository variables set"),
+            Err(e) => log::warn!("Failed to set repository variables: {}", e),
+        }
+    }
+
+    if let Some(team) = &settings.admin_team {
+        match github_repo
+            .grant_team_permission(repo_name, team, "admin")
+            .await
+        {
+            Ok(_) => log::info!("✅ Team '{}' assigned as repository admin", team),
+            Err(e) => log::warn!("Failed to assign admin team '{}': {}", team, e),
+        }
+    }
+
+    for (team, role) in &settings.teams {
+        match github_repo
+            .grant_team_permission(repo_name, team, role)
+            .await
+        {
+            Ok(_) => log::info!("✅ Team '{}' granted {} access", team, role),
+            Err(e) => log::warn!("Failed to grant {} access to team '{}': {}", role, team, e),
+        }
+    }
 
     // Set up repository branches
-    println!("🔧 Setting up repository branches...");
+    log::info!("🔧 Setting up repository branches...");
     match github_repo
-        .setup_repository_branches(repo_name, create_develop_branch)
+        .setup_repository_branches(repo_name, &settings.branches, &settings.branch_protection)
         .await
     {
-        Ok(_) => println!("✅ Repository branch setup completed successfully!"),
-        Err(e) => eprintln!("⚠️  Warning: Failed to set up repository branches: {}", e),
+        Ok(_) => log::info!("✅ Repository branch setup completed successfully!"),
+        Err(e) => log::warn!("Failed to set up repository branches: {}", e),
     }
 
-    // Trigger deployment workflows if this looks like an Astro project with CI/CD
-    if project_path
-        .join(".github/workflows/deploy-dev.yml")
-        .exists()
-        && project_path
-            .join(".github/workflows/deploy-prod.yml")
-            .exists()
+    if let Some(queue) = &settings.merge_queue {
+        log::info!("🚦 Requiring the merge queue on main...");
+        match github_repo
+            .enable_merge_queue(repo_name, "main", queue)
+            .await
+        {
+            Ok(_) => log::info!("✅ Merge queue enabled on main"),
+            Err(e) => log::warn!("Failed to enable the merge queue on main: {}", e),
+        }
+    }
+
+    for environment in &settings.environments {
+        log::info!("🌍 Creating environment {}...", environment.name);
+        let result = match github_repo
+            .create_environment(repo_name, &environment.name, &environment.settings)
+            .await
+        {
+            Ok(_) if environment.secrets.is_empty() => Ok(()),
+            Ok(_) => {
+                github_repo
+                    .set_environment_secrets(repo_name, &environment.name, &environment.secrets)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(_) => log::info!("✅ Environment {} configured", environment.name),
+            Err(e) => log::warn!(
+                "Failed to configure environment {}: {}",
+                environment.name,
+                e
+            ),
+        }
+    }
+
+    // A deployment missing a secret would fail on its first run
+    let deployments = preflight_secrets(github_repo, repo_name, settings).await?;
+    if deployments.len() < settings.deployments.len() && options.workflow_timeout.is_some() {
+        return Err(GeneratorError::Config(
+            "Deployment failed: some deployment workflows need secrets that are not set"
+                .to_string(),
+        ));
+    }
+
+    if !deployments.is_empty() {
+        log::info!("🔄 Triggering the template's deployment workflows...");
+
+        let started = Instant::now();
+        let deployed = github_repo
+            .trigger_deployments(repo_name, &deployments, options.workflow_timeout)
+            .await;
+        if options.workflow_timeout.is_some() {
+            notify::finished(options.notify, "Deployment", started, deployed.is_ok());
+        }
+        match deployed {
+            Ok(_) => log::info!("✅ Deployment workflows triggered successfully!"),
+            // Callers waiting on workflows rely on the exit code to detect a broken deploy
+            Err(e) if options.workflow_timeout.is_some() => {
+                return Err(e.context("Deployment failed"));
+            }
+            Err(e) => log::warn!("Failed to trigger deployments: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that the secrets read by the deployment workflows exist in the repository, the
+/// organization or the job's environment. Missing ones are read from the environment variable
+/// of the same name (prefixed with the environment name for environment secrets) or prompted
+/// for, then set. Returns the deployments whose secrets are all available.
+async fn preflight_secrets(
+    github_repo: &repo::GitHubRepo,
+    repo_name: &str,
+    settings: &RepositorySettings,
+) -> Result<Vec<crate::config::Deployment>, GeneratorError> {
+    if settings.required_secrets.is_empty() || repo::auto_deploy_disabled() {
+        return Ok(settings.deployments.clone());
+    }
+
+    log::info!("🔐 Checking the secrets required by the deployment workflows...");
+    let mut required: Vec<(String, Option<String>)> = Vec::new();
+    for secret in &settings.required_secrets {
+        let key = (secret.name.clone(), secret.environment.clone());
+        if !required.contains(&key) {
+            required.push(key);
+        }
+    }
+    let missing = github_repo
+        .find_missing_secrets(repo_name, &required)
+        .await
+        .map_err(|e| e.context("Failed to check workflow secrets"))?;
+
+    let mut unavailable = Vec::new();
+    for (name, environment) in missing {
+        let workflows: Vec<&str> = settings
+            .required_secrets
+            .iter()
+            .filter(|secret| secret.name == name && secret.environment == environment)
+            .map(|secret| secret.workflow.as_str())
+            .collect();
+        let (env_name, description) = match &environment {
+            Some(environment) => (
+                format!("{}_{}", environment.to_uppercase().replace('-', "_"), name),
+                format!("{}, needed by {}", environment, workflows.join(", ")),
+            ),
+            None => (name.clone(), format!("needed by {}", workflows.join(", "))),
+        };
+        let value = std::env::var(&env_name)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .or_else(|| crate::cli::functions::prompt_for_secret(&name, Some(&description)));
+        let Some(value) = value else {
+            log::warn!("Secret {} is not set ({})", env_name, description);
+            unavailable.push((name, environment));
+            continue;
+        };
+
+        let secret = [(name.clone(), value)];
+        let result = match &environment {
+            Some(environment) => {
+                github_repo
+                    .set_environment_secrets(repo_name, environment, &secret)
+                    .await
+            }
+            None => github_repo.set_repository_secrets(repo_name, &secret).await,
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to set secret {}: {}", env_name, e);
+            unavailable.push((name, environment));
+        }
+    }
+
+    let mut deployments = Vec::new();
+    for deployment in &settings.deployments {
+        let needs: Vec<&str> = settings
+            .required_secrets
+            .iter()
+            .filter(|secret| {
+                secret.workflow == deployment.workflow
+                    && unavailable.contains(&(secret.name.clone(), secret.environment.clone()))
+            })
+            .map(|secret| secret.name.as_str())
+            .collect();
+        if needs.is_empty() {
+            deployments.push(deployment.clone());
+        } else {
+            log::warn!(
+                "Not triggering {}, it needs secrets that are not set: {}",
+                deployment.workflow,
+                needs.join(", ")
+            );
+        }
+    }
+    if unavailable.is_empty() {
+        log::info!("✅ Workflow secrets are available");
+    }
+    Ok(deployments)
+}
+
+/// Whether the repository exists in the organization or, when used, the staging owner
+async fn repository_exists(
+    github_repo: &repo::GitHubRepo,
+    staging_repo: Option<&repo::GitHubRepo>,
+    repo_name: &str,
+) -> Result<bool, GeneratorError> {
+    for owner in std::iter::once(github_repo).chain(staging_repo) {
+        let exists = owner
+            .repository_exists(repo_name)
+            .await
+            .map_err(|e| e.context("Failed to check for an existing repository"))?;
+        if exists {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Push to a repository from a previous run: its history is kept and the generated code
+/// lands on `REGENERATE_BRANCH`, unless the run stopped before the initial push
+async fn push_to_existing_repository(
+    github_repo: &repo::GitHubRepo,
+    repo_name: &str,
+    project_path: &std::path::Path,
+    settings: &RepositorySettings,
+    options: &RemoteOptions,
+) -> Result<(), GeneratorError> {
+    if !github_repo
+        .repository_exists(repo_name)
+        .await
+        .map_err(|e| e.context("Failed to look up repository"))?
     {
-        println!("🔄 Detected CI/CD workflows, triggering deployments...");
+        return Err(GeneratorError::Config(format!(
+            "Repository {} only exists in the staging owner, transfer or delete it first",
+            repo_name
+        )));
+    }
+
+    let repo_url = format!("https://github.com/{}/{}", settings.organization, repo_name);
+    log::info!("📥 Repository {} already exists, pushing to it", repo_url);
+    summary::record_repository(&repo_url);
+    let dry_run = github_repo.is_dry_run();
+    hooks::run(
+        "pre_push",
+        &settings.hooks.pre_push,
+        project_path,
+        dry_run,
+        options.home.as_deref(),
+    )?;
+
+    let started = Instant::now();
+    let pushed = github_repo
+        .initialize_git_and_push(
+            project_path,
+            &repo_url,
+            &settings.submodules,
+            Some(REGENERATE_BRANCH),
+        )
+        .await;
+    notify::finished(options.notify, "Push", started, pushed.is_ok());
+    let branch = pushed.map_err(|e| e.context("Failed to push to the existing repository"))?;
+    summary::record_branch(&branch);
+    hooks::run(
+        "post_push",
+        &settings.hooks.post_push,
+        project_path,
+        dry_run,
+        options.home.as_deref(),
+    )?;
 
-        match github_repo.trigger_deployments(repo_name).await {
-            Ok(_) => println!("✅ Deployment workflows triggered successfully!"),
-            Err(e) => eprintln!("⚠️  Warning: Failed to trigger deployments: {}", e),
+    if branch != "main" {
+        log::info!(
+            "✅ Pushed the generated code to {}, review it in a pull request: {}/compare/main...{}",
+            branch,
+            repo_url,
+            branch
+        );
+        return Ok(());
+    }
+
+    // The repository was empty: finish the setup the previous run never reached
+    log::info!("Successfully pushed generated code to GitHub repository!");
+    if !settings.security_features.is_empty() {
+        if let Err(e) = github_repo
+            .enable_security_features(repo_name, &settings.security_features)
+            .await
+        {
+            log::warn!("Failed to enable security features: {}", e);
         }
     }
+    configure_repository(github_repo, repo_name, settings, options).await
+}
+
+async fn populate_repository(
+    github_repo: &repo::GitHubRepo,
+    repo_name: &str,
+    project_path: &std::path::Path,
+    settings: &RepositorySettings,
+    options: &RemoteOptions,
+    repo_url: &str,
+) -> Result<(), GeneratorError> {
+    if !settings.security_features.is_empty() {
+        log::info!("🔒 Enabling security features...");
+        github_repo
+            .enable_security_features(repo_name, &settings.security_features)
+            .await
+            .map_err(|e| e.context("Failed to enable security features"))?;
+    }
+
+    hooks::run(
+        "pre_push",
+        &settings.hooks.pre_push,
+        project_path,
+        github_repo.is_dry_run(),
+        options.home.as_deref(),
+    )?;
+
+    // Initialize git and push the generated code (includes pnpm install results)
+    let started = Instant::now();
+    let pushed = github_repo
+        .initialize_git_and_push(project_path, repo_url, &settings.submodules, None)
+        .await;
+    notify::finished(options.notify, "Push", started, pushed.is_ok());
+    let branch = pushed.map_err(|e| e.context("Failed to initialize and push to GitHub"))?;
+    summary::record_branch(&branch);
 
+    log::info!("Successfully pushed generated code to GitHub repository!");
     Ok(())
 }
+
+async fn rollback_repository(
+    github_repo: &repo::GitHubRepo,
+    repo_name: &str,
+    project_path: &std::path::Path,
+    mode: CleanupMode,
+) {
+    log::info!("🧹 Cleaning up after failure...");
+
+    match github_repo.delete_repository(repo_name).await {
+        Ok(_) => log::info!("✅ Deleted GitHub repository '{}'", repo_name),
+        Err(e) => log::warn!(
+            "Failed to delete GitHub repository '{}', remove it manually: {}",
+            repo_name,
+            e
+        ),
+    }
+
+    if mode == CleanupMode::All && project_path.exists() {
+        match std::fs::remove_dir_all(project_path) {
+            Ok(_) => log::info!("✅ Deleted local directory '{}'", project_path.display()),
+            Err(e) => log::warn!(
+                "Failed to delete local directory '{}': {}",
+                project_path.display(),
+                e
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn renders_metadata_with_the_current_year() {
+        let year = time::OffsetDateTime::now_utc().year().to_string();
+        let rendered = render_metadata(
+            "description",
+            "Site for {{client}} ({{year}})",
+            &variables(&[("client", "Acme")]),
+        )
+        .unwrap();
+        assert_eq!(rendered, format!("Site for Acme ({})", year));
+
+        let rendered =
+            render_metadata("description", "{{year}}", &variables(&[("year", "1999")])).unwrap();
+        assert_eq!(rendered, "1999");
+        assert!(render_metadata("description", "{{missing}}", &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn validates_topics_description_and_homepage() {
+        assert!(validate_topics(&["astro".to_string(), "web-app-2".to_string()]).is_ok());
+        let error = validate_topics(&["Astro".to_string()]).unwrap_err();
+        assert!(matches!(error, GeneratorError::Config(_)));
+        assert!(validate_topics(&["-astro".to_string()]).is_err());
+        assert!(validate_description(&"a".repeat(MAX_DESCRIPTION_LENGTH)).is_ok());
+        assert!(validate_description(&"a".repeat(MAX_DESCRIPTION_LENGTH + 1)).is_err());
+        assert!(validate_homepage("https://acme.dev").is_ok());
+        assert!(validate_homepage("acme.dev").is_err());
+    }
+}
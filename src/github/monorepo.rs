@@ -0,0 +1,175 @@
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::auth::Auth;
+use super::repo::GitHubRepo;
+use super::PullRequestOptions;
+use crate::generate::project_generator::GenerateOptions;
+use crate::generate::{self, workspace};
+use crate::utils::error::GeneratorError;
+
+/// Generate a package into a pnpm monorepo on GitHub and open a pull request adding it,
+/// instead of creating a repository. `target` is `owner/repo`, cloned for the run, or the
+/// path of a local checkout of it. The package goes under `apps/` for `apps` templates and
+/// `packages/` otherwise.
+pub async fn add_package(
+    auth: Arc<Auth>,
+    target: &str,
+    category: &str,
+    template_path: &Path,
+    project_name: &str,
+    options: &PullRequestOptions,
+    generate_options: &GenerateOptions,
+) -> Result<()> {
+    let local = Path::new(target);
+    let (checkout, owner, name, cloned) = if local.is_dir() {
+        let root = workspace::enclosing_root(local).ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "'{}' is not inside a pnpm workspace (no {} found)",
+                    local.display(),
+                    workspace::WORKSPACE_FILE
+                ),
+            )
+        })?;
+        let (owner, name) = origin_repository(&root)?;
+        ensure_clean(&root)?;
+        (root, owner, name, false)
+    } else {
+        let (owner, name) = target.split_once('/').ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Invalid monorepo '{}', expected owner/repo or a local checkout",
+                    target
+                ),
+            )
+        })?;
+        let dir = std::env::temp_dir().join(format!(
+            "project-monorepo-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis()
+        ));
+        (dir, owner.to_string(), name.to_string(), true)
+    };
+
+    let dry_run = options.dry_run;
+    let github_repo = GitHubRepo::new(auth, &owner, dry_run)
+        .with_retry_policy(options.retry)
+        .with_commit_settings(options.commit.clone());
+    let result = async {
+        let base = if cloned {
+            github_repo
+                .clone_repository(&name, &checkout)
+                .await?
+        } else {
+            current_branch(&checkout)?
+        };
+
+        let parent = packages_dir(&checkout, category);
+        let branch = format!("add-{}", project_name);
+        if dry_run && !cloned {
+            log::info!(
+                "🔍 [dry-run] Would generate {} into '{}'",
+                project_name,
+                parent.display()
+            );
+        } else {
+            fs::create_dir_all(&parent)?;
+            generate::handle_into_mode(template_path, project_name, &parent, generate_options)?;
+        }
+
+        let title = format!("Add {}", project_name);
+        github_repo
+            .push_new_branch(&checkout, &name, &branch, &title)
+            .await
+            .map_err(|e| e.context(format!("Failed to push {}", branch)))?;
+        let body = format!(
+            "Adds the `{}` package, generated from the `{}` template and registered in the workspace.",
+            project_name,
+            template_path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        );
+        let url = github_repo
+            .create_pull_request(&name, &branch, &base, &title, &body)
+            .await?;
+        if !dry_run {
+            log::info!("✅ Opened pull request adding {}: {}", project_name, url);
+        }
+        Ok(())
+    }
+    .await;
+
+    if cloned && checkout.exists() {
+        if let Err(e) = fs::remove_dir_all(&checkout) {
+            log::warn!("Failed to remove '{}': {}", checkout.display(), e);
+        }
+    }
+    result
+}
+
+/// `owner` and `repo` of the checkout's `origin` GitHub remote
+fn origin_repository(root: &Path) -> Result<(String, String)> {
+    let repo = git2::Repository::open(root).map_err(|e| {
+        Error::other(format!(
+            "'{}' is not a git repository: {}",
+            root.display(),
+            e
+        ))
+    })?;
+    let url = repo
+        .find_remote("origin")
+        .ok()
+        .and_then(|remote| remote.url().map(str::to_string))
+        .ok_or_else(|| Error::other(format!("'{}' has no origin remote", root.display())))?;
+    let path = url
+        .strip_prefix("https://github.com/")
+        .or_else(|| url.strip_prefix("git@github.com:"))
+        .or_else(|| url.strip_prefix("ssh://git@github.com/"))
+        .ok_or_else(|| Error::other(format!("origin '{}' is not a GitHub repository", url)))?;
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+    path.split_once('/')
+        .map(|(owner, name)| (owner.to_string(), name.to_string()))
+        .ok_or_else(|| Error::other(format!("origin '{}' is not a GitHub repository", url)))
+}
+
+/// The pull request commits every change of the checkout, so it must have none beforehand
+fn ensure_clean(root: &Path) -> Result<()> {
+    let repo = git2::Repository::open(root).map_err(GeneratorError::from)?;
+    let mut options = git2::StatusOptions::new();
+    options.include_untracked(true).include_ignored(false);
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .map_err(GeneratorError::from)?;
+    if !statuses.is_empty() {
+        return Err(Error::other(format!(
+            "'{}' has uncommitted changes, commit or stash them first",
+            root.display()
+        )));
+    }
+    Ok(())
+}
+
+fn current_branch(root: &Path) -> Result<String> {
+    let repo = git2::Repository::open(root).map_err(GeneratorError::from)?;
+    let head = repo.head().map_err(GeneratorError::from)?;
+    head.shorthand()
+        .map(str::to_string)
+        .ok_or_else(|| Error::other(format!("'{}' has no current branch", root.display())))
+}
+
+/// `apps/` for applications, `packages/` for everything else
+fn packages_dir(checkout: &Path, category: &str) -> PathBuf {
+    match category {
+        "apps" => checkout.join("apps"),
+        _ => checkout.join("packages"),
+    }
+}
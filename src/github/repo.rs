@@ -1,129 +1,277 @@
-use crate::config::REPO_URL;
-use git2::{Cred, RemoteCallbacks, Repository, Signature};
-use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, USER_AGENT};
-use serde_json::json;
-use std::path::Path;
+use crate::config::{
+    BranchProtection, CommitSettings, Deployment, EnvironmentSettings, GitAuth, MergeQueue,
+    SubmoduleDeclaration, Visibility,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use git2::{Cred, CredentialType, RemoteCallbacks, Repository, Signature};
+use indexmap::IndexMap;
+use reqwest::Method;
+use serde_json::{json, Value};
+
+use super::auth::Auth;
+use super::client::GitHubClient;
+use super::headers::ApiHeaders;
+use super::http;
+use super::retry::RetryPolicy;
+use super::sealed_box;
+use crate::utils::error::GeneratorError;
+use crate::utils::{commit, logging, summary};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Name of the ruleset requiring the merge queue, found again on regeneration
+const MERGE_QUEUE_RULESET: &str = "Merge queue";
+
+/// How long a dispatched run may take to be listed before its link is left out
+const RUN_LOOKUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where a deployment can be followed, for the summary of the run
+#[derive(Default)]
+struct DeploymentLinks {
+    run_url: Option<String>,
+    /// URL the deployment environment reported, once the run succeeded
+    site_url: Option<String>,
+}
+
+/// Whether the `no_deploy` variable turns off the deployment workflows
+pub fn auto_deploy_disabled() -> bool {
+    crate::utils::context::get_variable("no_deploy").is_some_and(|no_deploy| {
+        matches!(
+            no_deploy.to_lowercase().as_str(),
+            "true" | "1" | "yes" | "on"
+        )
+    })
+}
 
 pub struct GitHubRepo {
-    token: String,
+    client: GitHubClient,
+    organization: String,
+    owner_is_user: bool,
+    dry_run: bool,
+    git_auth: GitAuth,
+    ssh_key_path: Option<PathBuf>,
+    /// Message, author and signature of the initial commit
+    commit: CommitSettings,
+    /// Maximum wait for GitHub to make a pushed branch or workflow available
+    ready_timeout: Duration,
 }
 
 impl GitHubRepo {
-    pub fn new(token: &str) -> Self {
+    pub fn new(auth: Arc<Auth>, organization: &str, dry_run: bool) -> Self {
         Self {
-            token: token.to_string(),
+            client: GitHubClient::new(auth, organization),
+            organization: organization.to_string(),
+            owner_is_user: false,
+            dry_run,
+            git_auth: GitAuth::default(),
+            ssh_key_path: None,
+            commit: CommitSettings::default(),
+            ready_timeout: Duration::from_secs(60),
         }
     }
 
+    pub fn with_git_auth(mut self, git_auth: GitAuth, ssh_key_path: Option<PathBuf>) -> Self {
+        self.git_auth = git_auth;
+        self.ssh_key_path = ssh_key_path;
+        self
+    }
+
+    pub fn with_commit_settings(mut self, commit: CommitSettings) -> Self {
+        self.commit = commit;
+        self
+    }
+
+    /// Web URL of a repository of the owner
+    fn repository_url(&self, name: &str) -> String {
+        format!("https://github.com/{}/{}", self.organization, name)
+    }
+
+    /// Remote URL used for the push: SSH remotes are derived from the repository web URL
+    fn push_url(&self, repo_url: &str) -> String {
+        match self.git_auth {
+            GitAuth::Token => repo_url.to_string(),
+            GitAuth::SshAgent | GitAuth::SshKey => format!(
+                "{}.git",
+                repo_url.replacen("https://github.com/", "git@github.com:", 1)
+            ),
+        }
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.client = self.client.with_retry_policy(retry);
+        self
+    }
+
+    pub fn with_api_headers(mut self, headers: ApiHeaders) -> Self {
+        self.client = self.client.with_api_headers(headers);
+        self
+    }
+
+    pub fn with_ready_timeout(mut self, ready_timeout: Duration) -> Self {
+        self.ready_timeout = ready_timeout;
+        self
+    }
+
+    /// Create repositories under the authenticated user instead of an organization
+    pub fn with_user_owner(mut self, owner_is_user: bool) -> Self {
+        self.owner_is_user = owner_is_user;
+        self
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    fn print_dry_run(&self, action: &str) {
+        log::info!("🔍 [dry-run] Would {}", action);
+        crate::plan::record_remote(action);
+    }
+
+    /// API path of a repository of the owner, e.g. `/repos/{owner}/{name}/topics` with
+    /// `rest` set to `/topics`
+    fn repo_path(&self, name: &str, rest: &str) -> String {
+        format!("/repos/{}/{}{}", self.organization, name, rest)
+    }
+
     pub async fn create_repository(
         &self,
         name: &str,
         description: &str,
-        private: bool,
-        topic: Option<&str>,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Extract organization from REPO_URL constant
-        // REPO_URL = "https://github.com/NextNodeSolutions"
-        let org_name = REPO_URL
-            .split('/')
-            .next_back()
-            .ok_or("Could not extract organization from REPO_URL")?;
-
-        // Build headers
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", self.token))
-                .map_err(|_| "Failed to create authorization header")?,
-        );
-        headers.insert(
-            ACCEPT,
-            HeaderValue::from_str("application/vnd.github.v3+json")
-                .map_err(|_| "Failed to create accept header")?,
-        );
-        headers.insert(
-            USER_AGENT,
-            HeaderValue::from_str("NextNode-Project-Generator/1.0")
-                .map_err(|_| "Failed to create user-agent header")?,
-        );
+        homepage: Option<&str>,
+        visibility: Visibility,
+        topics: &[String],
+    ) -> Result<String, GeneratorError> {
+        let _stage = logging::stage("create_repository");
+        let org_name = self.organization.as_str();
+
+        if self.owner_is_user && visibility == Visibility::Internal {
+            return Err(GeneratorError::Config(format!(
+                "Internal visibility is only available to organizations, '{}' is a user account",
+                org_name
+            )));
+        }
 
         // Build request body
-        let body = json!({
+        let mut body = json!({
             "name": name,
             "description": description,
-            "private": private,
+            "private": visibility != Visibility::Public,
+            "visibility": visibility.as_str(),
             "auto_init": false
         });
+        if let Some(homepage) = homepage {
+            body["homepage"] = json!(homepage);
+        }
 
-        // Make GitHub API call to create repository
-        let client = reqwest::Client::new();
-        let response = client
-            .post(format!("https://api.github.com/orgs/{}/repos", org_name))
-            .headers(headers.clone())
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to send request to GitHub API: {}", e))?;
+        let create_path = if self.owner_is_user {
+            "/user/repos".to_string()
+        } else {
+            format!("/orgs/{}/repos", org_name)
+        };
+        let topics_path = self.repo_path(name, "/topics");
 
-        if !response.status().is_success() {
-            let error = response
-                .text()
-                .await
-                .map_err(|e| format!("Failed to read error response: {}", e))?;
-            return Err(format!("GitHub API error: {}", error).into());
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "POST {} with body {}",
+                GitHubClient::url(&create_path),
+                body
+            ));
+            if !topics.is_empty() {
+                self.print_dry_run(&format!(
+                    "PUT {} with body {}",
+                    GitHubClient::url(&topics_path),
+                    json!({ "names": topics })
+                ));
+            }
+            return Ok(format!("https://github.com/{}/{}", org_name, name));
         }
 
-        let repo_data: serde_json::Value = response
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse response: {}", e))?;
+        let repo_data: Value = self
+            .client
+            .post(&create_path, &body, "GitHub API error")
+            .await?;
 
         let repo_url = repo_data["html_url"]
             .as_str()
-            .ok_or("No html_url in response")?
+            .ok_or_else(|| GeneratorError::Http("No html_url in response".to_string()))?
             .to_string();
 
-        // Add topic if provided
-        if let Some(topic_name) = topic {
-            println!("Adding topic '{}' to repository...", topic_name);
+        // Add topics if provided
+        if !topics.is_empty() {
+            let topic_list = topics.join(", ");
+            log::info!("Adding topics '{}' to repository...", topic_list);
 
             let topics_body = json!({
-                "names": [topic_name]
+                "names": topics
             });
 
-            let topics_response = client
-                .put(format!(
-                    "https://api.github.com/repos/{}/{}/topics",
-                    org_name, name
-                ))
-                .headers(headers)
-                .json(&topics_body)
-                .send()
+            match self
+                .client
+                .execute(
+                    Method::PUT,
+                    &topics_path,
+                    Some(&topics_body),
+                    "Failed to add topics",
+                )
                 .await
-                .map_err(|e| format!("Failed to add topic: {}", e))?;
-
-            if !topics_response.status().is_success() {
-                let error = topics_response
-                    .text()
-                    .await
-                    .map_err(|e| format!("Failed to read topics error response: {}", e))?;
+            {
                 // Don't fail the entire operation for topic addition failure, just warn
-                eprintln!("Warning: Failed to add topic '{}': {}", topic_name, error);
-            } else {
-                println!("Successfully added topic '{}' to repository", topic_name);
+                Err(e) => log::warn!("Failed to add topics '{}': {}", topic_list, e),
+                Ok(_) => log::info!("Successfully added topics '{}' to repository", topic_list),
             }
         }
 
         Ok(repo_url)
     }
 
-    pub fn initialize_git_and_push(
+    /// Commit the generated project and push it to `main`. With `update_branch`, a remote
+    /// that already has a `main` gets the commit on top of it, pushed to that branch instead.
+    /// Returns the branch that was pushed.
+    pub async fn initialize_git_and_push(
         &self,
         local_path: &Path,
         repo_url: &str,
-        author_name: &str,
-        author_email: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        submodules: &[SubmoduleDeclaration],
+        update_branch: Option<&str>,
+    ) -> Result<String, GeneratorError> {
+        let _stage = logging::stage("push");
+        let push_url = self.push_url(repo_url);
+
+        if self.dry_run {
+            self.print_dry_run(&format!("git init in '{}'", local_path.display()));
+            for submodule in submodules {
+                self.print_dry_run(&format!(
+                    "git submodule add {} {} pinned to {}",
+                    submodule.url, submodule.path, submodule.commit
+                ));
+            }
+            self.print_dry_run("git add all generated files");
+            self.print_dry_run(&format!(
+                "git commit -m {:?} as {} <{}>{}",
+                self.commit.message,
+                self.commit.author_name,
+                self.commit.author_email,
+                commit::signing_note(self.commit.signing.as_ref())
+            ));
+            self.print_dry_run(&format!("git remote add origin {}", push_url));
+            if let Some(branch) = update_branch {
+                self.print_dry_run(&format!(
+                    "git fetch origin main, commit on top of it and push to {} if it exists",
+                    branch
+                ));
+            }
+            self.print_dry_run(&format!(
+                "git push -u origin main using {:?} authentication",
+                self.git_auth
+            ));
+            return Ok("main".to_string());
+        }
+
+        // Resolve the token up front, an app installation token may need a refresh
+        let token = self.client.token().await?;
+
         // Remove existing .git directory if it exists
         let git_dir = local_path.join(".git");
         if git_dir.exists() {
@@ -132,6 +280,7 @@ impl GitHubRepo {
 
         // 1. git init
         let repo = Repository::init(local_path)?;
+        self.add_submodules(&repo, submodules, &token)?;
 
         // 2. git branch -M main (la branche main est créée par défaut avec git2)
         // Note: git2 crée automatiquement la branche main lors du premier commit
@@ -146,31 +295,355 @@ impl GitHubRepo {
         // 5. git commit -m "first commit"
         let tree_id = index.write_tree()?;
         let tree = repo.find_tree(tree_id)?;
-        let signature = Signature::now(author_name, author_email)?;
 
-        repo.commit(
-            Some("HEAD"),
+        // 6. git remote add origin <url>
+        let mut remote = repo.remote("origin", &push_url)?;
+
+        // An existing repository keeps its history, the generated tree is committed on top
+        let mut parent = None;
+        if update_branch.is_some() {
+            let mut fetch_options = git2::FetchOptions::new();
+            fetch_options.remote_callbacks(self.remote_callbacks(&token));
+            fetch_options.proxy_options(http::git_proxy_options());
+            remote.fetch(
+                &["+refs/heads/*:refs/remotes/origin/*"],
+                Some(&mut fetch_options),
+                None,
+            )?;
+            parent = repo
+                .find_reference("refs/remotes/origin/main")
+                .and_then(|reference| reference.peel_to_commit())
+                .ok();
+        }
+        // The update branch is overwritten by each re-run, main is never forced
+        let (branch, refspec) = match (&parent, update_branch) {
+            (Some(_), Some(branch)) => (branch, format!("+HEAD:refs/heads/{}", branch)),
+            _ => ("main", "HEAD:refs/heads/main".to_string()),
+        };
+
+        let signature = Signature::now(&self.commit.author_name, &self.commit.author_email)?;
+        commit::create(
+            &repo,
             &signature,
+            &self.commit.message,
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+            self.commit.signing.as_ref(),
+        )?;
+
+        // 7. git push -u origin main (utiliser HEAD pour éviter les problèmes de référence)
+        self.push_with_retry(&mut remote, &refspec, token).await?;
+
+        Ok(branch.to_string())
+    }
+
+    /// Clone `name` of the owner into `destination`, returning its default branch
+    pub async fn clone_repository(
+        &self,
+        name: &str,
+        destination: &Path,
+    ) -> Result<String, GeneratorError> {
+        let url = self.push_url(&self.repository_url(name));
+        log::info!("📥 Cloning {}", url);
+        let token = self.client.token().await?;
+
+        let mut fetch_options = git2::FetchOptions::new();
+        fetch_options.remote_callbacks(self.remote_callbacks(&token));
+        fetch_options.proxy_options(http::git_proxy_options());
+        let repo = git2::build::RepoBuilder::new()
+            .fetch_options(fetch_options)
+            .clone(&url, destination)
+            .map_err(|e| GeneratorError::Git(format!("Failed to clone {}: {}", url, e)))?;
+        let branch = repo
+            .head()?
+            .shorthand()
+            .ok_or_else(|| {
+                GeneratorError::Git("The cloned repository has no default branch".to_string())
+            })?
+            .to_string();
+        Ok(branch)
+    }
+
+    /// Commit every change of the checkout on a new `branch` started from its HEAD, and push
+    /// that branch to the `name` repository of the owner
+    pub async fn push_new_branch(
+        &self,
+        local_path: &Path,
+        name: &str,
+        branch: &str,
+        message: &str,
+    ) -> Result<(), GeneratorError> {
+        let push_url = self.push_url(&self.repository_url(name));
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "git checkout -b {} in '{}'",
+                branch,
+                local_path.display()
+            ));
+            self.print_dry_run(&format!(
+                "git commit -am \"{}\"{}",
+                message,
+                commit::signing_note(self.commit.signing.as_ref())
+            ));
+            self.print_dry_run(&format!(
+                "git push {} {} using {:?} authentication",
+                push_url, branch, self.git_auth
+            ));
+            return Ok(());
+        }
+
+        let token = self.client.token().await?;
+        let repo = Repository::open(local_path)?;
+        let parent = repo.head()?.peel_to_commit()?;
+        repo.branch(branch, &parent, false).map_err(|e| {
+            GeneratorError::Git(format!("Failed to create branch {}: {}", branch, e))
+        })?;
+        repo.set_head(&format!("refs/heads/{}", branch))?;
+
+        let mut index = repo.index()?;
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+        index.update_all(["*"], None)?;
+        index.write()?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let signature = repo
+            .signature()
+            .or_else(|_| Signature::now("Project Generator", "generator@nextnode.dev"))?;
+        commit::create(
+            &repo,
             &signature,
-            "first commit",
+            message,
             &tree,
-            &[],
+            &[&parent],
+            self.commit.signing.as_ref(),
         )?;
 
-        // 6. git remote add origin <url>
-        let mut remote = repo.remote("origin", repo_url)?;
+        let mut remote = repo.remote_anonymous(&push_url)?;
+        let refspec = format!("refs/heads/{}:refs/heads/{}", branch, branch);
+        self.push_with_retry(&mut remote, &refspec, token).await
+    }
 
-        // 7. git push -u origin main (utiliser HEAD pour éviter les problèmes de référence)
+    /// Open a pull request merging `head` into `base`, returning its URL
+    pub async fn create_pull_request(
+        &self,
+        name: &str,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+    ) -> Result<String, GeneratorError> {
+        let path = self.repo_path(name, "/pulls");
+        let payload = json!({ "title": title, "head": head, "base": base, "body": body });
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "POST {} with body {}",
+                GitHubClient::url(&path),
+                payload
+            ));
+            return Ok(format!(
+                "https://github.com/{}/{}/compare/{}...{}",
+                self.organization, name, base, head
+            ));
+        }
+
+        let pull: Value = self
+            .client
+            .post(&path, &payload, "Failed to open pull request")
+            .await?;
+        Ok(pull["html_url"]
+            .as_str()
+            .ok_or_else(|| GeneratorError::Http("No html_url in response".to_string()))?
+            .to_string())
+    }
+
+    /// URL of the open pull request from `head` in `name`, if there is one
+    pub async fn find_open_pull_request(
+        &self,
+        name: &str,
+        head: &str,
+    ) -> Result<Option<String>, GeneratorError> {
+        let path = self.repo_path(
+            name,
+            &format!("/pulls?state=open&head={}:{}", self.organization, head),
+        );
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "GET {} to find an open pull request",
+                GitHubClient::url(&path)
+            ));
+            return Ok(None);
+        }
+
+        let pulls: Vec<Value> = self
+            .client
+            .get(&path, "GitHub API error listing pull requests")
+            .await?;
+        Ok(pulls
+            .first()
+            .and_then(|pull| pull["html_url"].as_str())
+            .map(str::to_string))
+    }
+
+    /// Whether `path` exists on the default branch of `name`
+    pub async fn file_exists(&self, name: &str, path: &str) -> Result<bool, GeneratorError> {
+        let api_path = self.repo_path(name, &format!("/contents/{}", path));
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "GET {} to check whether it exists",
+                GitHubClient::url(&api_path)
+            ));
+            return Ok(false);
+        }
+
+        let file: Option<Value> = self
+            .client
+            .get_optional(&api_path, format!("GitHub API error looking up {}", path))
+            .await?;
+        Ok(file.is_some())
+    }
+
+    /// Push the refspec. A freshly created repository is sometimes not ready for the first
+    /// push: network and server errors are retried with credentials resolved again, refused
+    /// credentials are not.
+    async fn push_with_retry(
+        &self,
+        remote: &mut git2::Remote<'_>,
+        refspec: &str,
+        token: String,
+    ) -> Result<(), GeneratorError> {
+        let mut token = token;
+        let mut attempt = 1;
+        loop {
+            let mut push_options = git2::PushOptions::new();
+            push_options.remote_callbacks(self.remote_callbacks(&token));
+            push_options.proxy_options(http::git_proxy_options());
+            let error = match remote.push(&[refspec], Some(&mut push_options)) {
+                Ok(()) => return Ok(()),
+                Err(e) => e,
+            };
+            if is_git_auth_error(&error) {
+                return Err(GeneratorError::Git(self.push_auth_error(&error)));
+            }
+            if !is_transient_git_error(&error) || attempt >= self.client.retry_policy().max_attempts
+            {
+                return Err(error.into());
+            }
+
+            let delay = self.client.retry_policy().backoff(attempt);
+            log::info!(
+                "⏳ git push failed ({}), retrying in {}s (attempt {}/{})...",
+                error.message(),
+                delay.as_secs(),
+                attempt + 1,
+                self.client.retry_policy().max_attempts
+            );
+            tokio::time::sleep(delay).await;
+            token = self.client.token().await?;
+            attempt += 1;
+        }
+    }
+
+    /// What to check when the remote refused the push credentials
+    fn push_auth_error(&self, error: &git2::Error) -> String {
+        let guidance = match self.git_auth {
+            GitAuth::Token => {
+                "Check that the token can write to the repository (classic tokens need the \
+                 `repo` scope, fine-grained tokens Contents: write) and, in organizations \
+                 enforcing SAML SSO, that it is authorized for the organization"
+            }
+            GitAuth::SshAgent => {
+                "Check that ssh-agent holds a key added to your GitHub account (`ssh-add -l`)"
+            }
+            GitAuth::SshKey => {
+                "Check that ssh_key_path is a key added to your GitHub account and that \
+                 SSH_KEY_PASSPHRASE is set if it has a passphrase"
+            }
+        };
+        format!("git push was refused: {}\n{}", error.message(), guidance)
+    }
+
+    /// Credentials for git operations against GitHub, following the configured git auth
+    fn remote_callbacks(&self, token: &str) -> RemoteCallbacks<'static> {
         let mut callbacks = RemoteCallbacks::new();
-        let token = self.token.clone();
-        callbacks.credentials(move |_url, username_from_url, _allowed_types| {
-            Cred::userpass_plaintext(username_from_url.unwrap_or("git"), &token)
+        let git_auth = self.git_auth;
+        let ssh_key_path = self.ssh_key_path.clone();
+        let passphrase = std::env::var("SSH_KEY_PASSPHRASE").ok();
+        let token = token.to_string();
+        // libgit2 asks again each time the server refuses the credentials: an ssh-agent
+        // without an accepted key would be offered forever
+        let mut attempts = 0;
+        callbacks.credentials(move |_url, username_from_url, allowed_types| {
+            attempts += 1;
+            if attempts > 1 {
+                return Err(git2::Error::new(
+                    git2::ErrorCode::Auth,
+                    git2::ErrorClass::Net,
+                    "authentication failed: the credentials were refused",
+                ));
+            }
+            let username = username_from_url.unwrap_or("git");
+            let ssh_key = allowed_types.contains(CredentialType::SSH_KEY);
+            match (git_auth, &ssh_key_path) {
+                (GitAuth::SshAgent, _) if ssh_key => Cred::ssh_key_from_agent(username),
+                (GitAuth::SshKey, Some(key_path)) if ssh_key => {
+                    Cred::ssh_key(username, None, key_path, passphrase.as_deref())
+                }
+                // The username GitHub documents for app installation tokens, which personal
+                // access tokens accept as well
+                _ if allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT) => {
+                    Cred::userpass_plaintext("x-access-token", &token)
+                }
+                _ => Err(git2::Error::new(
+                    git2::ErrorCode::Auth,
+                    git2::ErrorClass::Net,
+                    format!(
+                        "authentication failed: the remote accepts none of the configured \
+                         credentials ({:?})",
+                        allowed_types
+                    ),
+                )),
+            }
         });
+        callbacks
+    }
+
+    /// Clone each submodule at its pinned commit and stage it with its .gitmodules entry
+    fn add_submodules(
+        &self,
+        repo: &Repository,
+        submodules: &[SubmoduleDeclaration],
+        token: &str,
+    ) -> Result<(), GeneratorError> {
+        for declaration in submodules {
+            log::info!(
+                "📦 Adding submodule {} at {}",
+                declaration.url,
+                declaration.path
+            );
+            let mut submodule =
+                repo.submodule(&declaration.url, Path::new(&declaration.path), true)?;
 
-        let mut push_options = git2::PushOptions::new();
-        push_options.remote_callbacks(callbacks);
-        remote.push(&["HEAD:refs/heads/main"], Some(&mut push_options))?;
+            let mut fetch_options = git2::FetchOptions::new();
+            fetch_options.remote_callbacks(self.remote_callbacks(token));
+            fetch_options.proxy_options(http::git_proxy_options());
+            let mut update_options = git2::SubmoduleUpdateOptions::new();
+            update_options.fetch(fetch_options);
+            let submodule_repo = submodule.clone(Some(&mut update_options))?;
 
+            let commit = submodule_repo
+                .revparse_single(&declaration.commit)
+                .and_then(|object| object.peel_to_commit())
+                .map_err(|e| {
+                    GeneratorError::Git(format!(
+                        "Submodule {}: cannot resolve '{}': {}",
+                        declaration.path, declaration.commit, e
+                    ))
+                })?;
+            submodule_repo.set_head_detached(commit.id())?;
+            submodule_repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))?;
+
+            submodule.add_finalize()?;
+            log::debug!("Submodule {} pinned to {}", declaration.path, commit.id());
+        }
         Ok(())
     }
 
@@ -179,238 +652,1447 @@ impl GitHubRepo {
         repo_name: &str,
         workflow_file: &str,
         branch: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Extract organization from REPO_URL constant
-        let org_name = REPO_URL
-            .split('/')
-            .next_back()
-            .ok_or("Could not extract organization from REPO_URL")?;
-
-        // Build headers
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", self.token))
-                .map_err(|_| "Failed to create authorization header")?,
-        );
-        headers.insert(
-            ACCEPT,
-            HeaderValue::from_str("application/vnd.github.v3+json")
-                .map_err(|_| "Failed to create accept header")?,
-        );
-        headers.insert(
-            USER_AGENT,
-            HeaderValue::from_str("NextNode-Project-Generator/1.0")
-                .map_err(|_| "Failed to create user-agent header")?,
-        );
-
+        inputs: &IndexMap<String, String>,
+    ) -> Result<(), GeneratorError> {
         // Build request body for workflow dispatch
-        let body = json!({
+        let mut body = json!({
             "ref": branch
         });
+        if !inputs.is_empty() {
+            body["inputs"] = json!(inputs);
+        }
+        let path = self.repo_path(
+            repo_name,
+            &format!("/actions/workflows/{}/dispatches", workflow_file),
+        );
+
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "POST {} with body {}",
+                GitHubClient::url(&path),
+                body
+            ));
+            summary::record_workflow(workflow_file);
+            return Ok(());
+        }
+
+        self.client
+            .execute(
+                Method::POST,
+                &path,
+                Some(&body),
+                format!("GitHub API error for workflow {}", workflow_file),
+            )
+            .await?;
+
+        log::info!("✅ Successfully triggered workflow: {}", workflow_file);
+        summary::record_workflow(workflow_file);
+        Ok(())
+    }
+
+    fn dispatched_runs_path(&self, repo_name: &str, branch: &str) -> String {
+        self.repo_path(
+            repo_name,
+            &format!("/actions/runs?branch={}&event=workflow_dispatch", branch),
+        )
+    }
+
+    /// Dispatched runs of a workflow on a branch, newest first
+    async fn dispatched_runs(
+        &self,
+        repo_name: &str,
+        workflow_file: &str,
+        branch: &str,
+    ) -> Result<Vec<Value>, GeneratorError> {
+        let runs: Value = self
+            .client
+            .get(
+                &self.dispatched_runs_path(repo_name, branch),
+                "GitHub API error listing workflow runs",
+            )
+            .await?;
+        let suffix = format!("/{}", workflow_file);
+        Ok(runs["workflow_runs"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|run| {
+                run["path"]
+                    .as_str()
+                    .is_some_and(|path| path.ends_with(&suffix))
+            })
+            .cloned()
+            .collect())
+    }
+
+    /// IDs of the dispatched runs of a workflow on a branch, listed before dispatching it again
+    /// so that its new run is not mistaken for one of them
+    async fn dispatched_run_ids(
+        &self,
+        repo_name: &str,
+        workflow_file: &str,
+        branch: &str,
+    ) -> Result<Vec<u64>, GeneratorError> {
+        if self.dry_run {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .dispatched_runs(repo_name, workflow_file, branch)
+            .await?
+            .iter()
+            .filter_map(|run| run["id"].as_u64())
+            .collect())
+    }
+
+    /// The run of a dispatch: the newest dispatched run of the workflow on the branch that is
+    /// not one of the `previous_runs` listed before dispatching
+    async fn find_dispatched_run(
+        &self,
+        repo_name: &str,
+        workflow_file: &str,
+        branch: &str,
+        previous_runs: &[u64],
+    ) -> Result<Option<Value>, GeneratorError> {
+        Ok(self
+            .dispatched_runs(repo_name, workflow_file, branch)
+            .await?
+            .into_iter()
+            .find(|run| {
+                run["id"]
+                    .as_u64()
+                    .is_some_and(|id| !previous_runs.contains(&id))
+            }))
+    }
+
+    /// Poll the run of a dispatched workflow until it completes, printing status changes.
+    /// Returns the run conclusion (`success`, `failure`, `cancelled`...).
+    pub async fn wait_for_workflow_completion(
+        &self,
+        repo_name: &str,
+        workflow_file: &str,
+        branch: &str,
+        previous_runs: &[u64],
+        timeout: Duration,
+    ) -> Result<String, GeneratorError> {
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "poll GET {} until the {} run completes",
+                GitHubClient::url(&self.dispatched_runs_path(repo_name, branch)),
+                workflow_file
+            ));
+            return Ok("success".to_string());
+        }
+
+        let deadline = Instant::now() + timeout;
+        let mut last_status = String::new();
+        loop {
+            let run = self
+                .find_dispatched_run(repo_name, workflow_file, branch, previous_runs)
+                .await?;
+            if let Some(run) = run {
+                let status = run["status"].as_str().unwrap_or("unknown").to_string();
+                if status != last_status {
+                    log::info!("⏳ {} on {}: {}", workflow_file, branch, status);
+                    log::debug!(
+                        "Workflow run: {}",
+                        run["html_url"].as_str().unwrap_or_default()
+                    );
+                    last_status = status.clone();
+                }
+                if status == "completed" {
+                    return Ok(run["conclusion"].as_str().unwrap_or("unknown").to_string());
+                }
+            }
+
+            if Instant::now() >= deadline {
+                return Err(GeneratorError::Other(format!(
+                    "Workflow {} on {} did not complete within {}s",
+                    workflow_file,
+                    branch,
+                    timeout.as_secs()
+                )));
+            }
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        }
+    }
+
+    /// Dispatch a workflow and, with `wait_timeout`, wait for its run, which is told apart
+    /// from the `previous_runs` listed before. Returns the conclusion of the run when it was
+    /// waited for: an error means the dispatch or the wait failed, not the run.
+    async fn trigger_and_wait(
+        &self,
+        repo_name: &str,
+        workflow_file: &str,
+        branch: &str,
+        inputs: &IndexMap<String, String>,
+        previous_runs: &[u64],
+        wait_timeout: Option<Duration>,
+    ) -> Result<Option<String>, GeneratorError> {
+        // Workflows pushed with the code are not dispatchable until GitHub has indexed them
+        self.wait_for_workflow(repo_name, workflow_file).await?;
+        self.trigger_workflow_dispatch(repo_name, workflow_file, branch, inputs)
+            .await?;
+
+        let Some(timeout) = wait_timeout else {
+            return Ok(None);
+        };
+        let conclusion = self
+            .wait_for_workflow_completion(repo_name, workflow_file, branch, previous_runs, timeout)
+            .await?;
+        if conclusion == "success" {
+            log::info!("✅ Workflow {} on {} succeeded", workflow_file, branch);
+        }
+        Ok(Some(conclusion))
+    }
+
+    /// Links to the run of a deployment, waiting up to `timeout` for it to be listed.
+    /// Lookup failures only leave the links out.
+    async fn deployment_links(
+        &self,
+        repo_name: &str,
+        deployment: &Deployment,
+        previous_runs: &[u64],
+        timeout: Duration,
+    ) -> DeploymentLinks {
+        if self.dry_run {
+            return DeploymentLinks::default();
+        }
+
+        let deadline = Instant::now() + timeout;
+        let run = loop {
+            match self
+                .find_dispatched_run(
+                    repo_name,
+                    &deployment.workflow,
+                    &deployment.branch,
+                    previous_runs,
+                )
+                .await
+            {
+                Ok(Some(run)) => break run,
+                Ok(None) if Instant::now() < deadline => {
+                    tokio::time::sleep(Duration::from_secs(3)).await
+                }
+                Ok(None) => return DeploymentLinks::default(),
+                Err(e) => {
+                    log::debug!("Failed to look up the {} run: {}", deployment.workflow, e);
+                    return DeploymentLinks::default();
+                }
+            }
+        };
 
-        // Make GitHub API call to trigger workflow
-        let client = reqwest::Client::new();
-        let response = client
-            .post(format!(
-                "https://api.github.com/repos/{}/{}/actions/workflows/{}/dispatches",
-                org_name, repo_name, workflow_file
-            ))
-            .headers(headers)
-            .json(&body)
-            .send()
+        let site_url = match (run["conclusion"].as_str(), run["head_sha"].as_str()) {
+            (Some("success"), Some(sha)) => {
+                self.environment_url(repo_name, sha, &deployment.branch)
+                    .await
+            }
+            _ => None,
+        };
+        DeploymentLinks {
+            run_url: run["html_url"].as_str().map(str::to_string),
+            site_url,
+        }
+    }
+
+    /// URL reported by the latest environment deployment of a commit on a branch, set by
+    /// jobs declaring `environment: { name: ..., url: ... }`
+    async fn environment_url(&self, repo_name: &str, sha: &str, branch: &str) -> Option<String> {
+        let path = self.repo_path(repo_name, &format!("/deployments?sha={}", sha));
+        let deployments: Vec<Value> = self
+            .client
+            .get(&path, "GitHub API error listing deployments")
+            .await
+            .map_err(|e| log::debug!("{}", e))
+            .ok()?;
+        // Branches created from main share its commit
+        let deployment = deployments.iter().find(|deployment| {
+            deployment["ref"]
+                .as_str()
+                .is_some_and(|r| r == branch || r == format!("refs/heads/{}", branch))
+        })?;
+        let statuses_path = self.repo_path(
+            repo_name,
+            &format!("/deployments/{}/statuses", deployment["id"].as_u64()?),
+        );
+        let statuses: Vec<Value> = self
+            .client
+            .get(
+                &statuses_path,
+                "GitHub API error listing deployment statuses",
+            )
             .await
-            .map_err(|e| format!("Failed to trigger workflow {}: {}", workflow_file, e))?;
+            .map_err(|e| log::debug!("{}", e))
+            .ok()?;
+        // Statuses are listed newest first
+        statuses
+            .first()?
+            .get("environment_url")?
+            .as_str()
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+    }
+
+    /// Dispatch the deployment workflows in order. With `wait_timeout`, wait for each run to
+    /// complete and fail if one of them does not succeed.
+    pub async fn trigger_deployments(
+        &self,
+        repo_name: &str,
+        deployments: &[Deployment],
+        wait_timeout: Option<Duration>,
+    ) -> Result<(), GeneratorError> {
+        let _stage = logging::stage("deploy");
+        // Check if auto-deployment is disabled
+        if auto_deploy_disabled() {
+            log::info!(
+                "🚫 Auto-deployment disabled (no_deploy={}), skipping workflow triggers",
+                crate::utils::context::get_variable("no_deploy").unwrap_or_default()
+            );
+            return Ok(());
+        }
 
-        if !response.status().is_success() {
-            let error = response
-                .text()
+        log::info!("🚀 Triggering deployment workflows...");
+
+        let mut failures = Vec::new();
+        // One line per deployment, printed once they all completed
+        let mut summary = Vec::new();
+        let mut sites = Vec::new();
+        for (index, deployment) in deployments.iter().enumerate() {
+            // Wait between requests to avoid rate limiting
+            if index > 0 && !self.dry_run {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+            }
+
+            let (previous_runs, result) = match self
+                .dispatched_run_ids(repo_name, &deployment.workflow, &deployment.branch)
                 .await
-                .map_err(|e| format!("Failed to read error response: {}", e))?;
-            return Err(
-                format!("GitHub API error for workflow {}: {}", workflow_file, error).into(),
+            {
+                Ok(previous_runs) => {
+                    let result = self
+                        .trigger_and_wait(
+                            repo_name,
+                            &deployment.workflow,
+                            &deployment.branch,
+                            &deployment.inputs,
+                            &previous_runs,
+                            wait_timeout,
+                        )
+                        .await;
+                    (Some(previous_runs), result)
+                }
+                Err(e) => (None, Err(e)),
+            };
+            // A run followed to its end is already listed, a dispatched one shortly after
+            let lookup = match (&result, wait_timeout) {
+                (Ok(_), None) => RUN_LOOKUP_TIMEOUT,
+                _ => Duration::ZERO,
+            };
+            let links = match &previous_runs {
+                Some(previous_runs) => {
+                    self.deployment_links(repo_name, deployment, previous_runs, lookup)
+                        .await
+                }
+                None => DeploymentLinks::default(),
+            };
+            summary::record_deployment(
+                &deployment.workflow,
+                &deployment.branch,
+                links.run_url.as_deref(),
+                links.site_url.as_deref(),
             );
+            let run_link = links
+                .run_url
+                .as_ref()
+                .map(|url| format!(" - {}", url))
+                .unwrap_or_default();
+            if let Some(site_url) = &links.site_url {
+                sites.push(format!(
+                    "{} ({} on {})",
+                    site_url, deployment.workflow, deployment.branch
+                ));
+            }
+
+            // Only a run followed to its end is known to have failed, a failed dispatch or
+            // wait may not have deployed anything
+            let failure = match result {
+                Ok(Some(conclusion)) if conclusion != "success" => Some((
+                    format!(
+                        "Workflow {} on {} finished with conclusion '{}'",
+                        deployment.workflow, deployment.branch, conclusion
+                    ),
+                    true,
+                )),
+                Ok(_) => None,
+                Err(e) => Some((e.to_string(), false)),
+            };
+            match failure {
+                None => {
+                    log::info!(
+                        "✅ Deployment workflow {} triggered on {} branch",
+                        deployment.workflow,
+                        deployment.branch
+                    );
+                    let outcome = if wait_timeout.is_some() {
+                        "succeeded"
+                    } else {
+                        "triggered"
+                    };
+                    summary.push(format!(
+                        "✅ {} on {}: {}{}",
+                        deployment.workflow, deployment.branch, outcome, run_link
+                    ));
+                }
+                Some((error, run_failed)) => {
+                    log::warn!(
+                        "Deployment {} on {} failed: {}",
+                        deployment.workflow,
+                        deployment.branch,
+                        error
+                    );
+                    let outcome = if run_failed {
+                        self.recover(repo_name, deployment, wait_timeout).await
+                    } else {
+                        Vec::new()
+                    };
+                    let outcome = if outcome.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" ({})", outcome.join(", "))
+                    };
+                    summary.push(format!(
+                        "❌ {} on {}: failed{}{}",
+                        deployment.workflow, deployment.branch, outcome, run_link
+                    ));
+                    failures.push(format!("{}{}", error, outcome));
+                }
+            }
         }
 
-        println!("✅ Successfully triggered workflow: {}", workflow_file);
+        log::info!("📋 Deployment summary:");
+        for line in &summary {
+            log::info!("   {}", line);
+        }
+        for site in &sites {
+            log::info!("🌐 Your site is live at {}", site);
+        }
+        if wait_timeout.is_some() {
+            if !failures.is_empty() {
+                return Err(GeneratorError::Other(failures.join("; ")));
+            }
+            log::info!("🎉 Deployment workflows completed successfully!");
+        } else {
+            log::info!(
+                "🎉 Deployment workflows have been triggered! Check GitHub Actions for status."
+            );
+        }
         Ok(())
     }
 
-    pub async fn trigger_deployments(
+    /// Contain a deployment whose run failed as configured: cancel the other queued and
+    /// in-progress runs of its workflow, then dispatch the rollback workflow and wait for it.
+    /// Returns what was done, e.g. `rolled back with rollback-prod.yml`.
+    async fn recover(
         &self,
         repo_name: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Check if auto-deployment is disabled
-        if let Some(no_deploy) = crate::utils::context::get_variable("no_deploy") {
-            let is_disabled = match no_deploy.to_lowercase().as_str() {
-                "true" | "1" | "yes" | "on" => true,
-                _ => false,
+        deployment: &Deployment,
+        wait_timeout: Option<Duration>,
+    ) -> Vec<String> {
+        let mut outcome = Vec::new();
+        if deployment.cancel_on_failure {
+            match self
+                .cancel_in_progress_runs(repo_name, &deployment.workflow, &deployment.branch)
+                .await
+            {
+                Ok(cancelled) => {
+                    log::info!(
+                        "🛑 Cancelled {} in-progress {} runs",
+                        cancelled,
+                        deployment.workflow
+                    );
+                    outcome.push(format!("{} runs cancelled", cancelled));
+                }
+                Err(e) => {
+                    log::warn!("Failed to cancel workflow runs: {}", e);
+                    outcome.push("cancelling runs failed".to_string());
+                }
+            }
+        }
+
+        if let Some(rollback) = &deployment.rollback_workflow {
+            log::info!(
+                "↩️  Rolling back {} with {}...",
+                deployment.branch,
+                rollback
+            );
+            let result = match self
+                .dispatched_run_ids(repo_name, rollback, &deployment.branch)
+                .await
+            {
+                // Its inputs are the deployment workflow's, the rollback may not declare them
+                Ok(previous_runs) => {
+                    self.trigger_and_wait(
+                        repo_name,
+                        rollback,
+                        &deployment.branch,
+                        &IndexMap::new(),
+                        &previous_runs,
+                        wait_timeout,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
             };
-            if is_disabled {
-                println!(
-                    "🚫 Auto-deployment disabled (no_deploy={}), skipping workflow triggers",
-                    no_deploy
-                );
+            match result {
+                Ok(Some(conclusion)) if conclusion != "success" => {
+                    log::warn!(
+                        "Rollback with {} finished with conclusion '{}'",
+                        rollback,
+                        conclusion
+                    );
+                    outcome.push(format!("rollback with {} failed", rollback));
+                }
+                Ok(_) => outcome.push(format!("rolled back with {}", rollback)),
+                Err(e) => {
+                    log::warn!("Rollback with {} failed: {}", rollback, e);
+                    outcome.push(format!("rollback with {} failed", rollback));
+                }
+            }
+        }
+        outcome
+    }
+
+    /// Cancel the queued and in-progress runs of a workflow on a branch, returning how many
+    /// were cancelled
+    pub async fn cancel_in_progress_runs(
+        &self,
+        repo_name: &str,
+        workflow_file: &str,
+        branch: &str,
+    ) -> Result<usize, GeneratorError> {
+        let runs_path = self.repo_path(repo_name, "/actions/runs");
+
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "cancel the queued and in-progress {} runs on {} listed by GET {}",
+                workflow_file,
+                branch,
+                GitHubClient::url(&runs_path)
+            ));
+            return Ok(0);
+        }
+
+        let suffix = format!("/{}", workflow_file);
+        let mut cancelled = 0;
+        for status in ["in_progress", "queued"] {
+            let runs: Vec<Value> = self
+                .client
+                .paginate(
+                    &format!("{}?status={}&branch={}", runs_path, status, branch),
+                    Some("workflow_runs"),
+                    "GitHub API error listing workflow runs",
+                )
+                .await?;
+            let ids = runs
+                .iter()
+                .filter(|run| {
+                    run["path"]
+                        .as_str()
+                        .is_some_and(|path| path.ends_with(&suffix))
+                })
+                .filter_map(|run| run["id"].as_u64());
+
+            for id in ids {
+                let response = self
+                    .client
+                    .send(Method::POST, &format!("{}/{}/cancel", runs_path, id), None)
+                    .await
+                    .map_err(|e| e.context(format!("Failed to cancel workflow run {}", id)))?;
+                // A run completing meanwhile cannot be cancelled anymore
+                if response.status().is_success() {
+                    cancelled += 1;
+                } else {
+                    log::debug!(
+                        "Workflow run {} was not cancelled: {}",
+                        id,
+                        response.status()
+                    );
+                }
+            }
+        }
+        Ok(cancelled)
+    }
+
+    /// Create `branch` from `main`, unless it already exists
+    pub async fn create_branch(&self, repo_name: &str, branch: &str) -> Result<(), GeneratorError> {
+        let refs_path = self.repo_path(repo_name, "/git/refs");
+
+        if self.dry_run {
+            self.print_dry_run(&format!("GET {}/heads/main", GitHubClient::url(&refs_path)));
+            self.print_dry_run(&format!(
+                "POST {} with body {}",
+                GitHubClient::url(&refs_path),
+                json!({ "ref": format!("refs/heads/{}", branch), "sha": "<main SHA>" })
+            ));
+            return Ok(());
+        }
+
+        // First, get the SHA of the main branch
+        let main_ref_data: Value = self
+            .client
+            .get(
+                &format!("{}/heads/main", refs_path),
+                "GitHub API error getting main branch",
+            )
+            .await?;
+
+        let main_sha = main_ref_data["object"]["sha"].as_str().ok_or_else(|| {
+            GeneratorError::Http("No SHA found in main branch response".to_string())
+        })?;
+
+        log::info!("📋 Main branch SHA: {}", main_sha);
+
+        // Check if the branch already exists
+        let branch_check_response = self
+            .client
+            .send(
+                Method::GET,
+                &format!("{}/heads/{}", refs_path, branch),
+                None,
+            )
+            .await;
+
+        if let Ok(response) = branch_check_response {
+            if response.status().is_success() {
+                log::info!("ℹ️  Branch {} already exists, skipping creation", branch);
                 return Ok(());
             }
         }
 
-        println!("🚀 Triggering deployment workflows...");
+        // Create the branch from main SHA
+        let create_branch_body = json!({
+            "ref": format!("refs/heads/{}", branch),
+            "sha": main_sha
+        });
+
+        self.client
+            .execute(
+                Method::POST,
+                &refs_path,
+                Some(&create_branch_body),
+                format!("GitHub API error creating branch {}", branch),
+            )
+            .await?;
 
-        // Wait longer for GitHub to index the workflows
-        tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+        log::info!("✅ Successfully created {} branch from main", branch);
+        Ok(())
+    }
 
-        // Trigger dev deployment on develop branch
-        match self
-            .trigger_workflow_dispatch(repo_name, "deploy-dev.yml", "develop")
-            .await
-        {
-            Ok(_) => println!("✅ Dev deployment workflow triggered on develop branch"),
-            Err(e) => eprintln!("⚠️  Warning: Failed to trigger dev deployment: {}", e),
+    pub async fn protect_branch(
+        &self,
+        repo_name: &str,
+        branch: &str,
+        protection: &BranchProtection,
+    ) -> Result<(), GeneratorError> {
+        let path = self.repo_path(repo_name, &format!("/branches/{}/protection", branch));
+
+        let required_status_checks = if protection.required_status_checks.is_empty() {
+            serde_json::Value::Null
+        } else {
+            json!({
+                "strict": protection.strict_status_checks,
+                "contexts": protection.required_status_checks
+            })
+        };
+        let required_pull_request_reviews = match protection.required_approving_review_count {
+            Some(count) => json!({
+                "required_approving_review_count": count,
+                "dismiss_stale_reviews": protection.dismiss_stale_reviews,
+                "require_code_owner_reviews": protection.require_code_owner_reviews
+            }),
+            None => serde_json::Value::Null,
+        };
+        let body = json!({
+            "required_status_checks": required_status_checks,
+            "enforce_admins": protection.enforce_admins,
+            "required_pull_request_reviews": required_pull_request_reviews,
+            "restrictions": null,
+            "allow_force_pushes": protection.allow_force_pushes,
+            "allow_deletions": protection.allow_deletions
+        });
+
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "PUT {} with body {}",
+                GitHubClient::url(&path),
+                body
+            ));
+            return Ok(());
         }
 
-        // Wait between requests to avoid rate limiting
-        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        self.client
+            .execute(
+                Method::PUT,
+                &path,
+                Some(&body),
+                format!("GitHub API error protecting branch {}", branch),
+            )
+            .await?;
+        Ok(())
+    }
 
-        // Trigger prod deployment on main branch
-        match self
-            .trigger_workflow_dispatch(repo_name, "deploy-prod.yml", "main")
-            .await
-        {
-            Ok(_) => println!("✅ Production deployment workflow triggered on main branch"),
-            Err(e) => eprintln!("⚠️  Warning: Failed to trigger prod deployment: {}", e),
+    /// Require the merge queue on `branch` with a ruleset, updating the generator's ruleset
+    /// when the repository already has it
+    pub async fn enable_merge_queue(
+        &self,
+        repo_name: &str,
+        branch: &str,
+        queue: &MergeQueue,
+    ) -> Result<(), GeneratorError> {
+        let mut rules = vec![json!({
+            "type": "merge_queue",
+            "parameters": {
+                "merge_method": queue.merge_method.as_api_str(),
+                "grouping_strategy": "ALLGREEN",
+                "max_entries_to_build": queue.max_entries_to_build,
+                "max_entries_to_merge": queue.max_entries_to_merge,
+                "min_entries_to_merge": 1,
+                "min_entries_to_merge_wait_minutes": queue.min_entries_to_merge_wait_minutes,
+                "check_response_timeout_minutes": queue.check_response_timeout_minutes
+            }
+        })];
+        if !queue.required_checks.is_empty() {
+            let checks: Vec<Value> = queue
+                .required_checks
+                .iter()
+                .map(|check| json!({ "context": check }))
+                .collect();
+            rules.push(json!({
+                "type": "required_status_checks",
+                "parameters": {
+                    "strict_required_status_checks_policy": false,
+                    "required_status_checks": checks
+                }
+            }));
+        }
+        let body = json!({
+            "name": MERGE_QUEUE_RULESET,
+            "target": "branch",
+            "enforcement": "active",
+            "conditions": {
+                "ref_name": { "include": [format!("refs/heads/{}", branch)], "exclude": [] }
+            },
+            "rules": rules
+        });
+
+        let path = self.repo_path(repo_name, "/rulesets");
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "POST {} with body {}",
+                GitHubClient::url(&path),
+                body
+            ));
+            return Ok(());
+        }
+
+        let rulesets: Vec<Value> = self
+            .client
+            .paginate(&path, None, "GitHub API error listing rulesets")
+            .await?;
+        let existing = rulesets
+            .iter()
+            .find(|ruleset| ruleset["name"] == MERGE_QUEUE_RULESET)
+            .and_then(|ruleset| ruleset["id"].as_u64());
+        match existing {
+            Some(id) => {
+                self.client
+                    .execute(
+                        Method::PUT,
+                        &format!("{}/{}", path, id),
+                        Some(&body),
+                        "GitHub API error updating the merge queue ruleset",
+                    )
+                    .await?;
+            }
+            None => {
+                self.client
+                    .execute(
+                        Method::POST,
+                        &path,
+                        Some(&body),
+                        "GitHub API error creating the merge queue ruleset",
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn setup_repository_branches(
+        &self,
+        repo_name: &str,
+        branches: &[String],
+        branch_protection: &IndexMap<String, BranchProtection>,
+    ) -> Result<(), GeneratorError> {
+        let _stage = logging::stage("branch_setup");
+        if branches.is_empty() {
+            log::info!("ℹ️  Skipping branch creation (not configured)");
+        } else {
+            // Branches are created from main, which must be visible after the push
+            let main_ready = self.wait_for_branch(repo_name, "main").await;
+            for branch in branches {
+                log::info!("🔧 Creating {} branch...", branch);
+                let created = match &main_ready {
+                    Ok(_) => self
+                        .create_branch(repo_name, branch)
+                        .await
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                match created {
+                    Ok(_) => {
+                        log::info!("✅ Branch {} created successfully", branch);
+                        summary::record_branch(branch);
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to create {} branch: {}", branch, e)
+                    }
+                }
+            }
+        }
+
+        for (branch, protection) in branch_protection {
+            if branch != "main" && !branches.contains(branch) {
+                log::info!(
+                    "ℹ️  Skipping protection for {} branch (not created)",
+                    branch
+                );
+                continue;
+            }
+
+            log::info!("🛡️  Applying branch protection to {}...", branch);
+            match self.protect_branch(repo_name, branch, protection).await {
+                Ok(_) => log::info!("✅ Branch protection applied to {}", branch),
+                Err(e) => log::warn!("Failed to apply branch protection to {}: {}", branch, e),
+            }
         }
 
-        println!("🎉 Deployment workflows have been triggered! Check GitHub Actions for status.");
+        log::info!("✅ Repository setup completed!");
         Ok(())
     }
 
-    pub async fn create_develop_branch(
+    pub async fn enable_security_features(
         &self,
         repo_name: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Extract organization from REPO_URL constant
-        let org_name = REPO_URL
-            .split('/')
-            .next_back()
-            .ok_or("Could not extract organization from REPO_URL")?;
-
-        // Build headers
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", self.token))
-                .map_err(|_| "Failed to create authorization header")?,
-        );
-        headers.insert(
-            ACCEPT,
-            HeaderValue::from_str("application/vnd.github.v3+json")
-                .map_err(|_| "Failed to create accept header")?,
-        );
-        headers.insert(
-            USER_AGENT,
-            HeaderValue::from_str("NextNode-Project-Generator/1.0")
-                .map_err(|_| "Failed to create user-agent header")?,
-        );
+        features: &[String],
+    ) -> Result<(), GeneratorError> {
+        let repo_path = self.repo_path(repo_name, "");
 
-        let client = reqwest::Client::new();
+        for feature in features {
+            // Dependabot features have dedicated endpoints, the others live in security_and_analysis
+            let (method, path, body) = match feature.as_str() {
+                "dependabot_alerts" => (
+                    Method::PUT,
+                    format!("{}/vulnerability-alerts", repo_path),
+                    None,
+                ),
+                "dependabot_security_updates" => (
+                    Method::PUT,
+                    format!("{}/automated-security-fixes", repo_path),
+                    None,
+                ),
+                _ => (
+                    Method::PATCH,
+                    repo_path.clone(),
+                    Some(json!({
+                        "security_and_analysis": { feature.as_str(): { "status": "enabled" } }
+                    })),
+                ),
+            };
 
-        // First, get the SHA of the main branch
-        let main_ref_response = client
-            .get(format!(
-                "https://api.github.com/repos/{}/{}/git/refs/heads/main",
-                org_name, repo_name
-            ))
-            .headers(headers.clone())
-            .send()
-            .await
-            .map_err(|e| format!("Failed to get main branch SHA: {}", e))?;
+            if self.dry_run {
+                self.print_dry_run(&format!(
+                    "enable security feature '{}' on {}",
+                    feature,
+                    GitHubClient::url(&repo_path)
+                ));
+                continue;
+            }
 
-        if !main_ref_response.status().is_success() {
-            let error = main_ref_response
-                .text()
-                .await
-                .map_err(|e| format!("Failed to read error response: {}", e))?;
-            return Err(format!("GitHub API error getting main branch: {}", error).into());
+            self.client
+                .execute(
+                    method,
+                    &path,
+                    body.as_ref(),
+                    format!("GitHub API error enabling security feature {}", feature),
+                )
+                .await?;
+
+            log::info!("✅ Enabled security feature: {}", feature);
         }
 
-        let main_ref_data: serde_json::Value = main_ref_response
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse main branch response: {}", e))?;
+        Ok(())
+    }
 
-        let main_sha = main_ref_data["object"]["sha"]
-            .as_str()
-            .ok_or("No SHA found in main branch response")?;
-
-        println!("📋 Main branch SHA: {}", main_sha);
-
-        // Check if develop branch already exists
-        let develop_check_response = client
-            .get(format!(
-                "https://api.github.com/repos/{}/{}/git/refs/heads/develop",
-                org_name, repo_name
-            ))
-            .headers(headers.clone())
-            .send()
-            .await;
+    pub async fn repository_exists(&self, repo_name: &str) -> Result<bool, GeneratorError> {
+        let path = self.repo_path(repo_name, "");
+
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "GET {} to check whether it exists",
+                GitHubClient::url(&path)
+            ));
+            return Ok(false);
+        }
+
+        let repository: Option<Value> = self
+            .client
+            .get_optional(&path, "GitHub API error looking up repository")
+            .await?;
+        Ok(repository.is_some())
+    }
+
+    pub async fn delete_repository(&self, repo_name: &str) -> Result<(), GeneratorError> {
+        let path = self.repo_path(repo_name, "");
+
+        if self.dry_run {
+            self.print_dry_run(&format!("DELETE {}", GitHubClient::url(&path)));
+            return Ok(());
+        }
+
+        self.client
+            .execute(
+                Method::DELETE,
+                &path,
+                None,
+                "GitHub API error deleting repository",
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn is_user_account(&self, login: &str) -> Result<bool, GeneratorError> {
+        let path = format!("/users/{}", login);
+
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "GET {} to check the account type",
+                GitHubClient::url(&path)
+            ));
+            return Ok(false);
+        }
+
+        let account: Value = self
+            .client
+            .get(
+                &path,
+                format!("GitHub API error looking up account {}", login),
+            )
+            .await?;
+
+        Ok(account["type"].as_str() == Some("User"))
+    }
+
+    pub async fn transfer_repository(
+        &self,
+        repo_name: &str,
+        new_owner: &str,
+    ) -> Result<(), GeneratorError> {
+        let path = self.repo_path(repo_name, "/transfer");
+        let body = json!({ "new_owner": new_owner });
+
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "POST {} with body {}",
+                GitHubClient::url(&path),
+                body
+            ));
+            return Ok(());
+        }
+
+        self.client
+            .execute(
+                Method::POST,
+                &path,
+                Some(&body),
+                "GitHub API error transferring repository",
+            )
+            .await?;
+        Ok(())
+    }
 
-        if let Ok(response) = develop_check_response {
+    /// Poll the API `path` until it answers successfully or `timeout` elapses
+    async fn wait_until_available(
+        &self,
+        path: &str,
+        what: &str,
+        timeout: Duration,
+    ) -> Result<(), GeneratorError> {
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "poll GET {} until {} is available",
+                GitHubClient::url(path),
+                what
+            ));
+            return Ok(());
+        }
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let response = self.client.send(Method::GET, path, None).await?;
             if response.status().is_success() {
-                println!("ℹ️  Develop branch already exists, skipping creation");
                 return Ok(());
             }
+            if Instant::now() >= deadline {
+                return Err(GeneratorError::Other(format!(
+                    "{} not available after {}s",
+                    what,
+                    timeout.as_secs()
+                )));
+            }
+            log::debug!("{} not available yet ({})", what, response.status());
+            tokio::time::sleep(Duration::from_secs(2)).await;
         }
+    }
 
-        // Create develop branch from main SHA
-        let create_branch_body = json!({
-            "ref": "refs/heads/develop",
-            "sha": main_sha
-        });
+    /// Poll until the repository is reachable, e.g. after a transfer completes
+    pub async fn wait_for_repository(
+        &self,
+        repo_name: &str,
+        timeout: Duration,
+    ) -> Result<(), GeneratorError> {
+        let path = self.repo_path(repo_name, "");
+        let what = format!("Repository {}/{}", self.organization, repo_name);
+        self.wait_until_available(&path, &what, timeout).await
+    }
+
+    /// Poll until a pushed branch is visible through the API
+    pub async fn wait_for_branch(
+        &self,
+        repo_name: &str,
+        branch: &str,
+    ) -> Result<(), GeneratorError> {
+        let path = self.repo_path(repo_name, &format!("/git/ref/heads/{}", branch));
+        let what = format!("Branch {}", branch);
+        self.wait_until_available(&path, &what, self.ready_timeout)
+            .await
+    }
 
-        let create_response = client
-            .post(format!(
-                "https://api.github.com/repos/{}/{}/git/refs",
-                org_name, repo_name
-            ))
-            .headers(headers)
-            .json(&create_branch_body)
-            .send()
+    /// Poll until GitHub has indexed a pushed workflow file
+    pub async fn wait_for_workflow(
+        &self,
+        repo_name: &str,
+        workflow_file: &str,
+    ) -> Result<(), GeneratorError> {
+        let path = self.repo_path(repo_name, &format!("/actions/workflows/{}", workflow_file));
+        let what = format!("Workflow {}", workflow_file);
+        self.wait_until_available(&path, &what, self.ready_timeout)
             .await
-            .map_err(|e| format!("Failed to create develop branch: {}", e))?;
+    }
+
+    /// Encrypt each value with the repository public key and store it as an Actions secret
+    pub async fn set_repository_secrets(
+        &self,
+        repo_name: &str,
+        secrets: &[(String, String)],
+    ) -> Result<(), GeneratorError> {
+        let base_path = self.repo_path(repo_name, "/actions/secrets");
+        self.upload_secrets(&base_path, secrets).await
+    }
+
+    /// Create or update plain-text Actions variables, read by workflows as `vars.<NAME>`
+    pub async fn set_repository_variables(
+        &self,
+        repo_name: &str,
+        variables: &[(String, String)],
+    ) -> Result<(), GeneratorError> {
+        let base_path = self.repo_path(repo_name, "/actions/variables");
+        for (name, value) in variables {
+            let body = json!({ "name": name, "value": value });
+            if self.dry_run {
+                self.print_dry_run(&format!(
+                    "POST {} with body {}",
+                    GitHubClient::url(&base_path),
+                    body
+                ));
+                continue;
+            }
 
-        if !create_response.status().is_success() {
-            let error = create_response
-                .text()
+            let response = self
+                .client
+                .send(Method::POST, &base_path, Some(&body))
                 .await
-                .map_err(|e| format!("Failed to read error response: {}", e))?;
-            return Err(format!("GitHub API error creating develop branch: {}", error).into());
-        }
+                .map_err(|e| e.context(format!("Failed to set variable {}", name)))?;
+            // The variable exists already, e.g. pushing to an existing repository
+            let response = if response.status() == reqwest::StatusCode::CONFLICT {
+                self.client
+                    .send(
+                        Method::PATCH,
+                        &format!("{}/{}", base_path, name),
+                        Some(&body),
+                    )
+                    .await
+                    .map_err(|e| e.context(format!("Failed to update variable {}", name)))?
+            } else {
+                response
+            };
 
-        println!("✅ Successfully created develop branch from main");
+            if !response.status().is_success() {
+                return Err(GeneratorError::from_response(
+                    response,
+                    format!("GitHub API error for variable {}", name),
+                )
+                .await);
+            }
+        }
         Ok(())
     }
 
-    pub async fn setup_repository_branches(
+    /// Same as `set_repository_secrets`, scoped to a deployment environment
+    pub async fn set_environment_secrets(
         &self,
         repo_name: &str,
-        create_develop: bool,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        if create_develop {
-            println!("🔧 Creating develop branch...");
+        environment: &str,
+        secrets: &[(String, String)],
+    ) -> Result<(), GeneratorError> {
+        let base_path =
+            self.repo_path(repo_name, &format!("/environments/{}/secrets", environment));
+        self.upload_secrets(&base_path, secrets).await
+    }
+
+    /// Secrets of `required` (name and job environment) that the workflows could not read:
+    /// neither a repository secret, an organization secret shared with the repository, nor a
+    /// secret of the job's environment
+    pub async fn find_missing_secrets(
+        &self,
+        repo_name: &str,
+        required: &[(String, Option<String>)],
+    ) -> Result<Vec<(String, Option<String>)>, GeneratorError> {
+        let repo_path = self.repo_path(repo_name, "");
+        let repo_url = GitHubClient::url(&repo_path);
+        let mut environments: Vec<&str> = Vec::new();
+        for environment in required.iter().filter_map(|(_, e)| e.as_deref()) {
+            if !environments.contains(&environment) {
+                environments.push(environment);
+            }
+        }
+
+        if self.dry_run {
+            self.print_dry_run(&format!("GET {}/actions/secrets", repo_url));
+            self.print_dry_run(&format!("GET {}/actions/organization-secrets", repo_url));
+            for environment in environments {
+                self.print_dry_run(&format!(
+                    "GET {}/environments/{}/secrets",
+                    repo_url, environment
+                ));
+            }
+            return Ok(Vec::new());
+        }
+
+        let mut shared = self
+            .secret_names(&format!("{}/actions/secrets", repo_path))
+            .await?;
+        shared.extend(
+            self.secret_names(&format!("{}/actions/organization-secrets", repo_path))
+                .await?,
+        );
+        let mut environment_secrets = HashMap::new();
+        for environment in environments {
+            let names = self
+                .secret_names(&format!(
+                    "{}/environments/{}/secrets",
+                    repo_path, environment
+                ))
+                .await?;
+            environment_secrets.insert(environment, names);
+        }
+
+        Ok(required
+            .iter()
+            .filter(|(name, environment)| {
+                !shared.contains(name)
+                    && !environment
+                        .as_deref()
+                        .and_then(|environment| environment_secrets.get(environment))
+                        .is_some_and(|names| names.contains(name))
+            })
+            .cloned()
+            .collect())
+    }
+
+    /// Names listed by a secrets endpoint, none when it does not exist
+    async fn secret_names(&self, path: &str) -> Result<Vec<String>, GeneratorError> {
+        let secrets: Vec<Value> = match self
+            .client
+            .paginate(path, Some("secrets"), "GitHub API error listing secrets")
+            .await
+        {
+            Ok(secrets) => secrets,
+            Err(GeneratorError::Api { status: 404, .. }) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        Ok(secrets
+            .iter()
+            .filter_map(|secret| secret["name"].as_str().map(str::to_string))
+            .collect())
+    }
+
+    /// Upload secrets to a secrets endpoint (`.../secrets`) sealed with its public key
+    async fn upload_secrets(
+        &self,
+        base_path: &str,
+        secrets: &[(String, String)],
+    ) -> Result<(), GeneratorError> {
+        if self.dry_run {
+            for (name, _) in secrets {
+                self.print_dry_run(&format!(
+                    "PUT {}/{} with an encrypted value",
+                    GitHubClient::url(base_path),
+                    name
+                ));
+            }
+            return Ok(());
+        }
+
+        let public_key: Value = self
+            .client
+            .get(
+                &format!("{}/public-key", base_path),
+                "GitHub API error fetching public key",
+            )
+            .await?;
+        let key_id = public_key["key_id"]
+            .as_str()
+            .ok_or_else(|| GeneratorError::Http("No key_id in public key response".to_string()))?;
+        let key = STANDARD
+            .decode(
+                public_key["key"].as_str().ok_or_else(|| {
+                    GeneratorError::Http("No key in public key response".to_string())
+                })?,
+            )
+            .map_err(|e| GeneratorError::Http(format!("Invalid repository public key: {}", e)))?;
+
+        for (name, value) in secrets {
+            let body = json!({
+                "encrypted_value": STANDARD.encode(sealed_box::seal(value.as_bytes(), &key)?),
+                "key_id": key_id,
+            });
+
+            self.client
+                .execute(
+                    Method::PUT,
+                    &format!("{}/{}", base_path, name),
+                    Some(&body),
+                    format!("GitHub API error setting secret {}", name),
+                )
+                .await?;
+            log::info!("🔑 Secret {} set", name);
+        }
+
+        Ok(())
+    }
+
+    /// Numeric id of a user or team, as required for environment reviewers
+    async fn lookup_id(&self, path: &str) -> Result<u64, GeneratorError> {
+        let data: Value = self
+            .client
+            .get(path, format!("GitHub API error looking up {}", path))
+            .await?;
+        data["id"]
+            .as_u64()
+            .ok_or_else(|| GeneratorError::Http(format!("No id in response for {}", path)))
+    }
 
-            // Wait a bit for the repository to be fully initialized after push
-            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+    /// Teams (`org/slug`) that do not exist, e.g. before pushing a CODEOWNERS file using them
+    pub async fn find_missing_teams(
+        &self,
+        teams: &[String],
+    ) -> Result<Vec<String>, GeneratorError> {
+        if self.dry_run {
+            for team in teams {
+                self.print_dry_run(&format!("check that team {} exists", team));
+            }
+            return Ok(Vec::new());
+        }
+
+        let mut missing = Vec::new();
+        for team in teams {
+            let Some((org, slug)) = team.split_once('/') else {
+                missing.push(team.clone());
+                continue;
+            };
+            let found: Option<Value> = self
+                .client
+                .get_optional(
+                    &format!("/orgs/{}/teams/{}", org, slug),
+                    format!("GitHub API error looking up team {}", team),
+                )
+                .await?;
+            if found.is_none() {
+                missing.push(team.clone());
+            }
+        }
+        Ok(missing)
+    }
 
-            match self.create_develop_branch(repo_name).await {
-                Ok(_) => println!("✅ Develop branch created successfully"),
-                Err(e) => eprintln!("⚠️  Warning: Failed to create develop branch: {}", e),
+    /// Custom domains of `domains` already served by the GitHub Pages site of another
+    /// repository of the owner than `repo_name`, as (domain, `owner/repo`) pairs
+    pub async fn find_pages_domains_in_use(
+        &self,
+        domains: &[String],
+        repo_name: &str,
+    ) -> Result<Vec<(String, String)>, GeneratorError> {
+        let repos_path = if self.owner_is_user {
+            format!("/users/{}/repos?type=owner", self.organization)
+        } else {
+            format!("/orgs/{}/repos?type=all", self.organization)
+        };
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "check that no GitHub Pages site listed by GET {} uses {}",
+                GitHubClient::url(&repos_path),
+                domains.join(", ")
+            ));
+            return Ok(Vec::new());
+        }
+
+        let repositories: Vec<Value> = self
+            .client
+            .paginate(&repos_path, None, "GitHub API error listing repositories")
+            .await?;
+        let mut in_use = Vec::new();
+        for repository in &repositories {
+            let Some(name) = repository["name"].as_str() else {
+                continue;
+            };
+            if name == repo_name || repository["has_pages"].as_bool() != Some(true) {
+                continue;
             }
+            let pages: Option<Value> = self
+                .client
+                .get_optional(
+                    &self.repo_path(name, "/pages"),
+                    format!("GitHub API error looking up the Pages site of {}", name),
+                )
+                .await?;
+            let Some(cname) = pages.as_ref().and_then(|pages| pages["cname"].as_str()) else {
+                continue;
+            };
+            let cname = cname.trim_end_matches('.').to_lowercase();
+            if let Some(domain) = domains.iter().find(|domain| **domain == cname) {
+                in_use.push((domain.clone(), format!("{}/{}", self.organization, name)));
+            }
+        }
+        Ok(in_use)
+    }
+
+    /// Create or update a deployment environment with its protection rules
+    pub async fn create_environment(
+        &self,
+        repo_name: &str,
+        environment: &str,
+        settings: &EnvironmentSettings,
+    ) -> Result<(), GeneratorError> {
+        let path = self.repo_path(repo_name, &format!("/environments/{}", environment));
+
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "PUT {} with reviewers {:?}, team reviewers {:?}, wait timer {:?}",
+                GitHubClient::url(&path),
+                settings.reviewers,
+                settings.team_reviewers,
+                settings.wait_timer
+            ));
+            return Ok(());
+        }
+
+        let mut reviewers = Vec::new();
+        for user in &settings.reviewers {
+            let id = self.lookup_id(&format!("/users/{}", user)).await?;
+            reviewers.push(json!({ "type": "User", "id": id }));
+        }
+        for team in &settings.team_reviewers {
+            let id = self
+                .lookup_id(&format!("/orgs/{}/teams/{}", self.organization, team))
+                .await?;
+            reviewers.push(json!({ "type": "Team", "id": id }));
+        }
+
+        let deployment_branch_policy = if settings.protected_branches_only {
+            json!({ "protected_branches": true, "custom_branch_policies": false })
         } else {
-            println!("ℹ️  Skipping develop branch creation (not configured)");
+            serde_json::Value::Null
+        };
+        let body = json!({
+            "wait_timer": settings.wait_timer.unwrap_or(0),
+            "reviewers": reviewers,
+            "deployment_branch_policy": deployment_branch_policy,
+        });
+
+        self.client
+            .execute(
+                Method::PUT,
+                &path,
+                Some(&body),
+                format!("GitHub API error creating environment {}", environment),
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn grant_team_permission(
+        &self,
+        repo_name: &str,
+        team_slug: &str,
+        permission: &str,
+    ) -> Result<(), GeneratorError> {
+        let org_name = self.organization.as_str();
+        let path = format!(
+            "/orgs/{}/teams/{}/repos/{}/{}",
+            org_name, team_slug, org_name, repo_name
+        );
+        let body = json!({ "permission": permission });
+
+        if self.dry_run {
+            self.print_dry_run(&format!(
+                "PUT {} with body {}",
+                GitHubClient::url(&path),
+                body
+            ));
+            return Ok(());
         }
 
-        println!("✅ Repository setup completed!");
+        self.client
+            .execute(
+                Method::PUT,
+                &path,
+                Some(&body),
+                format!(
+                    "GitHub API error granting {} to team {}",
+                    permission, team_slug
+                ),
+            )
+            .await?;
         Ok(())
     }
 }
+
+/// Rejected credentials, which retrying does not fix
+fn is_git_auth_error(error: &git2::Error) -> bool {
+    if error.code() == git2::ErrorCode::Auth {
+        return true;
+    }
+    let message = error.message().to_lowercase();
+    ["401", "403", "authentication", "permission denied", "saml"]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+/// Network, server and not-yet-available repository errors
+fn is_transient_git_error(error: &git2::Error) -> bool {
+    matches!(
+        error.class(),
+        git2::ErrorClass::Net
+            | git2::ErrorClass::Os
+            | git2::ErrorClass::Http
+            | git2::ErrorClass::Ssh
+    ) || error.message().to_lowercase().contains("not found")
+}
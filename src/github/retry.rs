@@ -0,0 +1,235 @@
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::trace;
+use crate::utils::error::GeneratorError;
+
+/// Retry settings shared by every GitHub API call
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retrying after the given failed attempt
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Send the request, retrying transient failures and rate limits.
+    /// Non-retryable responses (including errors) are returned as-is for the caller to decode,
+    /// except SAML SSO refusals, which no caller can recover from.
+    /// A POST may have been applied when it timed out or got a server error, e.g. a repository
+    /// created anyway: it is only retried when it did not reach GitHub or was rate limited.
+    async fn send(&self, request: RequestBuilder) -> Result<Response, GeneratorError> {
+        let idempotent = !request
+            .try_clone()
+            .and_then(|request| request.build().ok())
+            .is_some_and(|request| request.method() == Method::POST);
+        let mut attempt = 1;
+        loop {
+            let current = request.try_clone().ok_or_else(|| {
+                GeneratorError::Other("Request body cannot be retried".to_string())
+            })?;
+
+            let delay = match trace::send(current).await {
+                Ok(response) => match retry_delay(&response, idempotent) {
+                    Some(delay) if attempt < self.max_attempts => {
+                        log::debug!(
+                            "GitHub API returned {} (attempt {}/{})",
+                            response.status(),
+                            attempt,
+                            self.max_attempts
+                        );
+                        delay.unwrap_or_else(|| self.backoff(attempt))
+                    }
+                    _ => match sso_error(&response) {
+                        Some(message) => {
+                            return Err(GeneratorError::Api {
+                                status: response.status().as_u16(),
+                                message,
+                            })
+                        }
+                        None => return Ok(response),
+                    },
+                },
+                Err(e) if is_transient(&e, idempotent) && attempt < self.max_attempts => {
+                    log::debug!(
+                        "Transient error calling GitHub API (attempt {}/{}): {}",
+                        attempt,
+                        self.max_attempts,
+                        e
+                    );
+                    self.backoff(attempt)
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            log::info!(
+                "⏳ GitHub API call failed, retrying in {}s (attempt {}/{})...",
+                delay.as_secs(),
+                attempt + 1,
+                self.max_attempts
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// Lets request chains end with `.send_with_retry(policy)` instead of `.send()`
+pub trait SendWithRetry {
+    fn send_with_retry(
+        self,
+        policy: RetryPolicy,
+    ) -> impl std::future::Future<Output = Result<Response, GeneratorError>> + Send;
+}
+
+impl SendWithRetry for RequestBuilder {
+    async fn send_with_retry(self, policy: RetryPolicy) -> Result<Response, GeneratorError> {
+        policy.send(self).await
+    }
+}
+
+/// Connection failures never reached GitHub, other request errors may have
+fn is_transient(error: &reqwest::Error, idempotent: bool) -> bool {
+    error.is_connect() || (idempotent && (error.is_timeout() || error.is_request()))
+}
+
+/// `None` when the response should not be retried, `Some(None)` to use the
+/// exponential backoff, `Some(Some(delay))` when GitHub told us how long to wait.
+/// Server errors are only retried for idempotent requests.
+fn retry_delay(response: &Response, idempotent: bool) -> Option<Option<Duration>> {
+    let status = response.status();
+    let headers = response.headers();
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+    };
+
+    let retry_after = header("retry-after").map(Duration::from_secs);
+    let rate_limit_reset = (header("x-ratelimit-remaining") == Some(0))
+        .then(|| header("x-ratelimit-reset"))
+        .flatten()
+        .map(|reset| {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            Duration::from_secs(reset.saturating_sub(now) + 1)
+        });
+
+    let rate_limited = status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::FORBIDDEN
+            && (retry_after.is_some() || rate_limit_reset.is_some()));
+
+    if rate_limited || (idempotent && status.is_server_error()) {
+        Some(retry_after.or(rate_limit_reset))
+    } else {
+        None
+    }
+}
+
+/// Explain a refusal by an organization enforcing SAML single sign-on, which GitHub signals
+/// with `X-GitHub-SSO: required; url=<authorization url>` on a 403
+fn sso_error(response: &Response) -> Option<String> {
+    if response.status() != StatusCode::FORBIDDEN {
+        return None;
+    }
+    let sso = response.headers().get("x-github-sso")?.to_str().ok()?;
+    if !sso.trim_start().starts_with("required") {
+        return None;
+    }
+    let url = sso
+        .split(';')
+        .find_map(|part| part.trim().strip_prefix("url="))
+        .unwrap_or("https://github.com/settings/tokens");
+
+    Some(format!(
+        "GitHub refused {}: the organization enforces SAML single sign-on and the token is not \
+         authorized for it.\n\
+         Authorize the token for the organization, then run the command again:\n  {}\n\
+         (or from https://github.com/settings/tokens, with \"Configure SSO\" next to the token)",
+        response.url().path(),
+        url
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, headers: &[(&str, &str)]) -> Response {
+        let mut builder = http::Response::builder().status(status);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body("").unwrap().into()
+    }
+
+    #[test]
+    fn backs_off_exponentially_up_to_the_maximum() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(4), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn retries_server_errors_of_idempotent_requests_only() {
+        assert_eq!(retry_delay(&response(502, &[]), true), Some(None));
+        assert_eq!(retry_delay(&response(502, &[]), false), None);
+        assert_eq!(retry_delay(&response(422, &[]), true), None);
+    }
+
+    #[test]
+    fn retries_rate_limits_after_the_given_delay() {
+        let limited = response(429, &[("retry-after", "7")]);
+        assert_eq!(
+            retry_delay(&limited, false),
+            Some(Some(Duration::from_secs(7)))
+        );
+        let limited = response(403, &[("retry-after", "2")]);
+        assert_eq!(
+            retry_delay(&limited, false),
+            Some(Some(Duration::from_secs(2)))
+        );
+        assert_eq!(retry_delay(&response(403, &[]), true), None);
+    }
+
+    #[test]
+    fn explains_sso_refusals() {
+        let refused = response(
+            403,
+            &[(
+                "x-github-sso",
+                "required; url=https://github.com/orgs/acme/sso",
+            )],
+        );
+        assert!(sso_error(&refused)
+            .unwrap()
+            .contains("https://github.com/orgs/acme/sso"));
+        assert_eq!(sso_error(&response(403, &[])), None);
+    }
+}
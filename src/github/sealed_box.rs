@@ -0,0 +1,40 @@
+//! libsodium-compatible sealed boxes (`crypto_box_seal`), as required by the GitHub Actions
+//! secrets API.
+
+use crypto_box::aead::OsRng;
+use crypto_box::PublicKey;
+
+use crate::utils::error::GeneratorError;
+
+/// Encrypt `message` for the holder of the X25519 `public_key`:
+/// ephemeral public key, then the XSalsa20-Poly1305 box of the message.
+pub fn seal(message: &[u8], public_key: &[u8]) -> Result<Vec<u8>, GeneratorError> {
+    let public_key: [u8; 32] = public_key.try_into().map_err(|_| {
+        GeneratorError::Http(format!(
+            "Invalid repository public key: expected 32 bytes, got {}",
+            public_key.len()
+        ))
+    })?;
+    PublicKey::from(public_key)
+        .seal(&mut OsRng, message)
+        .map_err(|e| GeneratorError::Other(format!("Failed to encrypt secret: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_box::SecretKey;
+
+    #[test]
+    fn sealed_messages_open_with_the_secret_key() {
+        let secret_key = SecretKey::generate(&mut OsRng);
+        let sealed = seal(b"hunter2", secret_key.public_key().as_bytes()).unwrap();
+        // Ephemeral public key and Poly1305 tag come first
+        assert_eq!(sealed.len(), 32 + 16 + 7);
+        assert_eq!(secret_key.unseal(&sealed).unwrap(), b"hunter2");
+
+        let other_key = SecretKey::generate(&mut OsRng);
+        assert!(other_key.unseal(&sealed).is_err());
+        assert!(seal(b"hunter2", &[0u8; 31]).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::auth::Auth;
+use super::repo::GitHubRepo;
+use super::PullRequestOptions;
+use crate::generate::lockfile;
+use crate::template::registry;
+use crate::utils::strings;
+
+/// Directory of the registry repository holding one `category/name.json` file per template
+pub const REGISTRY_DIR: &str = "templates";
+
+/// Open a pull request adding the template's metadata to the organization's template registry
+/// (`owner/repo`), unless the registry already lists it or a pull request adding it is open.
+/// `template_path` is the template as found in the templates repository.
+pub async fn propose(
+    auth: Arc<Auth>,
+    registry: &str,
+    category: &str,
+    name: &str,
+    template_path: &Path,
+    options: &PullRequestOptions,
+) -> Result<()> {
+    let (owner, repo) = registry.split_once('/').ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Invalid template registry '{}', expected owner/repo",
+                registry
+            ),
+        )
+    })?;
+    let dry_run = options.dry_run;
+    let github_repo = GitHubRepo::new(auth, owner, dry_run)
+        .with_retry_policy(options.retry)
+        .with_commit_settings(options.commit.clone());
+    let entry_path = format!("{}/{}/{}.json", REGISTRY_DIR, category, name);
+    let branch = format!("register-{}-{}", category, name);
+
+    if github_repo.file_exists(repo, &entry_path).await? {
+        log::debug!("{}/{} is already in the template registry", category, name);
+        return Ok(());
+    }
+    if let Some(url) = github_repo.find_open_pull_request(repo, &branch).await? {
+        log::info!(
+            "📚 {}/{} is awaiting approval in the template registry: {}",
+            category,
+            name,
+            url
+        );
+        return Ok(());
+    }
+
+    let entry = serde_json::to_string_pretty(&registry_entry(template_path, category, name))?;
+    let checkout = std::env::temp_dir().join(format!(
+        "project-registry-{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+    ));
+    let result = async {
+        let base = if dry_run {
+            log::info!("🔍 [dry-run] Would add {} to {}", entry_path, registry);
+            "main".to_string()
+        } else {
+            let base = github_repo
+                .clone_repository(repo, &checkout)
+                .await?;
+            let file = checkout.join(&entry_path);
+            if let Some(parent) = file.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(file, entry.clone() + "\n")?;
+            base
+        };
+
+        let title = format!("Register the {}/{} template", category, name);
+        github_repo
+            .push_new_branch(&checkout, repo, &branch, &title)
+            .await
+            .map_err(|e| e.context(format!("Failed to push {}", branch)))?;
+        let body = format!(
+            "The `{}/{}` template was used for the first time and is not in the registry yet.\n\n```json\n{}\n```",
+            category, name, entry
+        );
+        let url = github_repo
+            .create_pull_request(repo, &branch, &base, &title, &body)
+            .await?;
+        if !dry_run {
+            log::info!(
+                "📚 Opened pull request registering {}/{}: {}",
+                category,
+                name,
+                url
+            );
+        }
+        Ok(())
+    }
+    .await;
+
+    if checkout.exists() {
+        if let Err(e) = fs::remove_dir_all(&checkout) {
+            log::warn!("Failed to remove '{}': {}", checkout.display(), e);
+        }
+    }
+    result
+}
+
+/// Where the template comes from (repository and commit) and what it asks for
+fn registry_entry(template_path: &Path, category: &str, name: &str) -> serde_json::Value {
+    let manifest = strings::read_template_manifest(template_path).unwrap_or_default();
+    let info = registry::template_info(template_path, category, name);
+    let mut entry = serde_json::to_value(lockfile::template_record(template_path, &manifest))
+        .unwrap_or_default();
+    entry["description"] = info.description.into();
+    entry["variables"] = info.variables.into();
+    if let Some(range) = manifest.generator_version {
+        entry["generator_version"] = range.into();
+    }
+    entry
+}
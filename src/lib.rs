@@ -3,6 +3,8 @@ pub mod cli;
 pub mod config;
 pub mod generate;
 pub mod github;
+pub mod plan;
+pub mod policy;
 pub mod template;
 pub mod utils;
 
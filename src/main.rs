@@ -3,111 +3,468 @@ mod cli;
 mod config;
 mod generate;
 mod github;
+mod plan;
+mod policy;
 mod template;
 mod utils;
 
 use args::Args;
 use clap::Parser;
 use cli::{get_template_info, prompt_for_repo_name};
+use generate::project_generator::GenerateOptions;
 use generate::{handle_config_mode, handle_interactive_mode};
-use github::{create_github_repository_with_code, extract_organization_from_repo_url};
-use std::io::{Error, ErrorKind, Result};
+use github::{
+    create_github_repository_with_code, extract_organization_from_repo_url, EnvironmentSetup,
+    RemoteOptions, RepositorySettings,
+};
 use template::TemplateManager;
+use utils::error::GeneratorError;
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
     let args = Args::parse();
 
-    // Set debug mode in the global context
-    utils::context::set_debug_mode(args.debug);
+    // --debug is the historical spelling of -v
+    utils::logging::init(
+        args.verbose.max(u8::from(args.debug)),
+        args.quiet,
+        args.log_format,
+    );
+    if !args.no_progress {
+        // The spinner would be torn apart by debug messages
+        let verbosity = args.verbose.max(u8::from(args.debug));
+        utils::progress::enable(
+            verbosity == 0 && !args.quiet && args.log_format == utils::logging::LogFormat::Text,
+        );
+    }
+    if args.output == utils::summary::OutputFormat::Json {
+        utils::summary::enable(args.dry_run || args.plan.is_some());
+    }
 
-    // Get template branch from config if available
-    let template_branch = if let Some(config_path) = &args.config {
-        let config = crate::config::file_config::from_file(config_path).ok();
-        config
-            .as_ref()
-            .map(|c| c.get_template_branch())
-            .map(|s| s.to_string())
-    } else {
-        None
+    let result = run(args).await;
+    utils::summary::print(&result);
+    if let Err(e) = result {
+        log::error!("{}", e);
+        std::process::exit(e.exit_code());
+    }
+}
+
+async fn run(args: Args) -> Result<(), GeneratorError> {
+    // Variables from the environment, the answers file and the command line, which override
+    // the config file
+    let mut variables = config::environment::load(args.env_file.as_deref())?;
+    if let Some(answers_path) = &args.answers {
+        variables.extend(config::answers::load(answers_path)?);
+        cli::functions::disable_prompts();
+    }
+    variables.extend(args.vars.iter().cloned());
+    utils::context::set_variables(variables);
+
+    if let Some(trace_path) = &args.trace_http {
+        github::trace::enable(trace_path)?;
+        log::info!("📝 Tracing HTTP calls to {}", trace_path.display());
+    }
+
+    if let Some(reference) = &args.template_stats {
+        generate::stats::print(reference.as_deref())?;
+        return Ok(());
+    }
+    let sandbox = args
+        .isolated_home
+        .as_ref()
+        .map(|home| generate::sandbox::enable(home.as_deref()))
+        .transpose()?;
+    let mut generate_options = GenerateOptions {
+        prefetch: args.prefetch,
+        record_stats: !(args.no_stats || args.dry_run || args.plan.is_some()),
+        install: generate::package_manager::InstallOptions {
+            skip: args.skip_install,
+            offline: args.offline,
+            home: sandbox.as_ref().map(|sandbox| sandbox.home().to_path_buf()),
+        },
+        notify: args.notify,
+        ..Default::default()
     };
 
+    // Get template branch and source from config if available
+    let file_config = args
+        .config
+        .as_ref()
+        .and_then(|config_path| crate::config::file_config::from_file(config_path).ok());
+
+    // One HTTP client for the whole run, built before the first request
+    github::http::configure(&github::http::HttpSettings {
+        connect_timeout: args
+            .connect_timeout
+            .or_else(|| file_config.as_ref().and_then(|c| c.connect_timeout))
+            .map_or(
+                github::http::DEFAULT_CONNECT_TIMEOUT,
+                std::time::Duration::from_secs,
+            ),
+        timeout: args
+            .http_timeout
+            .or_else(|| file_config.as_ref().and_then(|c| c.http_timeout))
+            .map_or(
+                github::http::DEFAULT_TIMEOUT,
+                std::time::Duration::from_secs,
+            ),
+        proxy: args
+            .proxy
+            .clone()
+            .or_else(|| file_config.as_ref().and_then(|c| c.proxy.clone())),
+        ca_certificates: args
+            .ca_certificates
+            .clone()
+            .or_else(|| file_config.as_ref().and_then(|c| c.ca_certificates.clone())),
+    })?;
+
+    // Every commit the generator makes is signed once a signing format is configured
+    let signing = args
+        .sign_commits
+        .or_else(|| file_config.as_ref().and_then(|c| c.commit_signing));
+    let signing = match signing {
+        Some(format) => {
+            let key = args
+                .signing_key
+                .clone()
+                .or_else(|| file_config.as_ref().and_then(|c| c.signing_key.clone()));
+            let signing = utils::commit::Signing::new(format, key)?;
+            if !args.dry_run && args.plan.is_none() {
+                signing.check()?;
+            }
+            Some(signing)
+        }
+        None => None,
+    };
+    let template_branch = file_config
+        .as_ref()
+        .map(|c| c.get_template_branch())
+        .map(|s| s.to_string());
+    // An update reads the template from the repository the project was generated from
+    let update_lock = args
+        .update
+        .as_deref()
+        .map(generate::lockfile::read)
+        .transpose()?;
+    let template_source = args
+        .template_source
+        .clone()
+        .or_else(|| file_config.as_ref().and_then(|c| c.template_source.clone()))
+        .or_else(|| {
+            update_lock
+                .as_ref()
+                .and_then(|lock| lock.template.source.clone())
+                .filter(|source| {
+                    *source != format!("{}{}", config::REPO_URL, config::TEMPLATE_REPO_URL)
+                })
+        });
+
     // Initialize template manager and clone the repository
-    let template_manager = TemplateManager::new(template_branch.as_deref()).unwrap_or_else(|err| {
-        utils::error::print_error_and_exit_with_error("Failed to initialize template manager", &err)
-    });
+    let template_manager = match &template_source {
+        Some(source) => TemplateManager::from_source(source, args.template_cache_dir.as_deref()),
+        None => TemplateManager::new(template_branch.as_deref()),
+    }
+    .map_err(|e| GeneratorError::from(e).context("Failed to initialize template manager"))?;
+
+    if let Some(project_path) = &args.update {
+        generate::update::update_project(
+            &template_manager,
+            project_path,
+            args.update_patch.as_deref(),
+            args.allow_incompatible_template,
+        )?;
+        return Ok(());
+    }
+
+    if args.list_templates {
+        let templates = template::registry::list(&template_manager, args.search.as_deref())?;
+        template::registry::print_list(&templates);
+        return Ok(());
+    }
+
+    if let Some(reference) = &args.describe_template {
+        let (category, name) = template::registry::find(&template_manager, reference)?;
+        let info = template::registry::template_info(
+            &template_manager.get_template_path(&category, &name),
+            &category,
+            &name,
+        );
+        let template_path = template_manager.resolve_template_path(&category, &name)?;
+        template::registry::print_description(&info, &template_path);
+        return Ok(());
+    }
+
+    if let Some(reference) = &args.inspect_template {
+        let (category, name) = template::registry::find(&template_manager, reference)?;
+        let info = template::registry::template_info(
+            &template_manager.get_template_path(&category, &name),
+            &category,
+            &name,
+        );
+        let template_path = template_manager.resolve_template_path(&category, &name)?;
+        template::registry::print_inspection(&info, &template_path)?;
+        return Ok(());
+    }
 
     // Get template info and path
     let (category, template_name) = get_template_info(&args, &template_manager)?;
-    let template_path = template_manager.get_template_path(&category, &template_name);
+    let template_path = template_manager.resolve_template_path(&category, &template_name)?;
+    template::compatibility::check(
+        &template_path,
+        &format!("{}/{}", category, template_name),
+        args.allow_incompatible_template,
+    )?;
+    utils::summary::record_template(&format!("{}/{}", category, template_name));
+    if args.answers.is_some() {
+        generate::variables::check_answers(&template_path)?;
+    }
+
+    // Org naming conventions apply in every mode: to the names known so far, then to the
+    // names prompted for
+    let mut org_policy = policy::load(args.policy.as_deref(), template_manager.root_path())?;
+    let team = file_config.as_ref().and_then(|c| c.team.clone());
+    let mut variables = utils::context::get_variables();
+    org_policy
+        .naming
+        .apply_to_variables(&mut variables, team.as_deref())?;
+    utils::context::set_variables(variables);
+    generate_options.naming = org_policy.naming.clone();
+    generate_options.team = team;
+
+    if let Some(registry) = &args.register_template {
+        let registry = registry
+            .clone()
+            .or_else(|| file_config.as_ref().and_then(|c| c.template_registry.clone()))
+            .ok_or_else(|| {
+                GeneratorError::Config("--register-template needs a registry (owner/repo) or template_registry in the config file".to_string())
+            })?;
+        let (auth, _) = cli::resolve_auth(&args)?;
+        let options = pull_request_options(&args, file_config.as_ref(), signing.as_ref())?;
+        // The generation goes on without the registry
+        if let Err(e) = github::template_registry::propose(
+            auth,
+            &registry,
+            &category,
+            &template_name,
+            &template_manager.get_template_path(&category, &template_name),
+            &options,
+        )
+        .await
+        {
+            log::warn!("Failed to propose the template to the registry: {}", e);
+        }
+    }
+
+    if let Some(monorepo) = &args.into_repo {
+        let project_name = utils::context::get_variable("project_name").ok_or_else(|| {
+            GeneratorError::Config(
+                "project_name is required to add a package to a monorepo".to_string(),
+            )
+        })?;
+        let (auth, _) = cli::resolve_auth(&args)?;
+        let options = pull_request_options(&args, file_config.as_ref(), signing.as_ref())?;
+        github::monorepo::add_package(
+            auth,
+            monorepo,
+            &category,
+            &template_path,
+            &project_name,
+            &options,
+            &generate_options,
+        )
+        .await?;
+        return Ok(());
+    }
 
     // Handle local generation first (early return)
     if !args.remote {
+        let commit_settings = commit_settings(&args, file_config.as_ref(), signing.as_ref())?;
+        let git_init = args.git_init.then_some(&commit_settings);
         // Handle generation based on mode
-        if args.config.is_none() {
-            return handle_interactive_mode(&template_path)
-                .map_err(|e| Error::other(e.to_string()));
+        if args.config.is_none() && args.answers.is_none() {
+            handle_interactive_mode(
+                &template_path,
+                args.into.as_deref(),
+                git_init,
+                &generate_options,
+            )?;
+            return Ok(());
         }
 
         // Get project name from variables
-        let project_name = utils::context::get_variable("project_name").unwrap_or_else(|| {
-            utils::error::print_error_and_exit("project_name is required in configuration file")
-        });
+        let project_name = utils::context::get_variable("project_name").ok_or_else(|| {
+            GeneratorError::Config("project_name is required in configuration file".to_string())
+        })?;
 
-        return handle_config_mode(&template_path, &project_name)
-            .map_err(|e| Error::other(e.to_string()));
+        if let Some(into) = &args.into {
+            generate::handle_into_mode(&template_path, &project_name, into, &generate_options)?;
+            return Ok(());
+        }
+
+        handle_config_mode(&template_path, &project_name, git_init, &generate_options)?;
+        return Ok(());
     }
 
     // Remote mode: generate project locally, then create GitHub repo
-    let token = args
-        .token
-        .or_else(|| std::env::var("GITHUB_TOKEN").ok())
-        .ok_or_else(|| {
-            Error::new(
-                ErrorKind::InvalidInput,
-                "GitHub token is required for remote mode. Set GITHUB_TOKEN env var or use --token",
-            )
-        })?;
+    let (auth, account_organization) = cli::resolve_auth(&args)?;
+
+    // A plan is a dry run recording every action. Applying one records the actions of the run
+    // to compare them with the reviewed plan before anything is created.
+    let dry_run = args.dry_run || args.plan.is_some();
+    let reviewed_plan = args.apply_plan.as_deref().map(plan::load).transpose()?;
+    if args.plan.is_some() || reviewed_plan.is_some() {
+        plan::start_recording();
+    }
 
     // Config file is required for remote mode - check early
     let config_path = args.config.as_ref().ok_or_else(|| {
-        Error::new(
-            ErrorKind::InvalidInput,
-            "Config file is required for remote mode. Use --config to specify a config file.",
+        GeneratorError::Config(
+            "Config file is required for remote mode. Use --config to specify a config file."
+                .to_string(),
         )
     })?;
 
     // Read and parse config file early to get project name and validate github_tag
-    let file_config = crate::config::file_config::from_file(config_path).map_err(|e| {
-        Error::new(
-            ErrorKind::InvalidData,
-            format!("Failed to read config file: {}", e),
-        )
-    })?;
+    let file_config = crate::config::file_config::from_file(config_path)
+        .map_err(|e| GeneratorError::Config(format!("Failed to read config file: {}", e)))?;
 
     // Validate github_tag early (before pulling code)
-    file_config.validate_github_tag().map_err(|e| {
-        Error::new(
-            ErrorKind::InvalidData,
-            format!("GitHub tag validation failed: {}", e),
-        )
+    file_config
+        .validate_github_tag()
+        .map_err(|e| GeneratorError::Config(format!("GitHub tag validation failed: {}", e)))?;
+
+    file_config.validate_security_features().map_err(|e| {
+        GeneratorError::Config(format!("Security features validation failed: {}", e))
     })?;
 
-    let project_name = file_config.project_name.clone();
-    if project_name.is_empty() {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            "project_name is required in config file",
-        ));
+    file_config
+        .validate_teams()
+        .map_err(|e| GeneratorError::Config(format!("Team validation failed: {}", e)))?;
+
+    file_config.validate_git_auth().map_err(|e| {
+        GeneratorError::Config(format!("Git authentication validation failed: {}", e))
+    })?;
+
+    let commit_settings = commit_settings(&args, Some(&file_config), signing.as_ref())?;
+
+    // Repository settings shipped by the template itself
+    let template_manifest =
+        utils::strings::read_template_manifest(&template_path).map_err(|e| {
+            GeneratorError::Template(format!("Failed to read template configuration: {}", e))
+        })?;
+
+    // The command line overrides the configuration file, which overrides the template default
+    let visibility = args
+        .visibility
+        .or(file_config.visibility)
+        .or(template_manifest.repository.visibility)
+        .unwrap_or_default();
+
+    // Topics from the template, configuration file and command line, rendered with the
+    // variables known so far, since nothing has been prompted for yet
+    let variables = utils::context::get_variables();
+    let normalize_topic = |topic: &String| {
+        github::render_metadata("topic", topic, &variables).map(|topic| topic.to_lowercase())
+    };
+    let mut topics: Vec<String> = Vec::new();
+    for topic in template_manifest
+        .repository
+        .topics
+        .iter()
+        .chain(&file_config.get_topics())
+        .chain(&args.topics)
+        .chain(&template_manifest.project_kind.topic().map(str::to_string))
+    {
+        let topic = normalize_topic(topic)?;
+        if !topics.contains(&topic) {
+            topics.push(topic);
+        }
     }
+    github::validate_topics(&topics)?;
 
-    // Get organization from REPO_URL
-    let organization = extract_organization_from_repo_url()?;
-    println!("Using organization: {}", organization);
+    // Mandatory topics are compared with the normalized ones, they must be supplied
+    org_policy.mandatory_topics = org_policy
+        .mandatory_topics
+        .iter()
+        .map(normalize_topic)
+        .collect::<Result<_, GeneratorError>>()?;
+
+    // Enforce the organization policy before anything is generated or created
+    org_policy.enforce(&policy::PolicySubject {
+        visibility: visibility.as_str(),
+        topics: topics.iter().map(|s| s.as_str()).collect(),
+        security_features: file_config.get_security_features(),
+        license: file_config.get_license(),
+    })?;
+
+    // The config file values, unless overridden by the environment or the command line, with
+    // the naming conventions already applied
+    let project_name = utils::context::get_variable("project_name")
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| {
+            GeneratorError::Config(
+                "project_name is required in config file (or GEN_PROJECT_NAME, \
+                 --var project_name=...)"
+                    .to_string(),
+            )
+        })?;
+
+    // Get organization from the selected account, or from REPO_URL
+    let organization = match account_organization {
+        Some(organization) => organization,
+        None => extract_organization_from_repo_url()?,
+    };
+    log::info!("Using organization: {}", organization);
+    utils::context::set_variable("organization", &organization);
 
     // Ask for repository name with option to use project name
     let repo_name = prompt_for_repo_name(&project_name)
-        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Repository name is required"))?;
+        .ok_or_else(|| GeneratorError::Config("Repository name is required".to_string()))?;
+    let repo_name =
+        org_policy
+            .naming
+            .apply(&repo_name, file_config.team.as_deref(), "repository name")?;
+
+    // Unique hostnames, rendered into the deploy config and exposed to the workflows. A plan
+    // allocates them, so that applying it renders the reviewed hostnames instead of new ones.
+    let mut hostnames = indexmap::IndexMap::new();
+    if let Some(service) = &file_config.hostname_service {
+        match &reviewed_plan {
+            Some(reviewed) if !reviewed.hostnames.is_empty() => {
+                log::info!("🌐 Using the hostnames allocated for the plan");
+                hostnames = reviewed.hostnames.clone();
+            }
+            _ if args.dry_run => {
+                let action = format!(
+                    "request {} hostnames from {}",
+                    service.environments.join(", "),
+                    service.url
+                );
+                log::info!("🔍 [dry-run] Would {}", action);
+                plan::record_remote(&action);
+            }
+            _ => {
+                hostnames = generate::hostnames::allocate(
+                    service,
+                    &organization,
+                    &repo_name,
+                    &project_name,
+                )
+                .await?
+                .into_iter()
+                .collect();
+            }
+        }
+    }
+    let mut hostname_variables = Vec::new();
+    for (environment, hostname) in &hostnames {
+        log::info!("🌐 Allocated {} hostname: {}", environment, hostname);
+        utils::context::set_variable(&generate::hostnames::variable_name(environment), hostname);
+        hostname_variables.push((
+            generate::hostnames::actions_variable_name(environment),
+            hostname.clone(),
+        ));
+    }
 
     // Create temporary directory for remote mode
     let temp_dir = std::env::temp_dir().join(format!("project-generator-{}", project_name));
@@ -120,52 +477,403 @@ async fn main() -> Result<()> {
             &project_name,
             &project_path,
             false,
-        )
-        .map_err(|e| Error::other(e.to_string()))?;
+            &generate_options,
+        )?;
     } else {
-        handle_interactive_mode(&template_path)
-            .map_err(|e| Error::other(e.to_string()))?;
+        handle_interactive_mode(&template_path, None, None, &generate_options)?;
     }
 
-    // Install dependencies AFTER copying template files but BEFORE Git operations
-    crate::generate::project_generator::install_dependencies(&project_path).map_err(|e| {
-        Error::other(
-            format!("Failed to install dependencies: {}", e),
-        )
-    })?;
+    if plan::is_recording() {
+        plan::record_files(&project_path)?;
+        if !generate_options.install.skip {
+            for command in generate::project_kind::ProjectKind::for_project(&project_path)
+                .install_commands(&project_path, generate_options.install.offline)
+            {
+                plan::record(plan::ActionKind::Command, &command);
+            }
+        }
+        if generate::quality::is_enabled() {
+            plan::record(
+                plan::ActionKind::Command,
+                &format!(
+                    "record the quality baseline in {}",
+                    generate::quality::BASELINE_PATH
+                ),
+            );
+        }
+    }
+    if args.plan.is_none() {
+        // Install dependencies AFTER copying template files but BEFORE Git operations
+        crate::generate::project_generator::install_dependencies(&project_path, &generate_options)
+            .map_err(|e| GeneratorError::from(e).context("Failed to install dependencies"))?;
+
+        // Baseline scores are committed with the initial push
+        crate::generate::quality::record_baseline(
+            &template_path,
+            &project_path,
+            &generate_options.install,
+        )?;
+    }
+
+    // Description and homepage from the variables, else from the template, rendered with the
+    // final variables
+    let variables = utils::context::get_variables();
+    let config_field = |field: &str| variables.get(field).filter(|v| !v.is_empty()).cloned();
+    let description = match config_field("description")
+        .or_else(|| template_manifest.repository.description.clone())
+    {
+        Some(description) => github::render_metadata("description", &description, &variables)?,
+        None => "Generated project".to_string(),
+    };
+    github::validate_description(&description)?;
+    let homepage = config_field("homepage")
+        .or_else(|| template_manifest.repository.homepage.clone())
+        .map(|homepage| github::render_metadata("homepage", &homepage, &variables))
+        .transpose()?
+        .filter(|homepage| !homepage.is_empty());
+    if let Some(homepage) = &homepage {
+        github::validate_homepage(homepage)?;
+    }
+
+    let secrets = cli::resolve_secrets(&template_manifest.repository.secrets, None, dry_run)?;
+    let mut environments = Vec::new();
+    for (name, environment) in &template_manifest.repository.environments {
+        environments.push(EnvironmentSetup {
+            name: name.clone(),
+            secrets: cli::resolve_secrets(&environment.secrets, Some(name), dry_run)?,
+            settings: environment.clone(),
+        });
+    }
+
+    // The release flow needs its environment, gated by the template's approval rules
+    if let Some(flow) = template_manifest
+        .release
+        .as_ref()
+        .filter(|_| generate::release::is_requested())
+    {
+        match environments.iter().find(|e| e.name == flow.environment) {
+            Some(environment) if environment.settings.protected_branches_only => log::warn!(
+                "Environment {} only accepts protected branches, release tags cannot deploy to it",
+                flow.environment
+            ),
+            Some(_) => {}
+            None => environments.push(EnvironmentSetup {
+                name: flow.environment.clone(),
+                settings: config::EnvironmentSettings {
+                    reviewers: flow.reviewers.clone(),
+                    team_reviewers: flow.team_reviewers.clone(),
+                    wait_timer: flow.wait_timer,
+                    ..Default::default()
+                },
+                secrets: Vec::new(),
+            }),
+        }
+    }
+
+    // Branches and deployments come from the template, the config can still ask for develop
+    let mut branches = template_manifest.repository.branches.clone();
+    if file_config.should_create_develop_branch() && !branches.iter().any(|b| b == "develop") {
+        branches.push("develop".to_string());
+    }
+    let mut deployments = template_manifest
+        .repository
+        .deployments
+        .clone()
+        .unwrap_or_else(|| config::Deployment::detect(&project_path));
+    let shipped = |workflow: &str| {
+        project_path
+            .join(generate::workflows::WORKFLOWS_DIR)
+            .join(workflow)
+            .is_file()
+    };
+    deployments.retain_mut(|deployment| {
+        if let Some(rollback) = deployment.rollback_workflow.take() {
+            if shipped(&rollback) {
+                deployment.rollback_workflow = Some(rollback);
+            } else {
+                log::warn!(
+                    "Rollback workflow {} is not part of the project, {} will not be rolled back",
+                    rollback,
+                    deployment.workflow
+                );
+            }
+        }
+        if !shipped(&deployment.workflow) {
+            log::warn!(
+                "Deployment workflow {} is not part of the project, it will not be triggered",
+                deployment.workflow
+            );
+            return false;
+        }
+        if deployment.branch != "main" && !branches.contains(&deployment.branch) {
+            log::warn!(
+                "Branch {} is not created, {} will not be triggered on it",
+                deployment.branch,
+                deployment.workflow
+            );
+            return false;
+        }
+        true
+    });
+    // Inputs are rendered with the final variables, like the repository metadata
+    for deployment in &mut deployments {
+        // GitHub refuses the whole dispatch when one input is not declared
+        if let Some(declared) =
+            generate::workflows::dispatch_inputs(&project_path, &deployment.workflow)
+        {
+            for name in deployment
+                .inputs
+                .keys()
+                .filter(|name| !declared.contains(name))
+            {
+                log::warn!(
+                    "{} does not declare the workflow_dispatch input {}, GitHub will refuse to dispatch it",
+                    deployment.workflow, name
+                );
+            }
+        }
+        for (name, value) in deployment.inputs.iter_mut() {
+            *value =
+                generate::render::render(value, &variables, &Default::default()).map_err(|e| {
+                    GeneratorError::Config(format!(
+                        "Invalid input {} of {}: {}",
+                        name, deployment.workflow, e
+                    ))
+                })?;
+        }
+    }
+    // With the release flow, production is only deployed from approved release tags
+    if let Some(flow) = template_manifest
+        .release
+        .as_ref()
+        .filter(|_| generate::release::is_requested())
+    {
+        if deployments
+            .iter()
+            .any(|d| d.workflow == flow.deploy_workflow)
+        {
+            log::info!(
+                "🏷️  Release flow enabled, {} deploys from approved release tags",
+                flow.deploy_workflow
+            );
+            deployments.retain(|d| d.workflow != flow.deploy_workflow);
+        }
+    }
+
+    // Secrets the deployments read, checked before they are dispatched
+    let mut required_secrets = Vec::new();
+    for deployment in &deployments {
+        for (name, environment) in
+            generate::workflows::required_secrets(&project_path, &deployment.workflow)?
+        {
+            required_secrets.push(github::RequiredSecret {
+                name,
+                environment,
+                workflow: deployment.workflow.clone(),
+            });
+        }
+    }
 
-    // Get description from config or use default
-    let description = file_config
-        .additional_vars
-        .get("description")
-        .and_then(|v| v.as_str())
-        .unwrap_or("Generated project")
-        .to_string();
+    // Catch broken workflows before they are pushed and dispatched
+    let declared_secrets: Vec<String> = template_manifest
+        .repository
+        .secrets
+        .iter()
+        .chain(environments.iter().flat_map(|e| &e.settings.secrets))
+        .map(|secret| secret.name.clone())
+        .collect();
+    generate::workflows::validate(&project_path, &declared_secrets, args.validate_workflows)?;
+
+    // Required checks of queued pull requests are only reported by workflows run on the queue
+    let merge_queue = file_config
+        .merge_queue
+        .clone()
+        .or(template_manifest.repository.merge_queue);
+    if let Some(queue) = &merge_queue {
+        if !queue.required_checks.is_empty()
+            && generate::workflows::triggered_by(&project_path, "merge_group").is_empty()
+        {
+            log::warn!(
+                "The merge queue requires {} but no workflow runs on merge_group, queued pull requests would time out waiting for it",
+                queue.required_checks.join(", ")
+            );
+        }
+    }
 
     // Create GitHub repository and push the code (includes full Git workflow)
-    let github_tag = file_config.get_github_tag().map(|s| s.as_str());
-    let create_develop = file_config.should_create_develop_branch();
-    let result = create_github_repository_with_code(
-        &token,
-        &repo_name,
-        &project_path,
-        description.as_str(),
-        github_tag,
-        create_develop,
-    )
-    .await;
+    let settings = RepositorySettings {
+        organization,
+        description,
+        homepage,
+        visibility,
+        topics,
+        branches,
+        security_features: file_config.get_security_features().to_vec(),
+        branch_protection: template_manifest.repository.branch_protection,
+        merge_queue,
+        staging_owner: file_config.staging_owner.clone(),
+        admin_team: file_config.admin_team.clone(),
+        teams: file_config.get_teams(),
+        secrets,
+        variables: hostname_variables,
+        submodules: template_manifest.repository.submodules,
+        environments,
+        deployments,
+        required_secrets,
+        hooks: template_manifest.hooks,
+    };
+    let mut options = RemoteOptions {
+        dry_run,
+        cleanup_on_failure: args.cleanup_on_failure,
+        on_exists: args.on_exists,
+        workflow_timeout: args
+            .wait_for_workflows
+            .then(|| std::time::Duration::from_secs(args.workflow_timeout)),
+        ready_timeout: std::time::Duration::from_secs(args.ready_timeout),
+        retry: retry_policy(&args),
+        api_headers: github::headers::ApiHeaders {
+            user_agent: args
+                .user_agent
+                .clone()
+                .or_else(|| file_config.user_agent.clone())
+                .unwrap_or_else(|| github::headers::DEFAULT_USER_AGENT.to_string()),
+            api_version: args
+                .github_api_version
+                .clone()
+                .or_else(|| file_config.github_api_version.clone())
+                .unwrap_or_else(|| github::headers::DEFAULT_API_VERSION.to_string()),
+        },
+        git_auth: file_config.get_git_auth(),
+        ssh_key_path: file_config.get_ssh_key_path(),
+        commit: commit_settings,
+        home: generate_options.install.home.clone(),
+        notify: generate_options.notify,
+    };
+    let result = match &reviewed_plan {
+        Some(reviewed) => {
+            apply_plan(
+                reviewed,
+                auth,
+                &repo_name,
+                &project_path,
+                &settings,
+                &mut options,
+            )
+            .await
+        }
+        None => {
+            create_github_repository_with_code(auth, &repo_name, &project_path, &settings, &options)
+                .await
+        }
+    };
+    // The plan reads the template revision and variables from the generated project
+    let result = match (&args.plan, result) {
+        (Some(plan_path), Ok(())) => plan::finish(&project_path)
+            .and_then(|mut plan| {
+                plan.hostnames = hostnames;
+                plan::print(&plan);
+                plan::save(&plan, plan_path)?;
+                log::info!("📝 Saved plan to {}", plan_path.display());
+                Ok(())
+            })
+            .map_err(GeneratorError::from),
+        (_, result) => result,
+    };
 
-    // Clean up temporary directory
-    if let Err(e) = std::fs::remove_dir_all(&project_path) {
-        eprintln!(
-            "Warning: Failed to clean up temporary directory '{}': {}",
-            project_path.display(),
-            e
-        );
-    } else {
-        println!("Temporary directory cleaned up successfully");
+    // Clean up temporary directory (it may already be gone after a full rollback)
+    if project_path.exists() {
+        if let Err(e) = std::fs::remove_dir_all(&project_path) {
+            log::warn!(
+                "Failed to clean up temporary directory '{}': {}",
+                project_path.display(),
+                e
+            );
+        } else {
+            log::info!("Temporary directory cleaned up successfully");
+        }
     }
 
     result?;
     Ok(())
 }
+
+/// Retries of GitHub requests and git pushes, from the command line
+fn retry_policy(args: &Args) -> github::retry::RetryPolicy {
+    github::retry::RetryPolicy {
+        max_attempts: args.retry_attempts.max(1),
+        initial_backoff: std::time::Duration::from_millis(args.retry_backoff_ms),
+        ..Default::default()
+    }
+}
+
+/// Options of the pull requests opened against another repository (template registry,
+/// monorepo)
+fn pull_request_options(
+    args: &Args,
+    file_config: Option<&config::file_config::FileConfig>,
+    signing: Option<&utils::commit::Signing>,
+) -> Result<github::PullRequestOptions, GeneratorError> {
+    Ok(github::PullRequestOptions {
+        dry_run: args.dry_run,
+        retry: retry_policy(args),
+        commit: commit_settings(args, file_config, signing)?,
+    })
+}
+
+/// Initial commit settings of the configuration file, overridden by the command line, with
+/// the signing of every commit
+fn commit_settings(
+    args: &Args,
+    file_config: Option<&config::file_config::FileConfig>,
+    signing: Option<&utils::commit::Signing>,
+) -> Result<config::CommitSettings, GeneratorError> {
+    let mut commit_settings = file_config
+        .map(|file_config| file_config.get_commit_settings())
+        .unwrap_or_default();
+    commit_settings.signing = signing.cloned();
+    if let Some(message) = &args.commit_message {
+        commit_settings.message = message.clone();
+    }
+    if let Some((name, email)) = &args.commit_author {
+        commit_settings.author_name = name.clone();
+        commit_settings.author_email = email.clone();
+    }
+    if commit_settings.message.trim().is_empty() {
+        return Err(GeneratorError::Config(
+            "The commit message cannot be empty".to_string(),
+        ));
+    }
+    Ok(commit_settings)
+}
+
+/// Dry-run the remote actions to check the run against the reviewed plan, then run it for real
+async fn apply_plan(
+    reviewed: &plan::Plan,
+    auth: std::sync::Arc<github::auth::Auth>,
+    repo_name: &str,
+    project_path: &std::path::Path,
+    settings: &RepositorySettings,
+    options: &mut RemoteOptions,
+) -> Result<(), GeneratorError> {
+    log::info!("🔍 Checking the run against the plan...");
+    options.dry_run = true;
+    utils::summary::pause();
+    let rehearsal = create_github_repository_with_code(
+        auth.clone(),
+        repo_name,
+        project_path,
+        settings,
+        options,
+    )
+    .await;
+    utils::summary::resume();
+    rehearsal?;
+    plan::verify(reviewed, &plan::finish(project_path)?)?;
+
+    log::info!(
+        "✅ The run matches the plan, applying {} actions",
+        reviewed.actions.len()
+    );
+    options.dry_run = false;
+    create_github_repository_with_code(auth, repo_name, project_path, settings, options).await
+}
@@ -0,0 +1,309 @@
+use indexmap::IndexMap;
+use openssl::sha::sha256;
+use std::fs;
+use std::io::{Error, ErrorKind, Result, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::config::EXCLUDED_DIRS;
+use crate::generate::lockfile::{self, GeneratorLock};
+use crate::utils::summary;
+
+/// Everything a run would do, in order, for the template revision and variables it was
+/// produced with
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Plan {
+    #[serde(flatten)]
+    pub lock: GeneratorLock,
+    /// Hostnames allocated per environment while planning, reused when the plan is applied
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub hostnames: IndexMap<String, String>,
+    pub actions: Vec<Action>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Action {
+    pub kind: ActionKind,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionKind {
+    /// A generated file, with its content hash
+    File,
+    /// A local command run in the generated project
+    Command,
+    /// A git operation on the generated project
+    Git,
+    /// A GitHub API call
+    Api,
+    /// A workflow dispatch
+    Workflow,
+}
+
+impl ActionKind {
+    fn label(self) -> &'static str {
+        match self {
+            ActionKind::File => "file",
+            ActionKind::Command => "command",
+            ActionKind::Git => "git",
+            ActionKind::Api => "api",
+            ActionKind::Workflow => "workflow",
+        }
+    }
+}
+
+/// Actions recorded by the running plan, `None` when not planning
+static RECORDING: Mutex<Option<Vec<Action>>> = Mutex::new(None);
+
+/// Record the actions of this run instead of performing the remote ones
+pub fn start_recording() {
+    if let Ok(mut recording) = RECORDING.lock() {
+        *recording = Some(Vec::new());
+    }
+}
+
+pub fn is_recording() -> bool {
+    RECORDING.lock().is_ok_and(|recording| recording.is_some())
+}
+
+pub fn record(kind: ActionKind, description: &str) {
+    if let Ok(mut recording) = RECORDING.lock() {
+        if let Some(actions) = recording.as_mut() {
+            actions.push(Action {
+                kind,
+                description: description.to_string(),
+            });
+        }
+    }
+}
+
+/// Record a skipped remote action, as described by the dry-run output
+pub fn record_remote(description: &str) {
+    let kind = if description.starts_with("git ") {
+        ActionKind::Git
+    } else if description.contains("/dispatches") {
+        ActionKind::Workflow
+    } else {
+        ActionKind::Api
+    };
+    record(kind, description);
+}
+
+/// Record every file of the generated project, in path order
+pub fn record_files(project_path: &Path) -> Result<()> {
+    let mut files = Vec::new();
+    collect_files(project_path, project_path, &mut files)?;
+    files.sort();
+    for relative_path in files {
+        let content = fs::read(project_path.join(&relative_path))?;
+        record(
+            ActionKind::File,
+            &format!(
+                "write {} (sha256 {})",
+                relative_path,
+                hex(&sha256(&content))
+            ),
+        );
+    }
+    Ok(())
+}
+
+fn collect_files(dir: &Path, root: &Path, files: &mut Vec<String>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let file_name_str = file_name.to_string_lossy();
+        if entry.file_type()?.is_dir() {
+            if file_name_str != ".git" && !EXCLUDED_DIRS.contains(&file_name_str.as_ref()) {
+                collect_files(&path, root, files)?;
+            }
+            continue;
+        }
+        files.push(
+            path.strip_prefix(root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/"),
+        );
+    }
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Stop recording and build the plan, reading the template revision and variables from the
+/// lockfile of the generated project
+pub fn finish(project_path: &Path) -> Result<Plan> {
+    let actions = RECORDING
+        .lock()
+        .ok()
+        .and_then(|mut recording| recording.take())
+        .unwrap_or_default();
+    Ok(Plan {
+        lock: lockfile::read(project_path)?,
+        hostnames: IndexMap::new(),
+        actions,
+    })
+}
+
+pub fn save(plan: &Plan, path: &Path) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(plan)? + "\n")?;
+    log::debug!("Saved plan to {}", path.display());
+    Ok(())
+}
+
+pub fn load(path: &Path) -> Result<Plan> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        Error::new(
+            e.kind(),
+            format!("Failed to read plan {}: {}", path.display(), e),
+        )
+    })?;
+    serde_json::from_str(&content).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid plan {}: {}", path.display(), e),
+        )
+    })
+}
+
+/// Refuse a run whose template revision, variables or actions differ from the reviewed plan
+pub fn verify(reviewed: &Plan, current: &Plan) -> Result<()> {
+    let mut differences = Vec::new();
+
+    let (planned, now) = (&reviewed.lock.template, &current.lock.template);
+    if (&planned.category, &planned.name) != (&now.category, &now.name) {
+        differences.push(format!(
+            "the template is {}/{}, the plan was produced for {}/{}",
+            now.category, now.name, planned.category, planned.name
+        ));
+    } else if planned.commit != now.commit {
+        differences.push(format!(
+            "the template is at {}, the plan was produced at {}",
+            now.commit.as_deref().unwrap_or("an unknown revision"),
+            planned.commit.as_deref().unwrap_or("an unknown revision")
+        ));
+    }
+
+    let (planned, now) = (&reviewed.lock.variables, &current.lock.variables);
+    let changed: Vec<&str> = planned
+        .keys()
+        .chain(now.keys().filter(|name| !planned.contains_key(*name)))
+        .filter(|name| planned.get(*name) != now.get(*name))
+        .map(String::as_str)
+        .collect();
+    if !changed.is_empty() {
+        differences.push(format!("variables changed: {}", changed.join(", ")));
+    }
+
+    if reviewed.actions != current.actions {
+        let first = reviewed
+            .actions
+            .iter()
+            .zip(&current.actions)
+            .position(|(planned, now)| planned != now)
+            .unwrap_or(reviewed.actions.len().min(current.actions.len()));
+        let describe = |actions: &[Action]| {
+            actions
+                .get(first)
+                .map(|action| format!("[{}] {}", action.kind.label(), action.description))
+                .unwrap_or_else(|| "nothing".to_string())
+        };
+        differences.push(format!(
+            "{} actions planned, {} now; action {} was {}, is now {}",
+            reviewed.actions.len(),
+            current.actions.len(),
+            first + 1,
+            describe(&reviewed.actions),
+            describe(&current.actions)
+        ));
+    }
+
+    if differences.is_empty() {
+        return Ok(());
+    }
+    Err(Error::new(
+        ErrorKind::InvalidData,
+        format!(
+            "The run no longer matches the plan, refusing to apply it:\n  - {}",
+            differences.join("\n  - ")
+        ),
+    ))
+}
+
+pub fn print(plan: &Plan) {
+    let mut out = summary::status_output();
+    let template = &plan.lock.template;
+    let _ = writeln!(
+        out,
+        "\n📋 Plan for {}/{} at {} ({} actions):",
+        template.category,
+        template.name,
+        template.commit.as_deref().unwrap_or("an unknown revision"),
+        plan.actions.len()
+    );
+    for (environment, hostname) in &plan.hostnames {
+        let _ = writeln!(out, "       {} hostname: {}", environment, hostname);
+    }
+    for (index, action) in plan.actions.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "  {:>3}. [{}] {}",
+            index + 1,
+            action.kind.label(),
+            action.description
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::lockfile::{GeneratorRecord, TemplateRecord};
+
+    fn plan() -> Plan {
+        Plan {
+            lock: GeneratorLock {
+                generator: GeneratorRecord {
+                    name: "project-generator".to_string(),
+                    version: "0.1.0".to_string(),
+                },
+                template: TemplateRecord {
+                    category: "apps".to_string(),
+                    name: "demo".to_string(),
+                    version: None,
+                    source: None,
+                    commit: Some("abc".to_string()),
+                    extends: None,
+                    includes: Vec::new(),
+                },
+                variables: [("project_name".to_string(), "demo".to_string())].into(),
+                secret_variables: Vec::new(),
+            },
+            hostnames: IndexMap::new(),
+            actions: vec![Action {
+                kind: ActionKind::Api,
+                description: "POST /orgs/acme/repos".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn plans_keep_the_allocated_hostnames() {
+        let mut planned = plan();
+        assert!(!serde_json::to_string(&planned)
+            .unwrap()
+            .contains("hostnames"));
+
+        planned
+            .hostnames
+            .insert("prod".to_string(), "demo.sites.example.com".to_string());
+        let loaded: Plan = serde_json::from_str(&serde_json::to_string(&planned).unwrap()).unwrap();
+        assert_eq!(loaded.hostnames, planned.hostnames);
+    }
+}
@@ -0,0 +1,134 @@
+use std::fs;
+use std::path::Path;
+
+use crate::utils::error::GeneratorError;
+
+pub mod naming;
+
+/// File looked up at the root of the templates repository when no `--policy` is given
+pub const ORG_POLICY_FILE: &str = "org_policy.yaml";
+
+/// Organization-wide constraints checked before any GitHub resource is created
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct OrgPolicy {
+    #[serde(default)]
+    pub allowed_visibilities: Vec<String>,
+    #[serde(default)]
+    pub mandatory_topics: Vec<String>,
+    #[serde(default)]
+    pub required_security_features: Vec<String>,
+    #[serde(default)]
+    pub forbidden_licenses: Vec<String>,
+    #[serde(default)]
+    pub naming: naming::NamingRules,
+}
+
+/// What a generation run is about to create, as seen by the policy
+pub struct PolicySubject<'a> {
+    pub visibility: &'a str,
+    pub topics: Vec<&'a str>,
+    pub security_features: &'a [String],
+    pub license: Option<&'a str>,
+}
+
+impl OrgPolicy {
+    pub fn check(&self, subject: &PolicySubject) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if !self.allowed_visibilities.is_empty()
+            && !self
+                .allowed_visibilities
+                .iter()
+                .any(|v| v.eq_ignore_ascii_case(subject.visibility))
+        {
+            violations.push(format!(
+                "Visibility '{}' is not allowed (allowed: {})",
+                subject.visibility,
+                self.allowed_visibilities.join(", ")
+            ));
+        }
+
+        for topic in &self.mandatory_topics {
+            if !subject.topics.contains(&topic.as_str()) {
+                violations.push(format!("Mandatory topic '{}' is missing", topic));
+            }
+        }
+
+        for feature in &self.required_security_features {
+            if !subject.security_features.contains(feature) {
+                violations.push(format!(
+                    "Required security feature '{}' is not enabled",
+                    feature
+                ));
+            }
+        }
+
+        if let Some(license) = subject.license {
+            if self
+                .forbidden_licenses
+                .iter()
+                .any(|l| l.eq_ignore_ascii_case(license))
+            {
+                violations.push(format!("License '{}' is forbidden", license));
+            }
+        }
+
+        violations
+    }
+
+    pub fn enforce(&self, subject: &PolicySubject) -> Result<(), GeneratorError> {
+        let violations = self.check(subject);
+        if violations.is_empty() {
+            log::debug!("Organization policy check passed");
+            return Ok(());
+        }
+
+        let report = violations
+            .iter()
+            .map(|v| format!("  - {}", v))
+            .collect::<Vec<String>>()
+            .join("\n");
+        Err(GeneratorError::Config(format!(
+            "Organization policy violations:\n{}",
+            report
+        )))
+    }
+}
+
+/// Load the policy from an explicit path, or from the templates repository root.
+/// A missing org policy file means no constraints.
+pub fn load(
+    explicit_path: Option<&Path>,
+    templates_root: &Path,
+) -> Result<OrgPolicy, GeneratorError> {
+    let path = match explicit_path {
+        Some(path) => path.to_path_buf(),
+        None => {
+            let default_path = templates_root.join(ORG_POLICY_FILE);
+            if !default_path.exists() {
+                log::debug!("No organization policy found, skipping policy checks");
+                return Ok(OrgPolicy::default());
+            }
+            default_path
+        }
+    };
+
+    log::debug!("Loading organization policy: {}", path.display());
+
+    let content = fs::read_to_string(&path).map_err(|e| {
+        GeneratorError::Config(format!(
+            "Failed to read policy file '{}': {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    // YAML is a superset of JSON, so both formats are accepted here
+    serde_yaml::from_str(&content).map_err(|e| {
+        GeneratorError::Config(format!(
+            "Failed to parse policy file '{}': {}",
+            path.display(),
+            e
+        ))
+    })
+}
@@ -0,0 +1,292 @@
+use regex::Regex;
+use std::collections::HashMap;
+
+use crate::utils::error::GeneratorError;
+
+/// Org-defined naming conventions for repositories, packages and hostnames
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct NamingRules {
+    #[serde(default)]
+    pub kebab_case: bool,
+    #[serde(default)]
+    pub max_length: Option<usize>,
+    /// Prefix required for each team, e.g. `frontend: "fe-"`
+    #[serde(default)]
+    pub team_prefixes: HashMap<String, String>,
+    /// Rewrite non-compliant names instead of failing
+    #[serde(default)]
+    pub auto_apply: bool,
+    /// Template variables holding deployment hostnames or URLs
+    #[serde(default)]
+    pub hostname_variables: Vec<String>,
+}
+
+impl NamingRules {
+    fn team_prefix(&self, team: Option<&str>) -> Result<Option<&str>, GeneratorError> {
+        match team {
+            Some(team) if !self.team_prefixes.is_empty() => self
+                .team_prefixes
+                .get(team)
+                .map(|p| Some(p.as_str()))
+                .ok_or_else(|| {
+                    GeneratorError::Config(format!(
+                        "Unknown team '{}' (known teams: {})",
+                        team,
+                        self.team_prefixes
+                            .keys()
+                            .cloned()
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    ))
+                }),
+            _ => Ok(None),
+        }
+    }
+
+    fn violations(&self, name: &str, prefix: Option<&str>) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if self.kebab_case {
+            let kebab = Regex::new(r"^[a-z0-9]+(-[a-z0-9]+)*$").unwrap();
+            if !kebab.is_match(name) {
+                violations.push("must be kebab-case".to_string());
+            }
+        }
+
+        if let Some(prefix) = prefix {
+            if !name.starts_with(prefix) {
+                violations.push(format!("must start with '{}'", prefix));
+            }
+        }
+
+        if let Some(max_length) = self.max_length {
+            if name.chars().count() > max_length {
+                violations.push(format!("must be at most {} characters", max_length));
+            }
+        }
+
+        violations
+    }
+
+    fn normalize(&self, name: &str, prefix: Option<&str>) -> String {
+        let mut normalized = if self.kebab_case {
+            to_kebab_case(name)
+        } else {
+            name.to_string()
+        };
+
+        if let Some(prefix) = prefix {
+            if !normalized.starts_with(prefix) {
+                normalized = format!("{}{}", prefix, normalized);
+            }
+        }
+
+        if let Some(max_length) = self.max_length {
+            normalized = normalized.chars().take(max_length).collect();
+            normalized = normalized.trim_end_matches('-').to_string();
+        }
+
+        normalized
+    }
+
+    /// Validate `name`, rewriting it when `auto_apply` is set
+    pub fn apply(
+        &self,
+        name: &str,
+        team: Option<&str>,
+        what: &str,
+    ) -> Result<String, GeneratorError> {
+        let prefix = self.team_prefix(team)?;
+        let violations = self.violations(name, prefix);
+        if violations.is_empty() {
+            return Ok(name.to_string());
+        }
+
+        if !self.auto_apply {
+            return Err(GeneratorError::Config(format!(
+                "{} '{}' violates naming rules: {}",
+                what,
+                name,
+                violations.join(", ")
+            )));
+        }
+
+        // Truncation may cut the prefix of a long name off again
+        let normalized = self.normalize(name, prefix);
+        let violations = self.violations(&normalized, prefix);
+        if !violations.is_empty() {
+            return Err(GeneratorError::Config(format!(
+                "{} '{}' violates naming rules, even rewritten as '{}': {}",
+                what,
+                name,
+                normalized,
+                violations.join(", ")
+            )));
+        }
+        log::info!(
+            "✏️  Applied naming rules to {}: '{}' -> '{}'",
+            what,
+            name,
+            normalized
+        );
+        Ok(normalized)
+    }
+
+    /// Apply the rules to the project name, the package name and the hostname variables set
+    /// among `variables`
+    pub fn apply_to_variables(
+        &self,
+        variables: &mut HashMap<String, String>,
+        team: Option<&str>,
+    ) -> Result<(), GeneratorError> {
+        let set = |variables: &HashMap<String, String>, name: &str| {
+            variables
+                .get(name)
+                .filter(|value| !value.is_empty())
+                .cloned()
+        };
+        if let Some(project_name) = set(variables, "project_name") {
+            let project_name = self.apply(&project_name, team, "project name")?;
+            variables.insert("project_name".to_string(), project_name);
+        }
+        if let Some(package_name) = set(variables, "name") {
+            let package_name = self.apply_to_package(&package_name, team)?;
+            variables.insert("name".to_string(), package_name);
+        }
+        for variable in &self.hostname_variables {
+            if let Some(value) = set(variables, variable) {
+                let value = self.apply_to_hostname(&value, team)?;
+                variables.insert(variable.clone(), value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the rules to the bare part of a (possibly scoped) package name
+    pub fn apply_to_package(
+        &self,
+        package: &str,
+        team: Option<&str>,
+    ) -> Result<String, GeneratorError> {
+        match package.split_once('/') {
+            Some((scope, bare)) if scope.starts_with('@') => Ok(format!(
+                "{}/{}",
+                scope,
+                self.apply(bare, team, "package name")?
+            )),
+            _ => self.apply(package, team, "package name"),
+        }
+    }
+
+    /// Apply the rules to the first DNS label of a hostname or URL
+    pub fn apply_to_hostname(
+        &self,
+        value: &str,
+        team: Option<&str>,
+    ) -> Result<String, GeneratorError> {
+        let (scheme, rest) = match value.split_once("://") {
+            Some((scheme, rest)) => (format!("{}://", scheme), rest),
+            None => (String::new(), value),
+        };
+        let (label, tail) = match rest.find(['.', '/', ':']) {
+            Some(index) => rest.split_at(index),
+            None => (rest, ""),
+        };
+
+        log::debug!("Checking hostname label '{}' of '{}'", label, value);
+        let label = self.apply(label, team, "hostname")?;
+        Ok(format!("{}{}{}", scheme, label, tail))
+    }
+}
+
+fn to_kebab_case(input: &str) -> String {
+    let mut result = String::new();
+    let mut previous_lower = false;
+
+    for c in input.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && previous_lower {
+                result.push('-');
+            }
+            result.push(c.to_ascii_lowercase());
+            previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else {
+            if !result.ends_with('-') && !result.is_empty() {
+                result.push('-');
+            }
+            previous_lower = false;
+        }
+    }
+
+    result.trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(kebab_case: bool, max_length: usize) -> NamingRules {
+        NamingRules {
+            kebab_case,
+            max_length: Some(max_length),
+            team_prefixes: HashMap::from([("frontend".to_string(), "frontend-".to_string())]),
+            auto_apply: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lengths_count_characters() {
+        let rules = rules(false, 5);
+        assert_eq!(rules.apply("éclat", None, "name").unwrap(), "éclat");
+        // The cut falls inside a two-byte character when counting bytes
+        assert_eq!(rules.apply("ééééééé", None, "name").unwrap(), "ééééé");
+        assert_eq!(
+            rules.apply("日本語のアプリ", None, "name").unwrap(),
+            "日本語のア"
+        );
+    }
+
+    #[test]
+    fn rewritten_names_are_checked_again() {
+        let rules = rules(true, 20);
+        assert_eq!(
+            rules.apply("Shop", Some("frontend"), "name").unwrap(),
+            "frontend-shop"
+        );
+        // The prefix alone is longer than the limit, truncating would cut it off
+        let rules = NamingRules {
+            max_length: Some(8),
+            ..rules
+        };
+        let error = rules.apply("shop", Some("frontend"), "name").unwrap_err();
+        assert!(error.to_string().contains("must start with 'frontend-'"));
+    }
+
+    #[test]
+    fn applies_to_the_project_package_and_hostname_variables() {
+        let rules = NamingRules {
+            hostname_variables: vec!["preview_url".to_string()],
+            ..rules(true, 30)
+        };
+        let mut variables = HashMap::from([
+            ("project_name".to_string(), "My Shop".to_string()),
+            ("name".to_string(), "@acme/My Shop".to_string()),
+            (
+                "preview_url".to_string(),
+                "https://My Shop.acme.dev".to_string(),
+            ),
+            ("description".to_string(), "Not a name".to_string()),
+        ]);
+        rules
+            .apply_to_variables(&mut variables, Some("frontend"))
+            .unwrap();
+        assert_eq!(variables["project_name"], "frontend-my-shop");
+        assert_eq!(variables["name"], "@acme/frontend-my-shop");
+        assert_eq!(
+            variables["preview_url"],
+            "https://frontend-my-shop.acme.dev"
+        );
+        assert_eq!(variables["description"], "Not a name");
+    }
+}
@@ -0,0 +1,112 @@
+use std::io::Result;
+use std::path::Path;
+
+use crate::utils::error::GeneratorError;
+use crate::utils::strings;
+
+/// Version of the running generator
+pub const GENERATOR_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Refuse a template whose `generator_version` range excludes the running generator, or
+/// only warn with `allow_incompatible` (`--allow-incompatible-template`)
+pub fn check(template_path: &Path, template: &str, allow_incompatible: bool) -> Result<()> {
+    let Some(range) = strings::read_template_manifest(template_path)
+        .ok()
+        .and_then(|manifest| manifest.generator_version)
+    else {
+        return Ok(());
+    };
+    let compatible = matches(&range, GENERATOR_VERSION).map_err(|e| {
+        GeneratorError::Template(format!(
+            "Invalid generator_version '{}' in {}: {}",
+            range, template, e
+        ))
+    })?;
+    log::debug!(
+        "Template {} requires generator {}, running {}",
+        template,
+        range,
+        GENERATOR_VERSION
+    );
+    if compatible {
+        return Ok(());
+    }
+
+    let message = format!(
+        "Template {} requires generator version {}, this is {}",
+        template, range, GENERATOR_VERSION
+    );
+    if allow_incompatible {
+        log::warn!("{}, generating anyway", message);
+        return Ok(());
+    }
+    Err(GeneratorError::Template(format!(
+        "{}. Update the generator (pull and rebuild it), or pass --allow-incompatible-template to try anyway",
+        message
+    ))
+    .into())
+}
+
+/// Whether `version` satisfies every comma-separated comparator of `range`: `>=`, `>`, `<=`,
+/// `<`, `=`, `~` (same minor) and `^` (same left-most non-zero part, the default)
+pub fn matches(range: &str, version: &str) -> std::result::Result<bool, String> {
+    let version = parse_version(version)?;
+    let mut satisfied = true;
+    for comparator in range.split(',').map(str::trim) {
+        let operator_end = comparator
+            .find(|c: char| c.is_ascii_digit())
+            .ok_or_else(|| format!("no version in '{}'", comparator))?;
+        let (operator, bound) = comparator.split_at(operator_end);
+        let parts = bound.split('.').count();
+        let bound = parse_version(bound)?;
+        satisfied &= match operator.trim() {
+            ">=" => version >= bound,
+            ">" => version > bound,
+            "<=" => version <= bound,
+            "<" => version < bound,
+            "=" => version == bound,
+            "~" => version >= bound && version < tilde_limit(bound, parts),
+            "^" | "" => version >= bound && version < caret_limit(bound, parts),
+            other => return Err(format!("unknown operator '{}'", other)),
+        };
+    }
+    Ok(satisfied)
+}
+
+/// Major, minor and patch, missing parts being 0 and pre-release or build suffixes ignored
+fn parse_version(version: &str) -> std::result::Result<(u64, u64, u64), String> {
+    let core = version.trim().split(['-', '+']).next().unwrap_or_default();
+    let mut parts = core.split('.');
+    let mut next = || -> std::result::Result<u64, String> {
+        match parts.next() {
+            Some(part) => part
+                .parse()
+                .map_err(|_| format!("invalid version '{}'", version.trim())),
+            None => Ok(0),
+        }
+    };
+    let version = (next()?, next()?, next()?);
+    if parts.next().is_some() {
+        return Err(format!("invalid version '{}'", core));
+    }
+    Ok(version)
+}
+
+/// `~1.2.3` and `~1.2` allow patch updates, `~1` minor updates
+fn tilde_limit((major, minor, _): (u64, u64, u64), parts: usize) -> (u64, u64, u64) {
+    if parts == 1 {
+        (major + 1, 0, 0)
+    } else {
+        (major, minor + 1, 0)
+    }
+}
+
+/// `^1.2.3` allows anything below 2.0.0, `^0.2.3` below 0.3.0 and `^0.0.3` below 0.0.4
+fn caret_limit((major, minor, patch): (u64, u64, u64), parts: usize) -> (u64, u64, u64) {
+    match (major, minor) {
+        (0, 0) if parts == 3 => (0, 0, patch + 1),
+        (0, 0) if parts == 2 => (0, 1, 0),
+        (0, _) if parts > 1 => (0, minor + 1, 0),
+        _ => (major + 1, 0, 0),
+    }
+}
@@ -0,0 +1,319 @@
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+
+use crate::config::{
+    EXCLUDED_DIRS, EXCLUDED_FILES, TEMPLATE_CATEGORIES, TEMPLATE_CONFIG_FILE, TEMPLATE_LAYERS_DIR,
+};
+
+/// Directory of the template repository receiving the composed templates
+const COMPOSED_DIR: &str = ".composed";
+
+/// Compose a template declaring `extends` or `includes` into a directory holding its base,
+/// then its layers in order, then its own files. Templates without them are used as is.
+pub fn compose(root: &Path, template_path: &Path) -> Result<PathBuf> {
+    let manifest = read_manifest(template_path)?;
+    if parents(&manifest)?.is_empty() {
+        return Ok(template_path.to_path_buf());
+    }
+
+    let component = |path: Option<&Path>| {
+        path.and_then(|p| p.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    let name = component(Some(template_path));
+    // Under its category, so that the composed template keeps its `category/name` identity
+    let composed_path = root
+        .join(COMPOSED_DIR)
+        .join(component(template_path.parent()))
+        .join(&name);
+    if composed_path.exists() {
+        fs::remove_dir_all(&composed_path)?;
+    }
+    fs::create_dir_all(&composed_path)?;
+
+    let mut applied = Vec::new();
+    let mut config = compose_into(
+        root,
+        template_path,
+        &composed_path,
+        &mut Vec::new(),
+        &mut applied,
+    )?;
+    // Kept for the record (e.g. in the generator lockfile), composition is already done
+    for key in ["extends", "includes"] {
+        if let Some(value) = manifest.get(key) {
+            config.insert(key.to_string(), value.clone());
+        }
+    }
+    fs::write(
+        composed_path.join(TEMPLATE_CONFIG_FILE),
+        serde_json::to_string_pretty(&config)?,
+    )?;
+
+    log::info!(
+        "🧱 Composed template '{}' from {}",
+        name,
+        applied.join(" + ")
+    );
+    Ok(composed_path)
+}
+
+/// Layer `template_path` and its parents onto `target`, returning the merged manifest.
+/// `chain` holds the templates being composed and guards against cycles, a template shared
+/// by several layers is only `applied` once.
+fn compose_into(
+    root: &Path,
+    template_path: &Path,
+    target: &Path,
+    chain: &mut Vec<String>,
+    applied: &mut Vec<String>,
+) -> Result<IndexMap<String, Value>> {
+    let display = template_path
+        .strip_prefix(root)
+        .unwrap_or(template_path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    if chain.contains(&display) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Template composition cycle: {} -> {}",
+                chain.join(" -> "),
+                display
+            ),
+        ));
+    }
+    if applied.contains(&display) {
+        return Ok(IndexMap::new());
+    }
+
+    let mut manifest = read_manifest(template_path)?;
+    let mut config = IndexMap::new();
+    chain.push(display.clone());
+    for parent in parents(&manifest)? {
+        let parent_path = resolve(root, template_path, &parent)?;
+        let parent_config = compose_into(root, &parent_path, target, chain, applied)?;
+        merge_manifest(&mut config, parent_config);
+    }
+    chain.pop();
+
+    log::debug!("Layering template {}", display);
+    overlay_dir(template_path, target)?;
+    manifest.shift_remove("extends");
+    manifest.shift_remove("includes");
+    merge_manifest(&mut config, manifest);
+    applied.push(display);
+    Ok(config)
+}
+
+/// The manifest object of a template, a legacy list of file configurations becoming its `files`
+fn read_manifest(template_path: &Path) -> Result<IndexMap<String, Value>> {
+    let config_path = template_path.join(TEMPLATE_CONFIG_FILE);
+    if !config_path.exists() {
+        return Ok(IndexMap::new());
+    }
+    let content = fs::read_to_string(&config_path)?;
+    match serde_json::from_str(&content)? {
+        Value::Array(files) => Ok(IndexMap::from([("files".to_string(), Value::Array(files))])),
+        value => serde_json::from_value(value).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid {}: {}", config_path.display(), e),
+            )
+        }),
+    }
+}
+
+/// `extends` first, then `includes` in order
+fn parents(manifest: &IndexMap<String, Value>) -> Result<Vec<String>> {
+    let mut parents = Vec::new();
+    match manifest.get("extends") {
+        Some(Value::String(base)) => parents.push(base.clone()),
+        Some(Value::Null) | None => {}
+        Some(_) => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "'extends' must be a template name",
+            ))
+        }
+    }
+    match manifest.get("includes") {
+        Some(Value::Array(layers)) => {
+            for layer in layers {
+                let layer = layer.as_str().ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        "'includes' must be a list of template names",
+                    )
+                })?;
+                parents.push(layer.to_string());
+            }
+        }
+        Some(Value::Null) | None => {}
+        Some(_) => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "'includes' must be a list of template names",
+            ))
+        }
+    }
+    Ok(parents)
+}
+
+/// Find a template by `category/name`, or by name in the category of the template referencing
+/// it, then in the layers directory, then in the other categories
+fn resolve(root: &Path, template_path: &Path, reference: &str) -> Result<PathBuf> {
+    let candidates: Vec<PathBuf> = if reference.contains('/') {
+        vec![root.join(reference)]
+    } else {
+        template_path
+            .parent()
+            .map(|category| category.join(reference))
+            .into_iter()
+            .chain(std::iter::once(
+                root.join(TEMPLATE_LAYERS_DIR).join(reference),
+            ))
+            .chain(
+                TEMPLATE_CATEGORIES
+                    .iter()
+                    .map(|category| root.join(category).join(reference)),
+            )
+            .collect()
+    };
+
+    candidates
+        .into_iter()
+        .find(|candidate| candidate.is_dir() && candidate != template_path)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("Template '{}' to compose from not found", reference),
+            )
+        })
+}
+
+/// Copy the files of a layer over the target. JSON files present in both are deep-merged,
+/// other files are replaced. Manifests are merged separately.
+fn overlay_dir(source: &Path, target: &Path) -> Result<()> {
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let file_name_str = file_name.to_string_lossy();
+        let destination = target.join(&file_name);
+
+        if entry.file_type()?.is_dir() {
+            if file_name_str != ".git" && !EXCLUDED_DIRS.contains(&file_name_str.as_ref()) {
+                overlay_dir(&entry.path(), &destination)?;
+            }
+            continue;
+        }
+        if EXCLUDED_FILES.contains(&file_name_str.as_ref()) {
+            continue;
+        }
+
+        if file_name_str.ends_with(".json") && destination.exists() {
+            if let Some(merged) = merge_json_files(&destination, &entry.path())? {
+                fs::write(&destination, merged)?;
+                continue;
+            }
+        }
+        fs::copy(entry.path(), &destination)?;
+    }
+    Ok(())
+}
+
+/// Deep-merge the JSON `layer` file into the `base` one, `None` when either is not JSON
+fn merge_json_files(base: &Path, layer: &Path) -> Result<Option<String>> {
+    let base: std::result::Result<IndexMap<String, Value>, _> =
+        serde_json::from_str(&fs::read_to_string(base)?);
+    let layer: std::result::Result<IndexMap<String, Value>, _> =
+        serde_json::from_str(&fs::read_to_string(layer)?);
+    let (Ok(mut base), Ok(layer)) = (base, layer) else {
+        return Ok(None);
+    };
+    for (key, value) in layer {
+        match base.get_mut(&key) {
+            Some(existing) => deep_merge(existing, value),
+            None => {
+                base.insert(key, value);
+            }
+        }
+    }
+    Ok(Some(serde_json::to_string_pretty(&base)? + "\n"))
+}
+
+/// Objects are merged key by key, any other layer value replaces the base one
+fn deep_merge(base: &mut Value, layer: Value) {
+    match (base, layer) {
+        (Value::Object(base), Value::Object(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+/// Merge a layer manifest into the composed one. File configurations are merged by
+/// `files_to_replace`, other sections like JSON files.
+fn merge_manifest(config: &mut IndexMap<String, Value>, layer: IndexMap<String, Value>) {
+    for (key, value) in layer {
+        match (key.as_str(), config.get_mut(&key)) {
+            ("files", Some(Value::Array(files))) => {
+                if let Value::Array(layer_files) = value {
+                    merge_files(files, layer_files);
+                }
+            }
+            (_, Some(existing)) => deep_merge(existing, value),
+            (_, None) => {
+                config.insert(key, value);
+            }
+        }
+    }
+}
+
+/// A layer configuration for the same files adds its replacements to the existing one,
+/// replacing those that target the same key
+fn merge_files(files: &mut Vec<Value>, layer_files: Vec<Value>) {
+    for layer_file in layer_files {
+        let existing = files
+            .iter_mut()
+            .find(|file| file.get("files_to_replace") == layer_file.get("files_to_replace"));
+        let (Some(existing), Some(Value::Array(layer_replacements))) =
+            (existing, layer_file.get("replacements"))
+        else {
+            files.push(layer_file);
+            continue;
+        };
+        let Some(Value::Array(replacements)) = existing.get_mut("replacements") else {
+            *existing = layer_file;
+            continue;
+        };
+        for replacement in layer_replacements {
+            match replacements
+                .iter_mut()
+                .find(|r| replacement_target(r) == replacement_target(replacement))
+            {
+                Some(current) => *current = replacement.clone(),
+                None => replacements.push(replacement.clone()),
+            }
+        }
+    }
+}
+
+/// What a replacement writes to: its attribute, its key or else its variable
+fn replacement_target(replacement: &Value) -> Option<&Value> {
+    ["attribute", "key", "name"]
+        .iter()
+        .find_map(|field| replacement.get(field).filter(|value| !value.is_null()))
+}
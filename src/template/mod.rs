@@ -1,8 +1,14 @@
+pub mod compatibility;
+pub mod compose;
+pub mod registry;
+pub mod source;
+
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::config::{REPO_URL, TEMPLATE_BRANCH, TEMPLATE_CATEGORIES, TEMPLATE_REPO_URL};
+use crate::utils::error::GeneratorError;
 
 pub struct TemplateManager {
     repo_path: PathBuf,
@@ -21,28 +27,9 @@ impl TemplateManager {
         fs::create_dir_all(&temp_dir)?;
         let repo_path = temp_dir;
 
-        // Configure Git to use HTTPS with PAT
-        let mut callbacks = git2::RemoteCallbacks::new();
-        callbacks.credentials(|_url, username_from_url, _allowed_types| {
-            // Get the PAT from environment variable
-            let pat = std::env::var("GITHUB_TOKEN").map_err(|_| {
-                git2::Error::new(
-                    git2::ErrorCode::Auth,
-                    git2::ErrorClass::Http,
-                    "GITHUB_TOKEN environment variable not set",
-                )
-            })?;
-
-            git2::Cred::userpass_plaintext(username_from_url.unwrap_or("git"), &pat)
-        });
-
-        // Set up fetch options with the callbacks
-        let mut fetch_options = git2::FetchOptions::new();
-        fetch_options.remote_callbacks(callbacks);
-
         // Clone the repository
         let mut builder = git2::build::RepoBuilder::new();
-        builder.fetch_options(fetch_options);
+        builder.fetch_options(fetch_options());
 
         // Use provided branch or fallback to TEMPLATE_BRANCH constant
         let branch_to_use = branch.unwrap_or(TEMPLATE_BRANCH);
@@ -53,12 +40,25 @@ impl TemplateManager {
                 format!("{}{}", REPO_URL, TEMPLATE_REPO_URL).as_str(),
                 &repo_path,
             )
-            .map_err(|e| {
-                std::io::Error::other(
-                    format!("Failed to clone repository: {}", e),
+            .map_err(|e| GeneratorError::from(e).context("Failed to clone repository"))?;
+
+        Ok(Self { repo_path })
+    }
+
+    /// Use the templates repository at `source` (a git URL or GitHub `owner/repo`, optionally
+    /// pinned with `#<tag|branch|commit>`), cached under `cache_dir`
+    pub fn from_source(source: &str, cache_dir: Option<&Path>) -> std::io::Result<Self> {
+        let source = source::TemplateSource::parse(source)?;
+        let cache_dir = cache_dir
+            .map(Path::to_path_buf)
+            .or_else(source::default_cache_dir)
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "No cache directory for remote templates, set HOME or --template-cache-dir",
                 )
             })?;
-
+        let repo_path = source::checkout(&source, &cache_dir, fetch_options)?;
         Ok(Self { repo_path })
     }
 
@@ -86,9 +86,45 @@ impl TemplateManager {
         Ok(templates)
     }
 
+    pub fn root_path(&self) -> &Path {
+        &self.repo_path
+    }
+
     pub fn get_template_path(&self, category: &str, template: &str) -> PathBuf {
         self.repo_path.join(category).join(template)
     }
+
+    /// Path of the template to generate from, composed from its base and layers when it
+    /// declares `extends` or `includes`
+    pub fn resolve_template_path(
+        &self,
+        category: &str,
+        template: &str,
+    ) -> std::io::Result<PathBuf> {
+        compose::compose(&self.repo_path, &self.get_template_path(category, template))
+    }
+}
+
+/// Fetch options authenticating HTTPS remotes with the `GITHUB_TOKEN` PAT
+fn fetch_options() -> git2::FetchOptions<'static> {
+    let mut callbacks = git2::RemoteCallbacks::new();
+    callbacks.credentials(|_url, username_from_url, _allowed_types| {
+        // Get the PAT from environment variable
+        let pat = std::env::var("GITHUB_TOKEN").map_err(|_| {
+            git2::Error::new(
+                git2::ErrorCode::Auth,
+                git2::ErrorClass::Http,
+                "GITHUB_TOKEN environment variable not set",
+            )
+        })?;
+
+        git2::Cred::userpass_plaintext(username_from_url.unwrap_or("git"), &pat)
+    });
+
+    let mut fetch_options = git2::FetchOptions::new();
+    fetch_options.remote_callbacks(callbacks);
+    fetch_options.proxy_options(crate::github::http::git_proxy_options());
+    fetch_options
 }
 
 impl Drop for TemplateManager {
@@ -0,0 +1,354 @@
+use serde_json::Value;
+use std::fs;
+use std::io::{Result, Write};
+use std::path::Path;
+
+use crate::config::{TemplateManifest, EXCLUDED_DIRS, EXCLUDED_FILES, TEMPLATE_CATEGORIES};
+use crate::template::{compatibility, TemplateManager};
+use crate::utils::error::GeneratorError;
+use crate::utils::{strings, summary};
+
+/// Variables asked for every template, not listed as template variables
+const SYSTEM_VARIABLES: &[&str] = &["project_name", "name"];
+
+/// What a user needs to know to pick a template
+pub struct TemplateInfo {
+    pub category: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub version: Option<String>,
+    /// Base and layers the template is composed from
+    pub composed_from: Vec<String>,
+    pub variables: Vec<String>,
+}
+
+impl TemplateInfo {
+    fn matches(&self, term: &str) -> bool {
+        let term = term.to_lowercase();
+        [
+            Some(&self.category),
+            Some(&self.name),
+            self.description.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(&self.variables)
+        .any(|field| field.to_lowercase().contains(&term))
+    }
+}
+
+/// Read the metadata of a template. The description and version fall back to those of the
+/// template's package.json.
+pub fn template_info(template_path: &Path, category: &str, name: &str) -> TemplateInfo {
+    let manifest = strings::read_template_manifest(template_path).unwrap_or_default();
+    let package: Value = fs::read_to_string(template_path.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    let package_field = |field: &str| {
+        package
+            .get(field)
+            .and_then(Value::as_str)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+
+    TemplateInfo {
+        category: category.to_string(),
+        name: name.to_string(),
+        description: manifest
+            .description
+            .clone()
+            .or_else(|| package_field("description")),
+        version: manifest
+            .version
+            .clone()
+            .or_else(|| package_field("version")),
+        composed_from: manifest
+            .extends
+            .iter()
+            .chain(&manifest.includes)
+            .cloned()
+            .collect(),
+        variables: variables(template_path, &manifest),
+    }
+}
+
+/// Variables of the replacements, templated files and declared prompts, sorted
+fn variables(template_path: &Path, manifest: &TemplateManifest) -> Vec<String> {
+    let mut variables = strings::extract_unique_keys(template_path).unwrap_or_default();
+    for variable in manifest.prompts.iter().flat_map(|page| &page.variables) {
+        if !variables.contains(&variable.name) {
+            variables.push(variable.name.clone());
+        }
+    }
+    variables.retain(|variable| !SYSTEM_VARIABLES.contains(&variable.as_str()));
+    variables.sort();
+    variables
+}
+
+/// Every template of the repository, filtered by `search` on the category, name, description
+/// and variables
+pub fn list(manager: &TemplateManager, search: Option<&str>) -> Result<Vec<TemplateInfo>> {
+    Ok(manager
+        .list_templates()?
+        .into_iter()
+        .map(|(category, name)| {
+            template_info(
+                &manager.get_template_path(&category, &name),
+                &category,
+                &name,
+            )
+        })
+        .filter(|info| search.is_none_or(|term| info.matches(term)))
+        .collect())
+}
+
+/// Find a template given as `category/name`, or by name in any category
+pub fn find(manager: &TemplateManager, reference: &str) -> Result<(String, String)> {
+    let candidates: Vec<(String, String)> = match reference.split_once('/') {
+        Some((category, name)) => vec![(category.to_string(), name.to_string())],
+        None => TEMPLATE_CATEGORIES
+            .iter()
+            .map(|category| (category.to_string(), reference.to_string()))
+            .collect(),
+    };
+    candidates
+        .into_iter()
+        .find(|(category, name)| manager.get_template_path(category, name).is_dir())
+        .ok_or_else(|| {
+            GeneratorError::Template(format!("Template '{}' not found", reference)).into()
+        })
+}
+
+pub fn print_list(templates: &[TemplateInfo]) {
+    let mut out = summary::status_output();
+    if templates.is_empty() {
+        let _ = writeln!(out, "No templates found");
+        return;
+    }
+
+    let _ = writeln!(out, "📚 Available templates ({}):", templates.len());
+    for info in templates {
+        let mut line = format!("  {}/{}", info.category, info.name);
+        if let Some(version) = &info.version {
+            line.push_str(&format!(" ({})", version));
+        }
+        if let Some(description) = &info.description {
+            line.push_str(&format!(" - {}", description));
+        }
+        let _ = writeln!(out, "{}", line);
+        if !info.composed_from.is_empty() {
+            let _ = writeln!(
+                out,
+                "      composed from: {}",
+                info.composed_from.join(" + ")
+            );
+        }
+        if !info.variables.is_empty() {
+            let _ = writeln!(out, "      variables: {}", info.variables.join(", "));
+        }
+    }
+}
+
+/// Print a template with every variable it asks for, `template_path` being the composed
+/// template so that inherited variables are included
+pub fn print_description(info: &TemplateInfo, template_path: &Path) {
+    let mut out = summary::status_output();
+    let manifest = strings::read_template_manifest(template_path).unwrap_or_default();
+
+    let _ = writeln!(out, "📄 {}/{}", info.category, info.name);
+    if let Some(description) = &info.description {
+        let _ = writeln!(out, "   {}", description);
+    }
+    if let Some(version) = &info.version {
+        let _ = writeln!(out, "   Version: {}", version);
+    }
+    if let Some(range) = &manifest.generator_version {
+        let status = match compatibility::matches(range, compatibility::GENERATOR_VERSION) {
+            Ok(true) => "compatible".to_string(),
+            Ok(false) => format!("this is {}", compatibility::GENERATOR_VERSION),
+            Err(e) => format!("invalid: {}", e),
+        };
+        let _ = writeln!(out, "   Generator: {} ({})", range, status);
+    }
+    if !info.composed_from.is_empty() {
+        let _ = writeln!(out, "   Composed from: {}", info.composed_from.join(" + "));
+    }
+
+    let _ = writeln!(out, "\n   Variables:");
+    let _ = writeln!(out, "     - project_name (required)");
+    let _ = writeln!(out, "     - name (required, package name)");
+    for page in &manifest.prompts {
+        for variable in &page.variables {
+            let mut details = Vec::new();
+            if variable.required {
+                details.push("required".to_string());
+            }
+            for when in page.when.iter().chain(&variable.when) {
+                details.push(format!("when {}", when));
+            }
+            if !variable.choices.is_empty() {
+                details.push(format!("one of {}", variable.choices.join(", ")));
+            }
+            if let Some(default) = &variable.default {
+                details.push(format!("default {}", default));
+            }
+            if let Some(pattern) = &variable.pattern {
+                details.push(format!("matching {}", pattern));
+            }
+            match (variable.min_length, variable.max_length) {
+                (Some(min), Some(max)) => details.push(format!("{} to {} characters", min, max)),
+                (Some(min), None) => details.push(format!("at least {} characters", min)),
+                (None, Some(max)) => details.push(format!("at most {} characters", max)),
+                (None, None) => {}
+            }
+            let mut line = format!("     - {}", variable.name);
+            if !details.is_empty() {
+                line.push_str(&format!(" ({})", details.join(", ")));
+            }
+            if let Some(message) = &variable.message {
+                line.push_str(&format!(": {}", message));
+            }
+            let _ = writeln!(out, "{}", line);
+        }
+    }
+
+    let declared: Vec<&str> = manifest
+        .prompts
+        .iter()
+        .flat_map(|page| &page.variables)
+        .map(|variable| variable.name.as_str())
+        .collect();
+    for variable in variables(template_path, &manifest) {
+        if !declared.contains(&variable.as_str()) {
+            let _ = writeln!(out, "     - {}", variable);
+        }
+    }
+}
+
+/// Print what generating a template would produce, without generating anything: its file
+/// tree, variables, hooks, the files each replacement targets and the size of the output.
+/// `template_path` is the composed template.
+pub fn print_inspection(info: &TemplateInfo, template_path: &Path) -> Result<()> {
+    let mut out = summary::status_output();
+    let manifest = strings::read_template_manifest(template_path).unwrap_or_default();
+    let mut files = Vec::new();
+    collect_files(template_path, template_path, &mut files)?;
+    files.sort();
+    let total_size: u64 = files.iter().map(|(_, size)| size).sum();
+
+    let _ = writeln!(out, "🔎 {}/{}", info.category, info.name);
+    if let Some(description) = &info.description {
+        let _ = writeln!(out, "   {}", description);
+    }
+    let _ = writeln!(
+        out,
+        "   Estimated size: {} in {} files, before dependency installation",
+        format_size(total_size),
+        files.len()
+    );
+
+    let _ = writeln!(out, "\n   Files:");
+    let mut printed_dirs: Vec<String> = Vec::new();
+    for (path, size) in &files {
+        let components: Vec<&str> = path.split('/').collect();
+        for depth in 0..components.len() - 1 {
+            let dir = components[..=depth].join("/");
+            if !printed_dirs.contains(&dir) {
+                let _ = writeln!(out, "     {}{}/", "  ".repeat(depth), components[depth]);
+                printed_dirs.push(dir);
+            }
+        }
+        let _ = writeln!(
+            out,
+            "     {}{} ({})",
+            "  ".repeat(components.len() - 1),
+            components[components.len() - 1],
+            format_size(*size)
+        );
+    }
+
+    let _ = writeln!(out, "\n   Variables:");
+    for variable in SYSTEM_VARIABLES
+        .iter()
+        .copied()
+        .chain(info.variables.iter().map(String::as_str))
+    {
+        let _ = writeln!(out, "     - {}", variable);
+    }
+
+    let hooks = [
+        ("pre_generate", &manifest.hooks.pre_generate),
+        ("post_generate", &manifest.hooks.post_generate),
+        ("pre_push", &manifest.hooks.pre_push),
+        ("post_push", &manifest.hooks.post_push),
+    ];
+    if hooks.iter().any(|(_, commands)| !commands.is_empty()) {
+        let _ = writeln!(out, "\n   Hooks:");
+        for (stage, commands) in hooks {
+            for command in commands {
+                let _ = writeln!(out, "     - {}: {}", stage, command);
+            }
+        }
+    }
+
+    if !manifest.files.is_empty() {
+        let _ = writeln!(out, "\n   Replacements:");
+        for config in &manifest.files {
+            let variables: Vec<String> = config
+                .replacements
+                .iter()
+                .map(|replacement| match &replacement.key {
+                    Some(key) => format!("{} (key {})", replacement.name, key),
+                    None => replacement.name.clone(),
+                })
+                .collect();
+            let _ = writeln!(out, "     - {}", variables.join(", "));
+            for target in &config.files_to_replace {
+                // Paths rendered from variables can only be checked once generated
+                let missing = !target.contains("{{") && !template_path.join(target).is_file();
+                let _ = writeln!(
+                    out,
+                    "         {}{}",
+                    target,
+                    if missing { " (missing)" } else { "" }
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Files copied into a generated project, relative to `root` with their size
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<(String, u64)>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if EXCLUDED_DIRS.contains(&file_name.as_str())
+            || EXCLUDED_FILES.contains(&file_name.as_str())
+            || file_name == ".git"
+        {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            collect_files(root, &path, files)?;
+        } else {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            files.push((
+                relative.to_string_lossy().replace('\\', "/"),
+                entry.metadata()?.len(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
@@ -0,0 +1,222 @@
+use git2::build::CheckoutBuilder;
+use git2::{FetchOptions, Object, Repository};
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+
+/// Overrides the directory caching fetched template repositories
+pub const CACHE_DIR_ENV: &str = "PROJECT_GENERATOR_CACHE_DIR";
+
+/// A templates repository given as `<git url>[#<ref>]`, a local path, or `<owner>/<repo>[#<ref>]`
+/// for GitHub, where the ref is a tag, a branch or a commit
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateSource {
+    pub url: String,
+    pub reference: Option<String>,
+}
+
+impl TemplateSource {
+    pub fn parse(source: &str) -> Result<Self> {
+        let (location, reference) = match source.split_once('#') {
+            Some((location, reference)) if !reference.is_empty() => {
+                (location, Some(reference.to_string()))
+            }
+            Some((location, _)) => (location, None),
+            None => (source, None),
+        };
+
+        // An existing relative path such as `templates/foo` is local, not `owner/repo`
+        let is_shorthand = !location.contains(':')
+            && !location.starts_with('/')
+            && !location.starts_with('.')
+            && location.split('/').count() == 2
+            && !Path::new(location).exists();
+        let url = if is_shorthand {
+            format!(
+                "https://github.com/{}.git",
+                location.trim_end_matches(".git")
+            )
+        } else {
+            location.to_string()
+        };
+        if url.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid template source '{}'", source),
+            ));
+        }
+        Ok(Self { url, reference })
+    }
+
+    /// Cache directory name, e.g. `github.com_org_templates`
+    fn cache_key(&self) -> String {
+        let location = self
+            .url
+            .split_once("://")
+            .map(|(_, rest)| rest)
+            .unwrap_or(&self.url);
+        let location = location
+            .rsplit_once('@')
+            .map(|(_, rest)| rest)
+            .unwrap_or(location);
+        location
+            .trim_end_matches('/')
+            .trim_end_matches(".git")
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for TemplateSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.reference {
+            Some(reference) => write!(f, "{}#{}", self.url, reference),
+            None => write!(f, "{}", self.url),
+        }
+    }
+}
+
+/// `$PROJECT_GENERATOR_CACHE_DIR`, else `$XDG_CACHE_HOME/project-generator/templates`, else
+/// `~/.cache/project-generator/templates`
+pub fn default_cache_dir() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(CACHE_DIR_ENV) {
+        return Some(PathBuf::from(path));
+    }
+
+    let cache_dir = std::env::var("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| std::env::var("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .ok()?;
+
+    Some(cache_dir.join("project-generator").join("templates"))
+}
+
+/// Check out the source in its cache directory and return it. A repository already cached is
+/// only fetched again when the ref is a branch or missing: tags and commits are reused as is.
+pub fn checkout(
+    source: &TemplateSource,
+    cache_dir: &Path,
+    fetch_options: impl Fn() -> FetchOptions<'static>,
+) -> Result<PathBuf> {
+    let git_error =
+        |e: git2::Error| Error::other(format!("Failed to fetch templates from {}: {}", source, e));
+    let path = cache_dir.join(source.cache_key());
+
+    let repo = match Repository::open(&path) {
+        Ok(repo) => {
+            let pinned = source
+                .reference
+                .as_deref()
+                .is_some_and(|reference| pinned_object(&repo, reference).is_some());
+            if pinned {
+                log::info!("📦 Using cached templates {}", source);
+            } else {
+                log::info!("📦 Updating cached templates {}", source);
+                repo.find_remote("origin")
+                    .and_then(|mut remote| {
+                        remote.fetch(
+                            &[
+                                "+refs/heads/*:refs/remotes/origin/*",
+                                "+refs/tags/*:refs/tags/*",
+                            ],
+                            Some(&mut fetch_options()),
+                            None,
+                        )
+                    })
+                    .map_err(git_error)?;
+            }
+            repo
+        }
+        Err(_) => {
+            if path.exists() {
+                fs::remove_dir_all(&path)?;
+            }
+            fs::create_dir_all(cache_dir)?;
+            log::info!("📦 Fetching templates {}", source);
+            git2::build::RepoBuilder::new()
+                .fetch_options(fetch_options())
+                .clone(&source.url, &path)
+                .map_err(git_error)?
+        }
+    };
+
+    let target = match source.reference.as_deref() {
+        Some(reference) => pinned_object(&repo, reference)
+            .or_else(|| {
+                repo.revparse_single(&format!("refs/remotes/origin/{}", reference))
+                    .ok()
+            })
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!("Ref '{}' not found in {}", reference, source.url),
+                )
+            })?,
+        None => repo
+            .revparse_single("refs/remotes/origin/HEAD")
+            .or_else(|_| repo.revparse_single("HEAD"))
+            .map_err(git_error)?,
+    };
+
+    let commit = target.peel_to_commit().map_err(git_error)?;
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))
+        .map_err(git_error)?;
+    repo.set_head_detached(commit.id()).map_err(git_error)?;
+    log::debug!(
+        "Checked out templates {} at {} in {}",
+        source,
+        commit.id(),
+        path.display()
+    );
+    Ok(path)
+}
+
+/// A tag or commit of the repository, which never moves once fetched
+fn pinned_object<'r>(repo: &'r Repository, reference: &str) -> Option<Object<'r>> {
+    repo.revparse_single(&format!("refs/tags/{}", reference))
+        .ok()
+        .or_else(|| {
+            let is_commit =
+                reference.len() >= 7 && reference.chars().all(|c| c.is_ascii_hexdigit());
+            is_commit
+                .then(|| repo.revparse_single(reference).ok())
+                .flatten()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_github_shorthand_urls_and_refs() {
+        let source = TemplateSource::parse("acme/templates#v2").unwrap();
+        assert_eq!(source.url, "https://github.com/acme/templates.git");
+        assert_eq!(source.reference.as_deref(), Some("v2"));
+
+        let source = TemplateSource::parse("git@github.com:acme/templates.git#").unwrap();
+        assert_eq!(source.url, "git@github.com:acme/templates.git");
+        assert_eq!(source.reference, None);
+        assert!(TemplateSource::parse("#main").is_err());
+    }
+
+    #[test]
+    fn keeps_existing_relative_paths_local() {
+        // Tests run from the crate root
+        let source = TemplateSource::parse("src/template").unwrap();
+        assert_eq!(source.url, "src/template");
+    }
+
+    #[test]
+    fn derives_cache_keys_without_credentials() {
+        let source = TemplateSource::parse("https://token@github.com/acme/templates.git").unwrap();
+        assert_eq!(source.cache_key(), "github.com_acme_templates");
+    }
+}
@@ -0,0 +1,143 @@
+use git2::{Commit, Oid, Repository, Signature, Tree};
+use std::io::{Error, ErrorKind, Result, Write};
+use std::process::{Command, Stdio};
+
+use crate::config::file_config::expand_home;
+use crate::config::SigningFormat;
+
+/// Key signing every commit the generator makes, set by `--sign-commits` or `commit_signing`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signing {
+    pub format: SigningFormat,
+    /// GPG key ID or SSH private key path
+    key: Option<String>,
+}
+
+impl Signing {
+    /// Sign with `format`. SSH signing needs an existing private key file, GPG uses its
+    /// default key when `key` is unset.
+    pub fn new(format: SigningFormat, key: Option<String>) -> Result<Signing> {
+        let key = match (format, key) {
+            (SigningFormat::Ssh, None) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "A signing key is required to sign commits with ssh",
+                ))
+            }
+            (SigningFormat::Ssh, Some(key)) => {
+                let key_path = expand_home(&key);
+                if !key_path.exists() {
+                    return Err(Error::new(
+                        ErrorKind::NotFound,
+                        format!("SSH signing key not found: {}", key_path.display()),
+                    ));
+                }
+                Some(key_path.display().to_string())
+            }
+            (SigningFormat::Gpg, key) => key,
+        };
+        Ok(Signing { format, key })
+    }
+
+    /// Sign a test payload, so a missing key or a locked agent fails before anything is
+    /// created
+    pub fn check(&self) -> Result<()> {
+        sign(
+            self.format,
+            self.key.as_deref(),
+            "project-generator signing check\n",
+        )
+        .map_err(|e| {
+            Error::new(
+                e.kind(),
+                format!(
+                    "Commits cannot be signed with {}: {}",
+                    self.format.as_str(),
+                    e
+                ),
+            )
+        })?;
+        log::debug!("Commits will be signed with {}", self.format.as_str());
+        Ok(())
+    }
+}
+
+/// `, signed with <format>` when commits are signed, for dry-run output
+pub fn signing_note(signing: Option<&Signing>) -> String {
+    match signing {
+        Some(signing) => format!(", signed with {}", signing.format.as_str()),
+        None => String::new(),
+    }
+}
+
+/// Commit `tree` on HEAD, signed with `gpg` or `ssh-keygen` when `signing` is set
+pub fn create(
+    repo: &Repository,
+    signature: &Signature,
+    message: &str,
+    tree: &Tree,
+    parents: &[&Commit],
+    signing: Option<&Signing>,
+) -> std::result::Result<Oid, git2::Error> {
+    let Some(signing) = signing else {
+        return repo.commit(Some("HEAD"), signature, signature, message, tree, parents);
+    };
+
+    let buffer = repo.commit_create_buffer(signature, signature, message, tree, parents)?;
+    let content = buffer
+        .as_str()
+        .ok_or_else(|| git2::Error::from_str("The commit is not valid UTF-8"))?;
+    let armored = sign(signing.format, signing.key.as_deref(), content)
+        .map_err(|e| git2::Error::from_str(&format!("Failed to sign the commit: {}", e)))?;
+    let oid = repo.commit_signed(content, &armored, Some("gpgsig"))?;
+
+    // A signed commit is not written to HEAD, move the branch HEAD points to
+    let head = repo.find_reference("HEAD")?;
+    match head.symbolic_target() {
+        Some(branch) => {
+            repo.reference(branch, oid, true, message)?;
+        }
+        None => repo.set_head_detached(oid)?,
+    }
+    Ok(oid)
+}
+
+/// Detached, ASCII-armored signature of the commit content
+fn sign(format: SigningFormat, key: Option<&str>, content: &str) -> Result<String> {
+    let mut command = match format {
+        SigningFormat::Gpg => {
+            let mut command = Command::new("gpg");
+            command.args(["--detach-sign", "--armor"]);
+            if let Some(key) = key {
+                command.args(["--local-user", key]);
+            }
+            command
+        }
+        SigningFormat::Ssh => {
+            let mut command = Command::new("ssh-keygen");
+            command.args(["-Y", "sign", "-n", "git", "-f", key.unwrap_or_default()]);
+            command
+        }
+    };
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::other(format!("Failed to run {}: {}", program, e)))?;
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| Error::other(format!("Failed to write to {}", program)))?
+        .write_all(content.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(Error::other(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    String::from_utf8(output.stdout).map_err(Error::other)
+}
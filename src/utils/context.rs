@@ -1,29 +1,123 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::utils::summary;
+
+/// Shared by every thread, unlike the variables
+static DEBUG_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Held while writing debug output, so that blocks written by different threads do not
+/// interleave
+static DEBUG_OUTPUT: Mutex<()> = Mutex::new(());
 
 thread_local! {
     static VARIABLES: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
-    static DEBUG_MODE: RefCell<bool> = const { RefCell::new(false) };
+    static COMPUTED: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+    /// Debug tasks of the current thread, innermost last, with their buffered lines
+    static DEBUG_TASKS: RefCell<Vec<(String, Vec<String>)>> = const { RefCell::new(Vec::new()) };
 }
 
 pub fn set_variables(vars: HashMap<String, String>) {
     VARIABLES.with(|v| *v.borrow_mut() = vars);
 }
 
+pub fn set_variable(key: &str, value: &str) {
+    VARIABLES.with(|v| {
+        v.borrow_mut().insert(key.to_string(), value.to_string());
+    });
+}
+
 pub fn get_variable(key: &str) -> Option<String> {
     VARIABLES.with(|v| v.borrow().get(key).cloned())
 }
 
+pub fn get_variables() -> HashMap<String, String> {
+    VARIABLES.with(|v| v.borrow().clone())
+}
+
+/// Computed variable definitions of the config, evaluated during generation
+pub fn set_computed(definitions: Vec<(String, String)>) {
+    COMPUTED.with(|c| *c.borrow_mut() = definitions);
+}
+
+pub fn get_computed() -> Vec<(String, String)> {
+    COMPUTED.with(|c| c.borrow().clone())
+}
+
 pub fn set_debug_mode(debug: bool) {
-    DEBUG_MODE.with(|d| *d.borrow_mut() = debug);
+    DEBUG_MODE.store(debug, Ordering::Relaxed);
 }
 
 pub fn is_debug_mode() -> bool {
-    DEBUG_MODE.with(|d| *d.borrow())
+    DEBUG_MODE.load(Ordering::Relaxed)
+}
+
+/// Write a debug line, or buffer it in the current [`debug_task`] of this thread. Called by
+/// the logger for `log::debug!` records.
+pub fn write_debug_line(message: &str) {
+    let buffered = DEBUG_TASKS.with(|tasks| match tasks.borrow_mut().last_mut() {
+        Some((_, lines)) => {
+            lines.push(message.to_string());
+            true
+        }
+        None => false,
+    });
+    if !buffered {
+        write_debug(&[message.to_string()]);
+    }
+}
+
+/// Buffer the debug messages of the current thread until the returned guard is dropped, then
+/// print them as one block prefixed with `name`, so that files or steps handled in parallel
+/// keep their messages together and in order. A nested task is flushed into its parent.
+pub fn debug_task(name: impl Into<String>) -> DebugTask {
+    let active = is_debug_mode();
+    if active {
+        DEBUG_TASKS.with(|tasks| tasks.borrow_mut().push((name.into(), Vec::new())));
+    }
+    DebugTask {
+        active,
+        _thread_bound: PhantomData,
+    }
+}
+
+/// Flushes its task's messages when dropped, on the thread that started it
+pub struct DebugTask {
+    active: bool,
+    _thread_bound: PhantomData<*const ()>,
+}
+
+impl Drop for DebugTask {
+    fn drop(&mut self) {
+        if !self.active {
+            return;
+        }
+        let unflushed = DEBUG_TASKS.with(|tasks| {
+            let mut tasks = tasks.borrow_mut();
+            let (name, lines) = tasks.pop()?;
+            let lines = lines.into_iter().map(|line| format!("[{}] {}", name, line));
+            match tasks.last_mut() {
+                Some((_, parent)) => {
+                    parent.extend(lines);
+                    None
+                }
+                None => Some(lines.collect::<Vec<String>>()),
+            }
+        });
+        if let Some(lines) = unflushed {
+            write_debug(&lines);
+        }
+    }
 }
 
-pub fn debug_print(message: &str) {
-    if is_debug_mode() {
-        println!("[DEBUG] {}", message);
+fn write_debug(lines: &[String]) {
+    let _output = DEBUG_OUTPUT.lock();
+    let mut out = summary::status_output();
+    for line in lines {
+        let _ = writeln!(out, "[DEBUG] {}", line);
     }
 }
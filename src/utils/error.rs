@@ -1,18 +1,195 @@
-pub fn print_error(message: &str) {
-    let result_message = String::from(message);
-    eprintln!("{}", result_message);
+use std::fmt;
+use std::io;
+
+/// Failures callers can tell apart, e.g. to retry, to report them or to pick the exit code
+#[derive(Debug, thiserror::Error)]
+pub enum GeneratorError {
+    /// GitHub answered with an error status
+    #[error("{message} (HTTP {status})")]
+    Api { status: u16, message: String },
+    /// A request got no answer: network, TLS, timeout, or an unreadable response
+    #[error("{0}")]
+    Http(String),
+    /// A git operation (clone, commit, signature, push) failed
+    #[error("{0}")]
+    Git(String),
+    /// The template is missing, invalid or not compatible with the generator
+    #[error("{0}")]
+    Template(String),
+    /// The configuration, the credentials or the arguments are invalid
+    #[error("{0}")]
+    Config(String),
+    #[error("{0}")]
+    Io(#[source] io::Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl GeneratorError {
+    /// The error of a GitHub API response with an error status, `context` saying what was
+    /// being done
+    pub async fn from_response(response: reqwest::Response, context: impl fmt::Display) -> Self {
+        let status = response.status().as_u16();
+        match response.text().await {
+            Ok(body) => GeneratorError::Api {
+                status,
+                message: format!("{}: {}", context, api_error_message(&body)),
+            },
+            Err(e) => GeneratorError::Http(format!("Failed to read error response: {}", e)),
+        }
+    }
+
+    /// The same error, its message prefixed with `context`
+    pub fn context(self, context: impl fmt::Display) -> Self {
+        let prefix = |message: String| format!("{}: {}", context, message);
+        match self {
+            GeneratorError::Api { status, message } => GeneratorError::Api {
+                status,
+                message: prefix(message),
+            },
+            GeneratorError::Http(message) => GeneratorError::Http(prefix(message)),
+            GeneratorError::Git(message) => GeneratorError::Git(prefix(message)),
+            GeneratorError::Template(message) => GeneratorError::Template(prefix(message)),
+            GeneratorError::Config(message) => GeneratorError::Config(prefix(message)),
+            GeneratorError::Io(e) => {
+                GeneratorError::Io(io::Error::new(e.kind(), prefix(e.to_string())))
+            }
+            GeneratorError::Other(message) => GeneratorError::Other(prefix(message)),
+        }
+    }
+
+    /// Refused credentials or missing permissions
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(
+            self,
+            GeneratorError::Api {
+                status: 401 | 403,
+                ..
+            }
+        )
+    }
+
+    /// Process exit code: 2 for configuration errors, 3 for template errors, 4 for GitHub API
+    /// errors (5 when the credentials are refused), 6 for network errors, 7 for git errors
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            GeneratorError::Config(_) => 2,
+            GeneratorError::Template(_) => 3,
+            error if error.is_unauthorized() => 5,
+            GeneratorError::Api { .. } => 4,
+            GeneratorError::Http(_) => 6,
+            GeneratorError::Git(_) => 7,
+            GeneratorError::Io(_) | GeneratorError::Other(_) => 1,
+        }
+    }
+}
+
+/// The `message` of a GitHub error body, with the details of its `errors` when there are
+/// any. Other bodies are kept as they are.
+fn api_error_message(body: &str) -> String {
+    let Ok(error) = serde_json::from_str::<serde_json::Value>(body) else {
+        return body.to_string();
+    };
+    let Some(message) = error["message"].as_str() else {
+        return body.to_string();
+    };
+    let details: Vec<String> = error["errors"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|detail| match detail["message"].as_str() {
+            Some(message) => message.to_string(),
+            None => detail.to_string(),
+        })
+        .collect();
+    if details.is_empty() {
+        message.to_string()
+    } else {
+        format!("{} ({})", message, details.join(", "))
+    }
+}
+
+impl From<io::Error> for GeneratorError {
+    fn from(error: io::Error) -> Self {
+        // A generator error carried through an io::Error keeps its kind
+        match error
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<GeneratorError>())
+        {
+            Some(_) => *error
+                .into_inner()
+                .and_then(|inner| inner.downcast::<GeneratorError>().ok())
+                .expect("checked above"),
+            None => GeneratorError::Io(error),
+        }
+    }
 }
 
-pub fn print_error_with_error_message<T: std::fmt::Display>(message: &str, error: &T) {
-    eprintln!("{}: {}", message, error);
+impl From<git2::Error> for GeneratorError {
+    fn from(error: git2::Error) -> Self {
+        GeneratorError::Git(error.message().to_string())
+    }
 }
 
-pub fn print_error_and_exit(message: &str) -> ! {
-    print_error(message);
-    std::process::exit(1);
+impl From<reqwest::Error> for GeneratorError {
+    fn from(error: reqwest::Error) -> Self {
+        GeneratorError::Http(error.to_string())
+    }
 }
 
-pub fn print_error_and_exit_with_error<T: std::fmt::Display>(message: &str, error: &T) -> ! {
-    print_error_with_error_message(message, error);
-    std::process::exit(1);
+impl From<serde_json::Error> for GeneratorError {
+    fn from(error: serde_json::Error) -> Self {
+        GeneratorError::Other(error.to_string())
+    }
+}
+
+impl From<openssl::error::ErrorStack> for GeneratorError {
+    fn from(error: openssl::error::ErrorStack) -> Self {
+        GeneratorError::Other(error.to_string())
+    }
+}
+
+impl From<std::time::SystemTimeError> for GeneratorError {
+    fn from(error: std::time::SystemTimeError) -> Self {
+        GeneratorError::Other(error.to_string())
+    }
+}
+
+/// Most of the generator works with io::Result: the generator error travels inside and
+/// comes back out, with its kind, through `From<io::Error>`
+impl From<GeneratorError> for io::Error {
+    fn from(error: GeneratorError) -> Self {
+        match error {
+            GeneratorError::Io(e) => e,
+            error => io::Error::other(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_carried_through_io_keep_their_exit_code() {
+        let carried: io::Error = GeneratorError::Template("Template not found".to_string()).into();
+        let error = GeneratorError::from(carried).context("An error occurred while generating");
+        assert_eq!(error.exit_code(), 3);
+        assert_eq!(
+            error.to_string(),
+            "An error occurred while generating: Template not found"
+        );
+
+        let error = GeneratorError::from(io::Error::other("disk full"));
+        assert!(matches!(error, GeneratorError::Io(_)));
+        assert_eq!(error.exit_code(), 1);
+        assert_eq!(
+            GeneratorError::Api {
+                status: 403,
+                message: "Forbidden".to_string()
+            }
+            .to_string(),
+            "Forbidden (HTTP 403)"
+        );
+    }
 }
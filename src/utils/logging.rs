@@ -0,0 +1,135 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde_json::json;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::utils::{context, progress, summary};
+
+/// How log records are written
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq)]
+pub enum LogFormat {
+    /// Messages as they are: status on stdout, warnings and errors on stderr
+    #[default]
+    Text,
+    /// One JSON object per record on stderr, with its level, stage and timestamp
+    Json,
+}
+
+/// Records of the generator itself, as opposed to those of its dependencies
+const CRATE_TARGET: &str = "project_generator_cli";
+
+static LOGGER: Logger = Logger;
+static JSON: AtomicBool = AtomicBool::new(false);
+/// Level above which records of the dependencies are written too
+static DEPENDENCY_LEVEL: AtomicU8 = AtomicU8::new(LevelFilter::Warn as u8);
+/// Pipeline stages being run, innermost last
+static STAGES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+struct Logger;
+
+/// Install the logger: warnings and errors with `quiet`, status messages by default, debug
+/// messages with `-v` and trace messages of the dependencies too with `-vv`
+pub fn init(verbosity: u8, quiet: bool, format: LogFormat) {
+    let level = match (quiet, verbosity) {
+        (true, _) => LevelFilter::Warn,
+        (false, 0) => LevelFilter::Info,
+        (false, 1) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    };
+    if verbosity >= 2 {
+        DEPENDENCY_LEVEL.store(LevelFilter::Trace as u8, Ordering::Relaxed);
+    }
+    JSON.store(format == LogFormat::Json, Ordering::Relaxed);
+    context::set_debug_mode(level >= LevelFilter::Debug);
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+            && (metadata.target().starts_with(CRATE_TARGET)
+                || metadata.level() as u8 <= DEPENDENCY_LEVEL.load(Ordering::Relaxed))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let message = record.args().to_string();
+        if JSON.load(Ordering::Relaxed) {
+            write_json(record, &message);
+            return;
+        }
+        progress::write(|| match record.level() {
+            Level::Error | Level::Warn => eprintln!("{}", message),
+            // stdout is kept for the JSON summary
+            Level::Info if summary::is_enabled() => eprintln!("{}", message),
+            Level::Info => println!("{}", message),
+            // Kept together with the other messages of the current debug task
+            Level::Debug => context::write_debug_line(&message),
+            Level::Trace => context::write_debug_line(&format!("[TRACE] {}", message)),
+        });
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stdout().flush();
+        let _ = std::io::stderr().flush();
+    }
+}
+
+fn write_json(record: &Record, message: &str) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    let stage = STAGES.lock().ok().and_then(|stages| stages.last().copied());
+    let line = json!({
+        "timestamp_ms": timestamp,
+        "level": record.level().as_str().to_lowercase(),
+        "target": record.target(),
+        "stage": stage,
+        "message": message,
+    });
+    let _ = writeln!(std::io::stderr().lock(), "{}", line);
+}
+
+/// A pipeline stage (render, install, create repository, push...), logged with its duration
+/// when the guard is dropped. JSON records carry the innermost running stage.
+pub fn stage(name: &'static str) -> Stage {
+    if let Ok(mut stages) = STAGES.lock() {
+        stages.push(name);
+    }
+    log::debug!("Stage {} started", name);
+    progress::started(name);
+    Stage {
+        name,
+        started: Instant::now(),
+    }
+}
+
+pub struct Stage {
+    name: &'static str,
+    started: Instant,
+}
+
+impl Drop for Stage {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        log::debug!(
+            "Stage {} finished in {:.1}s",
+            self.name,
+            elapsed.as_secs_f64()
+        );
+        summary::record_step(self.name, elapsed);
+        progress::finished(self.name, elapsed);
+        if let Ok(mut stages) = STAGES.lock() {
+            if let Some(position) = stages.iter().rposition(|stage| *stage == self.name) {
+                stages.remove(position);
+            }
+        }
+    }
+}
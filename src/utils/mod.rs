@@ -1,4 +1,10 @@
+pub mod commit;
 pub mod context;
 pub mod error;
+pub mod glob;
+pub mod logging;
+pub mod notify;
+pub mod progress;
 pub mod strings;
+pub mod summary;
 pub mod validation;
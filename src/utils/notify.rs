@@ -0,0 +1,73 @@
+//! Notifications when a long phase (dependency installation, push, deployment wait) ends, for
+//! users who switched to another window while the generator runs. Desktop notifications go
+//! through the platform's notifier (`notify-send` on Linux, `osascript` on macOS), so that no
+//! desktop bus library is linked in.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Phases ending sooner than this are not worth a notification
+pub const MIN_DURATION: Duration = Duration::from_secs(30);
+
+/// How to notify the end of a long phase
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum NotifyMode {
+    /// Ring the terminal bell
+    Bell,
+    /// Show a desktop notification
+    Desktop,
+    /// Both
+    All,
+}
+
+/// Notify that `phase` (e.g. `Dependency installation`) ended, if a notification `mode` is
+/// set and it took at least [`MIN_DURATION`]
+pub fn finished(mode: Option<NotifyMode>, phase: &str, started: Instant, succeeded: bool) {
+    let Some(mode) = mode else {
+        return;
+    };
+    let elapsed = started.elapsed();
+    if elapsed < MIN_DURATION {
+        return;
+    }
+
+    let message = format!(
+        "{} {} after {}s",
+        phase,
+        if succeeded { "finished" } else { "failed" },
+        elapsed.as_secs()
+    );
+    if matches!(mode, NotifyMode::Bell | NotifyMode::All) {
+        let mut stdout = std::io::stdout();
+        let _ = write!(stdout, "\x07");
+        let _ = stdout.flush();
+    }
+    if matches!(mode, NotifyMode::Desktop | NotifyMode::All) {
+        if let Err(e) = desktop_notification(&message) {
+            log::debug!("Desktop notification failed: {}", e);
+        }
+    }
+}
+
+fn desktop_notification(message: &str) -> std::io::Result<()> {
+    let title = "Project generator";
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {:?} with title {:?}",
+            message, title
+        ));
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.arg(title).arg(message);
+        command
+    };
+    // The notifier must not hold the run
+    command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
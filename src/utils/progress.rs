@@ -0,0 +1,125 @@
+//! Progress of the pipeline phases (template render, dependency installation, repository
+//! creation, push, branch setup, workflow triggers). On a terminal, the running phase is shown
+//! with a spinner and its elapsed time; otherwise each phase is logged when it starts. Either
+//! way, the time a phase took is logged when it ends.
+
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const TICK: Duration = Duration::from_millis(100);
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static SPINNER: AtomicBool = AtomicBool::new(false);
+/// Prompts and tools writing to the terminal running, during which the spinner is hidden
+static PAUSED: AtomicUsize = AtomicUsize::new(0);
+/// Phases being run, innermost last
+static RUNNING: Mutex<Vec<(&'static str, Instant)>> = Mutex::new(Vec::new());
+/// Whether the spinner line is on screen
+static DRAWN: AtomicBool = AtomicBool::new(false);
+/// Held while writing to the terminal, so that messages never land on the spinner line
+static TERMINAL: Mutex<()> = Mutex::new(());
+
+/// Report the phases, with a spinner when `animate` is set and stderr is a terminal
+pub fn enable(animate: bool) {
+    ENABLED.store(true, Ordering::Relaxed);
+    if animate && std::io::stderr().is_terminal() {
+        SPINNER.store(true, Ordering::Relaxed);
+        thread::spawn(spin);
+    }
+}
+
+/// What the user reads for a pipeline stage
+fn label(stage: &str) -> &str {
+    match stage {
+        "render" => "Rendering template",
+        "install" => "Installing dependencies",
+        "create_repository" => "Creating repository",
+        "push" => "Pushing code",
+        "branch_setup" => "Setting up branches",
+        "deploy" => "Triggering workflows",
+        other => other,
+    }
+}
+
+pub fn started(stage: &'static str) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    if let Ok(mut running) = RUNNING.lock() {
+        running.push((stage, Instant::now()));
+    }
+    if !SPINNER.load(Ordering::Relaxed) {
+        log::info!("▶️  {}...", label(stage));
+    }
+}
+
+pub fn finished(stage: &'static str, elapsed: Duration) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    if let Ok(mut running) = RUNNING.lock() {
+        if let Some(position) = running.iter().rposition(|(name, _)| *name == stage) {
+            running.remove(position);
+        }
+    }
+    log::info!("⏱️  {}: {:.1}s", label(stage), elapsed.as_secs_f64());
+}
+
+fn clear_line() {
+    if DRAWN.swap(false, Ordering::Relaxed) {
+        let _ = write!(std::io::stderr().lock(), "\r\x1b[2K");
+    }
+}
+
+/// Write to the terminal with `write`, after erasing the spinner line. The next tick draws
+/// it again below.
+pub fn write(write: impl FnOnce()) {
+    let _terminal = TERMINAL.lock();
+    clear_line();
+    write();
+}
+
+/// Hide the spinner while a prompt or a tool owns the terminal, until the guard is dropped
+pub fn pause() -> Paused {
+    PAUSED.fetch_add(1, Ordering::Relaxed);
+    write(|| {});
+    Paused
+}
+
+pub struct Paused;
+
+impl Drop for Paused {
+    fn drop(&mut self) {
+        PAUSED.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn spin() {
+    for frame in FRAMES.iter().cycle() {
+        thread::sleep(TICK);
+        let current = RUNNING
+            .lock()
+            .ok()
+            .and_then(|running| running.last().copied());
+        let _terminal = TERMINAL.lock();
+        match current {
+            Some((stage, started)) if PAUSED.load(Ordering::Relaxed) == 0 => {
+                let mut stderr = std::io::stderr().lock();
+                let _ = write!(
+                    stderr,
+                    "\r\x1b[2K{} {} {:.1}s",
+                    frame,
+                    label(stage),
+                    started.elapsed().as_secs_f64()
+                );
+                let _ = stderr.flush();
+                DRAWN.store(true, Ordering::Relaxed);
+            }
+            _ => clear_line(),
+        }
+    }
+}
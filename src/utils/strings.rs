@@ -1,11 +1,15 @@
-use crate::config::TemplateJson;
+use crate::config::{TemplateConfigFile, TemplateJson, TemplateManifest};
 use std::path::Path;
 
-pub fn read_template_config(template_path: &Path) -> std::io::Result<TemplateJson> {
+pub fn read_template_manifest(template_path: &Path) -> std::io::Result<TemplateManifest> {
     let config_path = template_path.join("template_config.json");
     let config_content = std::fs::read_to_string(config_path)?;
-    let config: TemplateJson = serde_json::from_str(&config_content)?;
-    Ok(config)
+    let config: TemplateConfigFile = serde_json::from_str(&config_content)?;
+    Ok(config.into())
+}
+
+pub fn read_template_config(template_path: &Path) -> std::io::Result<TemplateJson> {
+    Ok(read_template_manifest(template_path)?.files)
 }
 
 pub fn extract_unique_keys(template_path: &Path) -> std::io::Result<Vec<String>> {
@@ -17,6 +21,7 @@ pub fn extract_unique_keys(template_path: &Path) -> std::io::Result<Vec<String>>
             keys.insert(replacement.name);
         }
     }
+    keys.extend(crate::generate::render::template_variables(template_path));
 
     Ok(keys.into_iter().collect())
 }
@@ -0,0 +1,212 @@
+//! Final summary of a run for wrapper tooling: with `--output json`, the repository, branches,
+//! workflows, deployments, variables and step durations are printed to stdout as one JSON
+//! document once the generator is done, while status messages move to stderr.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// What is printed on stdout at the end of a run
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq)]
+pub enum OutputFormat {
+    /// Status messages only
+    #[default]
+    Text,
+    /// A JSON summary of the run, status messages going to stderr
+    Json,
+}
+
+#[derive(Serialize)]
+struct Summary {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    dry_run: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    template: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repository: Option<Repository>,
+    branches: Vec<String>,
+    workflows: Vec<String>,
+    deployments: Vec<Deployment>,
+    variables: BTreeMap<String, String>,
+    steps: Vec<Step>,
+    duration_ms: u128,
+    #[serde(skip)]
+    started: Instant,
+    #[serde(skip)]
+    paused: bool,
+}
+
+#[derive(Serialize)]
+struct Repository {
+    url: String,
+    clone_url: String,
+    ssh_url: String,
+}
+
+#[derive(Serialize)]
+struct Deployment {
+    workflow: String,
+    branch: String,
+    run_url: Option<String>,
+    site_url: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Step {
+    name: &'static str,
+    duration_ms: u128,
+}
+
+static SUMMARY: Mutex<Option<Summary>> = Mutex::new(None);
+
+/// Start collecting the summary printed by [`print`]
+pub fn enable(dry_run: bool) {
+    if let Ok(mut summary) = SUMMARY.lock() {
+        *summary = Some(Summary {
+            success: false,
+            error: None,
+            dry_run,
+            template: None,
+            repository: None,
+            branches: Vec::new(),
+            workflows: Vec::new(),
+            deployments: Vec::new(),
+            variables: BTreeMap::new(),
+            steps: Vec::new(),
+            duration_ms: 0,
+            started: Instant::now(),
+            paused: false,
+        });
+    }
+}
+
+pub fn is_enabled() -> bool {
+    SUMMARY
+        .lock()
+        .map(|summary| summary.is_some())
+        .unwrap_or(false)
+}
+
+fn update(change: impl FnOnce(&mut Summary)) {
+    if let Ok(mut summary) = SUMMARY.lock() {
+        if let Some(summary) = summary.as_mut().filter(|summary| !summary.paused) {
+            change(summary);
+        }
+    }
+}
+
+/// Stop recording while the remote actions are rehearsed, e.g. before applying a plan
+pub fn pause() {
+    if let Ok(mut summary) = SUMMARY.lock() {
+        if let Some(summary) = summary.as_mut() {
+            summary.paused = true;
+        }
+    }
+}
+
+pub fn resume() {
+    if let Ok(mut summary) = SUMMARY.lock() {
+        if let Some(summary) = summary.as_mut() {
+            summary.paused = false;
+        }
+    }
+}
+
+/// Where the generator writes what is not the summary (plans, listings, debug messages):
+/// stderr when stdout is kept for the summary
+pub fn status_output() -> Box<dyn Write> {
+    if is_enabled() {
+        Box::new(std::io::stderr())
+    } else {
+        Box::new(std::io::stdout())
+    }
+}
+
+/// Where the tools the generator runs write their output: stderr when stdout is kept for the
+/// summary
+pub fn child_stdout() -> Stdio {
+    if is_enabled() {
+        Stdio::from(std::io::stderr())
+    } else {
+        Stdio::inherit()
+    }
+}
+
+pub fn record_template(template: &str) {
+    update(|summary| summary.template = Some(template.to_string()));
+}
+
+/// The repository the code ended up in, from its `https://github.com/owner/name` URL. A later
+/// call (after a transfer) replaces the earlier one.
+pub fn record_repository(url: &str) {
+    let path = url.trim_start_matches("https://github.com/");
+    update(|summary| {
+        summary.repository = Some(Repository {
+            url: url.to_string(),
+            clone_url: format!("{}.git", url),
+            ssh_url: format!("git@github.com:{}.git", path),
+        })
+    });
+}
+
+pub fn record_branch(branch: &str) {
+    update(|summary| {
+        if !summary.branches.iter().any(|known| known == branch) {
+            summary.branches.push(branch.to_string());
+        }
+    });
+}
+
+pub fn record_workflow(workflow: &str) {
+    update(|summary| summary.workflows.push(workflow.to_string()));
+}
+
+/// A dispatched deployment with its run and, once deployed, its site
+pub fn record_deployment(
+    workflow: &str,
+    branch: &str,
+    run_url: Option<&str>,
+    site_url: Option<&str>,
+) {
+    update(|summary| {
+        summary.deployments.push(Deployment {
+            workflow: workflow.to_string(),
+            branch: branch.to_string(),
+            run_url: run_url.map(str::to_string),
+            site_url: site_url.map(str::to_string),
+        })
+    });
+}
+
+/// The variables the project was rendered with, without those declared as secret
+pub fn record_variables(variables: &BTreeMap<String, String>) {
+    update(|summary| summary.variables = variables.clone());
+}
+
+pub fn record_step(name: &'static str, duration: Duration) {
+    update(|summary| {
+        summary.steps.push(Step {
+            name,
+            duration_ms: duration.as_millis(),
+        })
+    });
+}
+
+/// Print the summary of the run ending with `result` on stdout, if enabled
+pub fn print<T, E: std::fmt::Display>(result: &Result<T, E>) {
+    let Some(mut summary) = SUMMARY.lock().ok().and_then(|mut summary| summary.take()) else {
+        return;
+    };
+    summary.success = result.is_ok();
+    summary.error = result.as_ref().err().map(|e| e.to_string());
+    summary.duration_ms = summary.started.elapsed().as_millis();
+    match serde_json::to_string_pretty(&summary) {
+        Ok(json) => println!("{}", json),
+        Err(e) => log::error!("Failed to serialize the run summary: {}", e),
+    }
+}
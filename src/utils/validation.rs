@@ -1,6 +1,9 @@
 use inquire::validator::Validation;
 use regex::Regex;
 
+use crate::config::PromptVariable;
+use crate::generate::variables;
+
 pub fn validate_project_name(input: &str) -> Result<Validation, inquire::error::CustomUserError> {
     let regex = Regex::new(r"^[a-z][a-z0-9_-]*$").unwrap();
     if regex.is_match(input) {
@@ -22,3 +25,27 @@ pub fn validate_package_name(input: &str) -> Result<Validation, inquire::error::
         ))
     }
 }
+
+/// Validator enforcing the rules of a declared template variable
+pub fn declared_variable_validator(
+    variable: &PromptVariable,
+) -> impl Fn(&str) -> Result<Validation, inquire::error::CustomUserError> + Clone {
+    let required = variable.required;
+    let pattern = variable.pattern.clone();
+    let choices = variable.choices.clone();
+    let min_length = variable.min_length;
+    let max_length = variable.max_length;
+    move |input: &str| {
+        let rules = variables::Rules {
+            required,
+            pattern: pattern.as_deref(),
+            choices: &choices,
+            min_length,
+            max_length,
+        };
+        Ok(match variables::check(&rules, Some(input)).first() {
+            Some(problem) => Validation::Invalid(format!("Value {}", problem).into()),
+            None => Validation::Valid,
+        })
+    }
+}
@@ -0,0 +1,36 @@
+//! With `--output json`, stdout is the summary of the run and nothing else
+
+use std::process::Command;
+
+#[test]
+fn stdout_holds_only_the_summary() {
+    let dir = std::env::temp_dir().join(format!(
+        "project-generator-json-output-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let stats_path = dir.join("template-stats.json");
+    std::fs::write(
+        &stats_path,
+        r#"{"web/astro": {"generations": 2, "overridden": {"port": 1}}}"#,
+    )
+    .unwrap();
+
+    // Debug messages (-v) and the statistics would otherwise be written to stdout
+    let output = Command::new(env!("CARGO_BIN_EXE_project-generator-cli"))
+        .args(["--output", "json", "-v", "--template-stats"])
+        .current_dir(&dir)
+        .env("PROJECT_GENERATOR_STATS", &stats_path)
+        .env("GEN_PROJECT_NAME", "my-app")
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let summary: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(summary["success"], true);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("[DEBUG] Variable project_name"));
+    assert!(stderr.contains("web/astro: 2 generation(s)"));
+}